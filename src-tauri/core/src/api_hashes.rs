@@ -0,0 +1,551 @@
+// API名哈希数据库: 常见DLL导出名按几种常见算法预先计算 用于识别代码中按哈希解析API的常量
+// shellcode和加载器遍历PEB和导出表 对每个导出名计算哈希并与内置常量比较 从而不在文件中留下API名
+
+// 加壳和恶意代码运行时常解析的API 按所在DLL分组
+pub(crate) const COMMON_APIS: &[(&str, &[&str])] = &[
+    (
+        "kernel32.dll",
+        &[
+            "LoadLibraryA",
+            "LoadLibraryW",
+            "LoadLibraryExA",
+            "LoadLibraryExW",
+            "GetProcAddress",
+            "GetModuleHandleA",
+            "GetModuleHandleW",
+            "FreeLibrary",
+            "VirtualAlloc",
+            "VirtualAllocEx",
+            "VirtualProtect",
+            "VirtualProtectEx",
+            "VirtualFree",
+            "VirtualQuery",
+            "WriteProcessMemory",
+            "ReadProcessMemory",
+            "CreateRemoteThread",
+            "CreateThread",
+            "OpenProcess",
+            "OpenThread",
+            "CreateProcessA",
+            "CreateProcessW",
+            "WinExec",
+            "ExitProcess",
+            "TerminateProcess",
+            "GetCurrentProcess",
+            "Sleep",
+            "CreateFileA",
+            "CreateFileW",
+            "WriteFile",
+            "ReadFile",
+            "CloseHandle",
+            "DeleteFileA",
+            "DeleteFileW",
+            "CopyFileA",
+            "CopyFileW",
+            "MoveFileExW",
+            "GetTempPathA",
+            "GetTempPathW",
+            "GetSystemDirectoryA",
+            "GetSystemDirectoryW",
+            "GetModuleFileNameA",
+            "GetModuleFileNameW",
+            "CreateToolhelp32Snapshot",
+            "Process32First",
+            "Process32FirstW",
+            "Process32Next",
+            "Process32NextW",
+            "Thread32First",
+            "Thread32Next",
+            "Module32First",
+            "Module32Next",
+            "SuspendThread",
+            "ResumeThread",
+            "GetThreadContext",
+            "SetThreadContext",
+            "Wow64GetThreadContext",
+            "Wow64SetThreadContext",
+            "QueueUserAPC",
+            "IsDebuggerPresent",
+            "CheckRemoteDebuggerPresent",
+            "OutputDebugStringA",
+            "GetTickCount",
+            "QueryPerformanceCounter",
+            "CreateMutexA",
+            "CreateMutexW",
+            "OpenMutexA",
+            "CreateFileMappingA",
+            "CreateFileMappingW",
+            "MapViewOfFile",
+            "UnmapViewOfFile",
+            "GetComputerNameA",
+            "GetComputerNameW",
+            "GetVersionExA",
+            "GetVersionExW",
+            "GetNativeSystemInfo",
+            "SetFileAttributesW",
+            "FindFirstFileA",
+            "FindFirstFileW",
+            "FindNextFileA",
+            "FindNextFileW",
+            "GlobalAlloc",
+            "HeapAlloc",
+            "HeapCreate",
+            "LocalAlloc",
+            "WaitForSingleObject",
+            "CreateNamedPipeA",
+            "CreateNamedPipeW",
+            "ConnectNamedPipe",
+            "DeviceIoControl",
+            "GetEnvironmentVariableA",
+            "ExpandEnvironmentStringsW",
+            "SetUnhandledExceptionFilter",
+            "AddVectoredExceptionHandler",
+            "FlushInstructionCache",
+            "GetLogicalDrives",
+            "GetDriveTypeW",
+            "GetSystemInfo",
+            "GetCurrentProcessId",
+            "GetCurrentThreadId",
+            "GetLastError",
+            "SetLastError",
+            "GetCommandLineA",
+            "GetCommandLineW",
+            "CreateEventA",
+            "CreateEventW",
+            "SetEvent",
+            "MultiByteToWideChar",
+            "WideCharToMultiByte",
+            "lstrlenA",
+            "lstrlenW",
+            "lstrcmpiA",
+            "lstrcpyA",
+            "lstrcatA",
+            "GetFileSize",
+            "SetFilePointer",
+            "CreateDirectoryA",
+            "CreateDirectoryW",
+            "GetWindowsDirectoryA",
+            "GetWindowsDirectoryW",
+            "GetUserDefaultLangID",
+            "GetVolumeInformationA",
+            "GetVolumeInformationW",
+        ],
+    ),
+    (
+        "ntdll.dll",
+        &[
+            "NtAllocateVirtualMemory",
+            "NtProtectVirtualMemory",
+            "NtWriteVirtualMemory",
+            "NtReadVirtualMemory",
+            "NtQueryVirtualMemory",
+            "NtCreateThreadEx",
+            "NtQueueApcThread",
+            "NtUnmapViewOfSection",
+            "ZwUnmapViewOfSection",
+            "NtMapViewOfSection",
+            "NtCreateSection",
+            "NtQueryInformationProcess",
+            "NtSetInformationThread",
+            "NtQuerySystemInformation",
+            "NtResumeThread",
+            "NtGetContextThread",
+            "NtSetContextThread",
+            "NtOpenProcess",
+            "NtTerminateProcess",
+            "NtCreateFile",
+            "NtWriteFile",
+            "NtClose",
+            "NtDelayExecution",
+            "RtlCreateUserThread",
+            "RtlMoveMemory",
+            "RtlDecompressBuffer",
+            "RtlAdjustPrivilege",
+            "LdrLoadDll",
+            "LdrGetProcedureAddress",
+        ],
+    ),
+    (
+        "advapi32.dll",
+        &[
+            "RegOpenKeyExA",
+            "RegOpenKeyExW",
+            "RegCreateKeyExA",
+            "RegCreateKeyExW",
+            "RegSetValueExA",
+            "RegSetValueExW",
+            "RegQueryValueExA",
+            "RegQueryValueExW",
+            "RegDeleteValueA",
+            "RegDeleteValueW",
+            "RegCloseKey",
+            "OpenProcessToken",
+            "AdjustTokenPrivileges",
+            "LookupPrivilegeValueA",
+            "LookupPrivilegeValueW",
+            "OpenSCManagerA",
+            "OpenSCManagerW",
+            "CreateServiceA",
+            "CreateServiceW",
+            "StartServiceA",
+            "StartServiceW",
+            "CryptAcquireContextA",
+            "CryptAcquireContextW",
+            "CryptEncrypt",
+            "CryptDecrypt",
+            "CryptCreateHash",
+            "CryptHashData",
+            "CryptDeriveKey",
+            "CryptImportKey",
+            "CryptGenKey",
+            "GetUserNameA",
+            "GetUserNameW",
+            "ImpersonateLoggedOnUser",
+            "DuplicateTokenEx",
+            "CreateProcessAsUserW",
+            "CreateProcessWithTokenW",
+        ],
+    ),
+    (
+        "user32.dll",
+        &[
+            "MessageBoxA",
+            "MessageBoxW",
+            "SetWindowsHookExA",
+            "SetWindowsHookExW",
+            "GetAsyncKeyState",
+            "GetKeyState",
+            "GetForegroundWindow",
+            "GetWindowTextA",
+            "GetWindowTextW",
+            "FindWindowA",
+            "FindWindowW",
+            "ShowWindow",
+            "OpenClipboard",
+            "GetClipboardData",
+            "SetClipboardData",
+            "BlockInput",
+        ],
+    ),
+    (
+        "ws2_32.dll",
+        &[
+            "WSAStartup",
+            "WSASocketA",
+            "WSASocketW",
+            "WSAConnect",
+            "socket",
+            "connect",
+            "bind",
+            "listen",
+            "accept",
+            "send",
+            "recv",
+            "sendto",
+            "recvfrom",
+            "closesocket",
+            "ioctlsocket",
+            "select",
+            "gethostbyname",
+            "getaddrinfo",
+            "inet_addr",
+            "htons",
+        ],
+    ),
+    (
+        "wininet.dll",
+        &[
+            "InternetOpenA",
+            "InternetOpenW",
+            "InternetConnectA",
+            "InternetConnectW",
+            "InternetOpenUrlA",
+            "InternetOpenUrlW",
+            "HttpOpenRequestA",
+            "HttpOpenRequestW",
+            "HttpSendRequestA",
+            "HttpSendRequestW",
+            "InternetReadFile",
+            "InternetSetOptionA",
+            "InternetCloseHandle",
+        ],
+    ),
+    (
+        "winhttp.dll",
+        &[
+            "WinHttpOpen",
+            "WinHttpConnect",
+            "WinHttpOpenRequest",
+            "WinHttpSendRequest",
+            "WinHttpReceiveResponse",
+            "WinHttpReadData",
+            "WinHttpCloseHandle",
+        ],
+    ),
+    ("urlmon.dll", &["URLDownloadToFileA", "URLDownloadToFileW"]),
+    (
+        "shell32.dll",
+        &[
+            "ShellExecuteA",
+            "ShellExecuteW",
+            "ShellExecuteExA",
+            "ShellExecuteExW",
+            "SHGetFolderPathA",
+            "SHGetFolderPathW",
+            "SHGetSpecialFolderPathW",
+        ],
+    ),
+    (
+        "psapi.dll",
+        &[
+            "EnumProcesses",
+            "EnumProcessModules",
+            "GetModuleBaseNameA",
+            "GetModuleBaseNameW",
+        ],
+    ),
+    ("dbghelp.dll", &["MiniDumpWriteDump"]),
+    (
+        "crypt32.dll",
+        &["CryptStringToBinaryA", "CryptUnprotectData"],
+    ),
+    (
+        "bcrypt.dll",
+        &[
+            "BCryptOpenAlgorithmProvider",
+            "BCryptGenerateSymmetricKey",
+            "BCryptEncrypt",
+            "BCryptDecrypt",
+        ],
+    ),
+    ("amsi.dll", &["AmsiInitialize", "AmsiScanBuffer"]),
+    ("ole32.dll", &["CoInitializeEx", "CoCreateInstance"]),
+];
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ApiHash {
+    pub dll_name: &'static str,
+    pub name: &'static str,
+    pub algorithm: &'static str,
+}
+
+const fn ror13(hash: u32, byte: u8) -> u32 {
+    hash.rotate_right(13).wrapping_add(byte as u32)
+}
+
+// 函数名的ASCII字节 不含结尾0
+const fn ror13_name(name: &str) -> u32 {
+    let bytes = name.as_bytes();
+    let mut hash = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = ror13(hash, bytes[i]);
+        i += 1;
+    }
+    hash
+}
+
+// Metasploit block_api: 模块名为大写UTF-16并含结尾0 函数名含结尾0 两个哈希相加
+// 表中的DLL名都是ASCII 每个字符的UTF-16高字节为0
+const fn ror13_module(dll_name: &str, name: &str) -> u32 {
+    let bytes = dll_name.as_bytes();
+    let mut module = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        module = ror13(ror13(module, bytes[i].to_ascii_uppercase()), 0);
+        i += 1;
+    }
+    module = ror13(ror13(module, 0), 0);
+    let function = ror13(ror13_name(name), 0);
+    module.wrapping_add(function)
+}
+
+// 与zip相同的CRC-32(IEEE) 按位计算
+const fn crc32_name(name: &str) -> u32 {
+    let bytes = name.as_bytes();
+    let mut crc = !0u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        crc ^= bytes[i] as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    !crc
+}
+
+// hash * 33 + c 初值5381
+const fn djb2_name(name: &str) -> u32 {
+    let bytes = name.as_bytes();
+    let mut hash = 5381u32;
+    let mut i = 0;
+    while i < bytes.len() {
+        hash = hash.wrapping_mul(33).wrapping_add(bytes[i] as u32);
+        i += 1;
+    }
+    hash
+}
+
+// 算法名返回给前端 下标与hash_name中的分支对应
+const ALGORITHMS: [&str; 4] = ["ror13", "ror13_module", "crc32", "djb2"];
+
+const fn hash_name(algorithm: usize, dll_name: &str, name: &str) -> u32 {
+    match algorithm {
+        0 => ror13_name(name),
+        1 => ror13_module(dll_name, name),
+        2 => crc32_name(name),
+        _ => djb2_name(name),
+    }
+}
+
+const ENTRY_COUNT: usize = {
+    let mut count = 0;
+    let mut i = 0;
+    while i < COMMON_APIS.len() {
+        count += COMMON_APIS[i].1.len() * ALGORITHMS.len();
+        i += 1;
+    }
+    count
+};
+
+// 按哈希值排序的(哈希值, API)表 在编译期计算
+// 相同哈希值的项保持COMMON_APIS和ALGORITHMS中的顺序
+const DATABASE: ([u32; ENTRY_COUNT], [ApiHash; ENTRY_COUNT]) = {
+    let mut hashes = [0u32; ENTRY_COUNT];
+    let mut entries = [ApiHash {
+        dll_name: "",
+        name: "",
+        algorithm: "",
+    }; ENTRY_COUNT];
+    let mut order = [0usize; ENTRY_COUNT];
+    let mut count = 0;
+    let mut dll = 0;
+    while dll < COMMON_APIS.len() {
+        let (dll_name, functions) = COMMON_APIS[dll];
+        let mut function = 0;
+        while function < functions.len() {
+            let mut algorithm = 0;
+            while algorithm < ALGORITHMS.len() {
+                hashes[count] = hash_name(algorithm, dll_name, functions[function]);
+                entries[count] = ApiHash {
+                    dll_name,
+                    name: functions[function],
+                    algorithm: ALGORITHMS[algorithm],
+                };
+                order[count] = count;
+                count += 1;
+                algorithm += 1;
+            }
+            function += 1;
+        }
+        dll += 1;
+    }
+
+    // 堆排序 以(哈希值, 原始下标)为键 结果与稳定排序相同
+    const fn less(hashes: &[u32], order: &[usize], a: usize, b: usize) -> bool {
+        hashes[a] < hashes[b] || (hashes[a] == hashes[b] && order[a] < order[b])
+    }
+    const fn sift_down(
+        hashes: &mut [u32],
+        entries: &mut [ApiHash],
+        order: &mut [usize],
+        mut root: usize,
+        end: usize,
+    ) {
+        loop {
+            let mut child = root * 2 + 1;
+            if child >= end {
+                break;
+            }
+            if child + 1 < end && less(hashes, order, child, child + 1) {
+                child += 1;
+            }
+            if !less(hashes, order, root, child) {
+                break;
+            }
+            hashes.swap(root, child);
+            entries.swap(root, child);
+            order.swap(root, child);
+            root = child;
+        }
+    }
+    let mut start = ENTRY_COUNT / 2;
+    while start > 0 {
+        start -= 1;
+        sift_down(&mut hashes, &mut entries, &mut order, start, ENTRY_COUNT);
+    }
+    let mut end = ENTRY_COUNT;
+    while end > 1 {
+        end -= 1;
+        hashes.swap(0, end);
+        entries.swap(0, end);
+        order.swap(0, end);
+        sift_down(&mut hashes, &mut entries, &mut order, 0, end);
+    }
+    (hashes, entries)
+};
+
+static HASHES: [u32; ENTRY_COUNT] = DATABASE.0;
+static ENTRIES: [ApiHash; ENTRY_COUNT] = DATABASE.1;
+
+// 所有产生该哈希值的(DLL, 函数名, 算法)
+pub(crate) fn lookup(value: u32) -> &'static [ApiHash] {
+    let start = HASHES.partition_point(|&hash| hash < value);
+    let end = HASHES.partition_point(|&hash| hash <= value);
+    &ENTRIES[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn algorithms(value: u32, name: &str) -> Vec<&'static str> {
+        lookup(value)
+            .iter()
+            .filter(|hash| hash.name == name)
+            .map(|hash| hash.algorithm)
+            .collect()
+    }
+
+    #[test]
+    fn matches_metasploit_block_api_hashes() {
+        assert_eq!(ror13_module("kernel32.dll", "LoadLibraryA"), 0x0726_774C);
+        assert_eq!(ror13_module("kernel32.dll", "GetProcAddress"), 0x7802_F749);
+        assert_eq!(algorithms(0x0726_774C, "LoadLibraryA"), ["ror13_module"]);
+        assert_eq!(algorithms(0x7802_F749, "GetProcAddress"), ["ror13_module"]);
+        assert_eq!(lookup(0x0726_774C)[0].dll_name, "kernel32.dll");
+    }
+
+    #[test]
+    fn matches_known_name_hashes() {
+        // 不含模块名的ror13 常见于自写的shellcode
+        assert_eq!(ror13_name("LoadLibraryA"), 0xEC0E_4E8E);
+        assert_eq!(ror13_name("GetProcAddress"), 0x7C0D_FCAA);
+        assert_eq!(crc32_name("123456789"), 0xCBF4_3926);
+        assert_eq!(djb2_name(""), 5381);
+        assert_eq!(algorithms(0xEC0E_4E8E, "LoadLibraryA"), ["ror13"]);
+    }
+
+    #[test]
+    fn table_is_sorted_and_complete() {
+        assert!(HASHES.windows(2).all(|pair| pair[0] <= pair[1]));
+        for &(dll_name, functions) in COMMON_APIS {
+            for &name in functions {
+                for (algorithm, &algorithm_name) in ALGORITHMS.iter().enumerate() {
+                    let value = hash_name(algorithm, dll_name, name);
+                    assert!(lookup(value).iter().any(|hash| hash.dll_name == dll_name
+                        && hash.name == name
+                        && hash.algorithm == algorithm_name));
+                }
+            }
+        }
+        assert!(lookup(0).is_empty());
+    }
+}
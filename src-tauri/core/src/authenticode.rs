@@ -0,0 +1,409 @@
+// Authenticode签名 从证书目录中的PKCS#7 SignedData读取签名者证书
+use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use sha2::{Sha256, Sha384, Sha512};
+
+use crate::der::{self, Tlv, TAG_INTEGER, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE, TAG_SET};
+use crate::hashes::to_hex;
+use crate::reader::BoundedReader;
+use crate::DataDirectory;
+
+// WIN_CERTIFICATE.wCertificateType
+const WIN_CERT_TYPE_PKCS_SIGNED_DATA: u16 = 0x0002;
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_COMMON_NAME: &str = "2.5.4.3";
+const OID_SPC_INDIRECT_DATA: &str = "1.3.6.1.4.1.311.2.1.4";
+const OID_SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+const OID_COUNTER_SIGNATURE: &str = "1.2.840.113549.1.9.6";
+// Microsoft的RFC3161时间戳属性
+const OID_RFC3161_TIMESTAMP: &str = "1.3.6.1.4.1.311.3.3.1";
+// 证书链的最大长度
+const MAX_CHAIN_LENGTH: usize = 8;
+// Microsoft代码签名证书链的根
+const MICROSOFT_ROOTS: &[&str] = &[
+    "Microsoft Root Authority",
+    "Microsoft Root Certificate Authority",
+    "Microsoft Root Certificate Authority 2010",
+    "Microsoft Root Certificate Authority 2011",
+];
+// 上述根证书的SHA-1指纹 内嵌了同名根证书时必须匹配
+const MICROSOFT_ROOT_THUMBPRINTS: &[&str] = &[
+    "a43489159a520f0d93d032ccaf37e7fe20a8b419",
+    "cdd4eeae6000ac7f40c3802c171e30148030c072",
+    "3b1efd3a66ea28b16697394703a72ca340a05bd5",
+    "8f43288ad272f3103b6fb1428485ea3014c0bcfe",
+];
+const MICROSOFT_ORGANIZATION: &str = "O=Microsoft Corporation";
+
+// 计算Authenticode摘要时跳过的区域位置
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct DigestLayout {
+    // 可选头CheckSum字段的文件偏移
+    pub checksum_offset: u64,
+    // 证书目录项的文件偏移
+    pub security_entry_offset: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    pub serial_number: String,
+    // 证书DER编码的SHA-1 即Windows中显示的指纹
+    pub thumbprint: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SignatureInfo {
+    // 签名者证书的CN
+    pub signer: Option<String>,
+    pub signer_certificate: Option<CertificateInfo>,
+    pub certificates: Vec<CertificateInfo>,
+    // 从签名者证书沿颁发者向上 只按名称匹配内嵌证书 不校验证书签名
+    pub chain: Vec<CertificateInfo>,
+    // SpcIndirectDataContent中的摘要算法 如sha256
+    pub digest_algorithm: Option<String>,
+    // 重新计算的映像摘要与签名中的一致 算法不支持时为None
+    pub digest_valid: Option<bool>,
+    // 签名时间(Unix时间戳) 优先取时间戳服务器的时间
+    pub signing_time: Option<i64>,
+}
+
+// X.500名称 -> "CN=..., O=..."
+fn name_to_string(name: &Tlv) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for rdn in name.children() {
+        for attribute in rdn.children() {
+            let (Some(oid), Some(value)) = (attribute.child(0), attribute.child(1)) else {
+                continue;
+            };
+            let key = match der::oid_to_string(oid.contents).as_str() {
+                "2.5.4.3" => "CN".to_string(),
+                "2.5.4.6" => "C".to_string(),
+                "2.5.4.7" => "L".to_string(),
+                "2.5.4.8" => "S".to_string(),
+                "2.5.4.10" => "O".to_string(),
+                "2.5.4.11" => "OU".to_string(),
+                other => other.to_string(),
+            };
+            parts.push(format!("{}={}", key, der::decode_string(&value)));
+        }
+    }
+    parts.join(", ")
+}
+
+pub(crate) fn common_name(name: &Tlv) -> Option<String> {
+    name.children()
+        .iter()
+        .flat_map(|rdn| rdn.children())
+        .find(|attribute| {
+            attribute.child(0).is_some_and(|oid| {
+                oid.tag == TAG_OID && der::oid_to_string(oid.contents) == OID_COMMON_NAME
+            })
+        })
+        .and_then(|attribute| attribute.child(1))
+        .map(|value| der::decode_string(&value))
+}
+
+// TBSCertificate中issuer/serial/subject的位置 version字段可省略
+struct CertificateFields<'a> {
+    serial: Tlv<'a>,
+    issuer: Tlv<'a>,
+    subject: Tlv<'a>,
+}
+
+fn certificate_fields<'a>(certificate: &Tlv<'a>) -> Option<CertificateFields<'a>> {
+    let tbs = certificate.child(0)?;
+    let fields = tbs.children();
+    let offset = if fields.first()?.is_context(0) { 1 } else { 0 };
+    let serial = *fields.get(offset)?;
+    if serial.tag != TAG_INTEGER {
+        return None;
+    }
+    Some(CertificateFields {
+        serial,
+        issuer: *fields.get(offset + 2)?,
+        subject: *fields.get(offset + 4)?,
+    })
+}
+
+fn certificate_info(certificate: &Tlv) -> Option<CertificateInfo> {
+    let fields = certificate_fields(certificate)?;
+    Some(CertificateInfo {
+        subject: name_to_string(&fields.subject),
+        issuer: name_to_string(&fields.issuer),
+        serial_number: to_hex(fields.serial.contents),
+        thumbprint: to_hex(&Sha1::digest(certificate.raw)),
+    })
+}
+
+// 沿issuer -> subject在内嵌证书中向上查找 自签名证书为终点
+fn build_chain(signer: &CertificateInfo, certificates: &[CertificateInfo]) -> Vec<CertificateInfo> {
+    let mut chain: Vec<CertificateInfo> = vec![signer.clone()];
+    while chain.len() < MAX_CHAIN_LENGTH {
+        let current = &chain[chain.len() - 1];
+        if current.subject == current.issuer {
+            break;
+        }
+        let Some(parent) = certificates.iter().find(|c| {
+            c.subject == current.issuer && !chain.iter().any(|seen| seen.thumbprint == c.thumbprint)
+        }) else {
+            break;
+        };
+        chain.push(parent.clone());
+    }
+    chain
+}
+
+// Authenticode映像摘要: 跳过CheckSum、证书目录项和证书表本身
+// 证书表位于文件末尾时与规范中按节区排序计算的结果相同
+fn image_digest(
+    data: &[u8],
+    directory: DataDirectory,
+    layout: DigestLayout,
+    algorithm: &str,
+) -> Option<Vec<u8>> {
+    let reader = BoundedReader::new(data);
+    let size = reader.len();
+    // 未签名文件没有证书表 摘要覆盖到文件末尾
+    let table_start = if directory.size == 0 {
+        size
+    } else {
+        (directory.rva as u64).min(size)
+    };
+    let table_end = (table_start + directory.size as u64).min(size);
+    let (checksum, security_entry) = (layout.checksum_offset, layout.security_entry_offset);
+    if checksum + 4 > security_entry || security_entry + 8 > table_start {
+        return None;
+    }
+    // 参与摘要的各段(起点, 终点)
+    let parts = [
+        (0, checksum),
+        (checksum + 4, security_entry),
+        (security_entry + 8, table_start),
+        (table_end, size),
+    ]
+    .iter()
+    .map(|&(start, end)| reader.bytes(start, end - start))
+    .collect::<Result<Vec<&[u8]>, _>>()
+    .ok()?;
+    fn digest<D: Digest>(parts: &[&[u8]]) -> Vec<u8> {
+        let mut hasher = D::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().to_vec()
+    }
+    match algorithm {
+        "sha1" => Some(digest::<Sha1>(&parts)),
+        "sha256" => Some(digest::<Sha256>(&parts)),
+        "sha384" => Some(digest::<Sha384>(&parts)),
+        "sha512" => Some(digest::<Sha512>(&parts)),
+        _ => None,
+    }
+}
+
+// Authenticode SHA-256摘要 未签名的文件同样可以计算
+pub(crate) fn authentihash(
+    data: &[u8],
+    directory: DataDirectory,
+    layout: DigestLayout,
+) -> Option<String> {
+    image_digest(data, directory, layout, "sha256").map(|digest| to_hex(&digest))
+}
+
+fn digest_algorithm_name(oid: &str) -> String {
+    match oid {
+        "1.3.14.3.2.26" => "sha1".to_string(),
+        "2.16.840.1.101.3.4.2.1" => "sha256".to_string(),
+        "2.16.840.1.101.3.4.2.2" => "sha384".to_string(),
+        "2.16.840.1.101.3.4.2.3" => "sha512".to_string(),
+        "1.2.840.113549.2.5" => "md5".to_string(),
+        other => other.to_string(),
+    }
+}
+
+// SignedData.contentInfo = { SpcIndirectData OID, [0] { data, DigestInfo { algorithm, digest } } }
+fn indirect_digest<'a>(content_info: &Tlv<'a>) -> Option<(String, &'a [u8])> {
+    if der::oid_to_string(content_info.child(0)?.contents) != OID_SPC_INDIRECT_DATA {
+        return None;
+    }
+    // 规范中直接嵌入SEQUENCE 部分签名工具会再包一层OCTET STRING
+    let mut content = content_info.child(1)?.child(0)?;
+    if content.tag == TAG_OCTET_STRING {
+        content = der::parse_tlv(content.contents)?.0;
+    }
+    let digest_info = content.child(1)?;
+    let algorithm = digest_info.child(0)?.child(0)?;
+    let digest = digest_info.child(1)?;
+    Some((
+        digest_algorithm_name(&der::oid_to_string(algorithm.contents)),
+        digest.contents,
+    ))
+}
+
+// Attributes: SET OF { type OID, values SET } 取指定类型的第一个值
+fn attribute_value<'a>(attributes: &Tlv<'a>, oid: &str) -> Option<Tlv<'a>> {
+    attributes
+        .children()
+        .into_iter()
+        .find(|attribute| {
+            attribute
+                .child(0)
+                .is_some_and(|type_oid| der::oid_to_string(type_oid.contents) == oid)
+        })?
+        .child(1)?
+        .child(0)
+}
+
+// SignerInfo的signedAttrs [0]中的signingTime
+fn signed_attribute_time(signer_info: &Tlv) -> Option<i64> {
+    let signed_attributes = signer_info
+        .children()
+        .into_iter()
+        .find(|part| part.is_context(0))?;
+    der::parse_time(&attribute_value(&signed_attributes, OID_SIGNING_TIME)?)
+}
+
+// RFC3161时间戳令牌: ContentInfo { SignedData { .., encapContentInfo { TSTInfo } } }
+// TSTInfo: version, policy, messageImprint, serialNumber, genTime, ...
+fn rfc3161_time(token: &Tlv) -> Option<i64> {
+    let signed_data = token.child(1)?.child(0)?;
+    let encapsulated = signed_data.child(2)?.child(1)?.child(0)?;
+    let (tst_info, _) = der::parse_tlv(encapsulated.contents)?;
+    der::parse_time(&tst_info.child(4)?)
+}
+
+// unsignedAttrs [1]中的RFC3161时间戳或旧式副署 都没有时取签名者自己声明的时间
+fn signing_time(signer_info: &Tlv) -> Option<i64> {
+    if let Some(unsigned_attributes) = signer_info
+        .children()
+        .into_iter()
+        .find(|part| part.is_context(1))
+    {
+        if let Some(time) = attribute_value(&unsigned_attributes, OID_RFC3161_TIMESTAMP)
+            .and_then(|t| rfc3161_time(&t))
+        {
+            return Some(time);
+        }
+        if let Some(time) = attribute_value(&unsigned_attributes, OID_COUNTER_SIGNATURE)
+            .and_then(|counter| signed_attribute_time(&counter))
+        {
+            return Some(time);
+        }
+    }
+    signed_attribute_time(signer_info)
+}
+
+// 证书目录的RVA字段实际是文件偏移
+pub(crate) fn parse_signature(
+    data: &[u8],
+    directory: DataDirectory,
+    layout: DigestLayout,
+) -> Option<SignatureInfo> {
+    if directory.size < 8 {
+        return None;
+    }
+    let reader = BoundedReader::new(data);
+    let start = directory.rva as u64;
+    // 超出文件的部分截断
+    let length = (directory.size as u64).min(reader.len().checked_sub(start)?);
+    let table = BoundedReader::new(reader.bytes(start, length).ok()?);
+
+    // WIN_CERTIFICATE: dwLength, wRevision, wCertificateType, bCertificate
+    let length = table.u32(0).ok()? as u64;
+    let certificate_type = table.u16(6).ok()?;
+    if certificate_type != WIN_CERT_TYPE_PKCS_SIGNED_DATA {
+        return None;
+    }
+    let pkcs7 = table.bytes(8, length.clamp(8, table.len()) - 8).ok()?;
+
+    // ContentInfo { contentType, [0] SignedData }
+    let (content_info, _) = der::parse_tlv(pkcs7)?;
+    let content_type = content_info.child(0)?;
+    if content_info.tag != TAG_SEQUENCE
+        || der::oid_to_string(content_type.contents) != OID_SIGNED_DATA
+    {
+        return None;
+    }
+    let signed_data = content_info.child(1)?.child(0)?;
+    let parts = signed_data.children();
+
+    let certificates: Vec<Tlv> = parts
+        .iter()
+        .find(|part| part.is_context(0))
+        .map(|part| part.children())
+        .unwrap_or_default();
+
+    // SignerInfo.sid = IssuerAndSerialNumber
+    let signer_info = parts
+        .iter()
+        .rev()
+        .find(|part| part.tag == TAG_SET)
+        .and_then(|signer_infos| signer_infos.child(0));
+    let signer_certificate = signer_info
+        .and_then(|signer_info| signer_info.child(1))
+        .and_then(|sid| {
+            let issuer = sid.child(0)?;
+            let serial = sid.child(1)?;
+            certificates.iter().find(|certificate| {
+                certificate_fields(certificate).is_some_and(|fields| {
+                    fields.issuer.raw == issuer.raw && fields.serial.contents == serial.contents
+                })
+            })
+        });
+
+    let certificate_infos: Vec<CertificateInfo> =
+        certificates.iter().filter_map(certificate_info).collect();
+    let signer_certificate_info = signer_certificate.and_then(certificate_info);
+    let chain = signer_certificate_info
+        .as_ref()
+        .map(|signer| build_chain(signer, &certificate_infos))
+        .unwrap_or_default();
+
+    // SignedData: version, digestAlgorithms, contentInfo, ...
+    let indirect = parts.get(2).and_then(indirect_digest);
+    let digest_valid = indirect.as_ref().and_then(|(algorithm, expected)| {
+        image_digest(data, directory, layout, algorithm).map(|actual| actual == *expected)
+    });
+
+    Some(SignatureInfo {
+        signer: signer_certificate
+            .and_then(certificate_fields)
+            .and_then(|fields| common_name(&fields.subject)),
+        signer_certificate: signer_certificate_info,
+        certificates: certificate_infos,
+        chain,
+        digest_algorithm: indirect.map(|(algorithm, _)| algorithm),
+        digest_valid,
+        signing_time: signer_info.as_ref().and_then(signing_time),
+    })
+}
+
+// 已格式化的主题或颁发者名称中的CN
+pub fn subject_common_name(distinguished_name: &str) -> Option<&str> {
+    distinguished_name
+        .split(", ")
+        .find_map(|part| part.strip_prefix("CN="))
+}
+
+// 签名者属于Microsoft且证书链到达Microsoft根
+// 证书签名本身不做校验 只能排除内嵌了伪造同名根证书的情况
+pub fn is_microsoft_signature(signature: &SignatureInfo) -> bool {
+    let Some(signer) = signature.chain.first() else {
+        return false;
+    };
+    let is_root_name =
+        |name: &str| subject_common_name(name).is_some_and(|cn| MICROSOFT_ROOTS.contains(&cn));
+    let reaches_root = signature
+        .chain
+        .iter()
+        .any(|certificate| is_root_name(&certificate.subject) || is_root_name(&certificate.issuer));
+    let forged_root = signature.chain.iter().any(|certificate| {
+        is_root_name(&certificate.subject)
+            && !MICROSOFT_ROOT_THUMBPRINTS
+                .iter()
+                .any(|thumbprint| certificate.thumbprint.eq_ignore_ascii_case(thumbprint))
+    });
+    signer.subject.contains(MICROSOFT_ORGANIZATION) && reaches_root && !forged_root
+}
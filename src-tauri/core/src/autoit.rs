@@ -0,0 +1,81 @@
+// AutoIt编译脚本识别 3.2.6之后脚本保存在名为SCRIPT的RT_RCDATA资源中 更早的版本附加在文件末尾
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::find_all;
+use crate::reader::BoundedReader;
+use crate::resources::{ResourceEntry, RT_RCDATA};
+
+// 脚本数据开头的16字节magic 之后是"AU3!EA06"等版本标记
+const AU3_MAGIC: &[u8] = &[
+    0xA3, 0x48, 0x4B, 0xBE, 0x98, 0x6C, 0x4A, 0xA9, 0x99, 0x4C, 0x53, 0x0A, 0x86, 0xD6, 0x48, 0x7D,
+];
+const AU3_MARKER: &[u8] = b"AU3!";
+// 解释器中的特征字符串
+const AUTOIT_STRINGS: &[&str] = &[
+    ">>>AUTOIT SCRIPT<<<",
+    "AutoIt v3",
+    "AutoIt3GUI",
+    "AU3_GetPluginDetails",
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AutoItScript {
+    // EA06(3.2.6+) / EA05 等
+    pub format: Option<String>,
+    // resource / overlay
+    pub location: String,
+    pub offset: u64,
+    pub size: u64,
+    pub evidence: Vec<String>,
+}
+
+// magic之后的版本标记 如 AU3!EA06
+fn script_format(reader: &BoundedReader, offset: u64) -> Option<String> {
+    let marker = reader.bytes(offset + AU3_MAGIC.len() as u64, 8).ok()?;
+    marker
+        .starts_with(AU3_MARKER)
+        .then(|| String::from_utf8_lossy(&marker[AU3_MARKER.len()..]).to_string())
+}
+
+pub(crate) fn detect_autoit(
+    data: &[u8],
+    resources: &[ResourceEntry],
+    overlay_offset: u64,
+) -> Option<AutoItScript> {
+    let reader = BoundedReader::new(data);
+    let evidence: Vec<String> = AUTOIT_STRINGS
+        .iter()
+        .filter(|text| !find_all(data, text.as_bytes(), 1).is_empty())
+        .map(|text| format!("字符串 \"{}\"", text))
+        .collect();
+
+    // 新版: RT_RCDATA/SCRIPT
+    if let Some(resource) = resources
+        .iter()
+        .find(|r| r.type_id == Some(RT_RCDATA) && r.name.eq_ignore_ascii_case("SCRIPT"))
+    {
+        let offset = resource.offset.unwrap_or_default();
+        let format = script_format(&reader, offset);
+        if format.is_some() || !evidence.is_empty() {
+            return Some(AutoItScript {
+                format,
+                location: "resource".to_string(),
+                offset,
+                size: resource.size as u64,
+                evidence,
+            });
+        }
+    }
+
+    // 旧版: 附加数据中的magic
+    let overlay = reader.bytes_truncated(overlay_offset, u64::MAX);
+    let offset =
+        reader.len() - overlay.len() as u64 + *find_all(overlay, AU3_MAGIC, 1).first()? as u64;
+    Some(AutoItScript {
+        format: script_format(&reader, offset),
+        location: "overlay".to_string(),
+        offset,
+        size: reader.len() - offset,
+        evidence,
+    })
+}
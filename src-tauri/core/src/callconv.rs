@@ -0,0 +1,69 @@
+// x86导出函数的参数字节数 来自stdcall修饰名 或函数结尾的ret imm16
+use crate::demangle::undecorate_stdcall;
+use crate::heuristics::IMAGE_SCN_MEM_EXECUTE;
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, DataDirectory, ExportFunction, Section};
+
+// 从入口向后查找返回指令的范围
+const MAX_SCAN_BYTES: u64 = 0x400;
+// 超过该值的ret imm16多半是误匹配
+const MAX_STACK_BYTES: u16 = 0x100;
+// 返回指令前常见的收尾指令: pop ebp/ebx/esi/edi, leave
+const EPILOGUE_BYTES: &[u8] = &[0x5D, 0x5B, 0x5E, 0x5F, 0xC9];
+
+// 第一个紧跟在收尾指令之后的ret 不做反汇编 只用于推测
+fn scan_return(code: &[u8]) -> Option<u16> {
+    let reader = BoundedReader::new(code);
+    for (i, pair) in code.windows(2).enumerate() {
+        if !EPILOGUE_BYTES.contains(&pair[0]) {
+            continue;
+        }
+        match pair[1] {
+            0xC3 => return Some(0),
+            0xC2 => {
+                let bytes = reader.u16(i as u64 + 2).ok()?;
+                if bytes % 4 == 0 && bytes <= MAX_STACK_BYTES {
+                    return Some(bytes);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// 修饰名优先 转发导出(地址位于导出目录内)和导出的变量没有代码
+pub(crate) fn infer_stack_bytes(
+    data: &[u8],
+    sections: &[Section],
+    export_table: &mut [ExportFunction],
+    directory: DataDirectory,
+) {
+    let directory_end = directory.rva.saturating_add(directory.size);
+    for export in export_table.iter_mut() {
+        if let Some((_, bytes)) = undecorate_stdcall(&export.name) {
+            export.stack_bytes = Some(bytes);
+            export.stack_bytes_source = Some("decoration".to_string());
+            continue;
+        }
+        if export.address == 0
+            || (export.address >= directory.rva && export.address < directory_end)
+        {
+            continue;
+        }
+        // 导出的变量不在可执行节中
+        let executable = sections.iter().any(|section| {
+            export.address >= section.rva
+                && export.address < section.rv_end
+                && section.characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+        });
+        let Some(offset) = rva_to_offset(sections, export.address).filter(|_| executable) else {
+            continue;
+        };
+        let code = BoundedReader::new(data).bytes_truncated(offset as u64, MAX_SCAN_BYTES);
+        if let Some(bytes) = scan_return(code) {
+            export.stack_bytes = Some(bytes as u32);
+            export.stack_bytes_source = Some("ret".to_string());
+        }
+    }
+}
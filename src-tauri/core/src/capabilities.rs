@@ -0,0 +1,406 @@
+// 能力与反分析特征检测 依据导入函数、代码字节特征和字符串
+use serde::{Deserialize, Serialize};
+
+use crate::heuristics::{is_executable, Severity};
+use crate::reader::BoundedReader;
+use crate::{offset_to_rva, ImportTableEntry, Section};
+
+// 每个特征最多保留的证据数量
+const MAX_EVIDENCE: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Evidence {
+    pub description: String,
+    // 文件偏移
+    pub offset: Option<u64>,
+    pub rva: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Indicator {
+    pub category: String,
+    pub name: String,
+    pub severity: Severity,
+    pub description: String,
+    pub evidence: Vec<Evidence>,
+}
+
+// 反调试相关的导入函数
+const ANTI_DEBUG_IMPORTS: &[&str] = &[
+    "IsDebuggerPresent",
+    "CheckRemoteDebuggerPresent",
+    "NtQueryInformationProcess",
+    "ZwQueryInformationProcess",
+    "NtSetInformationThread",
+    "ZwSetInformationThread",
+    "NtQuerySystemInformation",
+    "ZwQuerySystemInformation",
+    "NtQueryObject",
+    "OutputDebugStringA",
+    "OutputDebugStringW",
+    "DebugActiveProcess",
+    "DbgUiRemoteBreakin",
+    "DbgBreakPoint",
+];
+
+// 计时相关的导入函数 单独出现很常见 只作为参考
+const TIMING_IMPORTS: &[&str] = &[
+    "GetTickCount",
+    "GetTickCount64",
+    "QueryPerformanceCounter",
+    "timeGetTime",
+    "NtQuerySystemTime",
+    "NtQueryPerformanceCounter",
+];
+
+// 反虚拟机相关的导入函数
+const ANTI_VM_IMPORTS: &[&str] = &["wine_get_unix_file_name", "GetSystemFirmwareTable"];
+
+// 调试器/分析工具名称
+const ANALYSIS_TOOL_STRINGS: &[&str] = &[
+    "OLLYDBG",
+    "x64dbg",
+    "x32dbg",
+    "WinDbgFrameClass",
+    "ida64.exe",
+    "idaq.exe",
+    "ProcessHacker",
+    "procmon",
+    "Wireshark",
+    "ImmunityDebugger",
+];
+
+// 虚拟机/沙箱特征字符串
+const VM_STRINGS: &[&str] = &[
+    "VMware",
+    "VBoxService",
+    "VBoxGuest",
+    "VBoxMouse",
+    "VirtualBox",
+    "vmtoolsd",
+    "vmci.sys",
+    "SbieDll.dll",
+    "QEMU",
+    "Xen HVM",
+    "prl_tools",
+];
+
+// 代码中的反虚拟机字节特征
+const VM_BYTE_PATTERNS: &[(&str, &[u8])] = &[
+    // mov eax, 0x40000000; cpuid 查询hypervisor信息叶
+    (
+        "cpuid hypervisor leaf",
+        &[0xB8, 0x00, 0x00, 0x00, 0x40, 0x0F, 0xA2],
+    ),
+    // bt ecx, 31 检查cpuid返回的hypervisor位
+    ("cpuid hypervisor bit test", &[0x0F, 0xBA, 0xE1, 0x1F]),
+    // mov eax, 'VMXh' VMware后门端口魔数
+    ("VMware backdoor magic", &[0xB8, 0x68, 0x58, 0x4D, 0x56]),
+];
+
+// rdtsc指令 用于计时检测单步调试
+const RDTSC: &[u8] = &[0x0F, 0x31];
+
+pub fn find_all(haystack: &[u8], needle: &[u8], limit: usize) -> Vec<usize> {
+    let mut result: Vec<usize> = Vec::new();
+    if needle.is_empty() || haystack.len() < needle.len() {
+        return result;
+    }
+    let first = needle[0];
+    let mut i = 0;
+    while i + needle.len() <= haystack.len() {
+        match haystack[i..=haystack.len() - needle.len()]
+            .iter()
+            .position(|&b| b == first)
+        {
+            Some(pos) => {
+                let start = i + pos;
+                if &haystack[start..start + needle.len()] == needle {
+                    result.push(start);
+                    if result.len() >= limit {
+                        break;
+                    }
+                }
+                i = start + 1;
+            }
+            None => break,
+        }
+    }
+    result
+}
+
+fn utf16_bytes(text: &str) -> Vec<u8> {
+    text.encode_utf16().flat_map(|c| c.to_le_bytes()).collect()
+}
+
+// 在整个文件中查找ASCII和UTF-16LE形式的字符串
+fn find_string(data: &[u8], sections: &[Section], text: &str) -> Vec<Evidence> {
+    let mut evidence: Vec<Evidence> = Vec::new();
+    for (encoding, needle) in [
+        ("ascii", text.as_bytes().to_vec()),
+        ("utf16", utf16_bytes(text)),
+    ] {
+        for offset in find_all(data, &needle, MAX_EVIDENCE) {
+            evidence.push(Evidence {
+                description: format!("字符串 \"{}\" ({})", text, encoding),
+                offset: Some(offset as u64),
+                rva: offset_to_rva(sections, offset as u64),
+            });
+        }
+    }
+    evidence.truncate(MAX_EVIDENCE);
+    evidence
+}
+
+// 只在可执行节区中查找字节特征
+fn find_code_pattern(data: &[u8], sections: &[Section], pattern: &[u8]) -> Vec<Evidence> {
+    let reader = BoundedReader::new(data);
+    let mut evidence: Vec<Evidence> = Vec::new();
+    for section in sections.iter().filter(|s| is_executable(s)) {
+        let start = section.ptr_raw_data as u64;
+        let code = reader.bytes_truncated(start, section.size_raw_data as u64);
+        for offset in find_all(code, pattern, MAX_EVIDENCE) {
+            let offset = start + offset as u64;
+            evidence.push(Evidence {
+                description: format!("{} 节区中的字节特征", section.name),
+                offset: Some(offset),
+                rva: offset_to_rva(sections, offset),
+            });
+        }
+    }
+    evidence.truncate(MAX_EVIDENCE);
+    evidence
+}
+
+// 查找导入函数 忽略大小写
+pub(crate) fn find_imports(import_table: &[ImportTableEntry], names: &[&str]) -> Vec<Evidence> {
+    let mut evidence: Vec<Evidence> = Vec::new();
+    for entry in import_table {
+        for func in &entry.functions {
+            if names
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(&func.name))
+            {
+                evidence.push(Evidence {
+                    description: format!("导入 {}!{}", entry.dll_name, func.name),
+                    offset: None,
+                    rva: Some(func.thunk_rva),
+                });
+            }
+        }
+    }
+    evidence
+}
+
+// 检测反调试和反虚拟机特征
+pub(crate) fn detect_anti_analysis(
+    import_table: &[ImportTableEntry],
+    sections: &[Section],
+    data: &[u8],
+) -> Vec<Indicator> {
+    let mut indicators: Vec<Indicator> = Vec::new();
+
+    let evidence = find_imports(import_table, ANTI_DEBUG_IMPORTS);
+    if !evidence.is_empty() {
+        indicators.push(Indicator {
+            category: "anti_debug".to_string(),
+            name: "debugger_detection_api".to_string(),
+            severity: if evidence.len() > 1 {
+                Severity::Medium
+            } else {
+                Severity::Low
+            },
+            description: "导入了用于检测调试器的API".to_string(),
+            evidence,
+        });
+    }
+
+    let evidence: Vec<Evidence> = ANALYSIS_TOOL_STRINGS
+        .iter()
+        .flat_map(|text| find_string(data, sections, text))
+        .collect();
+    if !evidence.is_empty() {
+        indicators.push(Indicator {
+            category: "anti_debug".to_string(),
+            name: "analysis_tool_names".to_string(),
+            severity: Severity::Medium,
+            description: "包含调试器或分析工具的窗口/进程名 可能用于检测分析环境".to_string(),
+            evidence,
+        });
+    }
+
+    let mut evidence = find_imports(import_table, TIMING_IMPORTS);
+    let rdtsc = find_code_pattern(data, sections, RDTSC);
+    let rdtsc_count = rdtsc.len();
+    evidence.extend(rdtsc);
+    // 计时API在CRT中很常见 只有多种计时手段同时出现才提高级别
+    if !evidence.is_empty() {
+        indicators.push(Indicator {
+            category: "timing".to_string(),
+            name: "timing_checks".to_string(),
+            severity: if rdtsc_count > 0 && evidence.len() > rdtsc_count + 1 {
+                Severity::Low
+            } else {
+                Severity::Info
+            },
+            description: "使用计时API或rdtsc指令 可用于通过执行耗时检测单步调试".to_string(),
+            evidence,
+        });
+    }
+
+    let mut evidence = find_imports(import_table, ANTI_VM_IMPORTS);
+    for (name, pattern) in VM_BYTE_PATTERNS {
+        for mut item in find_code_pattern(data, sections, pattern) {
+            item.description = format!("{} ({})", item.description, name);
+            evidence.push(item);
+        }
+    }
+    for text in VM_STRINGS {
+        evidence.extend(find_string(data, sections, text));
+    }
+    if !evidence.is_empty() {
+        indicators.push(Indicator {
+            category: "anti_vm".to_string(),
+            name: "virtual_machine_detection".to_string(),
+            severity: Severity::Medium,
+            description: "包含虚拟机/沙箱特征字符串或cpuid等检测指令".to_string(),
+            evidence,
+        });
+    }
+
+    indicators
+}
+
+// 进程注入手法 每组中任意一个导入满足即可
+struct InjectionTechnique {
+    name: &'static str,
+    description: &'static str,
+    requirements: &'static [&'static [&'static str]],
+}
+
+const WRITE_REMOTE_MEMORY: &[&str] = &[
+    "WriteProcessMemory",
+    "NtWriteVirtualMemory",
+    "ZwWriteVirtualMemory",
+];
+const ALLOC_REMOTE_MEMORY: &[&str] = &[
+    "VirtualAllocEx",
+    "VirtualAlloc2",
+    "NtAllocateVirtualMemory",
+    "ZwAllocateVirtualMemory",
+];
+
+const INJECTION_TECHNIQUES: &[InjectionTechnique] = &[
+    InjectionTechnique {
+        name: "remote_thread",
+        description: "打开目标进程、分配并写入远程内存后创建远程线程 (经典DLL/Shellcode注入)",
+        requirements: &[
+            &["OpenProcess", "NtOpenProcess", "ZwOpenProcess"],
+            ALLOC_REMOTE_MEMORY,
+            WRITE_REMOTE_MEMORY,
+            &[
+                "CreateRemoteThread",
+                "CreateRemoteThreadEx",
+                "NtCreateThreadEx",
+                "ZwCreateThreadEx",
+                "RtlCreateUserThread",
+            ],
+        ],
+    },
+    InjectionTechnique {
+        name: "apc_injection",
+        description: "写入远程内存后通过APC队列在目标线程中执行代码",
+        requirements: &[
+            &["OpenProcess", "OpenThread", "NtOpenProcess", "NtOpenThread"],
+            ALLOC_REMOTE_MEMORY,
+            WRITE_REMOTE_MEMORY,
+            &[
+                "QueueUserAPC",
+                "QueueUserAPC2",
+                "NtQueueApcThread",
+                "NtQueueApcThreadEx",
+            ],
+        ],
+    },
+    InjectionTechnique {
+        name: "section_mapping",
+        description: "创建共享节区并映射到目标进程 (NtMapViewOfSection注入)",
+        requirements: &[
+            &[
+                "NtCreateSection",
+                "ZwCreateSection",
+                "CreateFileMappingA",
+                "CreateFileMappingW",
+            ],
+            &[
+                "NtMapViewOfSection",
+                "ZwMapViewOfSection",
+                "MapViewOfFile2",
+                "MapViewOfFileNuma2",
+            ],
+        ],
+    },
+    InjectionTechnique {
+        name: "thread_hijacking",
+        description: "挂起目标线程并修改其上下文 使其跳转到写入的代码",
+        requirements: &[
+            &["OpenThread", "NtOpenThread"],
+            &["SuspendThread", "NtSuspendThread"],
+            &[
+                "GetThreadContext",
+                "Wow64GetThreadContext",
+                "NtGetContextThread",
+            ],
+            &[
+                "SetThreadContext",
+                "Wow64SetThreadContext",
+                "NtSetContextThread",
+            ],
+            WRITE_REMOTE_MEMORY,
+        ],
+    },
+    InjectionTechnique {
+        name: "process_hollowing",
+        description: "以挂起方式创建进程、卸载原始映像并写入新映像 (Process Hollowing)",
+        requirements: &[
+            &["CreateProcessA", "CreateProcessW", "CreateProcessInternalW"],
+            &["NtUnmapViewOfSection", "ZwUnmapViewOfSection"],
+            WRITE_REMOTE_MEMORY,
+            &[
+                "SetThreadContext",
+                "Wow64SetThreadContext",
+                "NtSetContextThread",
+            ],
+            &["ResumeThread", "NtResumeThread"],
+        ],
+    },
+];
+
+// 检测可用于进程注入的导入组合
+pub(crate) fn detect_injection(import_table: &[ImportTableEntry]) -> Vec<Indicator> {
+    let mut indicators: Vec<Indicator> = Vec::new();
+
+    for technique in INJECTION_TECHNIQUES {
+        let mut evidence: Vec<Evidence> = Vec::new();
+        let mut satisfied = true;
+        for alternatives in technique.requirements {
+            let found = find_imports(import_table, alternatives);
+            if found.is_empty() {
+                satisfied = false;
+                break;
+            }
+            evidence.extend(found);
+        }
+        if satisfied {
+            indicators.push(Indicator {
+                category: "injection".to_string(),
+                name: technique.name.to_string(),
+                severity: Severity::High,
+                description: technique.description.to_string(),
+                evidence,
+            });
+        }
+    }
+
+    indicators
+}
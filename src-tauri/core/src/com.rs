@@ -0,0 +1,289 @@
+// COM注册面 自注册导出、类型库资源以及ATL注册脚本(REGISTRY资源)中声明的类和Shell扩展
+use serde::{Deserialize, Serialize};
+
+use crate::guids::known_name;
+use crate::reader::decode_utf16;
+use crate::resources::{resource_data, ResourceEntry};
+use crate::ExportFunction;
+
+const COM_EXPORTS: &[&str] = &[
+    "DllRegisterServer",
+    "DllUnregisterServer",
+    "DllGetClassObject",
+    "DllCanUnloadNow",
+    "DllInstall",
+];
+// 注册到这些键下的类会被资源管理器或IE加载
+const SHELL_EXTENSION_KEYS: &[&str] = &[
+    "ContextMenuHandlers",
+    "PropertySheetHandlers",
+    "CopyHookHandlers",
+    "DragDropHandlers",
+    "IconHandler",
+    "ShellIconOverlayIdentifiers",
+    "ShellExecuteHooks",
+    "Browser Helper Objects",
+    "Shell Extensions",
+    "ColumnHandlers",
+    "PreviewHandlers",
+];
+// 注册脚本的修饰词
+const RGS_MODIFIERS: &[&str] = &["NoRemove", "ForceRemove", "Delete"];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ComClass {
+    pub clsid: String,
+    pub name: Option<String>,
+    pub prog_id: Option<String>,
+    // InprocServer32 / LocalServer32
+    pub server: Option<String>,
+    pub threading_model: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ComSurface {
+    pub exports: Vec<String>,
+    pub typelib_count: usize,
+    pub classes: Vec<ComClass>,
+    // 注册脚本中的Shell扩展/BHO键路径
+    pub shell_extensions: Vec<String>,
+}
+
+// 注册脚本中的一个键 default为键的默认值
+#[derive(Debug, Default)]
+struct RgsKey {
+    path: Vec<String>,
+    default: Option<String>,
+    values: Vec<(String, String)>,
+}
+
+// 注册脚本一般为ANSI 带BOM时按UTF-16解码
+fn script_text(bytes: &[u8]) -> String {
+    match bytes.strip_prefix(&[0xFF, 0xFE]) {
+        Some(wide) => decode_utf16(wide),
+        None => String::from_utf8_lossy(bytes).to_string(),
+    }
+}
+
+// '{'之后紧跟 GUID + '}'
+fn braced_guid(rest: impl Iterator<Item = char>) -> Option<()> {
+    let text: String = rest.take(37).collect();
+    let guid = text.strip_suffix('}')?;
+    let valid = guid.len() == 36
+        && guid.char_indices().all(|(i, c)| match i {
+            8 | 13 | 18 | 23 => c == '-',
+            _ => c.is_ascii_hexdigit(),
+        });
+    valid.then_some(())
+}
+
+fn tokenize(script: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut chars = script.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // 未加引号的{GUID}是键名 不是块的开始
+            '{' if braced_guid(chars.clone()).is_some() => {
+                let guid: String = chars.by_ref().take(37).collect();
+                tokens.push(format!("{{{}", guid));
+            }
+            '{' | '}' | '=' => tokens.push(c.to_string()),
+            // 单引号字符串 ''表示一个引号
+            '\'' => {
+                let mut text = String::new();
+                while let Some(c) = chars.next() {
+                    if c == '\'' {
+                        if chars.peek() == Some(&'\'') {
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    text.push(c);
+                }
+                tokens.push(text);
+            }
+            c if c.is_whitespace() => {}
+            c => {
+                let mut word = c.to_string();
+                while let Some(&next) = chars.peek() {
+                    if next.is_whitespace() || matches!(next, '{' | '}' | '=' | '\'') {
+                        break;
+                    }
+                    word.push(next);
+                    chars.next();
+                }
+                tokens.push(word);
+            }
+        }
+    }
+    tokens
+}
+
+// 形如 "= s 'text'" 的赋值 返回值和消耗的token数
+fn assignment(tokens: &[String]) -> Option<(String, usize)> {
+    if tokens.first()? != "=" {
+        return None;
+    }
+    Some((tokens.get(2)?.clone(), 3))
+}
+
+fn parse_rgs(script: &str) -> Vec<RgsKey> {
+    let tokens = tokenize(script);
+    let mut keys: Vec<RgsKey> = Vec::new();
+    // 当前嵌套的键 以及最近声明的键(遇到'{'时进入)
+    let mut stack: Vec<usize> = Vec::new();
+    let mut last: Option<usize> = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i].as_str();
+        i += 1;
+        match token {
+            "{" => {
+                if let Some(key) = last.take() {
+                    stack.push(key);
+                }
+            }
+            "}" => {
+                stack.pop();
+                last = None;
+            }
+            token if RGS_MODIFIERS.iter().any(|m| m.eq_ignore_ascii_case(token)) => {}
+            token if token.eq_ignore_ascii_case("val") => {
+                let Some(name) = tokens.get(i).cloned() else {
+                    break;
+                };
+                i += 1;
+                if let Some((value, used)) = assignment(&tokens[i..]) {
+                    i += used;
+                    if let Some(&key) = stack.last() {
+                        keys[key].values.push((name, value));
+                    }
+                }
+            }
+            name => {
+                let mut path: Vec<String> = stack
+                    .last()
+                    .map(|&parent| keys[parent].path.clone())
+                    .unwrap_or_default();
+                path.push(name.to_string());
+                let mut key = RgsKey {
+                    path,
+                    ..Default::default()
+                };
+                if let Some((value, used)) = assignment(&tokens[i..]) {
+                    i += used;
+                    key.default = Some(value);
+                }
+                keys.push(key);
+                last = Some(keys.len() - 1);
+            }
+        }
+    }
+    keys
+}
+
+fn strip_braces(name: &str) -> Option<String> {
+    let guid = name.strip_prefix('{')?.strip_suffix('}')?;
+    (guid.len() == 36).then(|| guid.to_ascii_uppercase())
+}
+
+fn child<'a>(keys: &'a [RgsKey], parent: &RgsKey, name: &str) -> Option<&'a RgsKey> {
+    keys.iter().find(|key| {
+        key.path.len() == parent.path.len() + 1
+            && key.path.starts_with(&parent.path)
+            && key
+                .path
+                .last()
+                .is_some_and(|last| last.eq_ignore_ascii_case(name))
+    })
+}
+
+fn child_count(keys: &[RgsKey], parent: &RgsKey) -> usize {
+    keys.iter()
+        .filter(|key| key.path.len() == parent.path.len() + 1 && key.path.starts_with(&parent.path))
+        .count()
+}
+
+fn collect_classes(keys: &[RgsKey], classes: &mut Vec<ComClass>) {
+    for key in keys {
+        let [.., parent, name] = key.path.as_slice() else {
+            continue;
+        };
+        if !parent.eq_ignore_ascii_case("CLSID") {
+            continue;
+        }
+        let Some(clsid) = strip_braces(name) else {
+            continue;
+        };
+        if classes.iter().any(|class| class.clsid == clsid) {
+            continue;
+        }
+        let server = ["InprocServer32", "LocalServer32"]
+            .into_iter()
+            .find_map(|server| child(keys, key, server).map(|key| (server, key)));
+        classes.push(ComClass {
+            name: key.default.clone().or_else(|| known_name(&clsid)),
+            prog_id: child(keys, key, "ProgID").and_then(|key| key.default.clone()),
+            server: server.map(|(server, _)| server.to_string()),
+            threading_model: server.and_then(|(_, key)| {
+                key.values
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("ThreadingModel"))
+                    .map(|(_, value)| value.clone())
+            }),
+            clsid,
+        });
+    }
+}
+
+pub(crate) fn detect_com_surface(
+    data: &[u8],
+    export_table: &[ExportFunction],
+    resources: &[ResourceEntry],
+) -> Option<ComSurface> {
+    let exports: Vec<String> = COM_EXPORTS
+        .iter()
+        .filter(|name| export_table.iter().any(|f| f.name == **name))
+        .map(|name| name.to_string())
+        .collect();
+    let typelib_count = resources
+        .iter()
+        .filter(|r| r.type_name.eq_ignore_ascii_case("TYPELIB"))
+        .count();
+    if exports.is_empty() && typelib_count == 0 {
+        return None;
+    }
+
+    let mut classes: Vec<ComClass> = Vec::new();
+    let mut shell_extensions: Vec<String> = Vec::new();
+    for resource in resources
+        .iter()
+        .filter(|r| r.type_name.eq_ignore_ascii_case("REGISTRY"))
+    {
+        let script = script_text(resource_data(data, resource));
+        let keys = parse_rgs(&script);
+        collect_classes(&keys, &mut classes);
+        for key in &keys {
+            let is_extension = key.path.iter().any(|segment| {
+                SHELL_EXTENSION_KEYS
+                    .iter()
+                    .any(|name| name.eq_ignore_ascii_case(segment))
+            });
+            // 只记录最深一层 即具体的处理程序
+            if is_extension && child_count(&keys, key) == 0 {
+                let mut path = key.path.join("\\");
+                if let Some(value) = &key.default {
+                    path.push_str(&format!(" = {}", value));
+                }
+                shell_extensions.push(path);
+            }
+        }
+    }
+    Some(ComSurface {
+        exports,
+        typelib_count,
+        classes,
+        shell_extensions,
+    })
+}
@@ -0,0 +1,89 @@
+// 文件覆盖率: 头部和节区原始数据描述了文件中多少字节 其余为覆盖数据或节间空隙 用于快速估计隐藏数据的多少
+use serde::{Deserialize, Serialize};
+
+use crate::{DataDirectory, Section};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FileCoverage {
+    pub headers: u64,
+    // 各节原始数据的并集 重叠部分只计一次 不含与头部重叠的字节
+    pub sections: u64,
+    // 证书表位于文件末尾 不映射到内存 但属于PE结构的一部分
+    pub certificate: u64,
+    // 最后一节之后、证书表以外的字节
+    pub overlay: u64,
+    // 节与节之间未被任何节引用的字节
+    pub gaps: u64,
+    // (头部 + 节区 + 证书) / 文件大小
+    pub covered_percent: f64,
+}
+
+// 区间并集的总长度
+fn union_length(mut ranges: Vec<(u64, u64)>) -> u64 {
+    ranges.retain(|(start, end)| start < end);
+    ranges.sort();
+    let mut total = 0;
+    let mut current: Option<(u64, u64)> = None;
+    for (start, end) in ranges {
+        match current {
+            Some((current_start, current_end)) if start <= current_end => {
+                current = Some((current_start, current_end.max(end)));
+            }
+            _ => {
+                if let Some((current_start, current_end)) = current {
+                    total += current_end - current_start;
+                }
+                current = Some((start, end));
+            }
+        }
+    }
+    total + current.map_or(0, |(start, end)| end - start)
+}
+
+pub(crate) fn compute_coverage(
+    file_size: u64,
+    sections: &[Section],
+    size_of_headers: u32,
+    overlay_offset: u64,
+    security_directory: DataDirectory,
+) -> FileCoverage {
+    let headers = (size_of_headers as u64).min(file_size);
+    let section_ranges: Vec<(u64, u64)> = sections
+        .iter()
+        .filter(|section| section.size_raw_data != 0)
+        .map(|section| {
+            let start = (section.ptr_raw_data as u64).clamp(headers, file_size);
+            let end = (section.ptr_raw_data as u64 + section.size_raw_data as u64)
+                .clamp(start, file_size);
+            (start, end)
+        })
+        .collect();
+    let sections_length = union_length(section_ranges);
+
+    // 证书表的VirtualAddress是文件偏移 只计入覆盖数据范围内的部分
+    let overlay_offset = overlay_offset.clamp(headers, file_size);
+    let certificate_start = (security_directory.rva as u64).clamp(overlay_offset, file_size);
+    let certificate = if security_directory.size == 0 {
+        0
+    } else {
+        (security_directory.rva as u64 + security_directory.size as u64)
+            .clamp(certificate_start, file_size)
+            - certificate_start
+    };
+    let overlay = file_size - overlay_offset - certificate;
+    let gaps = overlay_offset - headers - sections_length.min(overlay_offset - headers);
+
+    let covered = headers + sections_length + certificate;
+    FileCoverage {
+        headers,
+        sections: sections_length,
+        certificate,
+        overlay,
+        gaps,
+        covered_percent: if file_size == 0 {
+            0.0
+        } else {
+            covered as f64 * 100.0 / file_size as f64
+        },
+    }
+}
@@ -0,0 +1,144 @@
+// 调试目录 IMAGE_DEBUG_DIRECTORY数组
+use serde::{Deserialize, Serialize};
+
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, DataDirectory, Section};
+
+const DEBUG_ENTRY_SIZE: u64 = 28;
+// 调试目录项数量上限
+const MAX_DEBUG_ENTRIES: u64 = 64;
+pub const IMAGE_DEBUG_TYPE_POGO: u32 = 13;
+pub const IMAGE_DEBUG_TYPE_REPRO: u32 = 16;
+// POGO贡献节数量上限
+const MAX_POGO_ENTRIES: usize = 4096;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DebugEntry {
+    pub debug_type: u32,
+    pub type_name: String,
+    pub time_date_stamp: u32,
+    pub size: u32,
+    pub rva: u32,
+    pub file_offset: u32,
+}
+
+// 链接器按"节名$后缀"合并的一段输入节 如.text$mn、.rdata$zzzdbg
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PogoEntry {
+    pub name: String,
+    pub rva: u32,
+    pub size: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PogoInfo {
+    // LTCG(链接时代码生成) / PGI(插桩) / PGO / PGU(使用配置文件优化)
+    pub signature: String,
+    pub entries: Vec<PogoEntry>,
+}
+
+fn type_name(debug_type: u32) -> String {
+    match debug_type {
+        1 => "COFF".to_string(),
+        2 => "CODEVIEW".to_string(),
+        3 => "FPO".to_string(),
+        4 => "MISC".to_string(),
+        5 => "EXCEPTION".to_string(),
+        6 => "FIXUP".to_string(),
+        7 => "OMAP_TO_SRC".to_string(),
+        8 => "OMAP_FROM_SRC".to_string(),
+        9 => "BORLAND".to_string(),
+        11 => "CLSID".to_string(),
+        12 => "VC_FEATURE".to_string(),
+        13 => "POGO".to_string(),
+        14 => "ILTCG".to_string(),
+        16 => "REPRO".to_string(),
+        17 => "EMBEDDED_PORTABLE_PDB".to_string(),
+        19 => "PDBCHECKSUM".to_string(),
+        20 => "EX_DLLCHARACTERISTICS".to_string(),
+        other => other.to_string(),
+    }
+}
+
+pub(crate) fn parse_debug_directory(
+    data: &[u8],
+    sections: &[Section],
+    directory: DataDirectory,
+) -> Vec<DebugEntry> {
+    let mut entries: Vec<DebugEntry> = Vec::new();
+    if directory.rva == 0 || directory.size == 0 {
+        return entries;
+    }
+    let Some(table_ptr) = rva_to_offset(sections, directory.rva) else {
+        return entries;
+    };
+    let reader = BoundedReader::new(data);
+    let count = (directory.size as u64 / DEBUG_ENTRY_SIZE).min(MAX_DEBUG_ENTRIES);
+    for i in 0..count {
+        let entry = table_ptr as u64 + i * DEBUG_ENTRY_SIZE;
+        let (Ok(time_date_stamp), Ok(debug_type), Ok(size), Ok(rva), Ok(file_offset)) = (
+            reader.u32(entry + 4),
+            reader.u32(entry + 12),
+            reader.u32(entry + 16),
+            reader.u32(entry + 20),
+            reader.u32(entry + 24),
+        ) else {
+            break;
+        };
+        entries.push(DebugEntry {
+            debug_type,
+            type_name: type_name(debug_type),
+            time_date_stamp,
+            size,
+            rva,
+            file_offset,
+        });
+    }
+    entries
+}
+
+// 可重现构建的TimeDateStamp是内容哈希 不是时间
+pub(crate) fn is_reproducible(entries: &[DebugEntry]) -> bool {
+    entries
+        .iter()
+        .any(|entry| entry.debug_type == IMAGE_DEBUG_TYPE_REPRO)
+}
+
+// POGO数据: 4字节签名 后接 { rva, size, 以0结尾并按4字节对齐的名称 }
+pub(crate) fn parse_pogo(data: &[u8], entries: &[DebugEntry]) -> Option<PogoInfo> {
+    let entry = entries
+        .iter()
+        .find(|entry| entry.debug_type == IMAGE_DEBUG_TYPE_POGO)?;
+    let reader = BoundedReader::new(data);
+    let start = entry.file_offset as u64;
+    // 超出文件的部分截断 起点必须在文件内
+    let length = (entry.size as u64).min(reader.len().checked_sub(start)?);
+    let pogo = BoundedReader::new(reader.bytes(start, length).ok()?);
+    let signature = String::from_utf8_lossy(pogo.bytes(0, 4).ok()?)
+        .chars()
+        .rev()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>();
+
+    let mut contributions: Vec<PogoEntry> = Vec::new();
+    let mut offset = 4;
+    while contributions.len() < MAX_POGO_ENTRIES {
+        let (Ok(rva), Ok(size)) = (pogo.u32(offset), pogo.u32(offset + 4)) else {
+            break;
+        };
+        let name_start = offset + 8;
+        let Ok(name) = pogo.c_string(name_start, usize::MAX) else {
+            break;
+        };
+        contributions.push(PogoEntry {
+            name: String::from_utf8_lossy(name).to_string(),
+            rva,
+            size,
+        });
+        offset = name_start + (name.len() as u64 + 4) / 4 * 4;
+    }
+    Some(PogoInfo {
+        signature,
+        entries: contributions,
+    })
+}
@@ -0,0 +1,131 @@
+// Delphi程序识别 依据RT_RCDATA中的窗体(TPF0)、PACKAGEINFO单元列表、节区布局和版权字符串
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::find_all;
+use crate::reader::BoundedReader;
+use crate::resources::{resource_data, ResourceEntry, RT_RCDATA};
+use crate::Section;
+
+// 窗体资源的签名
+const FORM_SIGNATURE: &[u8] = b"TPF0";
+// PACKAGEINFO中单元数量的上限
+const MAX_UNITS: usize = 4096;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DelphiInfo {
+    // 依据节区布局和字符串推断的版本范围
+    pub version_hint: Option<String>,
+    // 窗体资源名 如TFORM1
+    pub forms: Vec<String>,
+    // PACKAGEINFO中的单元
+    pub units: Vec<String>,
+    // PACKAGEINFO中依赖的运行时包
+    pub packages: Vec<String>,
+    pub evidence: Vec<String>,
+}
+
+fn read_cstring(reader: &BoundedReader, offset: u64) -> Option<(String, u64)> {
+    let bytes = reader.c_string(offset, usize::MAX).ok()?;
+    Some((
+        String::from_utf8_lossy(bytes).to_string(),
+        offset + bytes.len() as u64 + 1,
+    ))
+}
+
+// PACKAGEINFO: Flags, RequiresCount, {HashCode, Name}*, ContainsCount, {Flags, HashCode, Name}*
+fn parse_package_info(data: &[u8]) -> Option<(Vec<String>, Vec<String>)> {
+    let reader = BoundedReader::new(data);
+    let requires = reader.u32(4).ok()? as usize;
+    let mut cursor = 8;
+    let mut packages: Vec<String> = Vec::new();
+    for _ in 0..requires.min(MAX_UNITS) {
+        let (name, next) = read_cstring(&reader, cursor + 1)?;
+        packages.push(name);
+        cursor = next;
+    }
+    let contains = reader.u32(cursor).ok()? as usize;
+    cursor += 4;
+    let mut units: Vec<String> = Vec::new();
+    for _ in 0..contains.min(MAX_UNITS) {
+        let Some((name, next)) = read_cstring(&reader, cursor + 2) else {
+            break;
+        };
+        units.push(name);
+        cursor = next;
+    }
+    Some((packages, units))
+}
+
+pub(crate) fn detect_delphi(
+    data: &[u8],
+    sections: &[Section],
+    resources: &[ResourceEntry],
+) -> Option<DelphiInfo> {
+    let mut evidence: Vec<String> = Vec::new();
+
+    let forms: Vec<String> = resources
+        .iter()
+        .filter(|r| r.type_id == Some(RT_RCDATA))
+        .filter(|r| resource_data(data, r).starts_with(FORM_SIGNATURE))
+        .map(|r| r.name.clone())
+        .collect();
+    if !forms.is_empty() {
+        evidence.push(format!("{} 个TPF0窗体资源", forms.len()));
+    }
+
+    let mut packages: Vec<String> = Vec::new();
+    let mut units: Vec<String> = Vec::new();
+    if let Some(resource) = resources
+        .iter()
+        .find(|r| r.type_id == Some(RT_RCDATA) && r.name == "PACKAGEINFO")
+    {
+        evidence.push("PACKAGEINFO资源".to_string());
+        if let Some((required, contained)) = parse_package_info(resource_data(data, resource)) {
+            packages = required;
+            units = contained;
+        }
+    }
+    if resources
+        .iter()
+        .any(|r| r.type_id == Some(RT_RCDATA) && r.name == "DVCLAL")
+    {
+        evidence.push("DVCLAL资源".to_string());
+    }
+
+    let has_section = |name: &str| sections.iter().any(|s| s.name == name);
+    let legacy_layout = has_section("CODE") && has_section("DATA");
+    if legacy_layout {
+        evidence.push("CODE/DATA节区布局".to_string());
+    }
+    let embarcadero = !find_all(data, b"Embarcadero", 1).is_empty();
+    let borland = !find_all(data, b"Borland", 1).is_empty();
+    let has_rtl_key = !find_all(data, b"SOFTWARE\\Borland\\Delphi\\RTL", 1).is_empty()
+        || !find_all(data, b"Software\\Embarcadero\\", 1).is_empty();
+    if has_rtl_key {
+        evidence.push("Delphi RTL注册表路径".to_string());
+    }
+
+    // 窗体或PACKAGEINFO是强特征 字符串和节区布局只作为补充
+    if forms.is_empty() && units.is_empty() && !(legacy_layout && (borland || has_rtl_key)) {
+        return None;
+    }
+
+    // .itext在Delphi 2009引入 Embarcadero品牌从XE开始
+    let version_hint = if embarcadero {
+        Some("Delphi XE或更新".to_string())
+    } else if has_section(".itext") {
+        Some("Delphi 2009-2010".to_string())
+    } else if legacy_layout {
+        Some("Delphi 2007或更早".to_string())
+    } else {
+        None
+    };
+
+    Some(DelphiInfo {
+        version_hint,
+        forms,
+        units,
+        packages,
+        evidence,
+    })
+}
@@ -0,0 +1,45 @@
+// 符号名还原 支持Rust、Itanium C++ (MinGW/Clang) 以及MSVC修饰名的简单形式
+
+// 返回还原后的名称 无法识别时返回None
+pub fn demangle(name: &str) -> Option<String> {
+    if let Ok(symbol) = rustc_demangle::try_demangle(name) {
+        return Some(format!("{:#}", symbol));
+    }
+    if name.starts_with("_Z") {
+        if let Ok(symbol) = cpp_demangle::Symbol::new(name) {
+            if let Ok(text) = symbol.demangle(&cpp_demangle::DemangleOptions::default()) {
+                return Some(text);
+            }
+        }
+    }
+    if name.starts_with('?') {
+        return demangle_msvc(name);
+    }
+    None
+}
+
+// MSVC修饰名: ?Name@Scope1@Scope2@@<类型信息> 只还原作用域和名称
+fn demangle_msvc(name: &str) -> Option<String> {
+    let body = name.strip_prefix('?')?;
+    // 特殊名称(构造/析构/运算符)以?开头 不做处理
+    if body.starts_with('?') {
+        return None;
+    }
+    let qualified = &body[..body.find("@@")?];
+    let mut parts: Vec<&str> = qualified.split('@').collect();
+    if parts.iter().any(|part| part.is_empty()) {
+        return None;
+    }
+    parts.reverse();
+    Some(parts.join("::"))
+}
+
+// x86 stdcall/fastcall修饰名: _Name@12 / @Name@8 返回名称和参数字节数
+pub fn undecorate_stdcall(name: &str) -> Option<(String, u32)> {
+    let body = name.strip_prefix('_').or_else(|| name.strip_prefix('@'))?;
+    let (base, bytes) = body.rsplit_once('@')?;
+    if base.is_empty() || bytes.is_empty() || !bytes.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    Some((base.to_string(), bytes.parse().ok()?))
+}
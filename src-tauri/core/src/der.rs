@@ -0,0 +1,146 @@
+// 最小化的DER解析 只支持确定长度编码 够用于读取Authenticode中的PKCS#7结构
+use crate::reader::BoundedReader;
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_UTC_TIME: u8 = 0x17;
+pub(crate) const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+pub(crate) const TAG_SET: u8 = 0x31;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Tlv<'a> {
+    pub tag: u8,
+    // 不含标签和长度的内容
+    pub contents: &'a [u8],
+    // 含标签和长度的完整编码
+    pub raw: &'a [u8],
+}
+
+impl<'a> Tlv<'a> {
+    // 解析内容中依次排列的子元素 遇到无法解析的数据即停止
+    pub fn children(&self) -> Vec<Tlv<'a>> {
+        let mut children: Vec<Tlv<'a>> = Vec::new();
+        let mut rest = self.contents;
+        while let Some((tlv, next)) = parse_tlv(rest) {
+            children.push(tlv);
+            rest = next;
+        }
+        children
+    }
+
+    // 第index个子元素
+    pub fn child(&self, index: usize) -> Option<Tlv<'a>> {
+        self.children().get(index).copied()
+    }
+
+    // 上下文相关的标签 [n]
+    pub fn is_context(&self, number: u8) -> bool {
+        self.tag & 0xC0 == 0x80 && self.tag & 0x1F == number
+    }
+}
+
+// 读取一个TLV 返回该元素和剩余数据
+pub(crate) fn parse_tlv(data: &[u8]) -> Option<(Tlv<'_>, &[u8])> {
+    let reader = BoundedReader::new(data);
+    let [tag, first] = reader.array::<2>(0).ok()?;
+    // 不支持多字节标签
+    if tag & 0x1F == 0x1F {
+        return None;
+    }
+    let (length, header_size) = if first < 0x80 {
+        (first as u64, 2)
+    } else {
+        // 长格式: 后续count字节为大端序的长度
+        let count = (first & 0x7F) as u64;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let length = reader
+            .bytes(2, count)
+            .ok()?
+            .iter()
+            .fold(0u64, |length, &byte| (length << 8) | byte as u64);
+        (length, 2 + count)
+    };
+    let end = header_size + length;
+    Some((
+        Tlv {
+            tag,
+            contents: reader.bytes(header_size, length).ok()?,
+            raw: reader.bytes(0, end).ok()?,
+        },
+        reader.bytes(end, reader.len() - end).ok()?,
+    ))
+}
+
+// OID -> 点分形式
+pub(crate) fn oid_to_string(contents: &[u8]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    let mut value: u64 = 0;
+    for &byte in contents {
+        value = (value << 7) | (byte & 0x7F) as u64;
+        if byte & 0x80 != 0 {
+            continue;
+        }
+        if parts.is_empty() {
+            let first = (value / 40).min(2);
+            parts.push(first.to_string());
+            parts.push((value - first * 40).to_string());
+        } else {
+            parts.push(value.to_string());
+        }
+        value = 0;
+    }
+    parts.join(".")
+}
+
+// 字符串类型的值 UTF8String/PrintableString/IA5String按字节解码 BMPString按UTF-16BE解码
+pub(crate) fn decode_string(tlv: &Tlv) -> String {
+    match tlv.tag {
+        0x1E => {
+            let units: Vec<u16> = tlv
+                .contents
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => String::from_utf8_lossy(tlv.contents).to_string(),
+    }
+}
+
+// 公历日期 -> 自1970-01-01起的天数
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// UTCTime(YYMMDDHHMMSSZ)/GeneralizedTime(YYYYMMDDHHMMSS[.f]Z) -> Unix时间戳
+pub(crate) fn parse_time(tlv: &Tlv) -> Option<i64> {
+    let text = std::str::from_utf8(tlv.contents).ok()?;
+    let (year, rest) = match tlv.tag {
+        TAG_UTC_TIME => {
+            let year: i64 = text.get(0..2)?.parse().ok()?;
+            (
+                if year >= 50 { 1900 + year } else { 2000 + year },
+                text.get(2..)?,
+            )
+        }
+        TAG_GENERALIZED_TIME => (text.get(0..4)?.parse().ok()?, text.get(4..)?),
+        _ => return None,
+    };
+    let field = |index: usize| -> Option<i64> { rest.get(index * 2..index * 2 + 2)?.parse().ok() };
+    let (month, day, hour, minute, second) =
+        (field(0)?, field(1)?, field(2)?, field(3)?, field(4)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    Some(days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+}
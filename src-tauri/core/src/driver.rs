@@ -0,0 +1,139 @@
+// 内核驱动分析 Subsystem为NATIVE且导入ntoskrnl.exe/hal.dll时识别为驱动
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::find_all;
+use crate::reader::BoundedReader;
+use crate::{offset_to_rva, ImportTableEntry, Section};
+
+pub(crate) const IMAGE_SUBSYSTEM_NATIVE: u16 = 1;
+// DllCharacteristics中的FORCE_INTEGRITY 加载时强制校验签名
+pub(crate) const IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY: u16 = 0x0080;
+
+const KERNEL_MODULES: &[&str] = &["ntoskrnl.exe", "hal.dll"];
+// KMDF驱动通过wdfldr.sys的WdfVersionBind绑定框架
+const WDF_LOADER: &str = "wdfldr.sys";
+// 创建设备对象或注册过滤器的函数
+const DEVICE_APIS: &[&str] = &[
+    "IoCreateDevice",
+    "IoCreateDeviceSecure",
+    "IoCreateSymbolicLink",
+    "IoRegisterDeviceInterface",
+    "FltRegisterFilter",
+];
+// 要求映像设置FORCE_INTEGRITY的回调注册函数
+const INTEGRITY_APIS: &[&str] = &[
+    "ObRegisterCallbacks",
+    "PsSetCreateProcessNotifyRoutineEx",
+    "PsSetCreateProcessNotifyRoutineEx2",
+    "CmRegisterCallbackEx",
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DriverInfo {
+    // WDM / KMDF
+    pub framework: String,
+    // 导入的内核模块
+    pub kernel_modules: Vec<String>,
+    // WDF_BIND_INFO中的KMDF版本 如1.15
+    pub kmdf_version: Option<String>,
+    pub kmdf_function_count: Option<u32>,
+    pub device_apis: Vec<String>,
+    // 注册回调但未设置FORCE_INTEGRITY时调用会失败
+    pub integrity_apis: Vec<String>,
+    pub force_integrity: bool,
+    pub embedded_signature: bool,
+}
+
+fn imported_functions(import_table: &[ImportTableEntry], names: &[&str]) -> Vec<String> {
+    names
+        .iter()
+        .filter(|name| {
+            import_table
+                .iter()
+                .flat_map(|entry| entry.functions.iter())
+                .any(|f| f.name == **name)
+        })
+        .map(|name| name.to_string())
+        .collect()
+}
+
+// WDF_BIND_INFO: Size, Component(L"KmdfLibrary"), Version{Major, Minor, Build}, FuncCount, FuncTable, Module
+// 先定位组件名字符串 再搜索指向它的指针
+fn parse_bind_info(
+    data: &[u8],
+    sections: &[Section],
+    image_base: u64,
+    is_x64: bool,
+) -> Option<(String, u32)> {
+    let component: Vec<u8> = "KmdfLibrary"
+        .encode_utf16()
+        .flat_map(|unit| unit.to_le_bytes())
+        .collect();
+    let reader = BoundedReader::new(data);
+    let pointer_size = if is_x64 { 8 } else { 4 };
+    for offset in find_all(data, &component, 4) {
+        let Some(rva) = offset_to_rva(sections, offset as u64) else {
+            continue;
+        };
+        let address = image_base.wrapping_add(rva as u64);
+        let pointer = &address.to_le_bytes()[..pointer_size];
+        for reference in find_all(data, pointer, 16) {
+            let version = (reference + pointer_size) as u64;
+            let (Ok(major), Ok(minor), Ok(count)) = (
+                reader.u32(version),
+                reader.u32(version + 4),
+                reader.u32(version + 12),
+            ) else {
+                continue;
+            };
+            if major == 1 && minor < 100 {
+                return Some((format!("{}.{}", major, minor), count));
+            }
+        }
+    }
+    None
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn analyze_driver(
+    data: &[u8],
+    sections: &[Section],
+    import_table: &[ImportTableEntry],
+    subsystem: u16,
+    dll_characteristics: u16,
+    image_base: u64,
+    is_x64: bool,
+    is_signed: bool,
+) -> Option<DriverInfo> {
+    if subsystem != IMAGE_SUBSYSTEM_NATIVE {
+        return None;
+    }
+    let imports_module = |module: &str| {
+        import_table
+            .iter()
+            .any(|entry| entry.dll_name.eq_ignore_ascii_case(module))
+    };
+    if !KERNEL_MODULES.iter().any(|module| imports_module(module)) {
+        return None;
+    }
+
+    let kernel_modules: Vec<String> = import_table
+        .iter()
+        .map(|entry| entry.dll_name.clone())
+        .collect();
+    let is_kmdf = imports_module(WDF_LOADER);
+    let bind_info = is_kmdf
+        .then(|| parse_bind_info(data, sections, image_base, is_x64))
+        .flatten();
+
+    Some(DriverInfo {
+        framework: if is_kmdf { "KMDF" } else { "WDM" }.to_string(),
+        kernel_modules,
+        kmdf_version: bind_info.as_ref().map(|(version, _)| version.clone()),
+        kmdf_function_count: bind_info.map(|(_, count)| count),
+        device_apis: imported_functions(import_table, DEVICE_APIS),
+        integrity_apis: imported_functions(import_table, INTEGRITY_APIS),
+        force_integrity: dll_characteristics & IMAGE_DLLCHARACTERISTICS_FORCE_INTEGRITY != 0,
+        embedded_signature: is_signed,
+    })
+}
@@ -0,0 +1,248 @@
+// 动态值重定位表(DVRT) 按符号分组的重定位块 内核与加载器据此在运行时改写代码或头部
+use serde::{Deserialize, Serialize};
+
+use crate::load_config::LoadConfig;
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, Section};
+
+const IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE: u64 = 1;
+const IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE: u64 = 2;
+const IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER: u64 = 3;
+const IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER: u64 = 4;
+const IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH: u64 = 5;
+pub(crate) const IMAGE_DYNAMIC_RELOCATION_ARM64X: u64 = 6;
+const IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE: u64 = 7;
+const IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER: u64 = 8;
+
+// 每种动态重定位的汇总
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DynamicRelocationSummary {
+    pub symbol: u64,
+    pub name: String,
+    pub description: String,
+    // 涉及的页数 以及条目数(格式不按页组织时为None)
+    pub pages: u32,
+    pub entries: Option<u32>,
+    pub size: u32,
+}
+
+// ARM64X条目: kind 0清零 1赋值 2加减增量 meta为条目头的高2位
+pub(crate) struct Arm64xFixup<'a> {
+    pub offset: u64,
+    pub kind: u16,
+    pub meta: usize,
+    pub payload: &'a [u8],
+}
+
+pub(crate) struct DynamicRelocation<'a> {
+    pub symbol: u64,
+    // 与基址重定位相同的块序列 每块以页RVA和块大小开头
+    pub fixups: &'a [u8],
+}
+
+// 表的文件偏移 优先使用节序号(从1开始)+偏移
+fn table_offset(sections: &[Section], load_config: &LoadConfig, image_base: u64) -> Option<u64> {
+    if let (Some(offset), Some(section)) = (
+        load_config.dynamic_value_reloc_table_offset,
+        load_config.dynamic_value_reloc_table_section,
+    ) {
+        if section != 0 {
+            let section = sections.get(section as usize - 1)?;
+            return Some(section.ptr_raw_data as u64 + offset as u64);
+        }
+    }
+    let va = load_config
+        .dynamic_value_reloc_table
+        .filter(|va| *va > image_base)?;
+    rva_to_offset(sections, (va - image_base) as u32).map(|offset| offset as u64)
+}
+
+pub(crate) fn dynamic_relocations<'a>(
+    data: &'a [u8],
+    sections: &[Section],
+    load_config: &LoadConfig,
+    image_base: u64,
+    is_x64: bool,
+) -> Vec<DynamicRelocation<'a>> {
+    let mut relocations: Vec<DynamicRelocation> = Vec::new();
+    let Some(table) = table_offset(sections, load_config, image_base) else {
+        return relocations;
+    };
+    let reader = BoundedReader::new(data);
+    let (Ok(version), Ok(size)) = (reader.u32(table), reader.u32(table + 4)) else {
+        return relocations;
+    };
+    let end = (table + 8 + size as u64).min(reader.len());
+    let mut cursor = table + 8;
+    while cursor < end {
+        let entry = match version {
+            // 版本1: Symbol(指针宽度) + BaseRelocSize
+            1 => {
+                let symbol_size = if is_x64 { 8 } else { 4 };
+                let symbol = reader.pointer(cursor, is_x64);
+                let fixup_size = reader.u32(cursor + symbol_size);
+                match (symbol, fixup_size) {
+                    (Ok(symbol), Ok(size)) => Some((symbol, cursor + symbol_size + 4, size)),
+                    _ => None,
+                }
+            }
+            // 版本2: HeaderSize + FixupInfoSize + Symbol(u64) + SymbolGroup + Flags
+            2 => {
+                let header_size = reader.u32(cursor);
+                let fixup_size = reader.u32(cursor + 4);
+                let symbol = reader.u64(cursor + 8);
+                match (header_size, fixup_size, symbol) {
+                    (Ok(header_size), Ok(size), Ok(symbol)) => {
+                        Some((symbol, cursor + header_size as u64, size))
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+        let Some((symbol, start, size)) = entry else {
+            break;
+        };
+        let fixup_end = start + size as u64;
+        if start > end {
+            break;
+        }
+        let Ok(fixups) = reader.bytes(start, fixup_end.min(end) - start) else {
+            break;
+        };
+        relocations.push(DynamicRelocation { symbol, fixups });
+        cursor = fixup_end;
+    }
+    relocations
+}
+
+// 把重定位数据拆成(页RVA, 条目)块
+pub(crate) fn blocks(fixups: &[u8]) -> Vec<(u32, &[u8])> {
+    let mut blocks: Vec<(u32, &[u8])> = Vec::new();
+    let reader = BoundedReader::new(fixups);
+    let mut cursor = 0;
+    while let (Ok(page), Ok(size)) = (reader.u32(cursor), reader.u32(cursor + 4)) {
+        if size < 8 {
+            break;
+        }
+        blocks.push((page, reader.bytes_truncated(cursor + 8, size as u64 - 8)));
+        cursor = (cursor + size as u64).min(reader.len());
+    }
+    blocks
+}
+
+// 一个块中的ARM64X条目 赋值条目后跟1/2/4/8字节的值 增量条目后跟2字节
+pub(crate) fn arm64x_fixups(page: u32, entries: &[u8]) -> Vec<Arm64xFixup<'_>> {
+    let mut fixups: Vec<Arm64xFixup> = Vec::new();
+    let reader = BoundedReader::new(entries);
+    let mut cursor = 0;
+    while let Ok(entry) = reader.u16(cursor) {
+        // 块按4字节对齐 末尾的0为填充
+        if entry == 0 && cursor + 2 >= reader.len() {
+            break;
+        }
+        cursor += 2;
+        let kind = (entry >> 12) & 3;
+        let meta = (entry >> 14) as usize;
+        let payload_size = match kind {
+            0 => 0,
+            1 => 1 << meta,
+            2 => 2,
+            _ => break,
+        };
+        let Ok(payload) = reader.bytes(cursor, payload_size) else {
+            break;
+        };
+        cursor += payload_size;
+        fixups.push(Arm64xFixup {
+            offset: page as u64 + (entry & 0x0FFF) as u64,
+            kind,
+            meta,
+            payload,
+        });
+    }
+    fixups
+}
+
+// 按符号统计 缓解措施相关的符号(retpoline/导入优化/ARM64X等)有固定编号 其余符号为普通的VA
+pub(crate) fn summarize(relocations: &[DynamicRelocation]) -> Vec<DynamicRelocationSummary> {
+    let mut summaries: Vec<DynamicRelocationSummary> = Vec::new();
+    for relocation in relocations {
+        let (name, description) = match relocation.symbol {
+            IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE => {
+                ("GUARD_RF_PROLOGUE", "Return Flow Guard函数序言(已废弃)")
+            }
+            IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE => {
+                ("GUARD_RF_EPILOGUE", "Return Flow Guard函数结尾(已废弃)")
+            }
+            IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER => (
+                "GUARD_IMPORT_CONTROL_TRANSFER",
+                "导入函数调用 内核可改写为retpoline或直接调用(导入优化)",
+            ),
+            IMAGE_DYNAMIC_RELOCATION_GUARD_INDIR_CONTROL_TRANSFER => (
+                "GUARD_INDIR_CONTROL_TRANSFER",
+                "间接调用/跳转 启用retpoline时改写",
+            ),
+            IMAGE_DYNAMIC_RELOCATION_GUARD_SWITCHTABLE_BRANCH => (
+                "GUARD_SWITCHTABLE_BRANCH",
+                "switch跳转表分支 启用retpoline时改写",
+            ),
+            IMAGE_DYNAMIC_RELOCATION_ARM64X => ("ARM64X", "ARM64X原生与混合视图之间的差异"),
+            IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE => {
+                ("FUNCTION_OVERRIDE", "函数覆盖 运行时替换函数实现(热补丁)")
+            }
+            IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER => (
+                "ARM64_KERNEL_IMPORT_CALL_TRANSFER",
+                "ARM64内核导入调用 可改写为直接调用",
+            ),
+            _ => ("SYMBOL", "按符号地址修正的普通重定位"),
+        };
+        let blocks = match relocation.symbol {
+            // 这几种的数据不是按页组织的块
+            IMAGE_DYNAMIC_RELOCATION_GUARD_RF_PROLOGUE
+            | IMAGE_DYNAMIC_RELOCATION_GUARD_RF_EPILOGUE
+            | IMAGE_DYNAMIC_RELOCATION_FUNCTION_OVERRIDE => None,
+            _ => Some(blocks(relocation.fixups)),
+        };
+        let entries = blocks.as_ref().map(|blocks| {
+            blocks
+                .iter()
+                .map(|(page, entries)| match relocation.symbol {
+                    IMAGE_DYNAMIC_RELOCATION_ARM64X => arm64x_fixups(*page, entries).len(),
+                    // 4字节条目
+                    IMAGE_DYNAMIC_RELOCATION_GUARD_IMPORT_CONTROL_TRANSFER
+                    | IMAGE_DYNAMIC_RELOCATION_ARM64_KERNEL_IMPORT_CALL_TRANSFER => entries
+                        .chunks_exact(4)
+                        .filter(|entry| entry.iter().any(|&b| b != 0))
+                        .count(),
+                    // 2字节条目 0为填充
+                    _ => entries
+                        .chunks_exact(2)
+                        .filter(|entry| entry.iter().any(|&b| b != 0))
+                        .count(),
+                })
+                .sum::<usize>() as u32
+        });
+        let pages = blocks.as_ref().map_or(0, |blocks| blocks.len() as u32);
+
+        // 同一符号可能出现多次 合并统计
+        if let Some(summary) = summaries
+            .iter_mut()
+            .find(|summary| summary.symbol == relocation.symbol)
+        {
+            summary.pages += pages;
+            summary.entries = summary.entries.zip(entries).map(|(a, b)| a + b);
+            summary.size += relocation.fixups.len() as u32;
+            continue;
+        }
+        summaries.push(DynamicRelocationSummary {
+            symbol: relocation.symbol,
+            name: name.to_string(),
+            description: description.to_string(),
+            pages,
+            entries,
+            size: relocation.fixups.len() as u32,
+        });
+    }
+    summaries
+}
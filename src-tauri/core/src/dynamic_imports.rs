@@ -0,0 +1,159 @@
+// 可能的动态导入: 加壳或手工解析API的程序导入表很小 运行时才取得函数地址
+// 在字符串中查找常见API名 在可执行节中查找这些API名的哈希常量 脱壳前大致了解程序会调用什么
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::api_hashes::{self, COMMON_APIS};
+use crate::heuristics::IMAGE_SCN_MEM_EXECUTE;
+use crate::reader::BoundedReader;
+use crate::{strings, ImportTableEntry, Section};
+
+// 作为字符串匹配的最短API名 更短的名字(send/recv等)容易与普通文本重合 只按哈希查找
+const MIN_NAME_LENGTH: usize = 6;
+// 每个API记录的出现位置上限
+const MAX_OFFSETS: usize = 16;
+
+// 静态导入了这些函数时 程序可以按名称在运行时取得其他函数
+const RESOLVERS: &[&str] = &[
+    "GetProcAddress",
+    "LoadLibraryA",
+    "LoadLibraryW",
+    "LoadLibraryExA",
+    "LoadLibraryExW",
+    "LdrLoadDll",
+    "LdrGetProcedureAddress",
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DynamicImport {
+    pub dll_name: String,
+    pub name: String,
+    // string: 以字符串形式出现 其余为匹配的哈希算法
+    // ror13 / ror13_module(Metasploit block_api) / crc32 / djb2
+    pub evidence: String,
+    // 哈希时为匹配的常量
+    pub hash: Option<u32>,
+    // 前几处出现的文件偏移
+    pub offsets: Vec<u64>,
+    pub occurrences: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HashAlgorithmMatch {
+    pub algorithm: String,
+    // 匹配到的不同API数 偶然碰撞通常只有一两个 最多的即为样本使用的算法
+    pub apis: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DynamicImportReport {
+    // 静态导入的解析函数 为空而找到哈希时 多半是遍历PEB和导出表自行解析
+    pub resolvers: Vec<String>,
+    // 按匹配数从多到少排列
+    pub hash_algorithms: Vec<HashAlgorithmMatch>,
+    pub imports: Vec<DynamicImport>,
+}
+
+#[derive(Default)]
+struct Hits {
+    offsets: Vec<u64>,
+    occurrences: u32,
+}
+
+impl Hits {
+    fn record(&mut self, offset: u64) {
+        if self.offsets.len() < MAX_OFFSETS {
+            self.offsets.push(offset);
+        }
+        self.occurrences += 1;
+    }
+}
+
+pub fn find_dynamic_imports(
+    data: &[u8],
+    sections: &[Section],
+    import_table: &[ImportTableEntry],
+) -> DynamicImportReport {
+    let reader = BoundedReader::new(data);
+    // 导入名称表里本来就有静态导入的函数名 不算动态导入
+    let imported: HashSet<&str> = import_table
+        .iter()
+        .flat_map(|entry| entry.functions.iter())
+        .map(|function| function.name.as_str())
+        .collect();
+    let resolvers: Vec<String> = RESOLVERS
+        .iter()
+        .filter(|name| imported.contains(*name))
+        .map(|name| name.to_string())
+        .collect();
+
+    let names: HashMap<&str, &str> = COMMON_APIS
+        .iter()
+        .flat_map(|&(dll_name, functions)| functions.iter().map(move |&name| (name, dll_name)))
+        .collect();
+
+    // (DLL, 函数名, 证据, 哈希) -> 出现位置 按DLL和函数名排序输出
+    let mut found: BTreeMap<(&str, &str, &str, Option<u32>), Hits> = BTreeMap::new();
+    for (offset, value) in strings::all_strings(data, MIN_NAME_LENGTH) {
+        if let Some((&name, &dll_name)) = names.get_key_value(value.as_str()) {
+            if !imported.contains(name) {
+                found
+                    .entry((dll_name, name, "string", None))
+                    .or_default()
+                    .record(offset as u64);
+            }
+        }
+    }
+    // 哈希常量作为立即数嵌在代码中 不要求对齐
+    for section in sections
+        .iter()
+        .filter(|section| section.characteristics & IMAGE_SCN_MEM_EXECUTE != 0)
+    {
+        let start = section.ptr_raw_data as u64;
+        let code = BoundedReader::new(reader.bytes_truncated(start, section.size_raw_data as u64));
+        for index in 0..code.len().saturating_sub(3) {
+            let Ok(value) = code.u32(index) else {
+                break;
+            };
+            for hash in api_hashes::lookup(value) {
+                found
+                    .entry((hash.dll_name, hash.name, hash.algorithm, Some(value)))
+                    .or_default()
+                    .record(start + index);
+            }
+        }
+    }
+
+    let mut counts: BTreeMap<&str, u32> = BTreeMap::new();
+    for (_, _, evidence, hash) in found.keys() {
+        if hash.is_some() {
+            *counts.entry(evidence).or_default() += 1;
+        }
+    }
+    let mut hash_algorithms: Vec<HashAlgorithmMatch> = counts
+        .into_iter()
+        .map(|(algorithm, apis)| HashAlgorithmMatch {
+            algorithm: algorithm.to_string(),
+            apis,
+        })
+        .collect();
+    hash_algorithms.sort_by_key(|algorithm| std::cmp::Reverse(algorithm.apis));
+
+    let imports = found
+        .into_iter()
+        .map(|((dll_name, name, evidence, hash), hits)| DynamicImport {
+            dll_name: dll_name.to_string(),
+            name: name.to_string(),
+            evidence: evidence.to_string(),
+            hash,
+            offsets: hits.offsets,
+            occurrences: hits.occurrences,
+        })
+        .collect();
+    DynamicImportReport {
+        resolvers,
+        hash_algorithms,
+        imports,
+    }
+}
@@ -0,0 +1,52 @@
+// 香农熵计算 结果范围0.0-8.0 接近8说明数据经过压缩或加密
+use serde::{Deserialize, Serialize};
+
+// 可执行节区熵超过该值时判断为未知壳
+pub const PACKED_ENTROPY: f64 = 7.2;
+
+// 熵条带的默认和最大分辨率(块数)
+pub const DEFAULT_STRIP_RESOLUTION: usize = 256;
+const MAX_STRIP_RESOLUTION: usize = 4096;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EntropyStrip {
+    pub file_size: u64,
+    // 每个值对应的字节数 最后一块可能更短
+    pub block_size: u64,
+    // 按文件偏移排列的块熵 使用f32减小传给前端的数据量
+    pub values: Vec<f32>,
+}
+
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    if data.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0u64; 256];
+    for &byte in data {
+        counts[byte as usize] += 1;
+    }
+    let len = data.len() as f64;
+    counts
+        .iter()
+        .filter(|&&count| count != 0)
+        .map(|&count| {
+            let p = count as f64 / len;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+// 把文件均分为resolution块 逐块计算熵 供前端绘制热度条
+pub fn entropy_strip(data: &[u8], resolution: usize) -> EntropyStrip {
+    let resolution = resolution.clamp(1, MAX_STRIP_RESOLUTION);
+    let block_size = data.len().div_ceil(resolution).max(1);
+    let values: Vec<f32> = data
+        .chunks(block_size)
+        .map(|block| shannon_entropy(block) as f32)
+        .collect();
+    EntropyStrip {
+        file_size: data.len() as u64,
+        block_size: block_size as u64,
+        values,
+    }
+}
@@ -0,0 +1,63 @@
+// 入口点字节 以及按入口点字节匹配的壳特征(??为通配)
+use serde::{Deserialize, Serialize};
+
+use crate::hashes::to_hex;
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, Section};
+
+// 返回给前端的入口点字节数
+const ENTRY_POINT_BYTES: u64 = 64;
+
+const EP_SIGNATURES: &[(&str, &str)] = &[
+    ("60 BE ?? ?? ?? ?? 8D BE ?? ?? ?? ??", "UPX"),
+    ("53 56 57 55 48 8D 35 ?? ?? ?? ?? 48 8D BE", "UPX"),
+    ("60 E8 03 00 00 00 E9 EB 04 5D 45 55 C3 E8 01", "ASPack"),
+    (
+        "B8 ?? ?? ?? ?? 50 64 FF 35 00 00 00 00 64 89 25 00 00 00 00",
+        "PECompact",
+    ),
+    ("60 E8 00 00 00 00 58 05", "MPRESS"),
+    ("87 25 ?? ?? ?? ?? 61 94 55 A4 B6 80 FF 13", "FSG"),
+    ("B8 ?? ?? ?? ?? 66 9C 60 50", "Petite"),
+    ("9C 60 E8 00 00 00 00 5D B8 07 00 00 00", "NsPack"),
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EntryPointBytes {
+    pub offset: u32,
+    // 小写十六进制 入口点之后不足64字节时更短
+    pub bytes: String,
+    // 匹配的壳特征
+    pub signature: Option<String>,
+}
+
+fn matches(pattern: &str, code: &[u8]) -> bool {
+    let tokens: Vec<&str> = pattern.split_whitespace().collect();
+    tokens.len() <= code.len()
+        && tokens.iter().zip(code).all(|(token, &byte)| {
+            *token == "??" || u8::from_str_radix(token, 16).is_ok_and(|value| value == byte)
+        })
+}
+
+pub fn read_entry_point(
+    data: &[u8],
+    sections: &[Section],
+    entry_point: u32,
+) -> Option<EntryPointBytes> {
+    if entry_point == 0 {
+        return None;
+    }
+    let offset = rva_to_offset(sections, entry_point)?;
+    let code = BoundedReader::new(data).bytes_truncated(offset as u64, ENTRY_POINT_BYTES);
+    if code.is_empty() {
+        return None;
+    }
+    Some(EntryPointBytes {
+        offset,
+        bytes: to_hex(code),
+        signature: EP_SIGNATURES
+            .iter()
+            .find(|(pattern, _)| matches(pattern, code))
+            .map(|(_, name)| name.to_string()),
+    })
+}
@@ -0,0 +1,150 @@
+// 异常目录(.pdata)解析 x64和ARM64的RUNTIME_FUNCTION表记录了每个非叶函数的范围
+use serde::{Deserialize, Serialize};
+
+use crate::entropy::shannon_entropy;
+use crate::entropy::PACKED_ENTROPY;
+use crate::reader::{BoundedReader, ReadError};
+use crate::{rva_to_offset, DataDirectory, ExportFunction, Section};
+
+pub const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+pub const IMAGE_FILE_MACHINE_ARM64: u16 = 0xAA64;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct RuntimeFunction {
+    pub begin: u32,
+    // ARM64非压缩形式的unwind数据无法读取时与begin相同
+    pub end: u32,
+}
+
+// 返回的函数个数默认值和上限
+pub const DEFAULT_FUNCTION_LIMIT: usize = 50;
+const MAX_FUNCTION_LIMIT: usize = 1000;
+// 太短的函数熵值没有意义 不判断是否为数据块
+const MIN_BLOB_SIZE: u32 = 256;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FunctionSize {
+    pub begin: u32,
+    pub end: u32,
+    pub size: u32,
+    // 起始地址或范围内的导出函数 优先起始地址相同的
+    pub export: Option<String>,
+    pub section: Option<String>,
+    pub entropy: f64,
+    // 熵接近加密/压缩数据 可能是伪装成函数的内嵌数据
+    pub blob: bool,
+}
+
+pub(crate) fn parse_runtime_functions(
+    reader: BoundedReader,
+    sections: &[Section],
+    directory: DataDirectory,
+    machine: u16,
+) -> Result<Vec<RuntimeFunction>, ReadError> {
+    let mut functions: Vec<RuntimeFunction> = Vec::new();
+    let entry_size: u32 = match machine {
+        IMAGE_FILE_MACHINE_AMD64 => 12,
+        IMAGE_FILE_MACHINE_ARM64 => 8,
+        _ => return Ok(functions),
+    };
+    if directory.rva == 0 || directory.size == 0 {
+        return Ok(functions);
+    }
+    let table_ptr = match rva_to_offset(sections, directory.rva) {
+        Some(ptr) => ptr as u64,
+        None => return Ok(functions),
+    };
+
+    for i in 0..(directory.size / entry_size) as u64 {
+        let entry_ptr = table_ptr + i * entry_size as u64;
+        let begin = reader.u32(entry_ptr)?;
+        if begin == 0 {
+            continue;
+        }
+        let end = if machine == IMAGE_FILE_MACHINE_AMD64 {
+            reader.u32(entry_ptr + 4)?
+        } else {
+            // ARM64: 低2位为Flag 非0时为压缩格式 FunctionLength位于bit2-12
+            let unwind = reader.u32(entry_ptr + 4)?;
+            if unwind & 0x3 != 0 {
+                begin.saturating_add(((unwind >> 2) & 0x7FF) * 4)
+            } else {
+                // 指向.xdata 首个DWORD低18位为FunctionLength
+                match rva_to_offset(sections, unwind) {
+                    Some(ptr) => begin.saturating_add((reader.u32(ptr as u64)? & 0x3FFFF) * 4),
+                    None => begin,
+                }
+            }
+        };
+        functions.push(RuntimeFunction { begin, end });
+    }
+    Ok(functions)
+}
+
+// 按大小排序的最大函数 同一起始地址的多条记录只保留最长的
+pub fn largest_functions(
+    data: &[u8],
+    sections: &[Section],
+    exports: &[ExportFunction],
+    runtime_functions: &[RuntimeFunction],
+    limit: usize,
+) -> Vec<FunctionSize> {
+    let mut ranges: Vec<RuntimeFunction> = runtime_functions
+        .iter()
+        .filter(|function| function.end > function.begin)
+        .copied()
+        .collect();
+    ranges.sort_by(|a, b| a.begin.cmp(&b.begin).then(b.end.cmp(&a.end)));
+    ranges.dedup_by_key(|function| function.begin);
+    ranges.sort_by_key(|function| std::cmp::Reverse(function.end - function.begin));
+    ranges.truncate(limit.clamp(1, MAX_FUNCTION_LIMIT));
+
+    let reader = BoundedReader::new(data);
+    ranges
+        .iter()
+        .map(|function| {
+            let export = exports
+                .iter()
+                .filter(|export| export.address >= function.begin && export.address < function.end)
+                .min_by_key(|export| (export.address != function.begin, export.address))
+                .map(|export| {
+                    let name = if !export.is_named {
+                        format!("#{}", export.ordinal)
+                    } else {
+                        export.name.clone()
+                    };
+                    match export.address - function.begin {
+                        0 => name,
+                        delta => format!("{}-0x{:X}", name, delta),
+                    }
+                });
+            let section = sections
+                .iter()
+                .find(|section| function.begin >= section.rva && function.begin < section.rv_end);
+            let size = function.end - function.begin;
+            // 只统计文件中存在的字节 超出原始数据的部分(未初始化)不计入
+            let bytes = rva_to_offset(sections, function.begin)
+                .and_then(|offset| {
+                    let available = section.map_or(size, |section| {
+                        section
+                            .ptr_raw_data
+                            .saturating_add(section.size_raw_data)
+                            .saturating_sub(offset)
+                            .min(size)
+                    });
+                    reader.bytes(offset as u64, available as u64).ok()
+                })
+                .unwrap_or_default();
+            let entropy = shannon_entropy(bytes);
+            FunctionSize {
+                begin: function.begin,
+                end: function.end,
+                size,
+                export,
+                section: section.map(|section| section.name.clone()),
+                entropy,
+                blob: bytes.len() >= MIN_BLOB_SIZE as usize && entropy > PACKED_ENTROPY,
+            }
+        })
+        .collect()
+}
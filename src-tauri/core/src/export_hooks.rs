@@ -0,0 +1,91 @@
+// 磁盘文件中的导出表篡改: 导出地址指向映像之外、头部、无文件数据的区域 或追加到数据节之后的代码节
+use serde::{Deserialize, Serialize};
+
+use crate::heuristics::IMAGE_SCN_MEM_EXECUTE;
+use crate::{DataDirectory, ExportFunction, Section};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportAnomaly {
+    pub name: String,
+    pub is_named: bool,
+    pub ordinal: u32,
+    pub address: u32,
+    pub section: Option<String>,
+    // outside_image / header / no_raw_data / appended_section
+    pub reason: String,
+}
+
+fn section_index(sections: &[Section], rva: u32) -> Option<usize> {
+    sections
+        .iter()
+        .position(|section| rva >= section.rva && rva < section.rv_end)
+}
+
+// 可执行节位于某个不可执行节之后 编译器总是把代码节放在数据节之前
+fn is_appended(sections: &[Section], index: usize) -> bool {
+    sections[index].characteristics & IMAGE_SCN_MEM_EXECUTE != 0
+        && sections[..index]
+            .iter()
+            .any(|section| section.characteristics & IMAGE_SCN_MEM_EXECUTE == 0)
+}
+
+pub(crate) fn find_export_anomalies(
+    sections: &[Section],
+    export_table: &[ExportFunction],
+    directory: DataDirectory,
+    size_of_headers: u32,
+) -> Vec<ExportAnomaly> {
+    let directory_end = directory.rva.saturating_add(directory.size);
+    // 转发导出的地址指向导出目录中的字符串
+    let exports: Vec<&ExportFunction> = export_table
+        .iter()
+        .filter(|export| {
+            export.address != 0
+                && !(export.address >= directory.rva && export.address < directory_end)
+        })
+        .collect();
+
+    // 多数导出所在的节 视为正常的代码位置
+    let mut counts = vec![0usize; sections.len()];
+    for export in &exports {
+        if let Some(index) = section_index(sections, export.address) {
+            counts[index] += 1;
+        }
+    }
+    let home = counts
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, count)| **count)
+        .filter(|(_, count)| **count > 0)
+        .map(|(index, _)| index);
+
+    let mut anomalies: Vec<ExportAnomaly> = Vec::new();
+    for export in exports {
+        let index = section_index(sections, export.address);
+        let reason = match index {
+            None if export.address < size_of_headers => "header",
+            None => "outside_image",
+            Some(index) => {
+                let section = &sections[index];
+                let executable = section.characteristics & IMAGE_SCN_MEM_EXECUTE != 0;
+                // 未初始化的导出变量本就没有文件数据 只检查代码节
+                if executable && export.address - section.rva >= section.size_raw_data {
+                    "no_raw_data"
+                } else if Some(index) != home && is_appended(sections, index) {
+                    "appended_section"
+                } else {
+                    continue;
+                }
+            }
+        };
+        anomalies.push(ExportAnomaly {
+            name: export.name.clone(),
+            is_named: export.is_named,
+            ordinal: export.ordinal,
+            address: export.address,
+            section: index.map(|index| sections[index].name.clone()),
+            reason: reason.to_string(),
+        });
+    }
+    anomalies
+}
@@ -0,0 +1,409 @@
+// Go程序识别 解析runtime/debug.BuildInfo对应的buildinfo段和pclntab头
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::find_all;
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, Section};
+
+const BUILDINFO_MAGIC: &[u8] = b"\xff Go buildinf:";
+// buildinfo头部固定32字节
+const BUILDINFO_HEADER_SIZE: u64 = 32;
+// Go 1.18起版本和模块信息直接以varint长度前缀内联在头部之后
+const BUILDINFO_FLAG_INLINE: u8 = 0x2;
+// modinfo前后各有16字节的哨兵
+const MODINFO_SENTINEL_SIZE: usize = 16;
+// 字符串长度上限 避免构造的文件声明超大长度
+const MAX_BUILDINFO_STRING: u64 = 1 << 20;
+
+// pclntab头部magic 与Go版本对应
+const PCLNTAB_MAGICS: &[(u32, &str)] = &[
+    (0xFFFF_FFFB, "go1.2-1.15"),
+    (0xFFFF_FFFA, "go1.16-1.17"),
+    (0xFFFF_FFF0, "go1.18-1.19"),
+    (0xFFFF_FFF1, "go1.20+"),
+];
+
+// 函数数量上限 防止构造的pclntab声明超大数量
+const MAX_GO_FUNCTIONS: usize = 1 << 20;
+const MAX_FUNCTION_NAME: usize = 4096;
+
+#[derive(Debug)]
+pub(crate) struct GoFunction {
+    pub rva: u32,
+    pub name: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GoModule {
+    pub path: String,
+    pub version: String,
+    pub sum: Option<String>,
+    // replace指令替换后的模块
+    pub replaced_by: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GoBuildSetting {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GoBuildInfo {
+    // buildinfo中记录的编译器版本 如go1.21.5
+    pub go_version: Option<String>,
+    // 由pclntab magic推断的版本范围
+    pub pclntab_version: Option<String>,
+    pub path: Option<String>,
+    pub main_module: Option<GoModule>,
+    pub dependencies: Vec<GoModule>,
+    pub settings: Vec<GoBuildSetting>,
+}
+
+fn read_uvarint(data: &[u8]) -> Option<(u64, usize)> {
+    let mut value: u64 = 0;
+    for (i, &byte) in data.iter().enumerate().take(10) {
+        value |= ((byte & 0x7F) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+fn read_pointer(
+    reader: &BoundedReader,
+    offset: u64,
+    pointer_size: u64,
+    big_endian: bool,
+) -> Option<u64> {
+    if !big_endian {
+        return reader.uint(offset, pointer_size).ok();
+    }
+    let bytes = reader.bytes(offset, pointer_size).ok()?;
+    Some(
+        bytes
+            .iter()
+            .fold(0, |value, &byte| (value << 8) | byte as u64),
+    )
+}
+
+// Go 1.18之前: 头部中是指向string结构{data, len}的指针 需要VA -> 文件偏移
+fn read_go_string(
+    reader: &BoundedReader,
+    sections: &[Section],
+    image_base: u64,
+    address: u64,
+    pointer_size: u64,
+    big_endian: bool,
+) -> Option<Vec<u8>> {
+    let to_offset = |va: u64| -> Option<u64> {
+        let rva = u32::try_from(va.checked_sub(image_base)?).ok()?;
+        rva_to_offset(sections, rva).map(|offset| offset as u64)
+    };
+    let header = to_offset(address)?;
+    let string_address = read_pointer(reader, header, pointer_size, big_endian)?;
+    let length = read_pointer(reader, header + pointer_size, pointer_size, big_endian)?;
+    if length > MAX_BUILDINFO_STRING {
+        return None;
+    }
+    let start = to_offset(string_address)?;
+    reader.bytes(start, length).ok().map(|bytes| bytes.to_vec())
+}
+
+fn parse_module(fields: &[&str]) -> Option<GoModule> {
+    Some(GoModule {
+        path: fields.first()?.to_string(),
+        version: fields.get(1).map(|v| v.to_string()).unwrap_or_default(),
+        sum: fields
+            .get(2)
+            .filter(|v| !v.is_empty())
+            .map(|v| v.to_string()),
+        replaced_by: None,
+    })
+}
+
+// modinfo是制表符分隔的文本: path/mod/dep/=>/build
+fn parse_modinfo(modinfo: &[u8], info: &mut GoBuildInfo) {
+    let bytes = if modinfo.len() >= MODINFO_SENTINEL_SIZE * 2 {
+        &modinfo[MODINFO_SENTINEL_SIZE..modinfo.len() - MODINFO_SENTINEL_SIZE]
+    } else {
+        modinfo
+    };
+    let text = String::from_utf8_lossy(bytes);
+    for line in text.lines() {
+        let fields: Vec<&str> = line.split('\t').collect();
+        match fields.first().copied() {
+            Some("path") => info.path = fields.get(1).map(|v| v.to_string()),
+            Some("mod") => info.main_module = parse_module(&fields[1..]),
+            Some("dep") => {
+                if let Some(module) = parse_module(&fields[1..]) {
+                    info.dependencies.push(module);
+                }
+            }
+            Some("=>") => {
+                let replacement = fields[1..]
+                    .iter()
+                    .filter(|v| !v.is_empty())
+                    .copied()
+                    .collect::<Vec<&str>>()
+                    .join(" ");
+                let target = match info.dependencies.last_mut() {
+                    Some(module) => Some(module),
+                    None => info.main_module.as_mut(),
+                };
+                if let Some(module) = target {
+                    module.replaced_by = Some(replacement);
+                }
+            }
+            Some("build") => {
+                if let Some((key, value)) = fields.get(1).and_then(|s| s.split_once('=')) {
+                    info.settings.push(GoBuildSetting {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_buildinfo(
+    reader: &BoundedReader,
+    offset: u64,
+    sections: &[Section],
+    image_base: u64,
+    info: &mut GoBuildInfo,
+) -> Option<()> {
+    let header = reader.bytes(offset, BUILDINFO_HEADER_SIZE).ok()?;
+    let header = BoundedReader::new(header);
+    let [pointer_size, flags] = header.array(14).ok()?;
+    let pointer_size = pointer_size as u64;
+    let big_endian = flags & 0x1 != 0;
+
+    let (version, modinfo) = if flags & BUILDINFO_FLAG_INLINE != 0 {
+        let mut cursor = offset + BUILDINFO_HEADER_SIZE;
+        let mut strings: Vec<Vec<u8>> = Vec::new();
+        for _ in 0..2 {
+            let (length, consumed) = read_uvarint(reader.bytes_truncated(cursor, 10))?;
+            if length > MAX_BUILDINFO_STRING {
+                return None;
+            }
+            cursor += consumed as u64;
+            let bytes = reader.bytes(cursor, length).ok()?;
+            strings.push(bytes.to_vec());
+            cursor += length;
+        }
+        let modinfo = strings.pop()?;
+        (strings.pop()?, modinfo)
+    } else {
+        if pointer_size != 4 && pointer_size != 8 {
+            return None;
+        }
+        let version_address = read_pointer(&header, 16, pointer_size, big_endian)?;
+        let modinfo_address = read_pointer(&header, 16 + pointer_size, pointer_size, big_endian)?;
+        (
+            read_go_string(
+                reader,
+                sections,
+                image_base,
+                version_address,
+                pointer_size,
+                big_endian,
+            )?,
+            read_go_string(
+                reader,
+                sections,
+                image_base,
+                modinfo_address,
+                pointer_size,
+                big_endian,
+            )
+            .unwrap_or_default(),
+        )
+    };
+
+    if !version.is_empty() {
+        info.go_version = Some(String::from_utf8_lossy(&version).to_string());
+    }
+    parse_modinfo(&modinfo, info);
+    Some(())
+}
+
+// pclntab头: magic, 两个0字节, 指令长度(1/2/4), 指针大小(4/8)
+// magic只有4字节 另外要求函数名表中出现runtime.main以减少误报
+// 返回pclntab的文件偏移和magic
+fn find_pclntab(data: &[u8]) -> Option<(u64, u32)> {
+    find_all(data, b"runtime.main", 1).first()?;
+    let reader = BoundedReader::new(data);
+    for &(magic, _) in PCLNTAB_MAGICS {
+        for offset in find_all(data, &magic.to_le_bytes(), 64) {
+            let offset = offset as u64;
+            let Ok(header) = reader.array::<4>(offset + 4) else {
+                continue;
+            };
+            if header[0] == 0
+                && header[1] == 0
+                && matches!(header[2], 1 | 2 | 4)
+                && matches!(header[3], 4 | 8)
+            {
+                return Some((offset, magic));
+            }
+        }
+    }
+    None
+}
+
+fn find_pclntab_version(data: &[u8]) -> Option<String> {
+    let (_, magic) = find_pclntab(data)?;
+    PCLNTAB_MAGICS
+        .iter()
+        .find(|(known, _)| *known == magic)
+        .map(|(_, version)| version.to_string())
+}
+
+fn read_name(reader: &BoundedReader, offset: u64) -> Option<String> {
+    let bytes = reader.c_string(offset, MAX_FUNCTION_NAME).ok()?;
+    Some(String::from_utf8_lossy(bytes).to_string())
+}
+
+// 各版本pclntab中(函数表偏移, 表项长度, 名称表偏移, text起始VA) 偏移相对pclntab起始
+// header(i)为头部中nfunc之后的第i个指针大小字段
+fn pclntab_layout(
+    magic: u32,
+    pointer_size: u64,
+    header: impl Fn(u64) -> Option<u64>,
+) -> Option<(u64, u64, u64, Option<u64>)> {
+    match magic {
+        // nfunc, nfiles, textStart, funcnameOffset, cuOffset, filetabOffset, pctabOffset, pclnOffset
+        0xFFFF_FFF0 | 0xFFFF_FFF1 => Some((header(7)?, 8, header(3)?, Some(header(2)?))),
+        // nfunc, nfiles, funcnameOffset, cuOffset, filetabOffset, pctabOffset, pclnOffset
+        0xFFFF_FFFA => Some((header(6)?, pointer_size * 2, header(2)?, None)),
+        // go1.2-1.15 函数表紧跟在nfunc之后 名称偏移相对pclntab起始
+        _ => Some((8 + pointer_size, pointer_size * 2, 0, None)),
+    }
+}
+
+// pclntab函数表中的函数入口和名称 按入口排序
+// 入口在表中是VA(1.18起为text段起始VA加偏移) 换算为RVA
+pub(crate) fn go_functions(data: &[u8], image_base: u64) -> Vec<GoFunction> {
+    let Some((pclntab, magic)) = find_pclntab(data) else {
+        return Vec::new();
+    };
+    let reader = BoundedReader::new(data);
+    let Ok([pointer_size]) = reader.array(pclntab + 7) else {
+        return Vec::new();
+    };
+    let pointer_size = pointer_size as u64;
+    let header = |index: u64| {
+        reader
+            .uint(pclntab + 8 + index * pointer_size, pointer_size)
+            .ok()
+    };
+    let Some(function_count) = header(0).map(|count| count as usize) else {
+        return Vec::new();
+    };
+    let function_count = function_count.min(MAX_GO_FUNCTIONS);
+
+    let Some((function_table, entry_size, function_names, text_start)) =
+        pclntab_layout(magic, pointer_size, header)
+    else {
+        return Vec::new();
+    };
+    // 头部中的偏移来自文件 相加时可能溢出
+    let (Some(function_table), Some(function_names)) = (
+        pclntab.checked_add(function_table),
+        pclntab.checked_add(function_names),
+    ) else {
+        return Vec::new();
+    };
+
+    let mut functions: Vec<GoFunction> = Vec::new();
+    for index in 0..function_count as u64 {
+        let Some(entry) = function_table.checked_add(index * entry_size) else {
+            break;
+        };
+        let parsed = if let Some(text_start) = text_start {
+            // {entryoff u32, funcoff u32} _func以entryOff u32开头 随后是nameOff
+            reader
+                .u32(entry)
+                .ok()
+                .zip(reader.u32(entry + 4).ok())
+                .and_then(|(entry_offset, function_offset)| {
+                    let name_offset = reader
+                        .u32(function_table.checked_add(function_offset as u64 + 4)?)
+                        .ok()?;
+                    Some((text_start.wrapping_add(entry_offset as u64), name_offset))
+                })
+        } else {
+            // {entry uintptr, funcoff uintptr} _func以entry uintptr开头 随后是nameoff
+            reader
+                .uint(entry, pointer_size)
+                .ok()
+                .zip(reader.uint(entry + pointer_size, pointer_size).ok())
+                .and_then(|(address, function_offset)| {
+                    let base = if magic == 0xFFFF_FFFA {
+                        function_table
+                    } else {
+                        pclntab
+                    };
+                    let name_offset = reader
+                        .u32(
+                            base.checked_add(function_offset)?
+                                .checked_add(pointer_size)?,
+                        )
+                        .ok()?;
+                    Some((address, name_offset))
+                })
+        };
+        let Some((address, name_offset)) = parsed else {
+            break;
+        };
+        let Some(rva) = address
+            .checked_sub(image_base)
+            .and_then(|rva| u32::try_from(rva).ok())
+        else {
+            continue;
+        };
+        if let Some(name) = read_name(&reader, function_names + name_offset as u64) {
+            functions.push(GoFunction { rva, name });
+        }
+    }
+    functions.sort_by_key(|function| function.rva);
+    functions
+}
+
+// "github.com/a/b.(*T).M" -> "github.com/a/b"  "runtime.main" -> "runtime"
+pub(crate) fn go_package(name: &str) -> &str {
+    let start = name.rfind('/').map_or(0, |index| index + 1);
+    let end = name[start..]
+        .find('.')
+        .map_or(name.len(), |index| start + index);
+    &name[..end]
+}
+
+pub(crate) fn parse_go_info(
+    data: &[u8],
+    sections: &[Section],
+    image_base: u64,
+) -> Option<GoBuildInfo> {
+    let mut info = GoBuildInfo {
+        go_version: None,
+        pclntab_version: None,
+        path: None,
+        main_module: None,
+        dependencies: Vec::new(),
+        settings: Vec::new(),
+    };
+    let buildinfo = find_all(data, BUILDINFO_MAGIC, 1).first().copied();
+    if let Some(offset) = buildinfo {
+        let reader = BoundedReader::new(data);
+        parse_buildinfo(&reader, offset as u64, sections, image_base, &mut info);
+    }
+    info.pclntab_version = find_pclntab_version(data);
+    if buildinfo.is_none() && info.pclntab_version.is_none() {
+        return None;
+    }
+    Some(info)
+}
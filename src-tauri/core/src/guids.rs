@@ -0,0 +1,204 @@
+// 文件中的GUID 文本形式全部提取 二进制形式只匹配已知的CLSID/IID 避免随机数据误报
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+
+// 去重后的GUID数量上限
+const MAX_GUIDS: usize = 256;
+// "XXXXXXXX-XXXX-XXXX-XXXX-XXXXXXXXXXXX"
+const GUID_TEXT_LENGTH: usize = 36;
+
+// 常被滥用或用于识别COM用途的类与接口
+const KNOWN_GUIDS: &[(&str, &str)] = &[
+    ("00000000-0000-0000-C000-000000000046", "IID_IUnknown"),
+    ("00000001-0000-0000-C000-000000000046", "IID_IClassFactory"),
+    ("0000000B-0000-0000-C000-000000000046", "IID_IStorage"),
+    ("0000000C-0000-0000-C000-000000000046", "IID_IStream"),
+    ("0000000F-0000-0000-C000-000000000046", "IID_IMoniker"),
+    ("0000010B-0000-0000-C000-000000000046", "IID_IPersistFile"),
+    ("00020400-0000-0000-C000-000000000046", "IID_IDispatch"),
+    ("00020401-0000-0000-C000-000000000046", "IID_ITypeInfo"),
+    ("00020402-0000-0000-C000-000000000046", "IID_ITypeLib"),
+    ("00021401-0000-0000-C000-000000000046", "CLSID_ShellLink"),
+    ("000214E4-0000-0000-C000-000000000046", "IID_IContextMenu"),
+    ("000214E8-0000-0000-C000-000000000046", "IID_IShellExtInit"),
+    ("000214EE-0000-0000-C000-000000000046", "IID_IShellLinkA"),
+    ("000214F9-0000-0000-C000-000000000046", "IID_IShellLinkW"),
+    (
+        "0002DF01-0000-0000-C000-000000000046",
+        "CLSID_InternetExplorer",
+    ),
+    ("D30C1661-CDAF-11D0-8A3E-00C04FC9E26E", "IID_IWebBrowser2"),
+    ("9BA05972-F6A8-11CF-A442-00A0C90A8F39", "CLSID_ShellWindows"),
+    (
+        "C08AFD90-F2A1-11D1-8455-00A0C91F3880",
+        "CLSID_ShellBrowserWindow",
+    ),
+    (
+        "49B2791A-B1AE-4C90-9B8E-E860BA07F889",
+        "CLSID_MMC20_Application",
+    ),
+    (
+        "13709620-C279-11CE-A49E-444553540000",
+        "CLSID_Shell_Application",
+    ),
+    (
+        "72C24DD5-D70A-438B-8A42-98424B88AFB8",
+        "CLSID_WScript_Shell",
+    ),
+    (
+        "FC4801A3-2BA9-11CF-A229-00AA003D7352",
+        "IID_IObjectWithSite",
+    ),
+    ("4590F811-1D3A-11D0-891F-00AA004B2E24", "CLSID_WbemLocator"),
+    ("DC12A687-737F-11CF-884D-00AA004B2E24", "IID_IWbemLocator"),
+    (
+        "0F87369F-A4E5-4CFC-BD3E-73E6154572DD",
+        "CLSID_TaskScheduler",
+    ),
+    ("2FABA4C7-4DA9-4013-9697-20CC3FD40F85", "IID_ITaskService"),
+    (
+        "4991D34B-80A1-4291-83B6-3328366B9097",
+        "CLSID_BackgroundCopyManager",
+    ),
+    (
+        "5CE34C0D-0DC9-4C1F-897C-DAA1B78CEE7C",
+        "IID_IBackgroundCopyManager",
+    ),
+    ("3E5FC7F9-9A51-4367-9063-A120244FBEC7", "CLSID_CMSTPLUA"),
+    ("6EDD6D74-C007-4E75-B76A-E5740995E24C", "IID_ICMLuaUtil"),
+    (
+        "3AD05575-8857-4850-9277-11B85BDB8E09",
+        "CLSID_FileOperation",
+    ),
+    ("947AAB5F-0A5C-4C13-B4D6-4BF7836FC9F8", "IID_IFileOperation"),
+    ("88D96A0A-F192-11D4-A65F-0040963251E5", "CLSID_XMLHTTP60"),
+    (
+        "2087C2F4-2CEF-4953-A8AB-66779B670495",
+        "CLSID_WinHttpRequest",
+    ),
+    ("9280188D-0E8E-4867-B30C-7FA83884E8DE", "CLSID_CLRMetaHost"),
+    ("D332DB9E-B9B3-4125-8207-A14884F53216", "IID_ICLRMetaHost"),
+    (
+        "BD39D1D2-BA2F-486A-89B0-B4B0CB466891",
+        "IID_ICLRRuntimeInfo",
+    ),
+    (
+        "CB2F6723-AB3A-11D2-9C40-00C04FA3A0A9",
+        "CLSID_CorRuntimeHost",
+    ),
+    (
+        "CB2F6722-AB3A-11D2-9C40-00C04FA3A0A9",
+        "IID_ICorRuntimeHost",
+    ),
+    ("05F696DC-2B29-3663-AD8B-C4389CF2A713", "IID_AppDomain"),
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GuidReference {
+    // 大写 不带花括号
+    pub guid: String,
+    pub name: Option<String>,
+    // binary / ascii / utf16
+    pub encoding: String,
+    // 首次出现的文件偏移
+    pub offset: u32,
+    pub count: u32,
+}
+
+fn parse_hex(text: &str) -> Option<Vec<u8>> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+// 内存布局: Data1(u32 LE) Data2(u16 LE) Data3(u16 LE) Data4(8字节)
+fn guid_bytes(guid: &str) -> Option<[u8; 16]> {
+    let hex = parse_hex(&guid.replace('-', ""))?;
+    let mut bytes: [u8; 16] = hex.try_into().ok()?;
+    bytes[0..4].reverse();
+    bytes[4..6].reverse();
+    bytes[6..8].reverse();
+    Some(bytes)
+}
+
+pub(crate) fn known_name(guid: &str) -> Option<String> {
+    KNOWN_GUIDS
+        .iter()
+        .find(|(known, _)| known.eq_ignore_ascii_case(guid))
+        .map(|(_, name)| name.to_string())
+}
+
+// 从offset开始按stride读取的字符构成8-4-4-4-12格式的GUID stride为2时高字节须为0
+fn text_guid(data: &[u8], offset: usize, stride: usize) -> Option<String> {
+    let mut text = String::with_capacity(GUID_TEXT_LENGTH);
+    for i in 0..GUID_TEXT_LENGTH {
+        let position = offset + i * stride;
+        let c = *data.get(position)?;
+        if stride == 2 && *data.get(position + 1)? != 0 {
+            return None;
+        }
+        let valid = match i {
+            8 | 13 | 18 | 23 => c == b'-',
+            _ => c.is_ascii_hexdigit(),
+        };
+        if !valid {
+            return None;
+        }
+        text.push(c.to_ascii_uppercase() as char);
+    }
+    Some(text)
+}
+
+pub(crate) fn extract_guids(data: &[u8]) -> Vec<GuidReference> {
+    let known: HashMap<[u8; 16], &str> = KNOWN_GUIDS
+        .iter()
+        .filter_map(|(guid, _)| Some((guid_bytes(guid)?, *guid)))
+        .collect();
+    // 按GUID和编码去重
+    let mut found: BTreeMap<(String, &str), (u32, u32)> = BTreeMap::new();
+    let mut record = |guid: String, encoding: &'static str, offset: usize| {
+        let key = (guid, encoding);
+        if let Some((_, count)) = found.get_mut(&key) {
+            *count += 1;
+        } else if found.len() < MAX_GUIDS {
+            found.insert(key, (offset as u32, 1));
+        }
+    };
+
+    // 编译器按4字节对齐存放GUID常量
+    for offset in (0..data.len().saturating_sub(15)).step_by(4) {
+        if let Some(guid) = data
+            .get(offset..offset + 16)
+            .and_then(|window| known.get(window))
+        {
+            record(guid.to_string(), "binary", offset);
+        }
+    }
+    // 文本形式 第9个字符必然是'-' 用作快速筛选
+    for offset in 0..data.len() {
+        if data.get(offset + 8) == Some(&b'-') {
+            if let Some(guid) = text_guid(data, offset, 1) {
+                record(guid, "ascii", offset);
+                continue;
+            }
+        }
+        if data.get(offset + 16) == Some(&b'-') {
+            if let Some(guid) = text_guid(data, offset, 2) {
+                record(guid, "utf16", offset);
+            }
+        }
+    }
+
+    found
+        .into_iter()
+        .map(|((guid, encoding), (offset, count))| GuidReference {
+            name: known_name(&guid),
+            guid,
+            encoding: encoding.to_string(),
+            offset,
+            count,
+        })
+        .collect()
+}
@@ -0,0 +1,208 @@
+// 文件哈希与imphash
+use std::collections::HashSet;
+
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use sha1::Sha1;
+use sha2::Sha256;
+
+use crate::layout::DIRECTORY_NAMES;
+use crate::ordinals::ordinal_name;
+use crate::reader::BoundedReader;
+use crate::{
+    rva_to_offset, DataDirectory, ImportTableEntry, Section, IMAGE_DIRECTORY_ENTRY_SECURITY,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FileHashes {
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+}
+
+// 单个数据目录原始字节的SHA-256 用于判断两次构建间哪些目录发生了变化
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryDigest {
+    pub index: usize,
+    pub name: String,
+    pub sha256: String,
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+pub fn compute_hashes(data: &[u8]) -> FileHashes {
+    FileHashes {
+        md5: to_hex(&Md5::digest(data)),
+        sha1: to_hex(&Sha1::digest(data)),
+        sha256: to_hex(&Sha256::digest(data)),
+    }
+}
+
+// 任一哈希在已知良性集合中即视为已知良性
+pub(crate) fn is_known_good(hashes: &FileHashes, known_good: &HashSet<String>) -> bool {
+    [&hashes.md5, &hashes.sha1, &hashes.sha256]
+        .iter()
+        .any(|hash| known_good.contains(hash.as_str()))
+}
+
+// 小写库名 去掉dll/ocx/sys扩展名
+fn library_stem(dll_name: &str) -> String {
+    let dll_name = dll_name.to_ascii_lowercase();
+    match dll_name.rsplit_once('.') {
+        Some((stem, "dll" | "ocx" | "sys")) => stem.to_string(),
+        _ => dll_name,
+    }
+}
+
+// 导入函数已按hint重新排序 这里还原为IAT中的顺序
+// 按序号导入时与pefile相同 ws2_32/wsock32/oleaut32查表得到函数名 其余记为ord<序号>
+fn functions_in_iat_order(entry: &ImportTableEntry) -> Vec<String> {
+    let mut functions: Vec<_> = entry.functions.iter().collect();
+    functions.sort_by_key(|f| f.thunk_rva);
+    functions
+        .into_iter()
+        .map(|func| {
+            if func.is_ordinal {
+                match ordinal_name(&entry.dll_name, func.ordinal) {
+                    Some(name) => name.to_ascii_lowercase(),
+                    None => format!("ord{}", func.ordinal),
+                }
+            } else {
+                func.name.to_ascii_lowercase()
+            }
+        })
+        .collect()
+}
+
+// 与pefile一致: 小写的 "库名.函数名" 以逗号连接后取MD5
+pub(crate) fn imphash(import_table: &[ImportTableEntry]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    for entry in import_table {
+        let library = library_stem(&entry.dll_name);
+        for name in functions_in_iat_order(entry) {
+            parts.push(format!("{}.{}", library, name));
+        }
+    }
+    if parts.is_empty() {
+        return String::new();
+    }
+    to_hex(&Md5::digest(parts.join(",").as_bytes()))
+}
+
+// 保留导入顺序的指纹: 按描述符顺序列出 "库名:函数,函数" 库之间以分号分隔
+// 与imphash不同 同一组函数在库之间重新分组或库的顺序改变时指纹也会改变
+pub(crate) fn import_order_hash(import_table: &[ImportTableEntry]) -> String {
+    if import_table.is_empty() {
+        return String::new();
+    }
+    let groups: Vec<String> = import_table
+        .iter()
+        .map(|entry| {
+            format!(
+                "{}:{}",
+                library_stem(&entry.dll_name),
+                functions_in_iat_order(entry).join(",")
+            )
+        })
+        .collect();
+    to_hex(&Md5::digest(groups.join(";").as_bytes()))
+}
+
+// 模糊imphash: 只取库名序列 不受库内函数增删和乱序的影响
+pub(crate) fn dll_sequence_hash(import_table: &[ImportTableEntry]) -> String {
+    if import_table.is_empty() {
+        return String::new();
+    }
+    let libraries: Vec<String> = import_table
+        .iter()
+        .map(|entry| library_stem(&entry.dll_name))
+        .collect();
+    to_hex(&Md5::digest(libraries.join(",").as_bytes()))
+}
+
+// 证书目录使用文件偏移 其余为RVA 位于头部的目录(如绑定导入)按原样映射
+// 超出文件的部分不参与计算
+pub(crate) fn directory_digests(
+    data: &[u8],
+    sections: &[Section],
+    directories: &[DataDirectory],
+    size_of_headers: u32,
+) -> Vec<DirectoryDigest> {
+    let reader = BoundedReader::new(data);
+    directories
+        .iter()
+        .enumerate()
+        .filter(|(_, directory)| directory.rva != 0 && directory.size != 0)
+        .filter_map(|(index, directory)| {
+            let in_file =
+                index == IMAGE_DIRECTORY_ENTRY_SECURITY || directory.rva < size_of_headers;
+            let offset = if in_file {
+                Some(directory.rva)
+            } else {
+                rva_to_offset(sections, directory.rva)
+            }? as u64;
+            if offset > reader.len() {
+                return None;
+            }
+            let bytes = reader.bytes_truncated(offset, directory.size as u64);
+            Some(DirectoryDigest {
+                index,
+                name: DIRECTORY_NAMES.get(index)?.to_string(),
+                sha256: to_hex(&Sha256::digest(bytes)),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ImportFunction;
+
+    fn by_ordinal(ordinal: u16, thunk_rva: u32) -> ImportFunction {
+        ImportFunction {
+            name: String::new(),
+            raw_name: None,
+            is_ordinal: true,
+            ordinal,
+            hint: 0,
+            thunk_rva,
+        }
+    }
+
+    fn entry(dll_name: &str, functions: Vec<ImportFunction>) -> ImportTableEntry {
+        ImportTableEntry {
+            dll_name: dll_name.to_string(),
+            raw_dll_name: None,
+            functions,
+        }
+    }
+
+    #[test]
+    fn imphash_names_ordinals_like_pefile() {
+        let import_table = [
+            entry(
+                "WS2_32.dll",
+                vec![by_ordinal(9999, 0x2004), by_ordinal(115, 0x2000)],
+            ),
+            entry("wsock32.dll", vec![by_ordinal(10, 0x2010)]),
+            entry("OLEAUT32.dll", vec![by_ordinal(6, 0x2020)]),
+        ];
+        // ws2_32.wsastartup,ws2_32.ord9999,wsock32.inet_addr,oleaut32.sysfreestring
+        assert_eq!(imphash(&import_table), "9e9b5004ec39eb48c9006b00e4a4e5f4");
+    }
+
+    #[test]
+    fn ordinals_outside_the_tables_keep_their_number() {
+        let import_table = [entry("mfc42.dll", vec![by_ordinal(6467, 0x2000)])];
+        assert_eq!(
+            imphash(&import_table),
+            to_hex(&Md5::digest(b"mfc42.ord6467"))
+        );
+        assert_eq!(ordinal_name("ws2_32", 115), None);
+        assert_eq!(ordinal_name("WSOCK32.DLL", 12), Some("ioctlsocket"));
+        assert_eq!(ordinal_name("ws2_32.dll", 12), Some("inet_ntoa"));
+    }
+}
@@ -0,0 +1,184 @@
+// 与WoW64相关的头部标志 用通俗语言说明它们对32位进程的影响
+use serde::{Deserialize, Serialize};
+
+use crate::headers::machine_name;
+
+// COFF头Characteristics
+const IMAGE_FILE_LARGE_ADDRESS_AWARE: u16 = 0x0020;
+// 可选头DllCharacteristics
+const IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE: u16 = 0x8000;
+// CLR头Flags
+const COMIMAGE_FLAGS_ILONLY: u32 = 0x0000_0001;
+const COMIMAGE_FLAGS_32BITREQUIRED: u32 = 0x0000_0002;
+const COMIMAGE_FLAGS_32BITPREFERRED: u32 = 0x0002_0000;
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014C;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HeaderFlag {
+    pub name: String,
+    pub set: bool,
+    pub explanation: String,
+}
+
+// .NET程序集的CorFlags与PE Machine合并后的目标平台
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClrPlatform {
+    // 已设置的CorFlags: ILONLY / 32BITREQUIRED / 32BITPREFERRED
+    pub cor_flags: Vec<String>,
+    pub machine: String,
+    // x86 / x64 / ARM64 / AnyCPU / AnyCPU (32位首选)
+    pub target: String,
+    pub runs_as: String,
+}
+
+fn flag(name: &str, set: bool, explanation: &str) -> HeaderFlag {
+    HeaderFlag {
+        name: name.to_string(),
+        set,
+        explanation: explanation.to_string(),
+    }
+}
+
+// .NET程序的目标平台 由PE格式和CLR标志共同决定
+fn clr_platform(is_x64: bool, clr_flags: u32) -> HeaderFlag {
+    let required = clr_flags & COMIMAGE_FLAGS_32BITREQUIRED != 0;
+    let preferred = clr_flags & COMIMAGE_FLAGS_32BITPREFERRED != 0;
+    let il_only = clr_flags & COMIMAGE_FLAGS_ILONLY != 0;
+    if is_x64 {
+        return flag(
+            "32BITPREFERRED",
+            false,
+            ".NET程序编译为x64/ARM64 只能以64位进程运行",
+        );
+    }
+    if required && preferred {
+        flag(
+            "32BITPREFERRED",
+            true,
+            "AnyCPU(首选32位): 在64位Windows上也以WoW64下的32位进程运行 可加载32位本机DLL 但地址空间受限",
+        )
+    } else if required {
+        flag(
+            "32BITPREFERRED",
+            false,
+            ".NET程序编译为x86 始终以32位进程运行 在64位Windows上通过WoW64执行",
+        )
+    } else if il_only {
+        flag(
+            "32BITPREFERRED",
+            false,
+            "AnyCPU: 在64位Windows上以64位进程运行 此时无法加载32位本机DLL",
+        )
+    } else {
+        flag(
+            "32BITPREFERRED",
+            false,
+            "混合模式程序集包含本机代码 只能以与PE格式一致的32位进程运行",
+        )
+    }
+}
+
+// 等价于corflags.exe的判断: PE32且仅IL为AnyCPU 其余情况由Machine决定
+pub(crate) fn clr_platform_target(machine: u16, is_x64: bool, clr_flags: u32) -> ClrPlatform {
+    let il_only = clr_flags & COMIMAGE_FLAGS_ILONLY != 0;
+    let required = clr_flags & COMIMAGE_FLAGS_32BITREQUIRED != 0;
+    let preferred = clr_flags & COMIMAGE_FLAGS_32BITPREFERRED != 0;
+    let cor_flags: Vec<String> = [
+        (il_only, "ILONLY"),
+        (required, "32BITREQUIRED"),
+        (preferred, "32BITPREFERRED"),
+    ]
+    .into_iter()
+    .filter(|(set, _)| *set)
+    .map(|(_, name)| name.to_string())
+    .collect();
+
+    let (target, runs_as) = if is_x64 {
+        let name = machine_name(machine);
+        if required || preferred {
+            (
+                name,
+                "PE32+程序集不允许设置32位标志 CLR会拒绝加载(BadImageFormatException)".to_string(),
+            )
+        } else {
+            (
+                name.clone(),
+                format!("只能以{}进程运行 无法在32位Windows上启动", name),
+            )
+        }
+    } else if machine != IMAGE_FILE_MACHINE_I386 {
+        let name = machine_name(machine);
+        (name.clone(), format!("只能在{}平台上运行", name))
+    } else if !il_only {
+        (
+            "x86".to_string(),
+            "混合模式程序集包含x86本机代码 始终以32位进程运行 在64位Windows上通过WoW64执行"
+                .to_string(),
+        )
+    } else if preferred && !required {
+        (
+            "AnyCPU".to_string(),
+            "32BITPREFERRED未与32BITREQUIRED同时设置 是无效组合 CLR会拒绝加载".to_string(),
+        )
+    } else if preferred {
+        (
+            "AnyCPU (32位首选)".to_string(),
+            "在任何Windows上都以32位进程运行 64位系统上通过WoW64执行".to_string(),
+        )
+    } else if required {
+        (
+            "x86".to_string(),
+            "始终以32位x86进程运行 在64位Windows上通过WoW64执行".to_string(),
+        )
+    } else {
+        (
+            "AnyCPU".to_string(),
+            "以操作系统的原生位数运行: 32位Windows上为x86 64位Windows上为x64或ARM64".to_string(),
+        )
+    };
+    ClrPlatform {
+        cor_flags,
+        machine: machine_name(machine),
+        target,
+        runs_as,
+    }
+}
+
+pub(crate) fn explain_wow64_flags(
+    is_x64: bool,
+    characteristics: u16,
+    dll_characteristics: u16,
+    clr_flags: Option<u32>,
+) -> Vec<HeaderFlag> {
+    let mut flags: Vec<HeaderFlag> = Vec::new();
+
+    let large_address_aware = characteristics & IMAGE_FILE_LARGE_ADDRESS_AWARE != 0;
+    let explanation = match (is_x64, large_address_aware) {
+        (false, true) => "32位进程在64位Windows上可使用4GB用户地址空间 在32位Windows上需开启/3GB(increaseuserva)才能超过2GB",
+        (false, false) => "32位进程的用户地址空间限制为2GB 即使运行在64位Windows上 超出后内存分配会失败",
+        (true, true) => "64位程序的默认设置 可使用完整的64位用户地址空间",
+        (true, false) => "64位程序被限制在低2GB地址 通常只在代码假定指针可截断为32位时才这样设置",
+    };
+    flags.push(flag(
+        "LARGE_ADDRESS_AWARE",
+        large_address_aware,
+        explanation,
+    ));
+
+    if let Some(clr_flags) = clr_flags {
+        flags.push(clr_platform(is_x64, clr_flags));
+    }
+
+    let terminal_server_aware =
+        dll_characteristics & IMAGE_DLLCHARACTERISTICS_TERMINAL_SERVER_AWARE != 0;
+    flags.push(flag(
+        "TERMINAL_SERVER_AWARE",
+        terminal_server_aware,
+        if terminal_server_aware {
+            "在远程桌面会话中不启用兼容性重定向 程序自行处理多用户场景"
+        } else {
+            "在远程桌面服务器上 Windows会把写入Windows目录的INI文件和部分HKLM注册表操作重定向到用户目录"
+        },
+    ));
+    flags
+}
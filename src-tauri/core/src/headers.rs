@@ -0,0 +1,317 @@
+// DOS头、COFF头和可选头的完整字段 字段名与winnt.h一致 供分诊时按表格查看
+use serde::{Deserialize, Serialize};
+
+use crate::reader::{BoundedReader, ReadError};
+
+// COFF头Characteristics
+const FILE_CHARACTERISTICS: &[(u16, &str)] = &[
+    (0x0001, "RELOCS_STRIPPED"),
+    (0x0002, "EXECUTABLE_IMAGE"),
+    (0x0004, "LINE_NUMS_STRIPPED"),
+    (0x0008, "LOCAL_SYMS_STRIPPED"),
+    (0x0010, "AGGRESSIVE_WS_TRIM"),
+    (0x0020, "LARGE_ADDRESS_AWARE"),
+    (0x0080, "BYTES_REVERSED_LO"),
+    (0x0100, "32BIT_MACHINE"),
+    (0x0200, "DEBUG_STRIPPED"),
+    (0x0400, "REMOVABLE_RUN_FROM_SWAP"),
+    (0x0800, "NET_RUN_FROM_SWAP"),
+    (0x1000, "SYSTEM"),
+    (0x2000, "DLL"),
+    (0x4000, "UP_SYSTEM_ONLY"),
+    (0x8000, "BYTES_REVERSED_HI"),
+];
+
+// 可选头DllCharacteristics
+const DLL_CHARACTERISTICS: &[(u16, &str)] = &[
+    (0x0020, "HIGH_ENTROPY_VA"),
+    (0x0040, "DYNAMIC_BASE"),
+    (0x0080, "FORCE_INTEGRITY"),
+    (0x0100, "NX_COMPAT"),
+    (0x0200, "NO_ISOLATION"),
+    (0x0400, "NO_SEH"),
+    (0x0800, "NO_BIND"),
+    (0x1000, "APPCONTAINER"),
+    (0x2000, "WDM_DRIVER"),
+    (0x4000, "GUARD_CF"),
+    (0x8000, "TERMINAL_SERVER_AWARE"),
+];
+
+// 可选头中CheckSum的相对偏移 32位和64位相同
+const CHECKSUM_OFFSET: u64 = 0x40;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct DosHeader {
+    pub e_magic: u16,
+    pub e_cblp: u16,
+    pub e_cp: u16,
+    pub e_crlc: u16,
+    pub e_cparhdr: u16,
+    pub e_minalloc: u16,
+    pub e_maxalloc: u16,
+    pub e_ss: u16,
+    pub e_sp: u16,
+    pub e_csum: u16,
+    pub e_ip: u16,
+    pub e_cs: u16,
+    pub e_lfarlc: u16,
+    pub e_ovno: u16,
+    pub e_oemid: u16,
+    pub e_oeminfo: u16,
+    pub e_lfanew: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct CoffHeader {
+    pub machine: u16,
+    pub machine_name: String,
+    pub number_of_sections: u16,
+    pub time_date_stamp: u32,
+    // UTC时间文本 为0或可重现构建(时间戳是内容哈希)时为None
+    pub timestamp_utc: Option<String>,
+    pub pointer_to_symbol_table: u32,
+    pub number_of_symbols: u32,
+    pub size_of_optional_header: u16,
+    pub characteristics: u16,
+    pub characteristic_names: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct OptionalHeader {
+    // 0x10B为PE32 0x20B为PE32+
+    pub magic: u16,
+    pub major_linker_version: u8,
+    pub minor_linker_version: u8,
+    pub size_of_code: u32,
+    pub size_of_initialized_data: u32,
+    pub size_of_uninitialized_data: u32,
+    pub address_of_entry_point: u32,
+    pub base_of_code: u32,
+    // 只有PE32有此字段
+    pub base_of_data: Option<u32>,
+    pub image_base: u64,
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub major_operating_system_version: u16,
+    pub minor_operating_system_version: u16,
+    pub major_image_version: u16,
+    pub minor_image_version: u16,
+    pub major_subsystem_version: u16,
+    pub minor_subsystem_version: u16,
+    pub win32_version_value: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub check_sum: u32,
+    // 按ImageHlp的CheckSumMappedFile算法重新计算 驱动加载时要求两者一致
+    pub computed_check_sum: u32,
+    pub subsystem: u16,
+    pub subsystem_name: String,
+    pub dll_characteristics: u16,
+    pub dll_characteristic_names: Vec<String>,
+    pub size_of_stack_reserve: u64,
+    pub size_of_stack_commit: u64,
+    pub size_of_heap_reserve: u64,
+    pub size_of_heap_commit: u64,
+    pub loader_flags: u32,
+    pub number_of_rva_and_sizes: u32,
+}
+
+fn flag_names(value: u16, flags: &[(u16, &str)]) -> Vec<String> {
+    flags
+        .iter()
+        .filter(|(bit, _)| value & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect()
+}
+
+// 自1970-01-01起的天数 -> 公历日期
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+// 与前端formatTimestamp格式一致
+pub(crate) fn format_utc(seconds: u32) -> String {
+    let seconds = seconds as i64;
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86400));
+    let time = seconds.rem_euclid(86400);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+// 16位累加并折叠进位 跳过CheckSum字段本身 最后加上文件长度
+fn compute_checksum(data: &[u8], checksum_offset: usize) -> u32 {
+    let mut sum: u64 = 0;
+    for (index, chunk) in data.chunks(2).enumerate() {
+        let offset = index * 2;
+        if (checksum_offset..checksum_offset + 4).contains(&offset) {
+            continue;
+        }
+        let word = chunk[0] as u64 | (chunk.get(1).copied().unwrap_or(0) as u64) << 8;
+        sum += word;
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    sum = (sum & 0xFFFF) + (sum >> 16);
+    (sum as u32).wrapping_add(data.len() as u32)
+}
+
+fn read_dos_header(reader: &BoundedReader) -> Result<DosHeader, ReadError> {
+    let word = |index: u64| reader.u16(index * 2);
+    Ok(DosHeader {
+        e_magic: word(0)?,
+        e_cblp: word(1)?,
+        e_cp: word(2)?,
+        e_crlc: word(3)?,
+        e_cparhdr: word(4)?,
+        e_minalloc: word(5)?,
+        e_maxalloc: word(6)?,
+        e_ss: word(7)?,
+        e_sp: word(8)?,
+        e_csum: word(9)?,
+        e_ip: word(10)?,
+        e_cs: word(11)?,
+        e_lfarlc: word(12)?,
+        e_ovno: word(13)?,
+        // 0x1C-0x23为e_res
+        e_oemid: reader.u16(0x24)?,
+        e_oeminfo: reader.u16(0x26)?,
+        // 0x28-0x3B为e_res2
+        e_lfanew: reader.u32(0x3C)?,
+    })
+}
+
+fn read_coff_header(reader: &BoundedReader, coff: u64) -> Result<CoffHeader, ReadError> {
+    let machine = reader.u16(coff + 0x04)?;
+    let time_date_stamp = reader.u32(coff + 0x08)?;
+    let characteristics = reader.u16(coff + 0x16)?;
+    Ok(CoffHeader {
+        machine,
+        machine_name: machine_name(machine),
+        number_of_sections: reader.u16(coff + 0x06)?,
+        time_date_stamp,
+        timestamp_utc: (time_date_stamp != 0).then(|| format_utc(time_date_stamp)),
+        pointer_to_symbol_table: reader.u32(coff + 0x0C)?,
+        number_of_symbols: reader.u32(coff + 0x10)?,
+        size_of_optional_header: reader.u16(coff + 0x14)?,
+        characteristics,
+        characteristic_names: flag_names(characteristics, FILE_CHARACTERISTICS),
+    })
+}
+
+// 64位可选头没有BaseOfData ImageBase和栈/堆大小扩展为8字节 之后的字段整体后移0x10
+fn read_optional_header(
+    reader: &BoundedReader,
+    data: &[u8],
+    optional: u64,
+    is_x64: bool,
+) -> Result<OptionalHeader, ReadError> {
+    let (image_base, base_of_data) = if is_x64 {
+        (reader.u64(optional + 0x18)?, None)
+    } else {
+        (
+            reader.u32(optional + 0x1C)? as u64,
+            Some(reader.u32(optional + 0x18)?),
+        )
+    };
+    let width: u64 = if is_x64 { 8 } else { 4 };
+    let stack_and_heap = optional + 0x48;
+    let tail = stack_and_heap + width * 4;
+    let subsystem = reader.u16(optional + 0x44)?;
+    let dll_characteristics = reader.u16(optional + 0x46)?;
+    let [major_linker_version, minor_linker_version] = reader.array::<2>(optional + 0x02)?;
+    Ok(OptionalHeader {
+        magic: reader.u16(optional)?,
+        major_linker_version,
+        minor_linker_version,
+        size_of_code: reader.u32(optional + 0x04)?,
+        size_of_initialized_data: reader.u32(optional + 0x08)?,
+        size_of_uninitialized_data: reader.u32(optional + 0x0C)?,
+        address_of_entry_point: reader.u32(optional + 0x10)?,
+        base_of_code: reader.u32(optional + 0x14)?,
+        base_of_data,
+        image_base,
+        section_alignment: reader.u32(optional + 0x20)?,
+        file_alignment: reader.u32(optional + 0x24)?,
+        major_operating_system_version: reader.u16(optional + 0x28)?,
+        minor_operating_system_version: reader.u16(optional + 0x2A)?,
+        major_image_version: reader.u16(optional + 0x2C)?,
+        minor_image_version: reader.u16(optional + 0x2E)?,
+        major_subsystem_version: reader.u16(optional + 0x30)?,
+        minor_subsystem_version: reader.u16(optional + 0x32)?,
+        win32_version_value: reader.u32(optional + 0x34)?,
+        size_of_image: reader.u32(optional + 0x38)?,
+        size_of_headers: reader.u32(optional + 0x3C)?,
+        check_sum: reader.u32(optional + CHECKSUM_OFFSET)?,
+        computed_check_sum: compute_checksum(data, (optional + CHECKSUM_OFFSET) as usize),
+        subsystem,
+        subsystem_name: subsystem_name(subsystem),
+        dll_characteristics,
+        dll_characteristic_names: flag_names(dll_characteristics, DLL_CHARACTERISTICS),
+        size_of_stack_reserve: reader.uint(stack_and_heap, width)?,
+        size_of_stack_commit: reader.uint(stack_and_heap + width, width)?,
+        size_of_heap_reserve: reader.uint(stack_and_heap + width * 2, width)?,
+        size_of_heap_commit: reader.uint(stack_and_heap + width * 3, width)?,
+        loader_flags: reader.u32(tail)?,
+        number_of_rva_and_sizes: reader.u32(tail + 4)?,
+    })
+}
+
+// coff为PE签名的偏移 调用前已确认签名和可选头magic
+pub fn read_headers(
+    reader: &BoundedReader,
+    data: &[u8],
+    coff: u64,
+    is_x64: bool,
+) -> Result<(DosHeader, CoffHeader, OptionalHeader), ReadError> {
+    Ok((
+        read_dos_header(reader)?,
+        read_coff_header(reader, coff)?,
+        read_optional_header(reader, data, coff + 0x18, is_x64)?,
+    ))
+}
+
+pub fn machine_name(machine: u16) -> String {
+    match machine {
+        0x014C => "x86".to_string(),
+        0x8664 => "x64".to_string(),
+        0xAA64 => "ARM64".to_string(),
+        0x01C4 => "ARMv7".to_string(),
+        0x0200 => "IA-64".to_string(),
+        other => format!("0x{:04X}", other),
+    }
+}
+
+pub fn subsystem_name(subsystem: u16) -> String {
+    match subsystem {
+        1 => "Native".to_string(),
+        2 => "Windows GUI".to_string(),
+        3 => "Windows CUI".to_string(),
+        9 => "Windows CE".to_string(),
+        10 => "EFI应用".to_string(),
+        11 => "EFI启动服务驱动".to_string(),
+        12 => "EFI运行时驱动".to_string(),
+        14 => "Xbox".to_string(),
+        16 => "启动应用".to_string(),
+        other => other.to_string(),
+    }
+}
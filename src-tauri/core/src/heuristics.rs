@@ -0,0 +1,824 @@
+// 结构异常检测 正常编译器生成的文件一般不会触发这些警告
+use serde::{Deserialize, Serialize};
+
+use crate::authenticode::{is_microsoft_signature, SignatureInfo};
+use crate::capabilities::Indicator;
+use crate::driver::DriverInfo;
+use crate::ep_signature::EntryPointBytes;
+use crate::export_hooks::ExportAnomaly;
+use crate::iat::IatState;
+use crate::limits::LimitHit;
+use crate::slack::SlackRegion;
+use crate::stub::StubInfo;
+use crate::tls::{RuntimeCallbacks, TlsInfo};
+use crate::version_info::{claims_microsoft, VersionInfo};
+use crate::{ImportDiagnostic, Section};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Warning {
+    pub kind: String,
+    pub severity: Severity,
+    pub message: String,
+    // 前端中展示相关详情的面板
+    pub pane: Option<String>,
+}
+
+impl Warning {
+    pub fn new(kind: &str, severity: Severity, message: String) -> Self {
+        Warning {
+            kind: kind.to_string(),
+            severity,
+            message,
+            pane: None,
+        }
+    }
+
+    pub fn with_pane(mut self, pane: &str) -> Self {
+        self.pane = Some(pane.to_string());
+        self
+    }
+}
+
+pub(crate) fn align_up(value: u64, alignment: u32) -> u64 {
+    if alignment == 0 {
+        return value;
+    }
+    let alignment = alignment as u64;
+    value.div_ceil(alignment) * alignment
+}
+
+// 节区在内存中实际占用的大小 VirtualSize为0时加载器使用SizeOfRawData
+pub(crate) fn virtual_extent(section: &Section) -> u64 {
+    let virtual_size = (section.rv_end - section.rva) as u64;
+    if virtual_size == 0 {
+        section.size_raw_data as u64
+    } else {
+        virtual_size
+    }
+}
+
+// 检查节表的几何关系: 虚拟地址重叠、文件范围重叠、空洞以及超出文件末尾
+pub(crate) fn check_section_layout(
+    sections: &[Section],
+    section_alignment: u32,
+    file_size: u64,
+) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+
+    // 两两比较是否重叠
+    for (i, a) in sections.iter().enumerate() {
+        for b in &sections[i + 1..] {
+            let a_start = a.rva as u64;
+            let a_end = a_start + virtual_extent(a);
+            let b_start = b.rva as u64;
+            let b_end = b_start + virtual_extent(b);
+            if a_start < b_end && b_start < a_end {
+                warnings.push(Warning::new(
+                    "section_virtual_overlap",
+                    Severity::High,
+                    format!(
+                        "节区 {} (0x{:X}-0x{:X}) 与 {} (0x{:X}-0x{:X}) 的虚拟地址范围重叠",
+                        a.name, a_start, a_end, b.name, b_start, b_end
+                    ),
+                ));
+            }
+
+            if a.size_raw_data == 0 || b.size_raw_data == 0 {
+                continue;
+            }
+            let a_start = a.ptr_raw_data as u64;
+            let a_end = a_start + a.size_raw_data as u64;
+            let b_start = b.ptr_raw_data as u64;
+            let b_end = b_start + b.size_raw_data as u64;
+            if a_start < b_end && b_start < a_end {
+                warnings.push(Warning::new(
+                    "section_raw_overlap",
+                    Severity::Medium,
+                    format!(
+                        "节区 {} (0x{:X}-0x{:X}) 与 {} (0x{:X}-0x{:X}) 的文件数据范围重叠",
+                        a.name, a_start, a_end, b.name, b_start, b_end
+                    ),
+                ));
+            }
+        }
+    }
+
+    // 按RVA排序后检查虚拟地址空洞
+    let mut by_rva: Vec<&Section> = sections.iter().collect();
+    by_rva.sort_by_key(|s| s.rva);
+    for pair in by_rva.windows(2) {
+        let expected = align_up(
+            pair[0].rva as u64 + virtual_extent(pair[0]),
+            section_alignment,
+        );
+        let next = pair[1].rva as u64;
+        if next > expected {
+            warnings.push(Warning::new(
+                "section_virtual_gap",
+                Severity::Low,
+                format!(
+                    "节区 {} 与 {} 之间存在 0x{:X} 字节的虚拟地址空洞",
+                    pair[0].name,
+                    pair[1].name,
+                    next - expected
+                ),
+            ));
+        }
+    }
+
+    // 按文件偏移排序后检查文件空洞
+    let mut by_raw: Vec<&Section> = sections.iter().filter(|s| s.size_raw_data != 0).collect();
+    by_raw.sort_by_key(|s| s.ptr_raw_data);
+    for pair in by_raw.windows(2) {
+        let expected = pair[0].ptr_raw_data as u64 + pair[0].size_raw_data as u64;
+        let next = pair[1].ptr_raw_data as u64;
+        if next > expected {
+            warnings.push(Warning::new(
+                "section_raw_gap",
+                Severity::Low,
+                format!(
+                    "节区 {} 与 {} 之间存在 0x{:X} 字节未被节表描述的文件数据",
+                    pair[0].name,
+                    pair[1].name,
+                    next - expected
+                ),
+            ));
+        }
+    }
+
+    // 检查是否超出文件末尾
+    for section in sections {
+        let raw_end = section.ptr_raw_data as u64 + section.size_raw_data as u64;
+        if section.size_raw_data != 0 && raw_end > file_size {
+            warnings.push(Warning::new(
+                "section_beyond_eof",
+                Severity::High,
+                format!(
+                    "节区 {} 的文件数据结束于 0x{:X} 超出了文件大小 0x{:X}",
+                    section.name, raw_end, file_size
+                ),
+            ));
+        }
+    }
+
+    warnings
+}
+
+pub const IMAGE_SCN_CNT_CODE: u32 = 0x0000_0020;
+pub const IMAGE_SCN_MEM_EXECUTE: u32 = 0x2000_0000;
+pub const IMAGE_SCN_MEM_WRITE: u32 = 0x8000_0000;
+
+// 带执行权限或标记为代码的节 只标记代码的节在加载后同样可执行
+pub fn is_executable(section: &Section) -> bool {
+    section.characteristics & (IMAGE_SCN_MEM_EXECUTE | IMAGE_SCN_CNT_CODE) != 0
+}
+
+pub fn is_writable(section: &Section) -> bool {
+    section.characteristics & IMAGE_SCN_MEM_WRITE != 0
+}
+
+// 检查节区权限: 可写可执行、无文件数据的可执行节区、入口点位于可写节区
+pub(crate) fn check_section_permissions(sections: &[Section], entry_point: u32) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+
+    for section in sections {
+        if is_executable(section) && is_writable(section) {
+            warnings.push(Warning::new(
+                "section_write_execute",
+                Severity::High,
+                format!(
+                    "节区 {} 同时具有可写和可执行权限 (Characteristics 0x{:08X}) 常见于加壳或自修改代码",
+                    section.name, section.characteristics
+                ),
+            ));
+        }
+        if is_executable(section) && section.size_raw_data == 0 {
+            warnings.push(Warning::new(
+                "section_exec_no_raw_data",
+                Severity::Medium,
+                format!(
+                    "可执行节区 {} 在文件中没有数据 代码只能在运行时写入 常见于UPX等壳的解压目标节区",
+                    section.name
+                ),
+            ));
+        }
+    }
+
+    if entry_point != 0 {
+        let entry_section = sections.iter().find(|s| {
+            let end = s.rva as u64 + virtual_extent(s);
+            entry_point >= s.rva && (entry_point as u64) < end
+        });
+        match entry_section {
+            Some(section) if is_writable(section) => {
+                warnings.push(Warning::new(
+                    "entry_point_writable",
+                    Severity::High,
+                    format!(
+                        "入口点 0x{:X} 位于可写节区 {} 中",
+                        entry_point, section.name
+                    ),
+                ));
+            }
+            Some(_) => {}
+            None => {
+                warnings.push(Warning::new(
+                    "entry_point_outside_sections",
+                    Severity::High,
+                    format!("入口点 0x{:X} 不在任何节区内", entry_point),
+                ));
+            }
+        }
+    }
+
+    warnings
+}
+
+// 常见壳/保护器使用的节区名
+pub(crate) const PACKER_SECTION_NAMES: &[(&str, &str)] = &[
+    ("UPX0", "UPX"),
+    ("UPX1", "UPX"),
+    ("UPX2", "UPX"),
+    (".aspack", "ASPack"),
+    (".adata", "ASPack"),
+    (".vmp0", "VMProtect"),
+    (".vmp1", "VMProtect"),
+    (".vmp2", "VMProtect"),
+    (".themida", "Themida"),
+    (".winlice", "WinLicense"),
+    (".MPRESS1", "MPRESS"),
+    (".MPRESS2", "MPRESS"),
+    (".petite", "Petite"),
+    (".nsp0", "NsPack"),
+    (".nsp1", "NsPack"),
+    (".nsp2", "NsPack"),
+    (".enigma1", "Enigma Protector"),
+    (".enigma2", "Enigma Protector"),
+    ("PEC2", "PECompact"),
+    ("pec1", "PECompact"),
+    ("pebundle", "PEBundle"),
+    (".perplex", "Perplex PE-Protector"),
+    (".yP", "Y0da Protector"),
+    (".spack", "Simple Pack"),
+    ("kkrunchy", "kkrunchy"),
+];
+
+pub fn packer_for_section_name(name: &str) -> Option<&'static str> {
+    PACKER_SECTION_NAMES
+        .iter()
+        .find(|(section_name, _)| section_name.eq_ignore_ascii_case(name))
+        .map(|(_, packer)| *packer)
+}
+
+// 入口点代码与已知壳的解压存根一致
+pub(crate) fn check_entry_point_signature(entry: &EntryPointBytes) -> Vec<Warning> {
+    entry
+        .signature
+        .iter()
+        .map(|packer| {
+            Warning::new(
+                "entry_point_packer",
+                Severity::Medium,
+                format!(
+                    "入口点(文件偏移0x{:X})的代码与 {} 的解压存根一致 文件很可能经过加壳",
+                    entry.offset, packer
+                ),
+            )
+        })
+        .collect()
+}
+
+// 导出地址被改写到注入的代码 加载器和GetProcAddress会把调用方引向这些地址
+pub(crate) fn check_export_anomalies(anomalies: &[ExportAnomaly]) -> Vec<Warning> {
+    if anomalies.is_empty() {
+        return Vec::new();
+    }
+    let mut names: Vec<String> = anomalies
+        .iter()
+        .take(8)
+        .map(|anomaly| {
+            let name = if !anomaly.is_named {
+                format!("#{}", anomaly.ordinal)
+            } else {
+                anomaly.name.clone()
+            };
+            let location = match anomaly.reason.as_str() {
+                "header" => "PE头".to_string(),
+                "outside_image" => "映像之外".to_string(),
+                "no_raw_data" => format!(
+                    "节 {} 中没有文件数据的部分",
+                    anomaly.section.as_deref().unwrap_or("")
+                ),
+                _ => format!("追加的代码节 {}", anomaly.section.as_deref().unwrap_or("")),
+            };
+            format!("{}(0x{:X}, {})", name, anomaly.address, location)
+        })
+        .collect();
+    if anomalies.len() > names.len() {
+        names.push(format!("等共{}个", anomalies.len()));
+    }
+    vec![Warning::new(
+        "export_hook",
+        Severity::High,
+        format!(
+            "导出地址指向异常位置 可能是导出表劫持(EAT Hook): {}",
+            names.join(", ")
+        ),
+    )
+    .with_pane("exports")]
+}
+
+// 未绑定却已填入地址的IAT 说明文件取自进程内存而非磁盘
+pub(crate) fn check_iat_state(iat: &IatState) -> Vec<Warning> {
+    let resolved: Vec<&str> = iat
+        .dlls
+        .iter()
+        .filter(|dll| dll.state == "resolved")
+        .map(|dll| dll.dll_name.as_str())
+        .collect();
+    if resolved.is_empty() {
+        return Vec::new();
+    }
+    vec![Warning::new(
+        "iat_resolved",
+        Severity::Medium,
+        format!(
+            "{} 的IAT已填入绝对地址且未标记为绑定 文件可能是从内存中转储的(如脱壳后的样本)",
+            resolved.join(", ")
+        ),
+    )
+    .with_pane("imports")]
+}
+
+// 空隙中少量非零字节可能是对齐指令 超过此数量才报告
+const MIN_SLACK_DATA: u32 = 64;
+// 空隙数据的熵超过此值视为压缩或加密的载荷
+const HIGH_SLACK_ENTROPY: f64 = 7.0;
+
+pub(crate) fn check_slack(regions: &[SlackRegion]) -> Vec<Warning> {
+    regions
+        .iter()
+        .filter(|region| region.non_zero_bytes >= MIN_SLACK_DATA)
+        .map(|region| {
+            let high_entropy = region.entropy >= HIGH_SLACK_ENTROPY;
+            Warning::new(
+                "slack_data",
+                if high_entropy {
+                    Severity::Medium
+                } else {
+                    Severity::Low
+                },
+                format!(
+                    "节 {} 的空隙(文件偏移0x{:X})中有{}个非零字节 熵{:.2}",
+                    region.section, region.offset, region.non_zero_bytes, region.entropy
+                ),
+            )
+            .with_pane("slack")
+        })
+        .collect()
+}
+
+// 触发解析上限 结果不完整 且正常文件很少有这么大的结构
+pub(crate) fn check_limit_hits(hits: &[LimitHit]) -> Vec<Warning> {
+    hits.iter()
+        .map(|hit| {
+            Warning::new(
+                "limit_hit",
+                Severity::Medium,
+                format!("达到解析上限({}): {}", hit.limit, hit.message),
+            )
+        })
+        .collect()
+}
+
+// 导入项的RVA不在任何节内 加载时会失败或被故意构造来干扰分析工具
+pub(crate) fn check_import_diagnostics(diagnostics: &[ImportDiagnostic]) -> Vec<Warning> {
+    if diagnostics.is_empty() {
+        return Vec::new();
+    }
+    let descriptors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.slot.is_none())
+        .count();
+    let functions = diagnostics.len() - descriptors;
+    vec![Warning::new(
+        "import_entries_dropped",
+        Severity::Medium,
+        format!(
+            "{}个导入描述符和{}个导入函数的RVA不在任何节内 已跳过",
+            descriptors, functions
+        ),
+    )
+    .with_pane("imports")]
+}
+
+// 导入目录大小小于实际的描述符数组 加载器不受影响 但按大小解析的工具会漏掉后面的DLL
+pub(crate) fn check_import_directory_size(size: u32, descriptors: u32) -> Vec<Warning> {
+    if descriptors <= size / 20 {
+        return Vec::new();
+    }
+    vec![Warning::new(
+        "import_directory_size",
+        Severity::Low,
+        format!(
+            "导入目录大小0x{:X}只覆盖{}个描述符 实际数组有{}个(以全0描述符结尾)",
+            size,
+            size / 20,
+            descriptors
+        ),
+    )
+    .with_pane("imports")]
+}
+
+// 检查节区名: 空名称、非ASCII/乱码、重复名称以及壳特征名称
+pub(crate) fn check_section_names(sections: &[Section]) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+
+    for (i, section) in sections.iter().enumerate() {
+        if section.name.is_empty() {
+            warnings.push(Warning::new(
+                "section_name_empty",
+                Severity::Medium,
+                format!(
+                    "第 {} 个节区没有名称 编译器总会为节区命名 空名称通常是手工构造或加壳的结果",
+                    i + 1
+                ),
+            ));
+        } else if !section
+            .name
+            .chars()
+            .all(|c| c.is_ascii_graphic() || c == ' ')
+        {
+            warnings.push(Warning::new(
+                "section_name_garbage",
+                Severity::Medium,
+                format!(
+                    "节区名 {:?} 含有不可打印或非ASCII字符 常见于加密壳随机生成的节区名",
+                    section.name
+                ),
+            ));
+        }
+
+        if !section.name.is_empty() && sections[..i].iter().any(|other| other.name == section.name)
+        {
+            warnings.push(Warning::new(
+                "section_name_duplicate",
+                Severity::Low,
+                format!(
+                    "节区名 {} 重复出现 链接器会合并同名节区 重复名称多为手工修改或壳添加的节区",
+                    section.name
+                ),
+            ));
+        }
+
+        if let Some(packer) = packer_for_section_name(&section.name) {
+            warnings.push(Warning::new(
+                "section_name_packer",
+                Severity::Medium,
+                format!(
+                    "节区名 {} 是 {} 的特征节区名 文件很可能经过加壳或保护处理",
+                    section.name, packer
+                ),
+            ));
+        }
+    }
+
+    warnings
+}
+
+// TLS回调在入口点之前执行 经常被用于反调试
+// 运行库自带的回调不单独报告 只在回调多于运行库注册的数量或指向代码节以外时警告
+pub(crate) fn check_tls_callbacks(
+    sections: &[Section],
+    tls: &TlsInfo,
+    runtime: &RuntimeCallbacks,
+) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+
+    // 回调指向非可执行节区或不在任何节区内时提高级别
+    let outside_code: Vec<u32> = tls
+        .callbacks
+        .iter()
+        .copied()
+        .filter(|&callback| {
+            !sections.iter().any(|s| {
+                is_executable(s)
+                    && callback >= s.rva
+                    && (callback as u64) < s.rva as u64 + virtual_extent(s)
+            })
+        })
+        .collect();
+    let extra = tls.callbacks.len().saturating_sub(runtime.count);
+    if outside_code.is_empty() && extra == 0 {
+        return warnings;
+    }
+    let severity = if outside_code.is_empty() {
+        Severity::Medium
+    } else {
+        Severity::High
+    };
+
+    let format_list = |callbacks: &[u32]| {
+        callbacks
+            .iter()
+            .map(|callback| format!("0x{:X}", callback))
+            .collect::<Vec<String>>()
+            .join(", ")
+    };
+    let runtime_note = if runtime.count == 0 {
+        String::new()
+    } else {
+        format!(
+            " 其中{}个可能由{}注册",
+            runtime.count.min(tls.callbacks.len()),
+            runtime.runtimes.join("/")
+        )
+    };
+    warnings.push(
+        Warning::new(
+            "tls_callbacks",
+            severity,
+            format!(
+                "存在 {} 个TLS回调 ({}){} 它们在入口点之前执行 调试器默认不会在此处中断 经常被用于反调试{}",
+                tls.callbacks.len(),
+                format_list(&tls.callbacks),
+                runtime_note,
+                if outside_code.is_empty() {
+                    String::new()
+                } else {
+                    format!(" 且回调 {} 不在可执行节区内", format_list(&outside_code))
+                }
+            ),
+        )
+        .with_pane("tls"),
+    );
+
+    warnings
+}
+
+// 头部中与布局相关的字段
+pub(crate) struct HeaderLayout {
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    // 节表结束位置 即头部实际占用的大小
+    pub section_table_end: u64,
+}
+
+// 交叉检查头部字段: SizeOfImage、SizeOfHeaders以及对齐值
+pub(crate) fn check_header_consistency(
+    sections: &[Section],
+    layout: &HeaderLayout,
+) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+
+    let alignments_valid =
+        layout.section_alignment.is_power_of_two() && layout.file_alignment.is_power_of_two();
+    if !layout.section_alignment.is_power_of_two() {
+        warnings.push(Warning::new(
+            "section_alignment_invalid",
+            Severity::High,
+            format!(
+                "SectionAlignment 0x{:X} 不是2的幂",
+                layout.section_alignment
+            ),
+        ));
+    }
+    if !layout.file_alignment.is_power_of_two() {
+        warnings.push(Warning::new(
+            "file_alignment_invalid",
+            Severity::High,
+            format!("FileAlignment 0x{:X} 不是2的幂", layout.file_alignment),
+        ));
+    }
+    if alignments_valid {
+        if layout.section_alignment < layout.file_alignment {
+            warnings.push(Warning::new(
+                "alignment_order_invalid",
+                Severity::Medium,
+                format!(
+                    "SectionAlignment 0x{:X} 小于 FileAlignment 0x{:X}",
+                    layout.section_alignment, layout.file_alignment
+                ),
+            ));
+        }
+        // 小于页大小时两者必须相等 否则FileAlignment应在0x200到0x10000之间
+        if layout.section_alignment >= 0x1000 && !(0x200..=0x10000).contains(&layout.file_alignment)
+        {
+            warnings.push(Warning::new(
+                "file_alignment_out_of_range",
+                Severity::Medium,
+                format!(
+                    "FileAlignment 0x{:X} 不在规范要求的 0x200-0x10000 范围内",
+                    layout.file_alignment
+                ),
+            ));
+        }
+    }
+
+    // SizeOfImage应等于最后一个节区按SectionAlignment对齐后的结束位置
+    let image_end = sections
+        .iter()
+        .map(|s| s.rva as u64 + virtual_extent(s))
+        .max()
+        .unwrap_or(layout.size_of_headers as u64);
+    let expected_size_of_image = align_up(image_end, layout.section_alignment);
+    if layout.size_of_image as u64 != expected_size_of_image {
+        warnings.push(Warning::new(
+            "size_of_image_mismatch",
+            Severity::Medium,
+            format!(
+                "SizeOfImage 0x{:X} 与根据节表计算的 0x{:X} 不一致",
+                layout.size_of_image, expected_size_of_image
+            ),
+        ));
+    }
+
+    // SizeOfHeaders应覆盖到节表末尾并按FileAlignment对齐
+    if (layout.size_of_headers as u64) < layout.section_table_end {
+        warnings.push(Warning::new(
+            "size_of_headers_too_small",
+            Severity::High,
+            format!(
+                "SizeOfHeaders 0x{:X} 小于节表结束位置 0x{:X}",
+                layout.size_of_headers, layout.section_table_end
+            ),
+        ));
+    } else {
+        let expected_size_of_headers = align_up(layout.section_table_end, layout.file_alignment);
+        if layout.size_of_headers as u64 != expected_size_of_headers {
+            warnings.push(Warning::new(
+                "size_of_headers_mismatch",
+                Severity::Low,
+                format!(
+                    "SizeOfHeaders 0x{:X} 与按FileAlignment对齐的头部大小 0x{:X} 不一致",
+                    layout.size_of_headers, expected_size_of_headers
+                ),
+            ));
+        }
+    }
+
+    // 第一个节区的文件数据不应与头部重叠
+    if let Some(first_raw) = sections
+        .iter()
+        .filter(|s| s.size_raw_data != 0)
+        .map(|s| s.ptr_raw_data)
+        .min()
+    {
+        if (first_raw as u64) < layout.section_table_end {
+            warnings.push(Warning::new(
+                "section_overlaps_headers",
+                Severity::High,
+                format!(
+                    "节区文件数据起始于 0x{:X} 与头部 (结束于 0x{:X}) 重叠",
+                    first_raw, layout.section_table_end
+                ),
+            ));
+        }
+    }
+
+    warnings
+}
+
+// 进程注入能力作为单独的高危警告
+pub(crate) fn check_injection(indicators: &[Indicator]) -> Vec<Warning> {
+    let techniques: Vec<&str> = indicators
+        .iter()
+        .filter(|i| i.category == "injection")
+        .map(|i| i.name.as_str())
+        .collect();
+    if techniques.is_empty() {
+        return Vec::new();
+    }
+    vec![Warning::new(
+        "process_injection",
+        Severity::High,
+        format!(
+            "导入函数组合具备向其他进程注入代码的能力: {}",
+            techniques.join(", ")
+        ),
+    )
+    .with_pane("indicators")]
+}
+
+// 驱动签名要求: 64位系统默认只加载已签名驱动 回调注册函数还要求FORCE_INTEGRITY
+pub(crate) fn check_driver_signing(driver: &DriverInfo) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+    if !driver.embedded_signature {
+        warnings.push(
+            Warning::new(
+                "unsigned_driver",
+                Severity::Medium,
+                "驱动未嵌入Authenticode签名 除非有编录签名 否则64位Windows默认拒绝加载".to_string(),
+            )
+            .with_pane("driver"),
+        );
+    }
+    if !driver.integrity_apis.is_empty() && !driver.force_integrity {
+        warnings.push(
+            Warning::new(
+                "driver_integrity_flag",
+                Severity::Low,
+                format!(
+                    "调用{}但未设置FORCE_INTEGRITY标志 注册会返回STATUS_ACCESS_DENIED",
+                    driver.integrity_apis.join(", ")
+                ),
+            )
+            .with_pane("driver"),
+        );
+    }
+    warnings
+}
+
+// 没有导入的加载器存根 实际功能全在入口代码中
+pub(crate) fn check_stub(stub: &StubInfo) -> Vec<Warning> {
+    let mut evidence: Vec<String> = Vec::new();
+    if let Some(hint) = &stub.self_relocating {
+        evidence.push(format!("{} @0x{:X}", hint.instruction, hint.rva));
+    }
+    if let Some(hint) = &stub.peb_access {
+        evidence.push(format!("{} @0x{:X}", hint.instruction, hint.rva));
+    }
+    let message = if evidence.is_empty() {
+        "没有导入表且代码位于PE头中 可能是包装成PE的shellcode".to_string()
+    } else {
+        format!(
+            "没有导入表 入口代码自行定位({}) 可能是包装成PE的shellcode",
+            evidence.join(", ")
+        )
+    };
+    vec![Warning::new("loader_stub", Severity::Medium, message).with_pane("stub")]
+}
+
+// 签名后被修改的文件 以及版本资源冒充Microsoft但签名者不是Microsoft的文件
+pub(crate) fn check_signature(
+    signature: &SignatureInfo,
+    version_info: Option<&VersionInfo>,
+) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+    if signature.digest_valid == Some(false) {
+        warnings.push(Warning::new(
+            "signature_digest_mismatch",
+            Severity::High,
+            "签名中的映像摘要与文件内容不符 文件在签名后被修改".to_string(),
+        ));
+    }
+    if claims_microsoft(version_info) && !is_microsoft_signature(signature) {
+        warnings.push(Warning::new(
+            "microsoft_masquerade",
+            Severity::High,
+            format!(
+                "版本资源声称来自Microsoft 但签名者为 {}",
+                signature.signer.as_deref().unwrap_or("未知")
+            ),
+        ));
+    }
+    warnings
+}
+
+// 编译时间晚于签名时间的容差 覆盖构建机与时间戳服务器的时钟偏差
+const SIGNING_CLOCK_SKEW: i64 = 24 * 3600;
+// 编译后超过两年才签名视为可疑
+const SIGNING_GAP_LIMIT: i64 = 2 * 365 * 24 * 3600;
+// 旧版Delphi链接器写入的固定时间戳(1992-06-19)
+const DELPHI_TIME_DATE_STAMP: u32 = 0x2A42_5E19;
+
+// 编译时间与签名时间的先后顺序 可重现构建的TimeDateStamp是哈希 不参与比较
+pub(crate) fn check_signing_time(
+    time_date_stamp: u32,
+    signing_time: i64,
+    reproducible: bool,
+) -> Vec<Warning> {
+    if reproducible || time_date_stamp == 0 || time_date_stamp == DELPHI_TIME_DATE_STAMP {
+        return Vec::new();
+    }
+    let gap = signing_time - time_date_stamp as i64;
+    if gap < -SIGNING_CLOCK_SKEW {
+        vec![Warning::new(
+            "signed_before_compiled",
+            Severity::Medium,
+            format!(
+                "编译时间比签名时间晚{}天 时间戳被修改或签名取自其他文件",
+                -gap / 86400
+            ),
+        )]
+    } else if gap > SIGNING_GAP_LIMIT {
+        vec![Warning::new(
+            "signing_gap",
+            Severity::Low,
+            format!("编译{}天后才签名 可能是旧文件被重新签名", gap / 86400),
+        )]
+    } else {
+        Vec::new()
+    }
+}
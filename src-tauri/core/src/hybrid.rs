@@ -0,0 +1,353 @@
+// ARM64X/ARM64EC/CHPE混合二进制 代码范围来自CHPE元数据 另一视图的头部由ARM64X动态重定位改写
+use serde::{Deserialize, Serialize};
+
+use crate::dvrt::{arm64x_fixups, blocks, DynamicRelocation, IMAGE_DYNAMIC_RELOCATION_ARM64X};
+use crate::exception::{IMAGE_FILE_MACHINE_AMD64, IMAGE_FILE_MACHINE_ARM64};
+use crate::headers::machine_name;
+use crate::load_config::LoadConfig;
+use crate::reader::BoundedReader;
+use crate::{
+    decode_name, rva_to_offset, DataDirectory, ExportFunction, ImportFunction, ImportTableEntry,
+    Section,
+};
+
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014C;
+// 另一视图中导出/导入表的读取上限
+const MAX_VIEW_ENTRIES: u32 = 65536;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HybridRange {
+    pub start: u32,
+    pub length: u32,
+    // ARM64 / ARM64EC / x64 / x86
+    pub architecture: String,
+}
+
+// 另一架构视图 即加载器按ARM64X重定位改写头部后看到的导入导出
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HybridView {
+    pub machine: String,
+    pub entry_point: u32,
+    pub export_table: Vec<ExportFunction>,
+    pub import_table: Vec<ImportTableEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HybridInfo {
+    // ARM64X / ARM64EC / CHPE(x86)
+    pub kind: String,
+    pub metadata_version: Option<u32>,
+    pub code_ranges: Vec<HybridRange>,
+    // ARM64EC元数据中的另一入口点
+    pub alternate_entry_point: Option<u32>,
+    pub arm64x_fixups: u32,
+    // 其中改写PE头的数量
+    pub header_fixups: u32,
+    pub alternate_view: Option<HybridView>,
+}
+
+fn read_name(
+    reader: &BoundedReader,
+    sections: &[Section],
+    rva: u32,
+    max_name_length: usize,
+) -> Option<(String, Option<String>)> {
+    let (bytes, _) = reader
+        .c_string_truncated(rva_to_offset(sections, rva)? as u64, max_name_length)
+        .ok()?;
+    Some(decode_name(bytes))
+}
+
+// CHPE元数据中的代码范围 ARM64EC用低2位表示架构 x86 CHPE用最低位表示本机ARM64代码
+fn code_ranges(
+    reader: &BoundedReader,
+    sections: &[Section],
+    metadata: u64,
+    machine: u16,
+) -> Vec<HybridRange> {
+    let mut ranges: Vec<HybridRange> = Vec::new();
+    let (Ok(map_rva), Ok(count)) = (reader.u32(metadata + 4), reader.u32(metadata + 8)) else {
+        return ranges;
+    };
+    let Some(map) = rva_to_offset(sections, map_rva) else {
+        return ranges;
+    };
+    for i in 0..count.min(MAX_VIEW_ENTRIES) as u64 {
+        let (Ok(start), Ok(length)) = (
+            reader.u32(map as u64 + i * 8),
+            reader.u32(map as u64 + i * 8 + 4),
+        ) else {
+            break;
+        };
+        let (start, architecture) = if machine == IMAGE_FILE_MACHINE_I386 {
+            (start & !1, if start & 1 != 0 { "ARM64" } else { "x86" })
+        } else {
+            (
+                start & !3,
+                match start & 3 {
+                    0 => "ARM64",
+                    1 => "ARM64EC",
+                    _ => "x64",
+                },
+            )
+        };
+        ranges.push(HybridRange {
+            start,
+            length,
+            architecture: architecture.to_string(),
+        });
+    }
+    ranges
+}
+
+// 把ARM64X重定位应用到头部副本 返回(全部条目数, 改写头部的条目数)
+fn apply_arm64x(relocations: &[DynamicRelocation], header: &mut [u8]) -> (u32, u32) {
+    let mut total: u32 = 0;
+    let mut in_header: u32 = 0;
+    for relocation in relocations
+        .iter()
+        .filter(|relocation| relocation.symbol == IMAGE_DYNAMIC_RELOCATION_ARM64X)
+    {
+        for (page, entries) in blocks(relocation.fixups) {
+            for fixup in arm64x_fixups(page, entries) {
+                total += 1;
+                let offset = fixup.offset;
+                let patch: Option<Vec<u8>> = match fixup.kind {
+                    0 => Some(vec![0; 1 << fixup.meta]),
+                    1 => Some(fixup.payload.to_vec()),
+                    // 增量为2字节值乘以4或8 meta最低位表示减
+                    _ => {
+                        let delta = BoundedReader::new(fixup.payload).u16(0).unwrap_or(0) as i64
+                            * if fixup.meta & 2 != 0 { 8 } else { 4 };
+                        BoundedReader::new(header).u32(offset).ok().map(|value| {
+                            let value = if fixup.meta & 1 != 0 {
+                                value as i64 - delta
+                            } else {
+                                value as i64 + delta
+                            };
+                            (value as u32).to_le_bytes().to_vec()
+                        })
+                    }
+                };
+                if let Some(patch) = patch {
+                    // 偏移超出头部副本的条目改写的是节数据 这里不关心
+                    let end = offset + patch.len() as u64;
+                    if end <= header.len() as u64 {
+                        header[offset as usize..end as usize].copy_from_slice(&patch);
+                        in_header += 1;
+                    }
+                }
+            }
+        }
+    }
+    (total, in_header)
+}
+
+fn parse_exports(
+    reader: &BoundedReader,
+    sections: &[Section],
+    directory: DataDirectory,
+    max_name_length: usize,
+) -> Vec<ExportFunction> {
+    let mut exports: Vec<ExportFunction> = Vec::new();
+    let Some(table) = rva_to_offset(sections, directory.rva).filter(|_| directory.size != 0) else {
+        return exports;
+    };
+    let table = table as u64;
+    let field = |offset: u64| reader.u32(table + offset).unwrap_or(0);
+    let (ordinal_base, address_count, name_count) = (field(0x10), field(0x14), field(0x18));
+    let (addresses, names, ordinals) = (
+        rva_to_offset(sections, field(0x1C)),
+        rva_to_offset(sections, field(0x20)),
+        rva_to_offset(sections, field(0x24)),
+    );
+    let Some(addresses) = addresses else {
+        return exports;
+    };
+    for i in 0..address_count.min(MAX_VIEW_ENTRIES) {
+        let Ok(address) = reader.u32(addresses as u64 + i as u64 * 4) else {
+            break;
+        };
+        exports.push(ExportFunction {
+            name: String::new(),
+            raw_name: None,
+            is_named: false,
+            ordinal: ordinal_base.wrapping_add(i),
+            address,
+            stack_bytes: None,
+            stack_bytes_source: None,
+        });
+    }
+    if let (Some(names), Some(ordinals)) = (names, ordinals) {
+        for i in 0..name_count.min(MAX_VIEW_ENTRIES) as u64 {
+            let (Ok(name_rva), Ok(index)) = (
+                reader.u32(names as u64 + i * 4),
+                reader.u16(ordinals as u64 + i * 2),
+            ) else {
+                break;
+            };
+            if let (Some(export), Some((name, raw_name))) = (
+                exports.get_mut(index as usize),
+                read_name(reader, sections, name_rva, max_name_length),
+            ) {
+                export.name = name;
+                export.raw_name = raw_name;
+                export.is_named = true;
+            }
+        }
+    }
+    exports
+}
+
+// 混合二进制总是PE32+ 查找表项为8字节
+fn parse_imports(
+    reader: &BoundedReader,
+    sections: &[Section],
+    directory: DataDirectory,
+    max_name_length: usize,
+) -> Vec<ImportTableEntry> {
+    let mut imports: Vec<ImportTableEntry> = Vec::new();
+    let Some(table) = rva_to_offset(sections, directory.rva).filter(|_| directory.size != 0) else {
+        return imports;
+    };
+    // 目录大小可能小于实际的描述符数组 与加载器一样读到全0描述符为止
+    for i in 0..MAX_VIEW_ENTRIES as u64 {
+        let descriptor = table as u64 + i * 20;
+        let (Ok(lookup_rva), Ok(name_rva), Ok(first_thunk)) = (
+            reader.u32(descriptor),
+            reader.u32(descriptor + 12),
+            reader.u32(descriptor + 16),
+        ) else {
+            break;
+        };
+        if name_rva == 0 && first_thunk == 0 {
+            break;
+        }
+        let Some((dll_name, raw_dll_name)) = read_name(reader, sections, name_rva, max_name_length)
+        else {
+            continue;
+        };
+        let lookup_rva = if lookup_rva != 0 {
+            lookup_rva
+        } else {
+            first_thunk
+        };
+        let mut functions: Vec<ImportFunction> = Vec::new();
+        if let Some(lookup) = rva_to_offset(sections, lookup_rva) {
+            for slot in 0..MAX_VIEW_ENTRIES {
+                let Ok(entry) = reader.u64(lookup as u64 + slot as u64 * 8) else {
+                    break;
+                };
+                if entry == 0 {
+                    break;
+                }
+                let thunk_rva = first_thunk.wrapping_add(slot * 8);
+                if entry & (1 << 63) != 0 {
+                    functions.push(ImportFunction {
+                        name: String::new(),
+                        raw_name: None,
+                        is_ordinal: true,
+                        ordinal: entry as u16,
+                        hint: 0,
+                        thunk_rva,
+                    });
+                    continue;
+                }
+                let hint_rva = entry as u32 & 0x7FFF_FFFF;
+                let hint = rva_to_offset(sections, hint_rva)
+                    .and_then(|offset| reader.u16(offset as u64).ok())
+                    .unwrap_or(0);
+                let (name, raw_name) =
+                    read_name(reader, sections, hint_rva.wrapping_add(2), max_name_length)
+                        .unwrap_or_default();
+                functions.push(ImportFunction {
+                    name,
+                    raw_name,
+                    is_ordinal: false,
+                    ordinal: 0,
+                    hint,
+                    thunk_rva,
+                });
+            }
+        }
+        imports.push(ImportTableEntry {
+            dll_name,
+            raw_dll_name,
+            functions,
+        });
+    }
+    imports
+}
+
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn detect_hybrid(
+    data: &[u8],
+    sections: &[Section],
+    load_config: &LoadConfig,
+    relocations: &[DynamicRelocation],
+    machine: u16,
+    image_base: u64,
+    size_of_headers: u32,
+    max_name_length: usize,
+) -> Option<HybridInfo> {
+    let metadata = load_config
+        .chpe_metadata_pointer
+        .filter(|va| *va > image_base)
+        .and_then(|va| rva_to_offset(sections, (va - image_base) as u32))
+        .map(|offset| offset as u64);
+    let has_arm64x = relocations
+        .iter()
+        .any(|relocation| relocation.symbol == IMAGE_DYNAMIC_RELOCATION_ARM64X);
+    if metadata.is_none() && !has_arm64x {
+        return None;
+    }
+
+    let kind = match machine {
+        IMAGE_FILE_MACHINE_ARM64 => "ARM64X",
+        IMAGE_FILE_MACHINE_AMD64 if has_arm64x => "ARM64X",
+        IMAGE_FILE_MACHINE_AMD64 => "ARM64EC",
+        IMAGE_FILE_MACHINE_I386 => "CHPE(x86)",
+        _ => "CHPE",
+    };
+    let reader = BoundedReader::new(data);
+    let metadata_version = metadata.and_then(|offset| reader.u32(offset).ok());
+    let code_ranges = metadata
+        .map(|offset| code_ranges(&reader, sections, offset, machine))
+        .unwrap_or_default();
+    let alternate_entry_point = metadata
+        .filter(|_| machine != IMAGE_FILE_MACHINE_I386)
+        .and_then(|offset| reader.u32(offset + 0x28).ok())
+        .filter(|rva| *rva != 0);
+
+    // 头部副本应用重定位后 按另一视图的Machine和数据目录解析导入导出
+    let mut header = reader.bytes_truncated(0, size_of_headers as u64).to_vec();
+    let (arm64x_fixups, header_fixups) = apply_arm64x(relocations, &mut header);
+    let alternate_view = (header_fixups > 0)
+        .then(|| {
+            let header = BoundedReader::new(&header);
+            let pe = header.u32(0x3C).ok()? as u64;
+            let alternate_machine = header.u16(pe + 4).ok()?;
+            let optional = pe + 24;
+            let directory = |index: u64| DataDirectory {
+                rva: header.u32(optional + 0x70 + index * 8).unwrap_or(0),
+                size: header.u32(optional + 0x74 + index * 8).unwrap_or(0),
+            };
+            Some(HybridView {
+                machine: machine_name(alternate_machine),
+                entry_point: header.u32(optional + 0x10).ok()?,
+                export_table: parse_exports(&reader, sections, directory(0), max_name_length),
+                import_table: parse_imports(&reader, sections, directory(1), max_name_length),
+            })
+        })
+        .flatten();
+
+    Some(HybridInfo {
+        kind: kind.to_string(),
+        metadata_version,
+        code_ranges,
+        alternate_entry_point,
+        arm64x_fixups,
+        header_fixups,
+        alternate_view,
+    })
+}
@@ -0,0 +1,146 @@
+// IAT状态: 磁盘上的文件中IAT与导入名称表一致(未绑定) 绑定后或加载器解析后为绝对地址
+use serde::{Deserialize, Serialize};
+
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, DataDirectory, Section};
+
+// 导入描述符与每个DLL槽位数的读取上限
+const MAX_DESCRIPTORS: u64 = 4096;
+const MAX_SLOTS: u32 = 4096;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IatBinding {
+    pub dll_name: String,
+    // 0为未绑定 0xFFFFFFFF为新式绑定(见绑定导入目录) 其余为旧式绑定的DLL时间戳
+    pub time_date_stamp: u32,
+    // unbound / bound / resolved
+    pub state: String,
+    pub slots: u32,
+    // 填入了绝对地址的槽位数
+    pub address_slots: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct IatState {
+    // unbound / bound / resolved
+    pub state: String,
+    pub bound_import_directory: bool,
+    pub dlls: Vec<IatBinding>,
+    pub explanation: String,
+}
+
+fn read_name(reader: &BoundedReader, sections: &[Section], rva: u32) -> String {
+    rva_to_offset(sections, rva)
+        .and_then(|offset| reader.c_string_truncated(offset as u64, 256).ok())
+        .map(|(bytes, _)| String::from_utf8_lossy(bytes).to_string())
+        .unwrap_or_default()
+}
+
+pub(crate) fn inspect_iat(
+    data: &[u8],
+    sections: &[Section],
+    directory: DataDirectory,
+    bound_directory: DataDirectory,
+    is_x64: bool,
+    size_of_image: u32,
+) -> Option<IatState> {
+    if directory.rva == 0 || directory.size == 0 {
+        return None;
+    }
+    let reader = BoundedReader::new(data);
+    let table = rva_to_offset(sections, directory.rva)? as u64;
+    let thunk_size: u32 = if is_x64 { 8 } else { 4 };
+    let ordinal_flag: u64 = if is_x64 { 1 << 63 } else { 1 << 31 };
+
+    let mut dlls: Vec<IatBinding> = Vec::new();
+    // 目录大小可能小于实际的描述符数组 与加载器一样读到全0描述符为止
+    for i in 0..MAX_DESCRIPTORS {
+        let descriptor = table + i * 20;
+        let (Ok(original_first_thunk), Ok(time_date_stamp), Ok(name_rva), Ok(first_thunk)) = (
+            reader.u32(descriptor),
+            reader.u32(descriptor + 4),
+            reader.u32(descriptor + 12),
+            reader.u32(descriptor + 16),
+        ) else {
+            break;
+        };
+        if first_thunk == 0 && name_rva == 0 {
+            break;
+        }
+        // 没有导入名称表时(早期Borland链接器) IAT是唯一的名称来源
+        let lookup = Some(original_first_thunk)
+            .filter(|rva| *rva != 0)
+            .and_then(|rva| rva_to_offset(sections, rva));
+
+        let mut slots: u32 = 0;
+        let mut address_slots: u32 = 0;
+        for slot in 0..MAX_SLOTS {
+            let Some(iat_value) =
+                rva_to_offset(sections, first_thunk.wrapping_add(slot * thunk_size))
+                    .and_then(|offset| reader.pointer(offset as u64, is_x64).ok())
+            else {
+                break;
+            };
+            let lookup_value = match lookup {
+                Some(offset) => {
+                    match reader.pointer(offset as u64 + (slot * thunk_size) as u64, is_x64) {
+                        Ok(value) => Some(value),
+                        Err(_) => break,
+                    }
+                }
+                None => None,
+            };
+            if lookup_value.unwrap_or(iat_value) == 0 {
+                break;
+            }
+            slots += 1;
+            // 名称表中是RVA或序号 IAT与之不同且超出映像范围时只能是绝对地址
+            let is_address = match lookup_value {
+                Some(value) => iat_value != value && iat_value >= size_of_image as u64,
+                None => iat_value & ordinal_flag == 0 && iat_value >= size_of_image as u64,
+            };
+            if is_address {
+                address_slots += 1;
+            }
+        }
+
+        let state = if address_slots == 0 {
+            "unbound"
+        } else if time_date_stamp != 0 {
+            "bound"
+        } else {
+            "resolved"
+        };
+        dlls.push(IatBinding {
+            dll_name: read_name(&reader, sections, name_rva),
+            time_date_stamp,
+            state: state.to_string(),
+            slots,
+            address_slots,
+        });
+    }
+
+    let bound_import_directory = bound_directory.rva != 0 && bound_directory.size != 0;
+    let (state, explanation) = if dlls.iter().any(|dll| dll.state == "resolved") {
+        (
+            "resolved",
+            "IAT中已填入绝对地址 但导入描述符未标记为绑定 这是加载器解析后的状态 文件很可能是从内存中转储的",
+        )
+    } else if dlls.iter().any(|dll| dll.state == "bound") {
+        (
+            "bound",
+            "IAT已预先绑定到特定版本DLL的导出地址(bind.exe) DLL版本或加载地址不一致时加载器会重新解析",
+        )
+    } else {
+        (
+            "unbound",
+            "IAT中是导入名称的RVA或序号 与磁盘上未加载的文件一致 由加载器在运行时填入地址",
+        )
+    };
+    Some(IatState {
+        state: state.to_string(),
+        bound_import_directory,
+        dlls,
+        explanation: explanation.to_string(),
+    })
+}
@@ -0,0 +1,154 @@
+// 映像的虚拟内存布局 按RVA排列的头部、节区和空隙 以及叠加在其上的数据目录
+use serde::{Deserialize, Serialize};
+
+use crate::debug::PogoEntry;
+use crate::heuristics::{align_up, virtual_extent};
+use crate::{rva_to_offset, DataDirectory, Section, IMAGE_DIRECTORY_ENTRY_SECURITY};
+
+pub const DIRECTORY_NAMES: [&str; 16] = [
+    "导出表",
+    "导入表",
+    "资源",
+    "异常",
+    "证书",
+    "基址重定位",
+    "调试",
+    "架构",
+    "全局指针",
+    "TLS",
+    "加载配置",
+    "绑定导入",
+    "IAT",
+    "延迟导入",
+    "CLR",
+    "保留",
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LayoutRange {
+    // headers / section / gap / directory
+    pub kind: String,
+    pub name: String,
+    pub rva: u32,
+    pub size: u32,
+    // 对应的文件范围 未映射自文件时为None
+    pub file_offset: Option<u32>,
+    pub file_size: u32,
+    // 节区属性 其他类型为0
+    pub characteristics: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MemoryLayout {
+    pub size_of_image: u32,
+    pub section_alignment: u32,
+    // 头部、节区和空隙 互不重叠且按RVA排序
+    pub regions: Vec<LayoutRange>,
+    // 数据目录 位于某个区域内部
+    pub directories: Vec<LayoutRange>,
+    // POGO调试项记录的链接器贡献节 如.text$mn 没有POGO时为空
+    pub contributions: Vec<LayoutRange>,
+}
+
+fn gap(start: u64, end: u64) -> LayoutRange {
+    LayoutRange {
+        kind: "gap".to_string(),
+        name: String::new(),
+        rva: start as u32,
+        size: (end - start) as u32,
+        file_offset: None,
+        file_size: 0,
+        characteristics: 0,
+    }
+}
+
+pub fn build_layout(
+    sections: &[Section],
+    data_directories: &[DataDirectory],
+    size_of_image: u32,
+    size_of_headers: u32,
+    section_alignment: u32,
+    pogo_entries: &[PogoEntry],
+) -> MemoryLayout {
+    let mut regions: Vec<LayoutRange> = Vec::new();
+    let headers_end = align_up(size_of_headers as u64, section_alignment);
+    regions.push(LayoutRange {
+        kind: "headers".to_string(),
+        name: "PE头".to_string(),
+        rva: 0,
+        size: headers_end as u32,
+        file_offset: Some(0),
+        file_size: size_of_headers,
+        characteristics: 0,
+    });
+
+    let mut ordered: Vec<&Section> = sections.iter().collect();
+    ordered.sort_by_key(|section| section.rva);
+    let mut cursor = headers_end;
+    for section in ordered {
+        let start = section.rva as u64;
+        let end = start + align_up(virtual_extent(section), section_alignment);
+        // 重叠的节区从上一个区域结束处截断
+        if end <= cursor {
+            continue;
+        }
+        if start > cursor {
+            regions.push(gap(cursor, start));
+        }
+        let start = start.max(cursor);
+        regions.push(LayoutRange {
+            kind: "section".to_string(),
+            name: section.name.clone(),
+            rva: start as u32,
+            size: (end - start) as u32,
+            file_offset: (section.size_raw_data != 0).then_some(section.ptr_raw_data),
+            file_size: section.size_raw_data,
+            characteristics: section.characteristics,
+        });
+        cursor = end;
+    }
+    if (size_of_image as u64) > cursor {
+        regions.push(gap(cursor, size_of_image as u64));
+    }
+
+    // 证书目录保存的是文件偏移 不会被映射
+    let mut directories: Vec<LayoutRange> = data_directories
+        .iter()
+        .enumerate()
+        .filter(|(index, directory)| {
+            *index != IMAGE_DIRECTORY_ENTRY_SECURITY && directory.rva != 0 && directory.size != 0
+        })
+        .map(|(index, directory)| LayoutRange {
+            kind: "directory".to_string(),
+            name: DIRECTORY_NAMES[index].to_string(),
+            rva: directory.rva,
+            size: directory.size,
+            file_offset: rva_to_offset(sections, directory.rva),
+            file_size: directory.size,
+            characteristics: 0,
+        })
+        .collect();
+    directories.sort_by_key(|directory| directory.rva);
+
+    let mut contributions: Vec<LayoutRange> = pogo_entries
+        .iter()
+        .map(|entry| LayoutRange {
+            kind: "contribution".to_string(),
+            name: entry.name.clone(),
+            rva: entry.rva,
+            size: entry.size,
+            file_offset: rva_to_offset(sections, entry.rva),
+            file_size: entry.size,
+            characteristics: 0,
+        })
+        .collect();
+    contributions.sort_by_key(|contribution| contribution.rva);
+
+    MemoryLayout {
+        size_of_image,
+        section_alignment,
+        regions,
+        directories,
+        contributions,
+    }
+}
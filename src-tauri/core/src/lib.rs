@@ -0,0 +1,402 @@
+// PE解析核心 只依赖文件内容 不依赖Tauri和本机环境 可以编译到wasm32
+use serde::{Deserialize, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+pub mod api_hashes;
+pub mod authenticode;
+pub mod autoit;
+pub mod callconv;
+pub mod capabilities;
+pub mod com;
+pub mod coverage;
+pub mod debug;
+pub mod delphi;
+pub mod demangle;
+mod der;
+pub mod driver;
+pub mod dvrt;
+pub mod dynamic_imports;
+pub mod entropy;
+pub mod ep_signature;
+pub mod exception;
+pub mod export_hooks;
+pub mod golang;
+pub mod guids;
+pub mod hashes;
+pub mod header_flags;
+pub mod headers;
+pub mod heuristics;
+pub mod hybrid;
+pub mod iat;
+pub mod layout;
+pub mod libraries;
+pub mod limits;
+pub mod load_config;
+pub mod manifest;
+pub mod mitigations;
+pub mod ordinals;
+pub mod pe_file;
+pub mod python;
+pub mod reader;
+pub mod resources;
+pub mod rustlang;
+pub mod score;
+pub mod sizes;
+pub mod slack;
+pub mod stack_strings;
+pub mod strings;
+pub mod stub;
+pub mod tls;
+pub mod vb6;
+pub mod version_info;
+
+use authenticode::SignatureInfo;
+use autoit::AutoItScript;
+use capabilities::Indicator;
+use com::ComSurface;
+use coverage::FileCoverage;
+use debug::{DebugEntry, PogoInfo};
+use delphi::DelphiInfo;
+use driver::DriverInfo;
+use dvrt::DynamicRelocationSummary;
+use ep_signature::EntryPointBytes;
+use exception::RuntimeFunction;
+use export_hooks::ExportAnomaly;
+use golang::GoBuildInfo;
+use guids::GuidReference;
+use hashes::{DirectoryDigest, FileHashes};
+use header_flags::{ClrPlatform, HeaderFlag};
+use headers::{CoffHeader, DosHeader, OptionalHeader};
+use heuristics::Warning;
+use hybrid::HybridInfo;
+use iat::IatState;
+use libraries::StaticLibrary;
+use load_config::LoadConfig;
+use mitigations::Mitigation;
+pub use pe_file::{PeError, PeFile};
+use python::PythonPackage;
+use reader::{BoundedReader, ReadError};
+use resources::ResourceEntry;
+use rustlang::RustInfo;
+use score::SuspicionScore;
+use slack::SlackRegion;
+use stack_strings::StackString;
+use stub::StubInfo;
+use tls::TlsInfo;
+use vb6::Vb6Info;
+use version_info::VersionInfo;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Section {
+    pub name: String,
+    // 名称不是有效UTF-8时保留原始字节(十六进制)
+    pub raw_name: Option<String>,
+    pub rva: u32,
+    pub ptr_raw_data: u32,
+    pub size_raw_data: u32,
+    pub rv_end: u32,
+    pub characteristics: u32,
+    pub entropy: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ImportFunction {
+    pub name: String,
+    pub raw_name: Option<String>,
+    pub is_ordinal: bool,
+    pub ordinal: u16,
+    pub hint: u16,
+    // IAT中对应槽位的RVA
+    pub thunk_rva: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportFunction {
+    pub name: String,
+    pub raw_name: Option<String>,
+    // 名称指针表中有对应项 为false时只按序号导出(.def中的NONAME)
+    pub is_named: bool,
+    pub ordinal: u32,
+    pub address: u32,
+    // x86下推测的参数字节数 stack_bytes_source为decoration(修饰名)或ret(ret imm16)
+    pub stack_bytes: Option<u32>,
+    pub stack_bytes_source: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OrdinalRange {
+    pub first: u32,
+    pub last: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportOrdinals {
+    pub base: u32,
+    // 地址表的项数 即序号空间的大小
+    pub slots: u32,
+    pub named: u32,
+    // 地址为0的空位 连续的合并为区间
+    pub unused: Vec<OrdinalRange>,
+    pub unused_count: u32,
+}
+
+// 因RVA不在任何节内而跳过的导入项
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ImportDiagnostic {
+    // 导入描述符的下标
+    pub descriptor: u32,
+    // 跳过查找表或名称时DLL名称未知
+    pub dll_name: Option<String>,
+    // lookup_table / dll_name / hint_name
+    pub field: String,
+    pub rva: u32,
+    // hint_name时为查找表中的项下标
+    pub slot: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ImportTableEntry {
+    pub dll_name: String,
+    pub raw_dll_name: Option<String>,
+    pub functions: Vec<ImportFunction>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PhaseTiming {
+    pub phase: String,
+    pub duration_us: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PeInfo {
+    pub path: String,
+    pub size: u64,
+    pub is_x64: bool,
+    pub machine: u16,
+    pub time_date_stamp: u32,
+    pub characteristics: u16,
+    pub subsystem: u16,
+    pub dll_characteristics: u16,
+    pub header_flags: Vec<HeaderFlag>,
+    // 三个头部的完整字段 上面常用的几个字段保留以兼容已有视图
+    pub dos_header: DosHeader,
+    pub coff_header: CoffHeader,
+    pub optional_header: OptionalHeader,
+    // .NET程序集的目标平台 非托管程序为None
+    pub clr_platform: Option<ClrPlatform>,
+    pub entry_point: u32,
+    // 入口点处的原始字节及匹配的壳特征
+    pub entry_point_bytes: Option<EntryPointBytes>,
+    // 入口点附近用mov指令在栈上拼出的字符串
+    pub stack_strings: Vec<StackString>,
+    pub image_base: u64,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub section_alignment: u32,
+    pub data_directories: Vec<DataDirectory>,
+    pub sections: Vec<Section>,
+    // 含非零数据的节区空隙
+    pub slack: Vec<SlackRegion>,
+    pub debug_entries: Vec<DebugEntry>,
+    pub pogo: Option<PogoInfo>,
+    pub export_table: Vec<ExportFunction>,
+    // 序号基数和地址表中未使用的序号
+    pub export_ordinals: Option<ExportOrdinals>,
+    // 指向异常位置的导出
+    pub export_anomalies: Vec<ExportAnomaly>,
+    pub import_table: Vec<ImportTableEntry>,
+    // 被跳过的导入描述符和导入函数
+    pub import_diagnostics: Vec<ImportDiagnostic>,
+    // IAT是否为磁盘状态 绑定或已被加载器解析
+    pub iat_state: Option<IatState>,
+    // Load Config中的CFG/XFG、易失元数据和CastGuard等字段
+    pub load_config: Option<LoadConfig>,
+    // ARM64X/ARM64EC/CHPE混合二进制 含另一架构视图的导入导出
+    pub hybrid: Option<HybridInfo>,
+    // 动态值重定位表 按类型汇总
+    pub dynamic_relocations: Vec<DynamicRelocationSummary>,
+    pub tls: Option<TlsInfo>,
+    pub resources: Vec<ResourceEntry>,
+    pub version_info: Option<VersionInfo>,
+    // 嵌入的应用程序清单(RT_MANIFEST)原文
+    pub manifest: Option<String>,
+    // 自注册导出、类型库和注册脚本中的COM类
+    pub com: Option<ComSurface>,
+    pub overlay_offset: u64,
+    // 头部、节区和证书表占文件的比例 其余为覆盖数据和节间空隙
+    pub coverage: FileCoverage,
+    pub indicators: Vec<Indicator>,
+    // 文件中出现的GUID 已知的CLSID/IID附带名称
+    pub guids: Vec<GuidReference>,
+    pub static_libraries: Vec<StaticLibrary>,
+    pub go_info: Option<GoBuildInfo>,
+    pub rust_info: Option<RustInfo>,
+    pub python_package: Option<PythonPackage>,
+    pub autoit: Option<AutoItScript>,
+    pub delphi: Option<DelphiInfo>,
+    pub vb6: Option<Vb6Info>,
+    pub driver: Option<DriverInfo>,
+    // 没有导入的类PE加载器存根 说明缺少的结构
+    pub stub: Option<StubInfo>,
+    pub warnings: Vec<Warning>,
+    pub is_signed: bool,
+    pub signature: Option<SignatureInfo>,
+    // ASLR/DEP/CFG/SafeSEH//GS/签名等加固选项
+    pub mitigations: Vec<Mitigation>,
+    pub hashes: FileHashes,
+    // 各数据目录的SHA-256
+    pub directory_digests: Vec<DirectoryDigest>,
+    pub imphash: String,
+    // 保留导入顺序的指纹 和只含库名序列的模糊imphash 用于聚类
+    pub import_order_hash: String,
+    pub dll_sequence_hash: String,
+    // Authenticode SHA-256 与是否签名无关 头部异常时为None
+    pub authentihash: Option<String>,
+    // 命中已知良性哈希集合
+    pub known_good: bool,
+    pub suspicion: SuspicionScore,
+    pub timings: Vec<PhaseTiming>,
+    pub total_duration_us: u64,
+}
+
+impl PeInfo {
+    // 数据目录 下标超出可选头中的目录数时为空目录
+    pub fn directory(&self, index: usize) -> DataDirectory {
+        self.data_directories
+            .get(index)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn rva_to_offset(&self, rva: u32) -> Option<u32> {
+        rva_to_offset(&self.sections, rva)
+    }
+
+    pub fn offset_to_rva(&self, offset: u64) -> Option<u32> {
+        offset_to_rva(&self.sections, offset)
+    }
+
+    // 异常目录中的函数范围 非x64/ARM64文件为空
+    pub fn runtime_functions(&self, data: &[u8]) -> Result<Vec<RuntimeFunction>, ReadError> {
+        exception::parse_runtime_functions(
+            BoundedReader::new(data),
+            &self.sections,
+            self.directory(IMAGE_DIRECTORY_ENTRY_EXCEPTION),
+            self.machine,
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct DataDirectory {
+    pub rva: u32,
+    pub size: u32,
+}
+
+// 名称按UTF-8宽松解码 解码有损时一并返回原始字节的十六进制 不丢失取证信息
+pub fn decode_name(bytes: &[u8]) -> (String, Option<String>) {
+    match std::str::from_utf8(bytes) {
+        Ok(name) => (name.to_string(), None),
+        Err(_) => (
+            String::from_utf8_lossy(bytes).to_string(),
+            Some(hashes::to_hex(bytes)),
+        ),
+    }
+}
+
+pub const IMAGE_DIRECTORY_ENTRY_EXPORT: usize = 0;
+pub const IMAGE_DIRECTORY_ENTRY_IMPORT: usize = 1;
+pub const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+pub const IMAGE_DIRECTORY_ENTRY_EXCEPTION: usize = 3;
+pub const IMAGE_DIRECTORY_ENTRY_SECURITY: usize = 4;
+pub const IMAGE_DIRECTORY_ENTRY_DEBUG: usize = 6;
+pub const IMAGE_DIRECTORY_ENTRY_TLS: usize = 9;
+pub const IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG: usize = 10;
+pub const IMAGE_DIRECTORY_ENTRY_BOUND_IMPORT: usize = 11;
+pub const IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR: usize = 14;
+
+// rva -> raw_ptr
+pub fn rva_to_offset(sections: &[Section], rva: u32) -> Option<u32> {
+    for section in sections {
+        if rva >= section.rva && rva < section.rv_end {
+            return section.ptr_raw_data.checked_add(rva - section.rva);
+        }
+    }
+    None
+}
+
+// raw_ptr -> rva
+pub fn offset_to_rva(sections: &[Section], offset: u64) -> Option<u32> {
+    for section in sections {
+        let start = section.ptr_raw_data as u64;
+        if offset >= start && offset < start + section.size_raw_data as u64 {
+            return section.rva.checked_add((offset - start) as u32);
+        }
+    }
+    None
+}
+
+// 附加数据的起始位置 即节区原始数据的最大结尾
+pub fn overlay_offset(sections: &[Section]) -> u64 {
+    sections
+        .iter()
+        .map(|s| s.ptr_raw_data as u64 + s.size_raw_data as u64)
+        .max()
+        .unwrap_or(0)
+}
+
+// 计时起点 wasm32-unknown-unknown没有系统时钟 在该目标上耗时均记为0
+#[derive(Clone, Copy)]
+pub struct PhaseClock {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: Instant,
+}
+
+impl PhaseClock {
+    pub fn start() -> Self {
+        PhaseClock {
+            #[cfg(not(target_arch = "wasm32"))]
+            start: Instant::now(),
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn elapsed_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn elapsed_us(&self) -> u64 {
+        0
+    }
+}
+
+// 记录一个阶段的耗时 并重置阶段起点
+pub fn record_phase(timings: &mut Vec<PhaseTiming>, phase: &str, phase_start: &mut PhaseClock) {
+    timings.push(PhaseTiming {
+        phase: phase.to_string(),
+        duration_us: phase_start.elapsed_us(),
+    });
+    *phase_start = PhaseClock::start();
+}
+
+// 模糊测试入口 任意字节按默认上限在内存中完整分析 解析失败可以返回错误但不能panic
+#[cfg(fuzzing)]
+pub fn fuzz_analyze(data: &[u8]) {
+    let known_good = std::collections::HashSet::new();
+    if let Ok(pe_info) = PeFile::parse_bytes("fuzz", data, &limits::Limits::default(), &known_good)
+    {
+        if let Ok(runtime_functions) = pe_info.runtime_functions(data) {
+            sizes::size_report(&pe_info, data, &runtime_functions);
+            exception::largest_functions(
+                data,
+                &pe_info.sections,
+                &pe_info.export_table,
+                &runtime_functions,
+                exception::DEFAULT_FUNCTION_LIMIT,
+            );
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// 静态链接的第三方组件识别 依据库自带的版本/版权字符串 供许可证和CVE排查参考
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::{find_all, Evidence};
+use crate::reader::BoundedReader;
+use crate::{offset_to_rva, Section};
+
+// 每个标记最多记录的位置
+const MAX_MARKER_HITS: usize = 4;
+// 版本号的最大长度
+const MAX_VERSION_LENGTH: usize = 32;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StaticLibrary {
+    pub name: String,
+    pub license: String,
+    pub version: Option<String>,
+    pub evidence: Vec<Evidence>,
+}
+
+struct LibrarySignature {
+    name: &'static str,
+    license: &'static str,
+    // 任意一个出现即认为链接了该库
+    markers: &'static [&'static str],
+    // 紧跟版本号的前缀
+    version_prefixes: &'static [&'static str],
+}
+
+const LIBRARY_SIGNATURES: &[LibrarySignature] = &[
+    LibrarySignature {
+        name: "OpenSSL",
+        license: "OpenSSL/Apache-2.0",
+        markers: &["OpenSSL 0.9", "OpenSSL 1.", "OpenSSL 3.", "OPENSSL_ia32cap"],
+        version_prefixes: &["OpenSSL "],
+    },
+    LibrarySignature {
+        name: "zlib",
+        license: "Zlib",
+        markers: &[" deflate 1.", " inflate 1.", "Jean-loup Gailly"],
+        version_prefixes: &[" deflate ", " inflate "],
+    },
+    LibrarySignature {
+        name: "SQLite",
+        license: "Public Domain",
+        markers: &["SQLite format 3", "sqlite_master"],
+        version_prefixes: &[],
+    },
+    LibrarySignature {
+        name: "libcurl",
+        license: "curl",
+        markers: &["libcurl/", "CURLOPT_"],
+        version_prefixes: &["libcurl/"],
+    },
+    LibrarySignature {
+        name: "Boost",
+        license: "BSL-1.0",
+        markers: &["@boost@@", "boost::"],
+        version_prefixes: &[],
+    },
+    LibrarySignature {
+        name: "libpng",
+        license: "libpng",
+        markers: &["libpng version "],
+        version_prefixes: &["libpng version "],
+    },
+    LibrarySignature {
+        name: "libjpeg-turbo",
+        license: "IJG/BSD-3-Clause",
+        markers: &["libjpeg-turbo version "],
+        version_prefixes: &["libjpeg-turbo version "],
+    },
+    LibrarySignature {
+        name: "Lua",
+        license: "MIT",
+        markers: &["Lua 5.", "lua_State"],
+        version_prefixes: &["Lua "],
+    },
+    LibrarySignature {
+        name: "Mbed TLS",
+        license: "Apache-2.0",
+        markers: &["mbed TLS ", "Mbed TLS "],
+        version_prefixes: &["mbed TLS ", "Mbed TLS "],
+    },
+    LibrarySignature {
+        name: "Protocol Buffers",
+        license: "BSD-3-Clause",
+        markers: &["google/protobuf/", "google.protobuf."],
+        version_prefixes: &[],
+    },
+];
+
+// 前缀之后的版本号 必须以数字开头
+fn version_after(data: &[u8], prefix: &str) -> Option<String> {
+    let reader = BoundedReader::new(data);
+    for offset in find_all(data, prefix.as_bytes(), MAX_MARKER_HITS) {
+        let start = (offset + prefix.len()) as u64;
+        let version: String = reader
+            .bytes_truncated(start, MAX_VERSION_LENGTH as u64)
+            .iter()
+            .take_while(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'-' | b'_'))
+            .map(|&b| b as char)
+            .collect();
+        if version.starts_with(|c: char| c.is_ascii_digit()) {
+            return Some(version.trim_end_matches(['.', '-', '_']).to_string());
+        }
+    }
+    None
+}
+
+pub(crate) fn detect_static_libraries(data: &[u8], sections: &[Section]) -> Vec<StaticLibrary> {
+    let mut libraries: Vec<StaticLibrary> = Vec::new();
+    for signature in LIBRARY_SIGNATURES {
+        let mut evidence: Vec<Evidence> = Vec::new();
+        for marker in signature.markers {
+            for offset in find_all(data, marker.as_bytes(), MAX_MARKER_HITS) {
+                evidence.push(Evidence {
+                    description: format!("字符串 \"{}\"", marker.trim()),
+                    offset: Some(offset as u64),
+                    rva: offset_to_rva(sections, offset as u64),
+                });
+            }
+        }
+        if evidence.is_empty() {
+            continue;
+        }
+        let version = signature
+            .version_prefixes
+            .iter()
+            .find_map(|prefix| version_after(data, prefix));
+        libraries.push(StaticLibrary {
+            name: signature.name.to_string(),
+            license: signature.license.to_string(),
+            version,
+            evidence,
+        });
+    }
+    libraries
+}
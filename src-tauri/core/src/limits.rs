@@ -0,0 +1,73 @@
+// 解析上限 防止构造的超大或自引用结构让分析卡死或耗尽内存
+// 由调用方传入解析 超出上限时只解析前面的部分并给出警告
+use serde::{Deserialize, Serialize};
+
+// 资源目录层数的可设置范围
+const MAX_RESOURCE_DEPTH: u32 = 32;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[serde(default)]
+pub struct Limits {
+    // 加载器最多接受65535个节 正常文件很少超过几十个
+    pub max_sections: u32,
+    pub max_import_descriptors: u32,
+    // 每个DLL的导入函数数
+    pub max_import_functions: u32,
+    // 导入导出名称的最大长度 缺少结尾0的名称不会一直读到文件末尾
+    pub max_name_length: u32,
+    // 资源目录层数 标准为3层(类型/名称/语言) 更深的目录加载器不会读取
+    pub max_resource_depth: u32,
+    // 遍历的资源目录项总数 包括各层目录项
+    pub max_resource_entries: u32,
+    // 解出Python归档时解压的总字节数 压缩炸弹不会耗尽内存
+    pub max_decompressed_size: u32,
+}
+
+impl Limits {
+    // 上限不能为0 资源目录按层递归遍历 层数有上限
+    pub fn validate(&self) -> Result<(), String> {
+        let fields = [
+            ("节数", self.max_sections),
+            ("导入描述符数", self.max_import_descriptors),
+            ("导入函数数", self.max_import_functions),
+            ("名称长度", self.max_name_length),
+            ("资源目录层数", self.max_resource_depth),
+            ("资源目录项数", self.max_resource_entries),
+            ("解压大小", self.max_decompressed_size),
+        ];
+        if let Some((name, _)) = fields.iter().find(|(_, value)| *value == 0) {
+            return Err(format!("{}上限不能为0", name));
+        }
+        if self.max_resource_depth > MAX_RESOURCE_DEPTH {
+            return Err(format!("资源目录层数上限不能超过{}", MAX_RESOURCE_DEPTH));
+        }
+        Ok(())
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            max_sections: 1024,
+            max_import_descriptors: 4096,
+            max_import_functions: 65536,
+            max_name_length: 4096,
+            max_resource_depth: 3,
+            max_resource_entries: 16384,
+            max_decompressed_size: 512 * 1024 * 1024,
+        }
+    }
+}
+
+// 一次分析中触发的上限
+#[derive(Debug)]
+pub struct LimitHit {
+    pub limit: &'static str,
+    pub message: String,
+}
+
+impl LimitHit {
+    pub fn new(limit: &'static str, message: String) -> Self {
+        LimitHit { limit, message }
+    }
+}
@@ -0,0 +1,223 @@
+// Load Config目录 只读取后续分析用到的字段 超出结构体Size的字段视为不存在(旧版本链接器生成的结构更短)
+use serde::{Deserialize, Serialize};
+
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, DataDirectory, Section};
+
+// GuardFlags 高4位为CFG函数表每项附加的元数据字节数
+const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK: u32 = 0xF000_0000;
+const IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT: u32 = 28;
+// CFG函数表元数据: 函数前8字节为XFG哈希
+const IMAGE_GUARD_FLAG_FID_XFG: u8 = 0x08;
+// 返回的XFG哈希样本数
+const MAX_XFG_HASHES: usize = 32;
+// CFG函数表的读取上限
+const MAX_GUARD_FUNCTIONS: u64 = 1 << 20;
+
+const GUARD_FLAG_NAMES: &[(u32, &str)] = &[
+    (0x0000_0100, "CF_INSTRUMENTED"),
+    (0x0000_0200, "CFW_INSTRUMENTED"),
+    (0x0000_0400, "CF_FUNCTION_TABLE_PRESENT"),
+    (0x0000_0800, "SECURITY_COOKIE_UNUSED"),
+    (0x0000_1000, "PROTECT_DELAYLOAD_IAT"),
+    (0x0000_2000, "DELAYLOAD_IAT_IN_ITS_OWN_SECTION"),
+    (0x0000_4000, "CF_EXPORT_SUPPRESSION_INFO_PRESENT"),
+    (0x0000_8000, "CF_ENABLE_EXPORT_SUPPRESSION"),
+    (0x0001_0000, "CF_LONGJUMP_TABLE_PRESENT"),
+    (0x0002_0000, "RF_INSTRUMENTED"),
+    (0x0004_0000, "RF_ENABLE"),
+    (0x0008_0000, "RF_STRICT"),
+    (0x0010_0000, "RETPOLINE_PRESENT"),
+    (0x0040_0000, "EH_CONTINUATION_TABLE_PRESENT"),
+    (0x0080_0000, "XFG_ENABLED"),
+    (0x0100_0000, "CASTGUARD_PRESENT"),
+    (0x0200_0000, "MEMCPY_PRESENT"),
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VolatileMetadata {
+    pub rva: u32,
+    pub version: u32,
+    // 易失访问表(RVA数组)和易失信息范围表(RVA+大小)的条目数
+    pub access_table_entries: u32,
+    pub range_table_entries: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct XfgHash {
+    pub rva: u32,
+    pub hash: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LoadConfig {
+    pub size: u32,
+    // /GS安全cookie的VA
+    pub security_cookie: Option<u64>,
+    // SafeSEH处理器表的VA和项数 只有32位x86结构中有意义
+    pub se_handler_table: Option<u64>,
+    pub se_handler_count: Option<u64>,
+    pub guard_flags: Option<u32>,
+    pub guard_flag_names: Vec<String>,
+    pub guard_cf_function_count: Option<u64>,
+    // 动态值重定位表 新版本用节序号+偏移 旧版本用VA
+    pub dynamic_value_reloc_table: Option<u64>,
+    pub dynamic_value_reloc_table_offset: Option<u32>,
+    pub dynamic_value_reloc_table_section: Option<u16>,
+    // ARM64EC/CHPE元数据的VA
+    pub chpe_metadata_pointer: Option<u64>,
+    // 以下为较新的字段 均为VA
+    pub volatile_metadata_pointer: Option<u64>,
+    pub volatile_metadata: Option<VolatileMetadata>,
+    pub guard_xfg_check_function_pointer: Option<u64>,
+    pub guard_xfg_dispatch_function_pointer: Option<u64>,
+    pub guard_xfg_table_dispatch_function_pointer: Option<u64>,
+    // 带XFG哈希的CFG函数数量 及前若干个哈希
+    pub xfg_functions: u32,
+    pub xfg_hashes: Vec<XfgHash>,
+    pub cast_guard_os_determined_failure_mode: Option<u64>,
+    pub guard_memcpy_function_pointer: Option<u64>,
+}
+
+fn va_to_offset(sections: &[Section], image_base: u64, va: u64) -> Option<(u32, u64)> {
+    let rva = u32::try_from(va.checked_sub(image_base)?).ok()?;
+    Some((rva, rva_to_offset(sections, rva)? as u64))
+}
+
+fn parse_volatile_metadata(
+    data: &[u8],
+    sections: &[Section],
+    image_base: u64,
+    va: u64,
+) -> Option<VolatileMetadata> {
+    let (rva, offset) = va_to_offset(sections, image_base, va)?;
+    let reader = BoundedReader::new(data);
+    Some(VolatileMetadata {
+        rva,
+        version: reader.u32(offset + 4).ok()?,
+        access_table_entries: reader.u32(offset + 12).ok()? / 4,
+        range_table_entries: reader.u32(offset + 20).ok()? / 8,
+    })
+}
+
+// CFG函数表每项为RVA加若干元数据字节 标记了FID_XFG的函数入口前8字节是XFG哈希
+fn collect_xfg_hashes(
+    data: &[u8],
+    sections: &[Section],
+    image_base: u64,
+    table: u64,
+    count: u64,
+    guard_flags: u32,
+) -> (u32, Vec<XfgHash>) {
+    let mut hashes: Vec<XfgHash> = Vec::new();
+    let mut functions: u32 = 0;
+    let stride = 4
+        + ((guard_flags & IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_MASK)
+            >> IMAGE_GUARD_CF_FUNCTION_TABLE_SIZE_SHIFT) as u64;
+    let Some((_, table)) = va_to_offset(sections, image_base, table).filter(|_| stride > 4) else {
+        return (functions, hashes);
+    };
+    let reader = BoundedReader::new(data);
+    for i in 0..count.min(MAX_GUARD_FUNCTIONS) {
+        let entry = table + i * stride;
+        let (Ok(rva), Ok([metadata])) = (reader.u32(entry), reader.array(entry + 4)) else {
+            break;
+        };
+        if metadata & IMAGE_GUARD_FLAG_FID_XFG == 0 {
+            continue;
+        }
+        functions += 1;
+        if hashes.len() < MAX_XFG_HASHES {
+            let hash = rva
+                .checked_sub(8)
+                .and_then(|rva| rva_to_offset(sections, rva))
+                .and_then(|offset| reader.u64(offset as u64).ok());
+            if let Some(hash) = hash {
+                hashes.push(XfgHash {
+                    rva,
+                    hash: format!("{:016X}", hash),
+                });
+            }
+        }
+    }
+    (functions, hashes)
+}
+
+pub(crate) fn parse_load_config(
+    data: &[u8],
+    sections: &[Section],
+    directory: DataDirectory,
+    is_x64: bool,
+    image_base: u64,
+) -> Option<LoadConfig> {
+    if directory.rva == 0 || directory.size == 0 {
+        return None;
+    }
+    let reader = BoundedReader::new(data);
+    let base = rva_to_offset(sections, directory.rva)? as u64;
+    let size = reader.u32(base).ok()?;
+    // 旧链接器写入的目录大小不可靠 以结构体自身的Size为准
+    let limit = size as usize;
+    let field = |offset: usize, width: usize| -> Option<u64> {
+        if offset + width > limit {
+            return None;
+        }
+        reader.uint(base + offset as u64, width as u64).ok()
+    };
+    // 32位与64位结构中指针宽度不同 字段偏移分别列出
+    let pointer = |offset32: usize, offset64: usize| {
+        if is_x64 {
+            field(offset64, 8)
+        } else {
+            field(offset32, 4)
+        }
+    };
+    let at = |offset32: usize, offset64: usize, width: usize| {
+        field(if is_x64 { offset64 } else { offset32 }, width)
+    };
+    // 值为0的指针字段等同于未设置
+    let non_zero = |value: Option<u64>| value.filter(|value| *value != 0);
+
+    let guard_flags = at(0x58, 0x90, 4).map(|value| value as u32);
+    let guard_cf_function_table = non_zero(pointer(0x50, 0x80));
+    let guard_cf_function_count = pointer(0x54, 0x88);
+    let (xfg_functions, xfg_hashes) = match (
+        guard_cf_function_table,
+        guard_cf_function_count,
+        guard_flags,
+    ) {
+        (Some(table), Some(count), Some(flags)) => {
+            collect_xfg_hashes(data, sections, image_base, table, count, flags)
+        }
+        _ => (0, Vec::new()),
+    };
+    let volatile_metadata_pointer = non_zero(pointer(0xA0, 0x100));
+
+    Some(LoadConfig {
+        size,
+        security_cookie: non_zero(pointer(0x3C, 0x58)),
+        se_handler_table: non_zero(pointer(0x40, 0x60)),
+        se_handler_count: pointer(0x44, 0x68),
+        guard_flags,
+        guard_flag_names: GUARD_FLAG_NAMES
+            .iter()
+            .filter(|(flag, _)| guard_flags.is_some_and(|flags| flags & flag != 0))
+            .map(|(_, name)| name.to_string())
+            .collect(),
+        guard_cf_function_count,
+        dynamic_value_reloc_table: pointer(0x78, 0xC0),
+        chpe_metadata_pointer: pointer(0x7C, 0xC8),
+        dynamic_value_reloc_table_offset: at(0x88, 0xE0, 4).map(|value| value as u32),
+        dynamic_value_reloc_table_section: at(0x8C, 0xE4, 2).map(|value| value as u16),
+        volatile_metadata: volatile_metadata_pointer
+            .and_then(|va| parse_volatile_metadata(data, sections, image_base, va)),
+        volatile_metadata_pointer,
+        guard_xfg_check_function_pointer: non_zero(pointer(0xAC, 0x118)),
+        guard_xfg_dispatch_function_pointer: non_zero(pointer(0xB0, 0x120)),
+        guard_xfg_table_dispatch_function_pointer: non_zero(pointer(0xB4, 0x128)),
+        xfg_functions,
+        xfg_hashes,
+        cast_guard_os_determined_failure_mode: non_zero(pointer(0xB8, 0x130)),
+        guard_memcpy_function_pointer: non_zero(pointer(0xBC, 0x138)),
+    })
+}
@@ -0,0 +1,65 @@
+// 嵌入的应用程序清单(RT_MANIFEST) 原文及其中的并行程序集(SxS)依赖标签
+use crate::reader::decode_utf16;
+use crate::resources::{resource_data, ResourceEntry, RT_MANIFEST};
+
+// 清单的编码可能是带BOM的UTF-8或UTF-16LE
+pub fn decode_manifest(data: &[u8]) -> String {
+    if let Some(rest) = data.strip_prefix(&[0xFF, 0xFE]) {
+        return decode_utf16(rest);
+    }
+    String::from_utf8_lossy(data.strip_prefix(&[0xEF, 0xBB, 0xBF]).unwrap_or(data)).to_string()
+}
+
+// 嵌入的应用程序清单原文 有多个时取第一个(通常为ID 1)
+pub(crate) fn embedded_manifest(data: &[u8], resources: &[ResourceEntry]) -> Option<String> {
+    resources
+        .iter()
+        .find(|resource| resource.type_id == Some(RT_MANIFEST))
+        .map(|resource| decode_manifest(resource_data(data, resource)))
+        .map(|xml| xml.trim_end_matches('\0').trim().to_string())
+        .filter(|xml| !xml.is_empty())
+}
+
+// 标签中的属性值 单双引号均可
+pub fn attribute(tag: &str, key: &str) -> Option<String> {
+    let mut rest = tag;
+    while let Some(position) = rest.find(key) {
+        let preceded = rest[..position]
+            .chars()
+            .next_back()
+            .is_some_and(char::is_whitespace);
+        let after = rest[position + key.len()..].trim_start();
+        rest = &rest[position + key.len()..];
+        let Some(after) = after.strip_prefix('=').map(str::trim_start) else {
+            continue;
+        };
+        let Some(quote) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            continue;
+        };
+        if preceded {
+            return after[1..].split(quote).next().map(str::to_string);
+        }
+    }
+    None
+}
+
+// 每个dependentAssembly中的第一个assemblyIdentity 标签可能带命名空间前缀
+pub fn dependent_identities(xml: &str) -> Vec<String> {
+    let mut identities: Vec<String> = Vec::new();
+    for (position, _) in xml.match_indices("dependentAssembly") {
+        // 跳过结束标签
+        let tag_start = xml[..position].rfind('<');
+        if tag_start.is_some_and(|start| xml[start + 1..].starts_with('/')) {
+            continue;
+        }
+        let rest = &xml[position..];
+        let Some(start) = rest.find("assemblyIdentity") else {
+            continue;
+        };
+        let tag = &rest[start..];
+        if let Some(end) = tag.find('>') {
+            identities.push(tag[..end].to_string());
+        }
+    }
+    identities
+}
@@ -0,0 +1,250 @@
+// 安全缓解措施: 解释DllCharacteristics和Load Config目录 快速判断程序是否启用了常见的加固选项(类似winchecksec)
+use serde::{Deserialize, Serialize};
+
+use crate::authenticode::SignatureInfo;
+use crate::header_flags::ClrPlatform;
+use crate::headers::{CoffHeader, OptionalHeader};
+use crate::load_config::LoadConfig;
+
+const IMAGE_FILE_RELOCS_STRIPPED: u16 = 0x0001;
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014C;
+const IMAGE_NT_OPTIONAL_HDR64_MAGIC: u16 = 0x20B;
+const IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA: u16 = 0x0020;
+const IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE: u16 = 0x0040;
+const IMAGE_DLLCHARACTERISTICS_NX_COMPAT: u16 = 0x0100;
+const IMAGE_DLLCHARACTERISTICS_NO_SEH: u16 = 0x0400;
+const IMAGE_DLLCHARACTERISTICS_GUARD_CF: u16 = 0x4000;
+// GuardFlags
+const IMAGE_GUARD_CF_INSTRUMENTED: u32 = 0x0000_0100;
+const IMAGE_GUARD_SECURITY_COOKIE_UNUSED: u32 = 0x0000_0800;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Mitigation {
+    // DYNAMIC_BASE / HIGH_ENTROPY_VA / NX_COMPAT / GUARD_CF / SAFESEH / GS / AUTHENTICODE
+    pub name: String,
+    // enabled / disabled / not_applicable
+    pub status: String,
+    pub explanation: String,
+}
+
+fn mitigation(name: &str, status: &str, explanation: String) -> Mitigation {
+    Mitigation {
+        name: name.to_string(),
+        status: status.to_string(),
+        explanation,
+    }
+}
+
+fn enabled_if(set: bool) -> &'static str {
+    if set {
+        "enabled"
+    } else {
+        "disabled"
+    }
+}
+
+fn aslr(coff: &CoffHeader, optional: &OptionalHeader) -> Mitigation {
+    let dynamic_base = optional.dll_characteristics & IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0;
+    let relocs_stripped = coff.characteristics & IMAGE_FILE_RELOCS_STRIPPED != 0;
+    match (dynamic_base, relocs_stripped) {
+        (true, true) => mitigation(
+            "DYNAMIC_BASE",
+            "disabled",
+            "声明了ASLR但去除了重定位信息 加载器无法改变基址".to_string(),
+        ),
+        (true, false) => mitigation(
+            "DYNAMIC_BASE",
+            "enabled",
+            "ASLR: 每次加载时随机化映像基址".to_string(),
+        ),
+        (false, _) => mitigation(
+            "DYNAMIC_BASE",
+            "disabled",
+            format!(
+                "未启用ASLR 总是加载到0x{:X} 代码地址可预测",
+                optional.image_base
+            ),
+        ),
+    }
+}
+
+fn high_entropy_va(optional: &OptionalHeader) -> Mitigation {
+    if optional.magic != IMAGE_NT_OPTIONAL_HDR64_MAGIC {
+        return mitigation(
+            "HIGH_ENTROPY_VA",
+            "not_applicable",
+            "32位程序的地址空间只能使用低熵ASLR".to_string(),
+        );
+    }
+    let set = optional.dll_characteristics & IMAGE_DLLCHARACTERISTICS_HIGH_ENTROPY_VA != 0;
+    let dynamic_base = optional.dll_characteristics & IMAGE_DLLCHARACTERISTICS_DYNAMIC_BASE != 0;
+    let explanation = match (set, dynamic_base) {
+        (true, true) => "64位ASLR使用完整的地址空间随机化",
+        (true, false) => "设置了高熵ASLR 但未启用DYNAMIC_BASE 不起作用",
+        (false, _) => "64位ASLR只在低4GB内随机化 熵较低",
+    };
+    mitigation(
+        "HIGH_ENTROPY_VA",
+        enabled_if(set && dynamic_base),
+        explanation.to_string(),
+    )
+}
+
+fn dep(optional: &OptionalHeader) -> Mitigation {
+    let set = optional.dll_characteristics & IMAGE_DLLCHARACTERISTICS_NX_COMPAT != 0;
+    if !set && optional.magic == IMAGE_NT_OPTIONAL_HDR64_MAGIC {
+        return mitigation(
+            "NX_COMPAT",
+            "enabled",
+            "未设置NX_COMPAT 但64位进程总是启用DEP".to_string(),
+        );
+    }
+    let explanation = if set {
+        "DEP: 数据页不可执行"
+    } else {
+        "未声明DEP兼容 按系统策略可能允许执行数据页"
+    };
+    mitigation("NX_COMPAT", enabled_if(set), explanation.to_string())
+}
+
+fn cfg(optional: &OptionalHeader, load_config: Option<&LoadConfig>) -> Mitigation {
+    let set = optional.dll_characteristics & IMAGE_DLLCHARACTERISTICS_GUARD_CF != 0;
+    let instrumented = load_config
+        .and_then(|load_config| load_config.guard_flags)
+        .is_some_and(|flags| flags & IMAGE_GUARD_CF_INSTRUMENTED != 0);
+    let explanation = match (set, instrumented) {
+        (true, true) => {
+            let functions = load_config
+                .and_then(|load_config| load_config.guard_cf_function_count)
+                .unwrap_or(0);
+            format!("控制流保护: 间接调用只能跳到登记的{}个函数", functions)
+        }
+        (true, false) => "设置了GUARD_CF 但Load Config中没有CFG插桩".to_string(),
+        (false, true) => "有CFG插桩 但未设置GUARD_CF 加载器不会启用检查".to_string(),
+        (false, false) => "未启用控制流保护(CFG)".to_string(),
+    };
+    mitigation("GUARD_CF", enabled_if(set && instrumented), explanation)
+}
+
+fn safe_seh(
+    coff: &CoffHeader,
+    optional: &OptionalHeader,
+    load_config: Option<&LoadConfig>,
+) -> Mitigation {
+    if coff.machine != IMAGE_FILE_MACHINE_I386 {
+        return mitigation(
+            "SAFESEH",
+            "not_applicable",
+            "只有32位x86使用栈上的SEH链 其他架构使用基于表的异常处理".to_string(),
+        );
+    }
+    if optional.dll_characteristics & IMAGE_DLLCHARACTERISTICS_NO_SEH != 0 {
+        return mitigation(
+            "SAFESEH",
+            "enabled",
+            "声明不使用SEH 不会调用任何异常处理器".to_string(),
+        );
+    }
+    match load_config.filter(|load_config| load_config.se_handler_table.is_some()) {
+        Some(load_config) => mitigation(
+            "SAFESEH",
+            "enabled",
+            format!(
+                "SafeSEH: 只允许调用登记的{}个异常处理器",
+                load_config.se_handler_count.unwrap_or(0)
+            ),
+        ),
+        None => mitigation(
+            "SAFESEH",
+            "disabled",
+            "没有SafeSEH处理器表 覆盖栈上的SEH记录即可劫持控制流".to_string(),
+        ),
+    }
+}
+
+fn stack_cookie(load_config: Option<&LoadConfig>) -> Mitigation {
+    let Some(load_config) = load_config else {
+        return mitigation(
+            "GS",
+            "disabled",
+            "没有Load Config目录 未找到/GS安全cookie".to_string(),
+        );
+    };
+    let unused = load_config
+        .guard_flags
+        .is_some_and(|flags| flags & IMAGE_GUARD_SECURITY_COOKIE_UNUSED != 0);
+    match (load_config.security_cookie, unused) {
+        (Some(_), false) => mitigation(
+            "GS",
+            "enabled",
+            "/GS: 函数返回前检查栈上的安全cookie".to_string(),
+        ),
+        (Some(_), true) => mitigation(
+            "GS",
+            "disabled",
+            "有安全cookie 但GuardFlags标记为未使用".to_string(),
+        ),
+        (None, _) => mitigation("GS", "disabled", "Load Config中没有安全cookie".to_string()),
+    }
+}
+
+fn authenticode(is_signed: bool, signature: Option<&SignatureInfo>) -> Mitigation {
+    let Some(signature) = signature else {
+        let explanation = if is_signed {
+            "有证书目录但无法解析签名"
+        } else {
+            "未签名"
+        };
+        return mitigation("AUTHENTICODE", "disabled", explanation.to_string());
+    };
+    let signer = signature.signer.as_deref().unwrap_or("未知签名者");
+    match signature.digest_valid {
+        Some(false) => mitigation(
+            "AUTHENTICODE",
+            "disabled",
+            format!("由{}签名 但摘要不一致 文件在签名后被修改", signer),
+        ),
+        Some(true) => mitigation(
+            "AUTHENTICODE",
+            "enabled",
+            format!("由{}签名 摘要一致(未校验证书链)", signer),
+        ),
+        None => mitigation(
+            "AUTHENTICODE",
+            "enabled",
+            format!("由{}签名 摘要算法不支持校验", signer),
+        ),
+    }
+}
+
+pub(crate) fn assess_mitigations(
+    coff: &CoffHeader,
+    optional: &OptionalHeader,
+    load_config: Option<&LoadConfig>,
+    clr_platform: Option<&ClrPlatform>,
+    is_signed: bool,
+    signature: Option<&SignatureInfo>,
+) -> Vec<Mitigation> {
+    let mut mitigations = vec![
+        aslr(coff, optional),
+        high_entropy_va(optional),
+        dep(optional),
+    ];
+    // 纯IL程序集的代码由CLR即时编译 编译器级别的缓解措施不适用
+    let il_only = clr_platform.is_some_and(|clr| clr.cor_flags.iter().any(|flag| flag == "ILONLY"));
+    if il_only {
+        for name in ["GUARD_CF", "SAFESEH", "GS"] {
+            mitigations.push(mitigation(
+                name,
+                "not_applicable",
+                "纯IL的.NET程序集 由CLR负责代码生成".to_string(),
+            ));
+        }
+    } else {
+        mitigations.push(cfg(optional, load_config));
+        mitigations.push(safe_seh(coff, optional, load_config));
+        mitigations.push(stack_cookie(load_config));
+    }
+    mitigations.push(authenticode(is_signed, signature));
+    mitigations
+}
@@ -0,0 +1,1332 @@
+// PE解析入口 只依赖文件内容 不依赖Tauri和本机环境
+// 界面命令、批量分析和模糊测试都经由PeFile解析 依赖本机环境的信息由调用方另行补充
+use std::collections::HashSet;
+use std::fmt;
+use std::io::Read;
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+
+use crate::heuristics::Warning;
+use crate::limits::{LimitHit, Limits};
+use crate::reader::{BoundedReader, ReadError};
+use crate::{
+    authenticode, autoit, callconv, capabilities, com, coverage, debug, decode_name, delphi,
+    driver, dvrt, entropy, ep_signature, export_hooks, golang, guids, hashes, header_flags,
+    headers, heuristics, hybrid, iat, libraries, load_config, manifest, mitigations,
+    overlay_offset, python, record_phase, resources, rustlang, rva_to_offset, score, slack,
+    stack_strings, stub, tls, vb6, version_info, DataDirectory, ExportFunction, ExportOrdinals,
+    ImportDiagnostic, ImportFunction, ImportTableEntry, OrdinalRange, PeInfo, PhaseClock,
+    PhaseTiming, Section, IMAGE_DIRECTORY_ENTRY_BOUND_IMPORT, IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR,
+    IMAGE_DIRECTORY_ENTRY_DEBUG, IMAGE_DIRECTORY_ENTRY_EXPORT, IMAGE_DIRECTORY_ENTRY_IMPORT,
+    IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG, IMAGE_DIRECTORY_ENTRY_RESOURCE,
+    IMAGE_DIRECTORY_ENTRY_SECURITY, IMAGE_DIRECTORY_ENTRY_TLS,
+};
+
+#[derive(Debug)]
+pub enum PeError {
+    // 读取输入失败
+    Io(String),
+    // 没有MZ/PE签名或可选头格式未知
+    NotPe(String),
+    // 结构越界、损坏或解析过程中的其他错误
+    Parse(String),
+}
+
+impl fmt::Display for PeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PeError::Io(message) => write!(f, "无法读取文件: {}", message),
+            PeError::NotPe(message) | PeError::Parse(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl std::error::Error for PeError {}
+
+impl From<ReadError> for PeError {
+    fn from(error: ReadError) -> Self {
+        PeError::Parse(error.to_string())
+    }
+}
+
+impl From<String> for PeError {
+    fn from(message: String) -> Self {
+        PeError::Parse(message)
+    }
+}
+
+impl From<&str> for PeError {
+    fn from(message: &str) -> Self {
+        PeError::Parse(message.to_string())
+    }
+}
+
+impl From<PeError> for String {
+    fn from(error: PeError) -> Self {
+        error.to_string()
+    }
+}
+
+pub struct PeFile;
+
+impl PeFile {
+    // 读入全部内容后解析 结果中的path为空
+    // 解析上限和已知良性哈希集合由调用方传入 解析本身不读取任何本机配置
+    pub fn parse<R: Read>(
+        mut reader: R,
+        limits: &Limits,
+        known_good: &HashSet<String>,
+    ) -> Result<PeInfo, PeError> {
+        let mut data: Vec<u8> = Vec::new();
+        reader
+            .read_to_end(&mut data)
+            .map_err(|e| PeError::Io(e.to_string()))?;
+        Self::parse_bytes("", &data, limits, known_good)
+    }
+
+    // path仅作为结果中的标识 可以是拖入文件的名称
+    pub fn parse_bytes(
+        path: &str,
+        data: &[u8],
+        limits: &Limits,
+        known_good: &HashSet<String>,
+    ) -> Result<PeInfo, PeError> {
+        parse_data(path, data, limits, known_good)
+    }
+}
+
+// 以0结尾的导入导出名称 超过长度上限时截断并计数 文件在名称结束前截止时返回错误
+fn read_name<'a>(
+    reader: BoundedReader<'a>,
+    offset: u64,
+    max_length: usize,
+    truncated: &mut u32,
+) -> Result<&'a [u8], ReadError> {
+    let (name, cut) = reader.c_string_truncated(offset, max_length)?;
+    if cut {
+        if name.len() < max_length {
+            return Err(ReadError::Unterminated {
+                offset,
+                limit: max_length as u64,
+            });
+        }
+        *truncated += 1;
+    }
+    Ok(name)
+}
+
+// 表中第index项的文件偏移 以u64计算 不会因构造的表地址或下标溢出
+fn table_entry_offset(table: u32, index: u32, entry_size: u64) -> u64 {
+    table as u64 + index as u64 * entry_size
+}
+
+// 按文件剩余大小截断表项数量 构造的计数不会导致超长循环或巨量内存分配
+fn entries_within_file(count: u32, table: u32, entry_size: u64, file_size: u64) -> u32 {
+    let available = file_size.saturating_sub(table as u64) / entry_size;
+    count.min(available.min(u32::MAX as u64) as u32)
+}
+
+fn parse_data(
+    file_path: &str,
+    file_data: &[u8],
+    limits: &Limits,
+    known_good: &HashSet<String>,
+) -> Result<PeInfo, PeError> {
+    // 各阶段耗时统计
+    let analysis_start = PhaseClock::start();
+    let mut phase_start = analysis_start;
+    let mut timings: Vec<PhaseTiming> = Vec::new();
+
+    // 触发的解析上限在最后作为警告给出
+    let mut limit_hits: Vec<LimitHit> = Vec::new();
+    let max_name_length = limits.max_name_length as usize;
+    let mut truncated_names: u32 = 0;
+
+    // 所有结构都经由BoundedReader读取 越界时返回带偏移的错误
+    let reader = BoundedReader::new(file_data);
+
+    // 获取文件字节长度
+    let size = reader.len();
+    // println!("文件大小: 0x{:X} 字节", size);
+
+    // 判断是否是PE文件
+    // PE文件的前两个字节是"MZ"
+    if reader.bytes(0, 2)? != b"MZ" {
+        // eprintln!("不是有效的PE文件");
+        // std::process::exit(1);
+        return Err(PeError::NotPe("不是有效的PE文件".to_string()));
+    }
+
+    // 0x3C-0x3F是coff头的偏移位置
+    // 后续的头部偏移都以u64计算 构造的e_lfanew不会导致溢出
+    let coff_header_ptr = reader.u32(0x3C)? as u64;
+    // println!("COFF头偏移位置: 0x{:X}", coff_header_ptr);
+
+    // PE头签名
+    if reader.bytes(coff_header_ptr, 4)? != b"PE\0\0" {
+        // eprintln!("不是有效的PE文件");
+        // std::process::exit(1);
+        return Err(PeError::NotPe("不是有效的PE文件".to_string()));
+    }
+
+    // 读可选头的magic 判断是否为64为文件
+    let magic_ptr = coff_header_ptr + 0x18;
+    // println!("magic_ptr: 0x{:X}", magic_ptr);
+    let is_x64 = match reader.u16(magic_ptr)? {
+        0x10B => false,
+        0x20B => true,
+        _ => {
+            // eprintln!("未知的PE文件格式");
+            // std::process::exit(1);
+            return Err(PeError::NotPe("未知的PE文件格式".to_string()));
+        }
+    };
+    // println!("架构: {}", if is_x64 { "x64" } else { "x86" });
+
+    // 读取sizeof_optional_header
+    let optional_header_size = reader.u16(coff_header_ptr + 0x14)?;
+    // println!("可选头大小: 0x{:X}", optional_header_size);
+    let optional_header_ptr = coff_header_ptr + 0x18;
+    // println!("可选头偏移位置: 0x{:X}", optional_header_ptr);
+
+    // 读AddressOfEntryPoint 32位和64位可选头中位置相同
+    let entry_point = reader.u32(optional_header_ptr + 0x10)?;
+
+    // 读ImageBase 64位文件为8字节
+    let image_base = if is_x64 {
+        reader.u64(optional_header_ptr + 0x18)?
+    } else {
+        reader.u32(optional_header_ptr + 0x1C)? as u64
+    };
+
+    // 读SectionAlignment和FileAlignment
+    let section_alignment = reader.u32(optional_header_ptr + 0x20)?;
+    let file_alignment = reader.u32(optional_header_ptr + 0x24)?;
+
+    // 读SizeOfImage和SizeOfHeaders
+    let size_of_image = reader.u32(optional_header_ptr + 0x38)?;
+    let size_of_headers = reader.u32(optional_header_ptr + 0x3C)?;
+
+    // 读Subsystem和DllCharacteristics 32位和64位可选头中位置相同
+    let subsystem = reader.u16(optional_header_ptr + 0x44)?;
+    let dll_characteristics = reader.u16(optional_header_ptr + 0x46)?;
+
+    // 读number_of_sections
+    let number_of_sections = reader.u16(coff_header_ptr + 0x06)?;
+    // println!("节区数量: {}", number_of_sections);
+
+    // 读Machine
+    let machine = reader.u16(coff_header_ptr + 0x04)?;
+    // 读TimeDateStamp
+    let time_date_stamp = reader.u32(coff_header_ptr + 0x08)?;
+    // 读COFF头Characteristics
+    let characteristics = reader.u16(coff_header_ptr + 0x16)?;
+    let (dos_header, mut coff_header, optional_header) =
+        headers::read_headers(&reader, file_data, coff_header_ptr, is_x64)?;
+    record_phase(&mut timings, "headers", &mut phase_start);
+
+    // 遍历节表信息 超出上限的节不解析
+    if number_of_sections as u32 > limits.max_sections {
+        limit_hits.push(LimitHit::new(
+            "max_sections",
+            format!(
+                "文件声明了{}个节 只解析前{}个",
+                number_of_sections, limits.max_sections
+            ),
+        ));
+    }
+    let number_of_sections = (number_of_sections as u32).min(limits.max_sections) as u16;
+    let mut sections: Vec<Section> = Vec::with_capacity(number_of_sections as usize);
+    // 节表偏移位置
+    let section_table_ptr = optional_header_ptr + optional_header_size as u64;
+
+    for i in 0..number_of_sections {
+        let item_ptr = section_table_ptr + i as u64 * 40;
+        let raw_name = reader.bytes(item_ptr, 8)?;
+        let name_length = raw_name
+            .iter()
+            .rposition(|&b| b != 0)
+            .map_or(0, |last| last + 1);
+        let (section_name, raw_section_name) = decode_name(&raw_name[..name_length]);
+
+        let virtual_size = reader.u32(item_ptr + 0x08)?;
+        let rva = reader.u32(item_ptr + 0x0C)?;
+
+        // 构造的VirtualSize可能让结尾超出32位
+        let rv_end = rva.saturating_add(virtual_size);
+
+        let size_raw_data = reader.u32(item_ptr + 0x10)?;
+        let ptr_raw_data = reader.u32(item_ptr + 0x14)?;
+
+        // 跳过重定位和行号字段 读Characteristics
+        let characteristics = reader.u32(item_ptr + 0x24)?;
+
+        sections.push(Section {
+            name: section_name,
+            raw_name: raw_section_name,
+            rva,
+            ptr_raw_data,
+            size_raw_data,
+            rv_end,
+            characteristics,
+            entropy: 0.0,
+        });
+    }
+
+    // 节表几何校验
+    let mut warnings: Vec<Warning> =
+        heuristics::check_section_layout(&sections, section_alignment, size);
+    warnings.extend(heuristics::check_section_permissions(
+        &sections,
+        entry_point,
+    ));
+    warnings.extend(heuristics::check_section_names(&sections));
+    warnings.extend(heuristics::check_header_consistency(
+        &sections,
+        &heuristics::HeaderLayout {
+            section_alignment,
+            file_alignment,
+            size_of_image,
+            size_of_headers,
+            section_table_end: section_table_ptr + number_of_sections as u64 * 40,
+        },
+    ));
+
+    record_phase(&mut timings, "sections", &mut phase_start);
+
+    // println!("节表信息:");
+    // println!(
+    //     "{:<10} {:<12} {:<12} {:<12}",
+    //     "名称", "原始地址", "RVA", "RV结束"
+    // );
+    // for section in &sections {
+    //     println!(
+    //         "{:<10}   0x{:08X}   0x{:08X}   0x{:08X}",
+    //         section.name, section.ptr_raw_data, section.rva, section.rv_end
+    //     );
+    // }
+
+    // 实现函数rva -> raw_ptr转换
+    let relative_virtual_difference = |rva: u32| -> Option<u32> { rva_to_offset(&sections, rva) };
+
+    // 测试rva -> raw_ptr转换
+    // let test_rva = 0x003BA1A4;
+    // let test_raw_ptr = relative_virtual_difference(test_rva);
+    // match test_raw_ptr {
+    //     Some(ptr) => println!("RVA 0x{:08X} 对应的原始地址: 0x{:08X}", test_rva, ptr),
+    //     None => println!("RVA 0x{:08X} 不在任何节区内", test_rva),
+    // }
+
+    // 获取导出表和导入表信息
+    // 导出表在可选头的数据目录中第1个位置
+    // 导入表在可选头的数据目录中第2个位置
+    let data_directory_ptr = if is_x64 {
+        optional_header_ptr + 0x70
+    } else {
+        optional_header_ptr + 0x60
+    };
+    // println!("数据目录偏移位置: 0x{:X}", data_directory_ptr);
+
+    // NumberOfRvaAndSizes位于数据目录之前
+    let number_of_rva_and_sizes = reader.u32(data_directory_ptr - 4)?.min(16);
+
+    // 读出全部数据目录
+    let mut data_directories: Vec<DataDirectory> = Vec::with_capacity(16);
+    for index in 0..number_of_rva_and_sizes as u64 {
+        let entry_ptr = data_directory_ptr + index * 8;
+        data_directories.push(DataDirectory {
+            rva: reader.u32(entry_ptr)?,
+            size: reader.u32(entry_ptr + 4)?,
+        });
+    }
+    let directory = |index: usize| data_directories.get(index).copied().unwrap_or_default();
+
+    // 导出表rva和size
+    let export_table_rva = directory(IMAGE_DIRECTORY_ENTRY_EXPORT).rva;
+    let export_table_size = directory(IMAGE_DIRECTORY_ENTRY_EXPORT).size;
+    // 导入表rva和size
+    let import_table_rva = directory(IMAGE_DIRECTORY_ENTRY_IMPORT).rva;
+    let import_table_size = directory(IMAGE_DIRECTORY_ENTRY_IMPORT).size;
+
+    // println!(
+    //     "导出表 RVA: 0x{:08X}, 大小: 0x{:X}",
+    //     export_table_rva, export_table_size
+    // );
+
+    let mut export_table: Vec<ExportFunction> = Vec::new();
+    let mut export_ordinals: Option<ExportOrdinals> = None;
+
+    if export_table_size != 0 {
+        // 导出表rva -> raw_ptr
+        let export_table_ptr = match relative_virtual_difference(export_table_rva) {
+            Some(ptr) => ptr,
+            None => {
+                // eprintln!("导出表RVA转换失败");
+                // std::process::exit(1);
+                return Err("导出表RVA转换失败".into());
+            }
+        };
+        // println!("导出表偏移位置: 0x{:X}", export_table_ptr);
+        // 读导出表的条目总数 和 以函数名导出的数量
+        let export_table_ptr = export_table_ptr as u64;
+        let ordinal_base = reader.u32(export_table_ptr + 0x10)?;
+        let addresses_amount = reader.u32(export_table_ptr + 0x14)?;
+        let name_pointers_amount = reader.u32(export_table_ptr + 0x18)?;
+
+        let address_table_rva = reader.u32(export_table_ptr + 0x1C)?;
+        let name_pointer_table_rva = reader.u32(export_table_ptr + 0x20)?;
+        let ordinal_table_rva = reader.u32(export_table_ptr + 0x24)?;
+
+        // rva全部转换成raw_ptr
+        let address_table_ptr = match relative_virtual_difference(address_table_rva) {
+            Some(ptr) => ptr,
+            None => {
+                // eprintln!("导出地址表RVA转换失败");
+                // std::process::exit(1);
+                return Err("导出地址表RVA转换失败".into());
+            }
+        };
+        let name_pointer_table_ptr = match relative_virtual_difference(name_pointer_table_rva) {
+            Some(ptr) => ptr,
+            None => {
+                // eprintln!("导出符号名表RVA转换失败");
+                // std::process::exit(1);
+                return Err("导出符号名表RVA转换失败".into());
+            }
+        };
+        let ordinal_table_ptr = match relative_virtual_difference(ordinal_table_rva) {
+            Some(ptr) => ptr,
+            None => {
+                // eprintln!("导出序号表RVA转换失败");
+                // std::process::exit(1);
+                return Err("导出序号表RVA转换失败".into());
+            }
+        };
+
+        let addresses_amount = entries_within_file(addresses_amount, address_table_ptr, 4, size);
+        let name_pointers_amount = entries_within_file(
+            entries_within_file(name_pointers_amount, name_pointer_table_ptr, 4, size),
+            ordinal_table_ptr,
+            2,
+            size,
+        );
+
+        // 先把所有地址都push进去
+        for i in 0..addresses_amount {
+            let func_rva = reader.u32(table_entry_offset(address_table_ptr, i, 4))?;
+            export_table.push(ExportFunction {
+                name: String::new(),
+                raw_name: None,
+                is_named: false,
+                ordinal: ordinal_base.wrapping_add(i),
+                address: func_rva,
+                stack_bytes: None,
+                stack_bytes_source: None,
+            });
+        }
+
+        // 读出所有名称 RVA无法转换的名称按未命名处理
+        let mut name_list: Vec<Option<(String, Option<String>)>> =
+            Vec::with_capacity(name_pointers_amount as usize);
+        for i in 0..name_pointers_amount {
+            let name_rva = reader.u32(table_entry_offset(name_pointer_table_ptr, i, 4))?;
+            let name_ptr = match relative_virtual_difference(name_rva) {
+                Some(ptr) => ptr,
+                None => {
+                    name_list.push(None);
+                    continue;
+                }
+            };
+            // 读名称
+            name_list.push(Some(decode_name(read_name(
+                reader,
+                name_ptr as u64,
+                max_name_length,
+                &mut truncated_names,
+            )?)));
+        }
+
+        // 读出所有序号
+        let mut ordinal_list: Vec<u16> = Vec::with_capacity(name_pointers_amount as usize);
+        for i in 0..name_pointers_amount {
+            let ordinal = reader.u16(table_entry_offset(ordinal_table_ptr, i, 2))?;
+            ordinal_list.push(ordinal);
+        }
+
+        // 遍历ordinal_list 序号表中保存的是地址表的下标
+        for (i, &ordinal) in ordinal_list.iter().enumerate() {
+            let Some((name, raw_name)) = name_list.get(i).cloned().flatten() else {
+                continue;
+            };
+            if let Some(func) = export_table.get_mut(ordinal as usize) {
+                func.name = name;
+                func.raw_name = raw_name;
+                func.is_named = true;
+            }
+        }
+
+        // 地址为0且没有名称的项是序号空间中的空位 单独列出而不作为导出函数
+        let mut unused: Vec<OrdinalRange> = Vec::new();
+        let mut unused_count = 0;
+        for func in export_table
+            .iter()
+            .filter(|func| func.address == 0 && !func.is_named)
+        {
+            unused_count += 1;
+            match unused.last_mut() {
+                Some(range) if range.last.wrapping_add(1) == func.ordinal => {
+                    range.last = func.ordinal
+                }
+                _ => unused.push(OrdinalRange {
+                    first: func.ordinal,
+                    last: func.ordinal,
+                }),
+            }
+        }
+        export_table.retain(|func| func.address != 0 || func.is_named);
+        export_ordinals = Some(ExportOrdinals {
+            base: ordinal_base,
+            slots: addresses_amount,
+            named: export_table.iter().filter(|func| func.is_named).count() as u32,
+            unused,
+            unused_count,
+        });
+    }
+
+    // 先通过序号排序
+    export_table.sort_by_key(|f| f.ordinal);
+    record_phase(&mut timings, "exports", &mut phase_start);
+    // println!("导出的函数:");
+    // println!("{:<8} {:<10} 名称", "序号", "地址");
+
+    // for func in &export_table {
+    //     println!("{:<8} 0x{:08X} {}", func.ordinal, func.address, func.name);
+    // }
+
+    // println!(
+    //     "导入表 RVA: 0x{:08X}, 大小: 0x{:X}",
+    //     import_table_rva, import_table_size
+    // );
+
+    let mut import_table: Vec<ImportTableEntry> = Vec::new();
+    let mut import_diagnostics: Vec<ImportDiagnostic> = Vec::new();
+    // 读到的描述符数 不含结尾的全0描述符
+    let mut import_descriptor_count = 0;
+
+    if import_table_size != 0 {
+        // 导入表rva -> raw_ptr
+        let import_table_ptr = match relative_virtual_difference(import_table_rva) {
+            Some(ptr) => ptr,
+            None => {
+                // eprintln!("导入表RVA转换失败");
+                // std::process::exit(1);
+                return Err("导入表RVA转换失败".into());
+            }
+        };
+        // println!("导入表偏移位置: 0x{:X}", import_table_ptr);
+        // 一个导入表项的大小是20字节 加载器以全0的描述符判断结尾而不看目录大小
+        // 部分链接器和壳填写的大小小于实际数组 所以一直读到结尾 最多读取上限个
+        let import_table_item_limit =
+            entries_within_file(limits.max_import_descriptors, import_table_ptr, 20, size);
+        let mut terminated = false;
+        // 遍历
+        for i in 0..import_table_item_limit {
+            let import_table_item_ptr = table_entry_offset(import_table_ptr, i, 20);
+            // 读第一个字段 OriginalFirstThunk
+            let lookup_table_rva = reader.u32(import_table_item_ptr)?;
+            // 读第四个字段 Name
+            let name_rva = reader.u32(import_table_item_ptr + 12)?;
+            // 读第五个字段 FirstThunk 即IAT的RVA
+            let first_thunk_rva = reader.u32(import_table_item_ptr + 16)?;
+            // 全0的描述符是导入表的结尾 加载器不再读取之后的内容
+            if lookup_table_rva == 0 && name_rva == 0 && first_thunk_rva == 0 {
+                terminated = true;
+                break;
+            }
+            import_descriptor_count = i + 1;
+
+            let lookup_table_ptr = match relative_virtual_difference(lookup_table_rva) {
+                Some(ptr) => ptr,
+                None => {
+                    import_diagnostics.push(ImportDiagnostic {
+                        descriptor: i,
+                        dll_name: None,
+                        field: "lookup_table".to_string(),
+                        rva: lookup_table_rva,
+                        slot: None,
+                    });
+                    continue;
+                }
+            };
+
+            let name_ptr = match relative_virtual_difference(name_rva) {
+                Some(ptr) => ptr,
+                None => {
+                    import_diagnostics.push(ImportDiagnostic {
+                        descriptor: i,
+                        dll_name: None,
+                        field: "dll_name".to_string(),
+                        rva: name_rva,
+                        slot: None,
+                    });
+                    continue;
+                }
+            };
+
+            // 读DLL名称
+            let (dll_name, raw_dll_name) = decode_name(read_name(
+                reader,
+                name_ptr as u64,
+                max_name_length,
+                &mut truncated_names,
+            )?);
+
+            // println!("DLL名称: {}", dll_name);
+
+            // 逐个读取函数名称和序号
+            let mut functions: Vec<ImportFunction> = Vec::new();
+            let mut lookup_item_ptr = lookup_table_ptr as u64;
+            let lookup_item_size: u64 = if is_x64 { 8 } else { 4 };
+            // 最高位为1时按序号导入
+            let ordinal_flag: u64 = if is_x64 { 1 << 63 } else { 1 << 31 };
+
+            loop {
+                if (lookup_item_ptr - lookup_table_ptr as u64) / lookup_item_size
+                    >= limits.max_import_functions as u64
+                {
+                    limit_hits.push(LimitHit::new(
+                        "max_import_functions",
+                        format!(
+                            "{} 的导入函数超过{}个 其余未解析",
+                            dll_name, limits.max_import_functions
+                        ),
+                    ));
+                    break;
+                }
+                let thunk_rva = first_thunk_rva
+                    .wrapping_add((lookup_item_ptr - lookup_table_ptr as u64) as u32);
+                let entry = reader.pointer(lookup_item_ptr, is_x64)?;
+                if entry == 0 {
+                    break;
+                }
+                if entry & ordinal_flag != 0 {
+                    let ordinal = (entry & 0xFFFF) as u16;
+                    functions.push(ImportFunction {
+                        name: String::new(),
+                        raw_name: None,
+                        is_ordinal: true,
+                        ordinal,
+                        hint: 0,
+                        thunk_rva,
+                    });
+                } else {
+                    let hint_name_rva = (entry & (ordinal_flag - 1)) as u32;
+                    let hint_name_ptr = match relative_virtual_difference(hint_name_rva) {
+                        Some(ptr) => ptr as u64,
+                        None => {
+                            import_diagnostics.push(ImportDiagnostic {
+                                descriptor: i,
+                                dll_name: Some(dll_name.clone()),
+                                field: "hint_name".to_string(),
+                                rva: hint_name_rva,
+                                slot: Some(
+                                    ((lookup_item_ptr - lookup_table_ptr as u64) / lookup_item_size)
+                                        as u32,
+                                ),
+                            });
+                            lookup_item_ptr += lookup_item_size;
+                            continue;
+                        }
+                    };
+                    // 读hint和name
+                    let hint = reader.u16(hint_name_ptr)?;
+                    let (func_name, raw_name) = decode_name(read_name(
+                        reader,
+                        hint_name_ptr + 2,
+                        max_name_length,
+                        &mut truncated_names,
+                    )?);
+                    functions.push(ImportFunction {
+                        name: func_name,
+                        raw_name,
+                        is_ordinal: false,
+                        ordinal: 0,
+                        hint,
+                        thunk_rva,
+                    });
+                }
+                lookup_item_ptr += lookup_item_size;
+            }
+
+            // 通过hint排序
+            functions.sort_by_key(|f| f.hint);
+
+            // println!("导入的函数:");
+            // println!("{:<8} 名称", "序号");
+            // for func in &functions {
+            //     if func.ordinal != 0 {
+            //         println!("{:<8} {}", func.ordinal, func.name);
+            //     } else {
+            //         println!("         {}", func.name);
+            //     }
+            // }
+
+            import_table.push(ImportTableEntry {
+                dll_name,
+                raw_dll_name,
+                functions,
+            });
+        }
+        if !terminated && import_table_item_limit == limits.max_import_descriptors {
+            limit_hits.push(LimitHit::new(
+                "max_import_descriptors",
+                format!(
+                    "导入描述符数组在前{}项中没有结尾 其余未解析",
+                    limits.max_import_descriptors
+                ),
+            ));
+        }
+    }
+
+    record_phase(&mut timings, "imports", &mut phase_start);
+    if truncated_names > 0 {
+        limit_hits.push(LimitHit::new(
+            "max_name_length",
+            format!(
+                "{}个导入导出名称超过{}字节 已截断",
+                truncated_names, limits.max_name_length
+            ),
+        ));
+    }
+
+    // TLS回调
+    let tls = tls::parse_tls(
+        reader,
+        &sections,
+        directory(IMAGE_DIRECTORY_ENTRY_TLS),
+        is_x64,
+        image_base,
+    )?;
+    record_phase(&mut timings, "tls", &mut phase_start);
+
+    // 调试目录
+    let debug_entries =
+        debug::parse_debug_directory(file_data, &sections, directory(IMAGE_DIRECTORY_ENTRY_DEBUG));
+    let pogo = debug::parse_pogo(file_data, &debug_entries);
+    // 可重现构建的TimeDateStamp是内容哈希 不是时间
+    if debug::is_reproducible(&debug_entries) {
+        coff_header.timestamp_utc = None;
+    }
+    // 入口点字节 用于按入口代码识别壳
+    let entry_point_bytes = ep_signature::read_entry_point(file_data, &sections, entry_point);
+    let stack_strings =
+        stack_strings::find_stack_strings(file_data, &sections, entry_point, machine);
+    if let Some(bytes) = &entry_point_bytes {
+        warnings.extend(heuristics::check_entry_point_signature(bytes));
+    }
+
+    // Load Config中的动态重定位 以及由此描述的混合架构视图
+    let load_config = load_config::parse_load_config(
+        file_data,
+        &sections,
+        directory(IMAGE_DIRECTORY_ENTRY_LOAD_CONFIG),
+        is_x64,
+        image_base,
+    );
+    let relocations = load_config
+        .as_ref()
+        .map(|load_config| {
+            dvrt::dynamic_relocations(file_data, &sections, load_config, image_base, is_x64)
+        })
+        .unwrap_or_default();
+    let dynamic_relocations = dvrt::summarize(&relocations);
+    let hybrid = load_config.as_ref().and_then(|load_config| {
+        hybrid::detect_hybrid(
+            file_data,
+            &sections,
+            load_config,
+            &relocations,
+            machine,
+            image_base,
+            size_of_headers,
+            max_name_length,
+        )
+    });
+
+    let export_anomalies = export_hooks::find_export_anomalies(
+        &sections,
+        &export_table,
+        directory(IMAGE_DIRECTORY_ENTRY_EXPORT),
+        size_of_headers,
+    );
+    warnings.extend(heuristics::check_export_anomalies(&export_anomalies));
+    let iat_state = iat::inspect_iat(
+        file_data,
+        &sections,
+        directory(IMAGE_DIRECTORY_ENTRY_IMPORT),
+        directory(IMAGE_DIRECTORY_ENTRY_BOUND_IMPORT),
+        is_x64,
+        size_of_image,
+    );
+    if let Some(iat_state) = &iat_state {
+        warnings.extend(heuristics::check_iat_state(iat_state));
+    }
+    warnings.extend(heuristics::check_import_diagnostics(&import_diagnostics));
+    warnings.extend(heuristics::check_import_directory_size(
+        import_table_size,
+        import_descriptor_count,
+    ));
+
+    // x86导出函数的参数字节数
+    if !is_x64 {
+        callconv::infer_stack_bytes(
+            file_data,
+            &sections,
+            &mut export_table,
+            directory(IMAGE_DIRECTORY_ENTRY_EXPORT),
+        );
+    }
+
+    // 资源与附加数据
+    let resources = resources::list_resources(
+        file_data,
+        &sections,
+        directory(IMAGE_DIRECTORY_ENTRY_RESOURCE),
+        limits,
+        &mut limit_hits,
+    );
+    let overlay_offset = overlay_offset(&sections).min(size);
+    let coverage = coverage::compute_coverage(
+        size,
+        &sections,
+        size_of_headers,
+        overlay_offset,
+        directory(IMAGE_DIRECTORY_ENTRY_SECURITY),
+    );
+    let version_info = version_info::parse_version_info(file_data, &resources);
+    let manifest = manifest::embedded_manifest(file_data, &resources);
+    let com = com::detect_com_surface(file_data, &export_table, &resources);
+    // CLR头Flags位于偏移0x10 与头部标志一起解释WoW64下的行为
+    let clr_flags = Some(directory(IMAGE_DIRECTORY_ENTRY_COM_DESCRIPTOR))
+        .filter(|clr| clr.rva != 0)
+        .and_then(|clr| rva_to_offset(&sections, clr.rva))
+        .and_then(|offset| reader.u32(offset as u64 + 0x10).ok());
+    let header_flags =
+        header_flags::explain_wow64_flags(is_x64, characteristics, dll_characteristics, clr_flags);
+    let clr_platform =
+        clr_flags.map(|flags| header_flags::clr_platform_target(machine, is_x64, flags));
+    record_phase(&mut timings, "resources", &mut phase_start);
+
+    let digest_layout = authenticode::DigestLayout {
+        checksum_offset: optional_header_ptr + 0x40,
+        security_entry_offset: data_directory_ptr + IMAGE_DIRECTORY_ENTRY_SECURITY as u64 * 8,
+    };
+    // 节表建立后以下几组扫描互不依赖 在作用域线程中并行执行 耗时各自记录
+    // wasm32没有线程 依次执行
+    let entropy_pass = || {
+        let mut pass_start = PhaseClock::start();
+        let mut pass_timings: Vec<PhaseTiming> = Vec::new();
+        // 节区熵
+        let entropies: Vec<f64> = sections
+            .iter()
+            .map(|section| {
+                entropy::shannon_entropy(
+                    reader
+                        .bytes_truncated(section.ptr_raw_data as u64, section.size_raw_data as u64),
+                )
+            })
+            .collect();
+        let slack = slack::find_slack(file_data, &sections);
+        record_phase(&mut pass_timings, "entropy", &mut pass_start);
+        (entropies, slack, pass_timings)
+    };
+    let indicator_pass = || {
+        let mut pass_start = PhaseClock::start();
+        let mut pass_timings: Vec<PhaseTiming> = Vec::new();
+        // 反调试/反虚拟机特征
+        let mut indicators =
+            capabilities::detect_anti_analysis(&import_table, &sections, file_data);
+        // 进程注入能力
+        indicators.extend(capabilities::detect_injection(&import_table));
+        let guids = guids::extract_guids(file_data);
+        // 静态链接的第三方库
+        let static_libraries = libraries::detect_static_libraries(file_data, &sections);
+        // Go程序的构建信息
+        let go_info = golang::parse_go_info(file_data, &sections, image_base);
+        // Rust程序的crate线索
+        let rust_info = rustlang::parse_rust_info(file_data, &export_table);
+        // PyInstaller/py2exe打包
+        let python_package = python::detect_python_package(file_data, &resources, overlay_offset);
+        // AutoIt编译脚本
+        let autoit = autoit::detect_autoit(file_data, &resources, overlay_offset);
+        // Delphi和VB6程序
+        let delphi = delphi::detect_delphi(file_data, &sections, &resources);
+        let vb6 = vb6::detect_vb6(file_data, &sections, &import_table, entry_point, image_base);
+        record_phase(&mut pass_timings, "indicators", &mut pass_start);
+        (
+            indicators,
+            guids,
+            static_libraries,
+            go_info,
+            rust_info,
+            python_package,
+            autoit,
+            delphi,
+            vb6,
+            pass_timings,
+        )
+    };
+    let hash_pass = || {
+        let mut pass_start = PhaseClock::start();
+        let mut pass_timings: Vec<PhaseTiming> = Vec::new();
+        // 哈希与签名者
+        let hashes = hashes::compute_hashes(file_data);
+        let directory_digests =
+            hashes::directory_digests(file_data, &sections, &data_directories, size_of_headers);
+        let imphash = hashes::imphash(&import_table);
+        let import_order_hash = hashes::import_order_hash(&import_table);
+        let dll_sequence_hash = hashes::dll_sequence_hash(&import_table);
+        let known_good = hashes::is_known_good(&hashes, known_good);
+        let signature = authenticode::parse_signature(
+            file_data,
+            directory(IMAGE_DIRECTORY_ENTRY_SECURITY),
+            digest_layout,
+        );
+        let authentihash = authenticode::authentihash(
+            file_data,
+            directory(IMAGE_DIRECTORY_ENTRY_SECURITY),
+            digest_layout,
+        );
+        record_phase(&mut pass_timings, "hashes", &mut pass_start);
+        (
+            hashes,
+            directory_digests,
+            (imphash, import_order_hash, dll_sequence_hash),
+            known_good,
+            signature,
+            authentihash,
+            pass_timings,
+        )
+    };
+    #[cfg(not(target_arch = "wasm32"))]
+    let (entropy_pass, indicator_pass, hash_pass) = thread::scope(|scope| {
+        let entropy_pass = scope.spawn(entropy_pass);
+        let indicator_pass = scope.spawn(indicator_pass);
+        let hash_pass = scope.spawn(hash_pass);
+        (entropy_pass.join(), indicator_pass.join(), hash_pass.join())
+    });
+    #[cfg(target_arch = "wasm32")]
+    let (entropy_pass, indicator_pass, hash_pass) = (
+        Ok::<_, ()>(entropy_pass()),
+        Ok::<_, ()>(indicator_pass()),
+        Ok::<_, ()>(hash_pass()),
+    );
+    let (entropies, slack, entropy_timings) =
+        entropy_pass.map_err(|_| "熵计算线程异常退出".to_string())?;
+    let (
+        indicators,
+        guids,
+        static_libraries,
+        go_info,
+        rust_info,
+        python_package,
+        autoit,
+        delphi,
+        vb6,
+        indicator_timings,
+    ) = indicator_pass.map_err(|_| "特征扫描线程异常退出".to_string())?;
+    let (
+        hashes,
+        directory_digests,
+        (imphash, import_order_hash, dll_sequence_hash),
+        known_good,
+        signature,
+        authentihash,
+        hash_timings,
+    ) = hash_pass.map_err(|_| "哈希计算线程异常退出".to_string())?;
+    timings.extend(entropy_timings);
+    timings.extend(indicator_timings);
+    timings.extend(hash_timings);
+
+    for (section, entropy) in sections.iter_mut().zip(entropies) {
+        section.entropy = entropy;
+    }
+    warnings.extend(heuristics::check_slack(&slack));
+    // 运行库的回调数依赖POGO和Rust特征 在特征扫描之后检查
+    if let Some(tls) = &tls {
+        let runtime = tls::runtime_callbacks(
+            file_data,
+            pogo.as_ref(),
+            &import_table,
+            rust_info.is_some(),
+            is_x64,
+        );
+        warnings.extend(heuristics::check_tls_callbacks(&sections, tls, &runtime));
+    }
+    warnings.extend(heuristics::check_injection(&indicators));
+    if let Some(signature) = &signature {
+        warnings.extend(heuristics::check_signature(
+            signature,
+            version_info.as_ref(),
+        ));
+        if let Some(signing_time) = signature.signing_time {
+            warnings.extend(heuristics::check_signing_time(
+                time_date_stamp,
+                signing_time,
+                debug::is_reproducible(&debug_entries),
+            ));
+        }
+    }
+
+    // 综合可疑度评分 证书目录存在即视为已签名
+    let is_signed = directory(IMAGE_DIRECTORY_ENTRY_SECURITY).size != 0;
+    let mitigations = mitigations::assess_mitigations(
+        &coff_header,
+        &optional_header,
+        load_config.as_ref(),
+        clr_platform.as_ref(),
+        is_signed,
+        signature.as_ref(),
+    );
+    // 内核驱动的框架和签名要求
+    let driver = driver::analyze_driver(
+        file_data,
+        &sections,
+        &import_table,
+        subsystem,
+        dll_characteristics,
+        image_base,
+        is_x64,
+        is_signed,
+    );
+    if let Some(driver) = &driver {
+        warnings.extend(heuristics::check_driver_signing(driver));
+    }
+    let stub = stub::detect_stub(
+        file_data,
+        &sections,
+        &import_table,
+        &data_directories,
+        entry_point,
+        size_of_headers,
+        is_x64,
+    );
+    if let Some(stub) = &stub {
+        warnings.extend(heuristics::check_stub(stub));
+    }
+    warnings.extend(heuristics::check_limit_hits(&limit_hits));
+    let suspicion = score::compute_score(
+        &sections,
+        entry_point_bytes.as_ref(),
+        &warnings,
+        &indicators,
+        is_signed,
+    );
+
+    let pe_info = PeInfo {
+        path: String::from(file_path),
+        size,
+        is_x64,
+        machine,
+        time_date_stamp,
+        characteristics,
+        subsystem,
+        dll_characteristics,
+        header_flags,
+        dos_header,
+        coff_header,
+        optional_header,
+        clr_platform,
+        entry_point,
+        entry_point_bytes,
+        stack_strings,
+        image_base,
+        size_of_image,
+        size_of_headers,
+        section_alignment,
+        data_directories,
+        sections,
+        slack,
+        debug_entries,
+        pogo,
+        export_table,
+        export_ordinals,
+        export_anomalies,
+        import_table,
+        import_diagnostics,
+        iat_state,
+        load_config,
+        hybrid,
+        dynamic_relocations,
+        tls,
+        resources,
+        version_info,
+        manifest,
+        com,
+        overlay_offset,
+        coverage,
+        indicators,
+        guids,
+        static_libraries,
+        go_info,
+        rust_info,
+        python_package,
+        autoit,
+        delphi,
+        vb6,
+        driver,
+        stub,
+        warnings,
+        is_signed,
+        signature,
+        mitigations,
+        hashes,
+        directory_digests,
+        imphash,
+        import_order_hash,
+        dll_sequence_hash,
+        authentihash,
+        known_good,
+        suspicion,
+        timings,
+        total_duration_us: analysis_start.elapsed_us(),
+    };
+
+    // let pe_info_json = serde_json::to_string(&pe_info).unwrap();
+    // println!("PE信息(JSON):\n{}", pe_info_json);
+    Ok(pe_info)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 最小的PE32映像 一个.text节 RVA 0x1000映射到文件偏移0x200
+    const PE_HEADER: usize = 0x40;
+    const OPTIONAL_HEADER: usize = PE_HEADER + 0x18;
+    const DATA_DIRECTORIES: usize = OPTIONAL_HEADER + 0x60;
+    const SECTION_TABLE: usize = OPTIONAL_HEADER + 0xE0;
+
+    fn put_u16(data: &mut [u8], offset: usize, value: u16) {
+        data[offset..offset + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn put_u32(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    // .text节内RVA对应的文件偏移
+    fn offset(rva: u32) -> usize {
+        (rva - 0x1000 + 0x200) as usize
+    }
+
+    fn image() -> Vec<u8> {
+        let mut data = vec![0u8; 0x600];
+        data[..2].copy_from_slice(b"MZ");
+        put_u32(&mut data, 0x3C, PE_HEADER as u32);
+        data[PE_HEADER..PE_HEADER + 4].copy_from_slice(b"PE\0\0");
+        // COFF头
+        put_u16(&mut data, PE_HEADER + 0x04, 0x14C);
+        put_u16(&mut data, PE_HEADER + 0x06, 1);
+        put_u16(&mut data, PE_HEADER + 0x14, 0xE0);
+        put_u16(&mut data, PE_HEADER + 0x16, 0x0102);
+        // 可选头
+        put_u16(&mut data, OPTIONAL_HEADER, 0x10B);
+        put_u32(&mut data, OPTIONAL_HEADER + 0x10, 0x1000);
+        put_u32(&mut data, OPTIONAL_HEADER + 0x1C, 0x0040_0000);
+        put_u32(&mut data, OPTIONAL_HEADER + 0x20, 0x1000);
+        put_u32(&mut data, OPTIONAL_HEADER + 0x24, 0x200);
+        put_u32(&mut data, OPTIONAL_HEADER + 0x38, 0x2000);
+        put_u32(&mut data, OPTIONAL_HEADER + 0x3C, 0x200);
+        put_u16(&mut data, OPTIONAL_HEADER + 0x44, 3);
+        put_u32(&mut data, OPTIONAL_HEADER + 0x5C, 16);
+        // 节表
+        data[SECTION_TABLE..SECTION_TABLE + 5].copy_from_slice(b".text");
+        put_u32(&mut data, SECTION_TABLE + 0x08, 0x400);
+        put_u32(&mut data, SECTION_TABLE + 0x0C, 0x1000);
+        put_u32(&mut data, SECTION_TABLE + 0x10, 0x400);
+        put_u32(&mut data, SECTION_TABLE + 0x14, 0x200);
+        put_u32(&mut data, SECTION_TABLE + 0x24, 0x6000_0020);
+        data
+    }
+
+    fn set_directory(data: &mut [u8], index: usize, rva: u32, size: u32) {
+        put_u32(data, DATA_DIRECTORIES + index * 8, rva);
+        put_u32(data, DATA_DIRECTORIES + index * 8 + 4, size);
+    }
+
+    fn parse(data: &[u8], limits: &Limits) -> Result<PeInfo, PeError> {
+        PeFile::parse_bytes("test.exe", data, limits, &HashSet::new())
+    }
+
+    #[test]
+    fn parses_minimal_image() {
+        let pe_info = parse(&image(), &Limits::default()).unwrap();
+        assert!(!pe_info.is_x64);
+        assert_eq!(pe_info.entry_point, 0x1000);
+        assert_eq!(pe_info.image_base, 0x0040_0000);
+        assert_eq!(pe_info.sections.len(), 1);
+        assert_eq!(pe_info.sections[0].name, ".text");
+        assert!(pe_info.import_table.is_empty());
+        assert!(pe_info.export_ordinals.is_none());
+    }
+
+    #[test]
+    fn rejects_missing_signatures() {
+        let mut data = image();
+        data[0] = b'Z';
+        assert!(matches!(
+            parse(&data, &Limits::default()),
+            Err(PeError::NotPe(_))
+        ));
+
+        let mut data = image();
+        put_u16(&mut data, OPTIONAL_HEADER, 0x107);
+        assert!(matches!(
+            parse(&data, &Limits::default()),
+            Err(PeError::NotPe(_))
+        ));
+    }
+
+    #[test]
+    fn truncated_headers_are_parse_errors() {
+        let data = image();
+        // 分别截断在PE签名、COFF头、可选头和节表中
+        for cut in [
+            0x3E,
+            PE_HEADER + 2,
+            PE_HEADER + 0x10,
+            OPTIONAL_HEADER + 0x30,
+        ] {
+            assert!(
+                matches!(
+                    parse(&data[..cut], &Limits::default()),
+                    Err(PeError::Parse(_))
+                ),
+                "截断于0x{:X}",
+                cut
+            );
+        }
+        assert!(parse(&data[..SECTION_TABLE + 20], &Limits::default()).is_err());
+    }
+
+    #[test]
+    fn out_of_range_import_rvas_are_reported() {
+        let mut data = image();
+        set_directory(&mut data, IMAGE_DIRECTORY_ENTRY_IMPORT, 0x1100, 60);
+        // 第一个描述符正常 第二个的名称不在任何节内 第三个为结尾
+        let descriptor = offset(0x1100);
+        put_u32(&mut data, descriptor, 0x1180);
+        put_u32(&mut data, descriptor + 12, 0x1160);
+        put_u32(&mut data, descriptor + 16, 0x11A0);
+        put_u32(&mut data, descriptor + 20, 0x1180);
+        put_u32(&mut data, descriptor + 20 + 12, 0x9000);
+        put_u32(&mut data, descriptor + 20 + 16, 0x11A0);
+        data[offset(0x1160)..offset(0x1160) + 12].copy_from_slice(b"kernel32.dll");
+        // 查找表 第二项的hint/name也越界
+        put_u32(&mut data, offset(0x1180), 0x11C0);
+        put_u32(&mut data, offset(0x1184), 0x0000_9000);
+        data[offset(0x11C2)..offset(0x11C2) + 11].copy_from_slice(b"ExitProcess");
+
+        let pe_info = parse(&data, &Limits::default()).unwrap();
+        assert_eq!(pe_info.import_table.len(), 1);
+        assert_eq!(pe_info.import_table[0].dll_name, "kernel32.dll");
+        let functions = &pe_info.import_table[0].functions;
+        assert_eq!(functions.len(), 1);
+        assert_eq!(functions[0].name, "ExitProcess");
+        assert_eq!(functions[0].thunk_rva, 0x11A0);
+
+        let diagnostics: Vec<(u32, &str, u32, Option<u32>)> = pe_info
+            .import_diagnostics
+            .iter()
+            .map(|d| (d.descriptor, d.field.as_str(), d.rva, d.slot))
+            .collect();
+        assert_eq!(
+            diagnostics,
+            [
+                (0, "hint_name", 0x9000, Some(1)),
+                (1, "dll_name", 0x9000, None)
+            ]
+        );
+        assert!(pe_info
+            .warnings
+            .iter()
+            .any(|warning| warning.kind == "import_entries_dropped"));
+    }
+
+    #[test]
+    fn unterminated_import_descriptors_stop_at_the_limit() {
+        let mut data = image();
+        set_directory(&mut data, IMAGE_DIRECTORY_ENTRY_IMPORT, 0x1100, 20);
+        // 整个节填满非0描述符 没有全0的结尾
+        for descriptor in (offset(0x1100)..offset(0x1400) - 20).step_by(20) {
+            put_u32(&mut data, descriptor, 0x9000);
+            put_u32(&mut data, descriptor + 12, 0x9000);
+            put_u32(&mut data, descriptor + 16, 0x9000);
+        }
+        let limits = Limits {
+            max_import_descriptors: 4,
+            ..Limits::default()
+        };
+
+        let pe_info = parse(&data, &limits).unwrap();
+        assert!(pe_info.import_table.is_empty());
+        assert_eq!(pe_info.import_diagnostics.len(), 4);
+        assert!(pe_info
+            .import_diagnostics
+            .iter()
+            .all(|d| d.field == "lookup_table"));
+        assert!(pe_info
+            .warnings
+            .iter()
+            .any(|warning| warning.kind == "limit_hit"
+                && warning.message.contains("max_import_descriptors")));
+
+        // 上限足够时读到文件末尾为止 不算触发上限
+        let pe_info = parse(&data, &Limits::default()).unwrap();
+        assert!(!pe_info
+            .warnings
+            .iter()
+            .any(|warning| warning.message.contains("max_import_descriptors")));
+    }
+
+    #[test]
+    fn export_ordinal_gaps_are_listed_separately() {
+        let mut data = image();
+        set_directory(&mut data, IMAGE_DIRECTORY_ENTRY_EXPORT, 0x1100, 0x100);
+        let directory = offset(0x1100);
+        put_u32(&mut data, directory + 0x10, 5);
+        put_u32(&mut data, directory + 0x14, 5);
+        put_u32(&mut data, directory + 0x18, 1);
+        put_u32(&mut data, directory + 0x1C, 0x1140);
+        put_u32(&mut data, directory + 0x20, 0x1160);
+        put_u32(&mut data, directory + 0x24, 0x1170);
+        // 序号5和8有地址 6、7、9为空位
+        put_u32(&mut data, offset(0x1140), 0x1000);
+        put_u32(&mut data, offset(0x1140) + 12, 0x1010);
+        // 唯一的名称指向地址表下标3 即序号8
+        put_u32(&mut data, offset(0x1160), 0x1180);
+        put_u16(&mut data, offset(0x1170), 3);
+        data[offset(0x1180)..offset(0x1180) + 3].copy_from_slice(b"Run");
+
+        let pe_info = parse(&data, &Limits::default()).unwrap();
+        let exports: Vec<(u32, &str, u32)> = pe_info
+            .export_table
+            .iter()
+            .map(|f| (f.ordinal, f.name.as_str(), f.address))
+            .collect();
+        assert_eq!(exports, [(5, "", 0x1000), (8, "Run", 0x1010)]);
+
+        let ordinals = pe_info.export_ordinals.unwrap();
+        assert_eq!(ordinals.base, 5);
+        assert_eq!(ordinals.slots, 5);
+        assert_eq!(ordinals.named, 1);
+        assert_eq!(ordinals.unused_count, 3);
+        let unused: Vec<(u32, u32)> = ordinals
+            .unused
+            .iter()
+            .map(|range| (range.first, range.last))
+            .collect();
+        assert_eq!(unused, [(6, 7), (9, 9)]);
+    }
+}
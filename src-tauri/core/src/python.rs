@@ -0,0 +1,406 @@
+// PyInstaller/py2exe打包程序识别 列出内嵌的Python模块与归档
+use flate2::read::ZlibDecoder;
+use serde::{Deserialize, Serialize};
+
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+
+use crate::capabilities::find_all;
+use crate::limits::Limits;
+use crate::reader::BoundedReader;
+use crate::resources::{resource_data, ResourceEntry};
+
+// PyInstaller CArchive cookie
+const PYINSTALLER_MAGIC: &[u8] = b"MEI\x0c\x0b\x0a\x0b\x0e";
+// 2.1之后的cookie带有64字节的Python库名
+const COOKIE_SIZE_V21: u64 = 88;
+const COOKIE_SIZE_V20: u64 = 24;
+// TOC表项的固定部分
+const TOC_ENTRY_HEADER_SIZE: u64 = 18;
+const MAX_TOC_ENTRIES: usize = 65536;
+// py2exe在PYTHONSCRIPT资源开头写入的magic
+const PY2EXE_SCRIPT_MAGIC: u32 = 0x7856_3412;
+// zip中央目录结尾
+const ZIP_EOCD_MAGIC: &[u8] = b"PK\x05\x06";
+const ZIP_CENTRAL_MAGIC: &[u8] = b"PK\x01\x02";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PyArchiveEntry {
+    pub name: String,
+    pub kind: String,
+    // 数据在文件中的偏移
+    pub offset: u64,
+    pub compressed_size: u32,
+    pub size: u32,
+    pub compressed: bool,
+    // 附加zip中的成员 只列出 解包时随整个zip写出
+    pub in_zip: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct PythonPackage {
+    // PyInstaller / py2exe
+    pub packer: String,
+    pub python_version: Option<String>,
+    pub python_library: Option<String>,
+    pub entries: Vec<PyArchiveEntry>,
+}
+
+fn toc_kind(kind: u8) -> &'static str {
+    match kind {
+        b's' => "script",
+        b'm' | b'M' => "module",
+        b'z' | b'Z' => "pyz",
+        b'b' => "binary",
+        b'x' => "data",
+        b'o' => "option",
+        b'd' => "dependency",
+        b'l' => "splash",
+        b'n' => "symlink",
+        _ => "unknown",
+    }
+}
+
+// 38 -> 3.8, 312 -> 3.12
+fn python_version(value: u32) -> String {
+    if value >= 100 {
+        format!("{}.{}", value / 100, value % 100)
+    } else {
+        format!("{}.{}", value / 10, value % 10)
+    }
+}
+
+fn parse_pyinstaller(data: &[u8], overlay_offset: u64) -> Option<PythonPackage> {
+    let reader = BoundedReader::new(data);
+    let overlay = reader.bytes_truncated(overlay_offset, u64::MAX);
+    let overlay_offset = reader.len() - overlay.len() as u64;
+    // cookie位于归档末尾 取最后一次出现的位置
+    let cookie = find_all(overlay, PYINSTALLER_MAGIC, usize::MAX)
+        .last()
+        .map(|offset| overlay_offset + *offset as u64)?;
+
+    let package_length = reader.u32_be(cookie + 8).ok()? as u64;
+    let toc_offset = reader.u32_be(cookie + 12).ok()? as u64;
+    let toc_length = reader.u32_be(cookie + 16).ok()? as u64;
+    let version = reader.u32_be(cookie + 20).ok()?;
+
+    // 先按新版cookie计算归档起点 不合理时退回旧版
+    // 偏移都按u64计算 32位平台上不会溢出
+    let (package_start, python_library) =
+        [COOKIE_SIZE_V21, COOKIE_SIZE_V20]
+            .iter()
+            .find_map(|&cookie_size| {
+                let start = (cookie + cookie_size).checked_sub(package_length)?;
+                if start + toc_offset + toc_length > cookie {
+                    return None;
+                }
+                let library = if cookie_size == COOKIE_SIZE_V21 {
+                    reader
+                        .bytes(cookie + 24, COOKIE_SIZE_V21 - 24)
+                        .ok()
+                        .map(|bytes| {
+                            String::from_utf8_lossy(bytes)
+                                .trim_end_matches('\0')
+                                .to_string()
+                        })
+                        .filter(|name| !name.is_empty())
+                } else {
+                    None
+                };
+                Some((start, library))
+            })?;
+
+    let mut entries: Vec<PyArchiveEntry> = Vec::new();
+    let mut cursor = package_start + toc_offset;
+    let toc_end = cursor + toc_length;
+    while toc_end - cursor >= TOC_ENTRY_HEADER_SIZE && entries.len() < MAX_TOC_ENTRIES {
+        let entry_size = reader.u32_be(cursor).ok()? as u64;
+        if entry_size < TOC_ENTRY_HEADER_SIZE || entry_size > toc_end - cursor {
+            break;
+        }
+        let entry_offset = reader.u32_be(cursor + 4).ok()?;
+        let compressed_size = reader.u32_be(cursor + 8).ok()?;
+        let size = reader.u32_be(cursor + 12).ok()?;
+        let [compressed, kind] = reader.array::<2>(cursor + 16).ok()?;
+        let name = reader
+            .bytes(
+                cursor + TOC_ENTRY_HEADER_SIZE,
+                entry_size - TOC_ENTRY_HEADER_SIZE,
+            )
+            .ok()?;
+        entries.push(PyArchiveEntry {
+            name: String::from_utf8_lossy(name)
+                .trim_end_matches('\0')
+                .to_string(),
+            kind: toc_kind(kind).to_string(),
+            offset: package_start + entry_offset as u64,
+            compressed_size,
+            size,
+            compressed: compressed != 0,
+            in_zip: false,
+        });
+        cursor += entry_size;
+    }
+
+    Some(PythonPackage {
+        packer: "PyInstaller".to_string(),
+        python_version: Some(python_version(version)),
+        python_library,
+        entries,
+    })
+}
+
+// 附加数据中的zip(py2exe的library.zip) 第一项为整个zip
+fn list_overlay_zip(data: &[u8], overlay_offset: u64) -> Vec<PyArchiveEntry> {
+    let reader = BoundedReader::new(data);
+    let overlay = reader.bytes_truncated(overlay_offset, u64::MAX);
+    let overlay_offset = reader.len() - overlay.len() as u64;
+    let mut entries: Vec<PyArchiveEntry> = Vec::new();
+    let Some(eocd) = find_all(overlay, ZIP_EOCD_MAGIC, usize::MAX)
+        .last()
+        .map(|offset| overlay_offset + *offset as u64)
+    else {
+        return entries;
+    };
+    let (Ok(count), Ok(directory_size), Ok(directory_offset)) = (
+        reader.u16(eocd + 10),
+        reader.u32(eocd + 12),
+        reader.u32(eocd + 16),
+    ) else {
+        return entries;
+    };
+    // 附加到exe后的zip偏移相对于zip自身的起点
+    let Some(zip_start) = eocd.checked_sub(directory_size as u64 + directory_offset as u64) else {
+        return entries;
+    };
+
+    let zip_size = (eocd + 22).min(reader.len()) - zip_start;
+    entries.push(PyArchiveEntry {
+        name: "library.zip".to_string(),
+        kind: "pyz".to_string(),
+        offset: zip_start,
+        compressed_size: zip_size as u32,
+        size: zip_size as u32,
+        compressed: false,
+        in_zip: false,
+    });
+
+    let mut cursor = zip_start + directory_offset as u64;
+    for _ in 0..count {
+        if reader.bytes(cursor, 4) != Ok(ZIP_CENTRAL_MAGIC) {
+            break;
+        }
+        let (
+            Ok(method),
+            Ok(compressed_size),
+            Ok(size),
+            Ok(name_length),
+            Ok(extra_length),
+            Ok(comment_length),
+            Ok(local_offset),
+        ) = (
+            reader.u16(cursor + 10),
+            reader.u32(cursor + 20),
+            reader.u32(cursor + 24),
+            reader.u16(cursor + 28),
+            reader.u16(cursor + 30),
+            reader.u16(cursor + 32),
+            reader.u32(cursor + 42),
+        )
+        else {
+            break;
+        };
+        let name_start = cursor + 46;
+        let Ok(name) = reader.bytes(name_start, name_length as u64) else {
+            break;
+        };
+        let name = String::from_utf8_lossy(name).to_string();
+        let kind = if name.ends_with(".pyc") || name.ends_with(".pyo") {
+            "module"
+        } else if name.ends_with(".pyd") || name.ends_with(".dll") {
+            "binary"
+        } else {
+            "data"
+        };
+        entries.push(PyArchiveEntry {
+            name,
+            kind: kind.to_string(),
+            offset: zip_start + local_offset as u64,
+            compressed_size,
+            size,
+            compressed: method != 0,
+            in_zip: true,
+        });
+        cursor = name_start + name_length as u64 + extra_length as u64 + comment_length as u64;
+    }
+    entries
+}
+
+fn parse_py2exe(
+    data: &[u8],
+    resources: &[ResourceEntry],
+    overlay_offset: u64,
+) -> Option<PythonPackage> {
+    let script = resources
+        .iter()
+        .find(|r| r.type_name.eq_ignore_ascii_case("PYTHONSCRIPT"))?;
+    let reader = BoundedReader::new(data);
+    let script_data = BoundedReader::new(resource_data(data, script));
+    if script_data.u32(0) != Ok(PY2EXE_SCRIPT_MAGIC) {
+        return None;
+    }
+    // python库名保存在PYTHONDLL资源中 或者以字符串形式出现
+    let python_library = ["python3", "python2"].iter().find_map(|prefix| {
+        find_all(data, prefix.as_bytes(), 16)
+            .into_iter()
+            .find_map(|offset| {
+                let name: String = reader
+                    .bytes_truncated(offset as u64, 32)
+                    .iter()
+                    .take_while(|b| b.is_ascii_alphanumeric() || **b == b'.')
+                    .map(|&b| b as char)
+                    .collect();
+                name.to_ascii_lowercase().ends_with(".dll").then_some(name)
+            })
+    });
+    let python_version = python_library.as_ref().and_then(|name| {
+        let digits: String = name.chars().filter(|c| c.is_ascii_digit()).collect();
+        let value: u32 = digits.parse().ok()?;
+        Some(python_version(value))
+    });
+
+    let mut entries = vec![PyArchiveEntry {
+        name: "PYTHONSCRIPT".to_string(),
+        kind: "script".to_string(),
+        offset: script.offset.unwrap_or_default(),
+        compressed_size: script.size,
+        size: script.size,
+        compressed: false,
+        in_zip: false,
+    }];
+    entries.extend(list_overlay_zip(data, overlay_offset));
+    Some(PythonPackage {
+        packer: "py2exe".to_string(),
+        python_version,
+        python_library,
+        entries,
+    })
+}
+
+pub(crate) fn detect_python_package(
+    data: &[u8],
+    resources: &[ResourceEntry],
+    overlay_offset: u64,
+) -> Option<PythonPackage> {
+    parse_pyinstaller(data, overlay_offset)
+        .or_else(|| parse_py2exe(data, resources, overlay_offset))
+}
+
+// 归档中的路径可能包含..或绝对路径 只保留普通路径部分
+fn sanitize_entry_path(name: &str) -> PathBuf {
+    Path::new(&name.replace('\\', "/"))
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part),
+            _ => None,
+        })
+        .collect()
+}
+
+// 解出全部条目 压缩的条目先解压 返回相对路径和内容 由调用方写出
+// 条目中的大小由文件给出 不可信 解压总量受limits.max_decompressed_size限制
+pub fn extract_entries(
+    data: &[u8],
+    package: &PythonPackage,
+    limits: &Limits,
+) -> Result<Vec<(PathBuf, Vec<u8>)>, String> {
+    let reader = BoundedReader::new(data);
+    let mut remaining = limits.max_decompressed_size as u64;
+    let mut files: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    for entry in package.entries.iter().filter(|e| !e.in_zip) {
+        let relative = sanitize_entry_path(&entry.name);
+        if relative.as_os_str().is_empty() {
+            continue;
+        }
+        let raw = reader.bytes_truncated(entry.offset, entry.compressed_size as u64);
+        let content = if entry.compressed {
+            let mut decompressed: Vec<u8> = Vec::new();
+            // 多读一个字节 用来判断是否超出上限
+            ZlibDecoder::new(raw)
+                .take(remaining + 1)
+                .read_to_end(&mut decompressed)
+                .map_err(|e| format!("解压失败 {}: {}", entry.name, e))?;
+            if decompressed.len() as u64 > remaining {
+                return Err(format!(
+                    "解压后的数据超过{}字节上限: {}",
+                    limits.max_decompressed_size, entry.name
+                ));
+            }
+            remaining -= decompressed.len() as u64;
+            decompressed
+        } else {
+            raw.to_vec()
+        };
+        files.push((relative, content));
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::ZlibEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    fn package(data: &[u8], size: u32) -> PythonPackage {
+        PythonPackage {
+            packer: "PyInstaller".to_string(),
+            python_version: None,
+            python_library: None,
+            entries: vec![PyArchiveEntry {
+                name: "../bomb.pyc".to_string(),
+                kind: "module".to_string(),
+                offset: 0,
+                compressed_size: data.len() as u32,
+                size,
+                compressed: true,
+                in_zip: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn caps_decompressed_size() {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(&[0u8; 4096]).unwrap();
+        let data = encoder.finish().unwrap();
+        // 表项中的大小不可信 不按它分配
+        let package = package(&data, u32::MAX);
+
+        let limits = Limits {
+            max_decompressed_size: 4096,
+            ..Limits::default()
+        };
+        let files = extract_entries(&data, &package, &limits).unwrap();
+        assert_eq!(files[0].0, PathBuf::from("bomb.pyc"));
+        assert_eq!(files[0].1.len(), 4096);
+
+        let limits = Limits {
+            max_decompressed_size: 4095,
+            ..Limits::default()
+        };
+        assert!(extract_entries(&data, &package, &limits).is_err());
+    }
+
+    #[test]
+    fn ignores_toc_offsets_past_the_end() {
+        let mut data = vec![0u8; 64];
+        data.extend_from_slice(PYINSTALLER_MAGIC);
+        // 包长度、TOC偏移和长度都指向文件之外
+        for value in [u32::MAX, u32::MAX - 8, 0x100, 312] {
+            data.extend_from_slice(&value.to_be_bytes());
+        }
+        assert!(parse_pyinstaller(&data, 0).is_none());
+    }
+}
@@ -0,0 +1,278 @@
+// 有边界检查的读取层 各解析器都经由这里读取文件内容
+// 偏移与长度使用checked运算 越界或溢出时返回带位置的错误 构造的文件不会导致panic
+// PE中的多字节值都是小端序 这里显式按小端解码 结果与主机字节序无关
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadError {
+    // 读取范围超出数据末尾
+    OutOfBounds { offset: u64, length: u64, size: u64 },
+    // 偏移加长度超出地址范围
+    Overflow { offset: u64, length: u64 },
+    // 以0结尾的字符串在上限内没有结尾
+    Unterminated { offset: u64, limit: u64 },
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReadError::OutOfBounds {
+                offset,
+                length,
+                size,
+            } => write!(
+                f,
+                "读取越界: 偏移0x{:X}处的{}字节超出文件大小0x{:X}",
+                offset, length, size
+            ),
+            ReadError::Overflow { offset, length } => {
+                write!(f, "偏移溢出: 0x{:X} + 0x{:X}", offset, length)
+            }
+            ReadError::Unterminated { offset, limit } => {
+                write!(f, "偏移0x{:X}处的字符串在{}字节内没有结尾", offset, limit)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ReadError {}
+
+// 命令的错误类型为String
+impl From<ReadError> for String {
+    fn from(error: ReadError) -> Self {
+        error.to_string()
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BoundedReader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> BoundedReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        BoundedReader { data }
+    }
+
+    pub fn len(&self) -> u64 {
+        self.data.len() as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn bytes(&self, offset: u64, length: u64) -> Result<&'a [u8], ReadError> {
+        let end = offset
+            .checked_add(length)
+            .ok_or(ReadError::Overflow { offset, length })?;
+        if end > self.len() {
+            return Err(ReadError::OutOfBounds {
+                offset,
+                length,
+                size: self.len(),
+            });
+        }
+        Ok(&self.data[offset as usize..end as usize])
+    }
+
+    // 同上 超出数据末尾的部分截断 起点在末尾之后时为空
+    pub fn bytes_truncated(&self, offset: u64, length: u64) -> &'a [u8] {
+        let start = offset.min(self.len());
+        let length = length.min(self.len() - start);
+        &self.data[start as usize..(start + length) as usize]
+    }
+
+    pub fn array<const N: usize>(&self, offset: u64) -> Result<[u8; N], ReadError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.bytes(offset, N as u64)?);
+        Ok(array)
+    }
+
+    pub fn u16(&self, offset: u64) -> Result<u16, ReadError> {
+        self.array(offset).map(u16::from_le_bytes)
+    }
+
+    pub fn i16(&self, offset: u64) -> Result<i16, ReadError> {
+        self.array(offset).map(i16::from_le_bytes)
+    }
+
+    pub fn u32(&self, offset: u64) -> Result<u32, ReadError> {
+        self.array(offset).map(u32::from_le_bytes)
+    }
+
+    pub fn i32(&self, offset: u64) -> Result<i32, ReadError> {
+        self.array(offset).map(i32::from_le_bytes)
+    }
+
+    pub fn u64(&self, offset: u64) -> Result<u64, ReadError> {
+        self.array(offset).map(u64::from_le_bytes)
+    }
+
+    // 1到8字节的小端无符号数 用于宽度由结构定义决定的字段
+    pub fn uint(&self, offset: u64, width: u64) -> Result<u64, ReadError> {
+        let bytes = self.bytes(offset, width.min(8))?;
+        let mut buffer = [0u8; 8];
+        buffer[..bytes.len()].copy_from_slice(bytes);
+        Ok(u64::from_le_bytes(buffer))
+    }
+
+    pub fn u32_be(&self, offset: u64) -> Result<u32, ReadError> {
+        self.array(offset).map(u32::from_be_bytes)
+    }
+
+    // 64位文件中的指针为8字节 32位文件为4字节
+    pub fn pointer(&self, offset: u64, is_x64: bool) -> Result<u64, ReadError> {
+        if is_x64 {
+            self.u64(offset)
+        } else {
+            self.u32(offset).map(u64::from)
+        }
+    }
+
+    // 从offset到数据末尾 至少包含一个字节
+    fn tail(&self, offset: u64) -> Result<&'a [u8], ReadError> {
+        self.bytes(offset, 1)?;
+        self.bytes(offset, self.len() - offset)
+    }
+
+    // 以0结尾的字节串 不含结尾的0 limit字节内没有结尾时返回错误
+    pub fn c_string(&self, offset: u64, limit: usize) -> Result<&'a [u8], ReadError> {
+        let bytes = self.tail(offset)?;
+        match bytes.iter().take(limit).position(|&b| b == 0) {
+            Some(length) => Ok(&bytes[..length]),
+            None => Err(ReadError::Unterminated {
+                offset,
+                limit: limit as u64,
+            }),
+        }
+    }
+
+    // 同上 没有结尾时截断在limit字节或数据末尾 返回值的第二项表示是否截断
+    pub fn c_string_truncated(
+        &self,
+        offset: u64,
+        limit: usize,
+    ) -> Result<(&'a [u8], bool), ReadError> {
+        let bytes = self.tail(offset)?;
+        match bytes.iter().take(limit).position(|&b| b == 0) {
+            Some(length) => Ok((&bytes[..length], false)),
+            None => Ok((&bytes[..bytes.len().min(limit)], true)),
+        }
+    }
+}
+
+// 只关心能否读到的解析器使用的简写
+pub fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    BoundedReader::new(data).u16(offset as u64).ok()
+}
+
+pub fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    BoundedReader::new(data).u32(offset as u64).ok()
+}
+
+pub(crate) fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    BoundedReader::new(data).i32(offset as u64).ok()
+}
+
+pub fn read_u64(data: &[u8], offset: usize) -> Option<u64> {
+    BoundedReader::new(data).u64(offset as u64).ok()
+}
+
+// UTF-16LE的编码单元 末尾不足2字节的部分忽略
+pub fn utf16_units(bytes: &[u8]) -> impl Iterator<Item = u16> + '_ {
+    bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+}
+
+pub fn decode_utf16(bytes: &[u8]) -> String {
+    String::from_utf16_lossy(&utf16_units(bytes).collect::<Vec<u16>>())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_little_endian_values() {
+        let data = [0x4D, 0x5A, 0x90, 0x00, 0x03, 0x00, 0x00, 0x80];
+        let reader = BoundedReader::new(&data);
+        assert_eq!(reader.u16(0), Ok(0x5A4D));
+        assert_eq!(reader.u32(4), Ok(0x8000_0003));
+        assert_eq!(reader.i32(4), Ok(0x8000_0003u32 as i32));
+        assert_eq!(reader.u64(0), Ok(0x8000_0003_0090_5A4D));
+        assert_eq!(reader.uint(1, 3), Ok(0x90_5A));
+        assert_eq!(reader.pointer(4, false), Ok(0x8000_0003));
+        assert_eq!(reader.u32_be(0), Ok(0x4D5A_9000));
+    }
+
+    #[test]
+    fn rejects_reads_past_the_end() {
+        let data = [0u8; 4];
+        let reader = BoundedReader::new(&data);
+        assert_eq!(reader.bytes(4, 0), Ok(&[][..]));
+        assert_eq!(
+            reader.u32(1),
+            Err(ReadError::OutOfBounds {
+                offset: 1,
+                length: 4,
+                size: 4
+            })
+        );
+        assert!(reader.pointer(0, true).is_err());
+        assert_eq!(reader.bytes_truncated(2, 8).len(), 2);
+        assert_eq!(reader.bytes_truncated(u64::MAX, u64::MAX), &[][..]);
+        assert_eq!(read_u16(&data, 3), None);
+    }
+
+    #[test]
+    fn rejects_overflowing_offsets() {
+        let reader = BoundedReader::new(&[0u8; 4]);
+        assert_eq!(
+            reader.bytes(u64::MAX, 2),
+            Err(ReadError::Overflow {
+                offset: u64::MAX,
+                length: 2
+            })
+        );
+        assert_eq!(read_u32(&[0u8; 4], usize::MAX), None);
+    }
+
+    #[test]
+    fn c_string_needs_a_terminator_within_the_limit() {
+        let data = b"kernel32.dll\0user32";
+        let reader = BoundedReader::new(data);
+        assert_eq!(reader.c_string(0, 64), Ok(&b"kernel32.dll"[..]));
+        assert_eq!(
+            reader.c_string(0, 4),
+            Err(ReadError::Unterminated {
+                offset: 0,
+                limit: 4
+            })
+        );
+        // 数据末尾之前没有0
+        assert!(reader.c_string(13, 64).is_err());
+        assert!(reader.c_string(data.len() as u64, 64).is_err());
+    }
+
+    #[test]
+    fn truncated_c_string_reports_the_cut() {
+        let data = b"GetProcAddress\0LoadLibrary";
+        let reader = BoundedReader::new(data);
+        assert_eq!(
+            reader.c_string_truncated(0, 64),
+            Ok((&b"GetProcAddress"[..], false))
+        );
+        assert_eq!(reader.c_string_truncated(0, 3), Ok((&b"Get"[..], true)));
+        assert_eq!(
+            reader.c_string_truncated(15, 64),
+            Ok((&b"LoadLibrary"[..], true))
+        );
+    }
+
+    #[test]
+    fn decodes_utf16_and_ignores_odd_trailing_byte() {
+        assert_eq!(decode_utf16(&[0x50, 0x00, 0x45, 0x00, 0x41]), "PE");
+    }
+}
@@ -0,0 +1,388 @@
+// 资源目录遍历 三层结构: 类型 -> 名称 -> 语言
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use std::collections::HashSet;
+
+use crate::hashes::to_hex;
+use crate::limits::{LimitHit, Limits};
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, DataDirectory, Section};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ResourceEntry {
+    // 标准类型显示为RT_*名称 自定义类型为字符串名称
+    pub type_name: String,
+    pub type_id: Option<u16>,
+    pub name: String,
+    pub language: u16,
+    pub rva: u32,
+    pub size: u32,
+    // 数据在文件中的偏移 RVA无法转换时为None
+    pub offset: Option<u64>,
+    // 资源数据的SHA-256 超出文件的部分被截断
+    pub sha256: String,
+}
+
+// 两个文件中同一位置(类型/名称/语言)或同一内容的资源
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResourceMatch {
+    pub type_name: String,
+    pub name: String,
+    pub language: u16,
+    // identical / different / moved(内容相同但位置不同) / only_first / only_second
+    pub status: String,
+    pub sha256: String,
+    pub size: u32,
+    // moved时另一个文件中的名称和语言
+    pub other_name: Option<String>,
+    pub other_language: Option<u16>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResourceComparison {
+    pub first_count: u32,
+    pub second_count: u32,
+    // 内容相同的资源数(identical + moved)
+    pub shared: u32,
+    pub entries: Vec<ResourceMatch>,
+}
+
+pub(crate) const RT_RCDATA: u16 = 10;
+pub(crate) const RT_VERSION: u16 = 16;
+pub const RT_MANIFEST: u16 = 24;
+
+fn resource_type_name(id: u16) -> String {
+    match id {
+        1 => "RT_CURSOR".to_string(),
+        2 => "RT_BITMAP".to_string(),
+        3 => "RT_ICON".to_string(),
+        4 => "RT_MENU".to_string(),
+        5 => "RT_DIALOG".to_string(),
+        6 => "RT_STRING".to_string(),
+        7 => "RT_FONTDIR".to_string(),
+        8 => "RT_FONT".to_string(),
+        9 => "RT_ACCELERATOR".to_string(),
+        10 => "RT_RCDATA".to_string(),
+        11 => "RT_MESSAGETABLE".to_string(),
+        12 => "RT_GROUP_CURSOR".to_string(),
+        14 => "RT_GROUP_ICON".to_string(),
+        16 => "RT_VERSION".to_string(),
+        17 => "RT_DLGINCLUDE".to_string(),
+        19 => "RT_PLUGPLAY".to_string(),
+        20 => "RT_VXD".to_string(),
+        21 => "RT_ANICURSOR".to_string(),
+        22 => "RT_ANIICON".to_string(),
+        23 => "RT_HTML".to_string(),
+        24 => "RT_MANIFEST".to_string(),
+        _ => format!("#{}", id),
+    }
+}
+
+// 目录项: (名称或ID, 偏移, 是否子目录) 最多读取limit项
+fn directory_entries(
+    reader: &BoundedReader,
+    base: u64,
+    offset: u32,
+    limit: u32,
+) -> Vec<(u32, u32, bool)> {
+    let directory = base + offset as u64;
+    let named = reader.u16(directory + 12).unwrap_or(0) as u32;
+    let ids = reader.u16(directory + 14).unwrap_or(0) as u32;
+    (0..(named + ids).min(limit) as u64)
+        .map_while(|i| {
+            let entry = directory + 16 + i * 8;
+            let name = reader.u32(entry).ok()?;
+            let target = reader.u32(entry + 4).ok()?;
+            Some((name, target & 0x7FFF_FFFF, target & 0x8000_0000 != 0))
+        })
+        .collect()
+}
+
+// 名称: 高位置1时为相对资源起始的IMAGE_RESOURCE_DIR_STRING_U 否则为ID
+fn entry_name(reader: &BoundedReader, base: u64, name: u32) -> (String, Option<u16>) {
+    if name & 0x8000_0000 == 0 {
+        return (format!("#{}", name & 0xFFFF), Some(name as u16));
+    }
+    let offset = base + (name & 0x7FFF_FFFF) as u64;
+    let length = reader.u16(offset).unwrap_or(0) as u64;
+    // 名称超出文件时保留能读到的部分
+    let units: Vec<u16> = (0..length)
+        .map_while(|i| reader.u16(offset + 2 + i * 2).ok())
+        .collect();
+    (String::from_utf16_lossy(&units), None)
+}
+
+// 遍历状态 目录项计数覆盖所有层 已访问的目录用于发现自引用形成的环
+struct Walker<'a> {
+    reader: BoundedReader<'a>,
+    sections: &'a [Section],
+    base: u64,
+    limits: &'a Limits,
+    visited: HashSet<u32>,
+    entries: u32,
+    resources: Vec<ResourceEntry>,
+    depth_exceeded: bool,
+    entries_exceeded: bool,
+    cycle: bool,
+}
+
+impl Walker<'_> {
+    // depth: 0为类型层 1为名称层 2及以下为语言层 更深的层按最后一层的ID作为语言
+    fn walk(&mut self, offset: u32, depth: u32, type_label: &(String, Option<u16>), name: &str) {
+        if !self.visited.insert(offset) {
+            self.cycle = true;
+            return;
+        }
+        let remaining = self
+            .limits
+            .max_resource_entries
+            .saturating_sub(self.entries);
+        for (entry_id, target, is_directory) in
+            directory_entries(&self.reader, self.base, offset, remaining.saturating_add(1))
+        {
+            if self.entries >= self.limits.max_resource_entries {
+                self.entries_exceeded = true;
+                return;
+            }
+            self.entries += 1;
+            if is_directory {
+                if depth + 1 >= self.limits.max_resource_depth {
+                    self.depth_exceeded = true;
+                    continue;
+                }
+                match depth {
+                    0 => {
+                        let (label, type_id) = entry_name(&self.reader, self.base, entry_id);
+                        let label = match type_id {
+                            Some(id) => resource_type_name(id),
+                            None => label,
+                        };
+                        self.walk(target, 1, &(label, type_id), "");
+                    }
+                    1 => {
+                        let (label, _) = entry_name(&self.reader, self.base, entry_id);
+                        self.walk(target, 2, type_label, &label);
+                    }
+                    _ => self.walk(target, depth + 1, type_label, name),
+                }
+            } else if depth >= 2 {
+                // IMAGE_RESOURCE_DATA_ENTRY: OffsetToData(RVA), Size, CodePage, Reserved
+                let entry = self.base + target as u64;
+                let (Ok(rva), Ok(size)) = (self.reader.u32(entry), self.reader.u32(entry + 4))
+                else {
+                    continue;
+                };
+                let mut resource = ResourceEntry {
+                    type_name: type_label.0.clone(),
+                    type_id: type_label.1,
+                    name: name.to_string(),
+                    language: entry_id as u16,
+                    rva,
+                    size,
+                    offset: rva_to_offset(self.sections, rva).map(|offset| offset as u64),
+                    sha256: String::new(),
+                };
+                resource.sha256 = to_hex(&Sha256::digest(resource_bytes(&self.reader, &resource)));
+                self.resources.push(resource);
+            }
+        }
+    }
+}
+
+pub(crate) fn list_resources(
+    data: &[u8],
+    sections: &[Section],
+    directory: DataDirectory,
+    limits: &Limits,
+    hits: &mut Vec<LimitHit>,
+) -> Vec<ResourceEntry> {
+    if directory.rva == 0 || directory.size == 0 {
+        return Vec::new();
+    }
+    let Some(base) = rva_to_offset(sections, directory.rva) else {
+        return Vec::new();
+    };
+    let mut walker = Walker {
+        reader: BoundedReader::new(data),
+        sections,
+        base: base as u64,
+        limits,
+        visited: HashSet::new(),
+        entries: 0,
+        resources: Vec::new(),
+        depth_exceeded: false,
+        entries_exceeded: false,
+        cycle: false,
+    };
+    walker.walk(0, 0, &(String::new(), None), "");
+
+    if walker.depth_exceeded {
+        hits.push(LimitHit::new(
+            "max_resource_depth",
+            format!(
+                "资源目录超过{}层 更深的目录未解析",
+                limits.max_resource_depth
+            ),
+        ));
+    }
+    if walker.entries_exceeded {
+        hits.push(LimitHit::new(
+            "max_resource_entries",
+            format!("资源目录项超过{}个 其余未解析", limits.max_resource_entries),
+        ));
+    }
+    if walker.cycle {
+        hits.push(LimitHit::new(
+            "resource_cycle",
+            "资源目录中有指向上层或已访问目录的项(自引用) 已跳过".to_string(),
+        ));
+    }
+    walker.resources
+}
+
+// 资源数据 超出文件范围的部分被截断
+pub fn resource_data<'a>(data: &'a [u8], resource: &ResourceEntry) -> &'a [u8] {
+    resource_bytes(&BoundedReader::new(data), resource)
+}
+
+fn resource_bytes<'a>(reader: &BoundedReader<'a>, resource: &ResourceEntry) -> &'a [u8] {
+    let Some(offset) = resource.offset else {
+        return &[];
+    };
+    reader.bytes_truncated(offset, resource.size as u64)
+}
+
+fn resource_match(resource: &ResourceEntry, status: &str) -> ResourceMatch {
+    ResourceMatch {
+        type_name: resource.type_name.clone(),
+        name: resource.name.clone(),
+        language: resource.language,
+        status: status.to_string(),
+        sha256: resource.sha256.clone(),
+        size: resource.size,
+        other_name: None,
+        other_language: None,
+    }
+}
+
+// 先按(类型, 名称, 语言)配对 剩余的再按类型和内容哈希配对 以发现改名或换语言后复用的资源
+pub fn compare_resources(first: &[ResourceEntry], second: &[ResourceEntry]) -> ResourceComparison {
+    let same_position = |a: &ResourceEntry, b: &ResourceEntry| {
+        a.type_name == b.type_name && a.name == b.name && a.language == b.language
+    };
+    let mut matched = vec![false; second.len()];
+    let mut entries: Vec<ResourceMatch> = Vec::new();
+    let mut unmatched: Vec<&ResourceEntry> = Vec::new();
+    for resource in first {
+        match second
+            .iter()
+            .position(|other| same_position(resource, other))
+        {
+            Some(index) => {
+                matched[index] = true;
+                let status = if second[index].sha256 == resource.sha256 {
+                    "identical"
+                } else {
+                    "different"
+                };
+                entries.push(resource_match(resource, status));
+            }
+            None => unmatched.push(resource),
+        }
+    }
+    for resource in unmatched {
+        let moved = second.iter().enumerate().position(|(index, other)| {
+            !matched[index]
+                && other.type_name == resource.type_name
+                && other.sha256 == resource.sha256
+        });
+        match moved {
+            Some(index) => {
+                matched[index] = true;
+                let mut entry = resource_match(resource, "moved");
+                entry.other_name = Some(second[index].name.clone());
+                entry.other_language = Some(second[index].language);
+                entries.push(entry);
+            }
+            None => entries.push(resource_match(resource, "only_first")),
+        }
+    }
+    for (index, resource) in second.iter().enumerate() {
+        if !matched[index] {
+            entries.push(resource_match(resource, "only_second"));
+        }
+    }
+    ResourceComparison {
+        first_count: first.len() as u32,
+        second_count: second.len() as u32,
+        shared: entries
+            .iter()
+            .filter(|entry| entry.status == "identical" || entry.status == "moved")
+            .count() as u32,
+        entries,
+    }
+}
+
+// .res中的类型或名称: 序号写为0xFFFF+ID 字符串写为以0结尾的UTF-16
+fn res_name(buffer: &mut Vec<u8>, label: &str, id: Option<u16>) {
+    let id = id.or_else(|| label.strip_prefix('#').and_then(|id| id.parse().ok()));
+    match id {
+        Some(id) => {
+            buffer.extend_from_slice(&0xFFFFu16.to_le_bytes());
+            buffer.extend_from_slice(&id.to_le_bytes());
+        }
+        None => {
+            for unit in label.encode_utf16().chain(Some(0)) {
+                buffer.extend_from_slice(&unit.to_le_bytes());
+            }
+        }
+    }
+}
+
+fn align_dword(buffer: &mut Vec<u8>) {
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+}
+
+// 与RC编译器输出相同的.res格式: 开头一个空条目 之后每个资源一个RESOURCEHEADER加数据 均按4字节对齐
+pub fn build_res_file(data: &[u8], resources: &[ResourceEntry]) -> Vec<u8> {
+    // MOVEABLE | PURE | DISCARDABLE
+    const MEMORY_FLAGS: u16 = 0x1030;
+    let mut output: Vec<u8> = Vec::new();
+    let mut entries: Vec<(Vec<u8>, u16, u16, &[u8])> = vec![(
+        [0xFFFFu16, 0, 0xFFFF, 0]
+            .iter()
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect(),
+        0,
+        0,
+        &[],
+    )];
+    for resource in resources {
+        let mut names: Vec<u8> = Vec::new();
+        res_name(&mut names, &resource.type_name, resource.type_id);
+        res_name(&mut names, &resource.name, None);
+        entries.push((
+            names,
+            MEMORY_FLAGS,
+            resource.language,
+            resource_data(data, resource),
+        ));
+    }
+    for (mut names, memory_flags, language, content) in entries {
+        align_dword(&mut names);
+        // DataSize + HeaderSize + 类型和名称 + DataVersion + MemoryFlags + LanguageId + Version + Characteristics
+        let header_size = 8 + names.len() + 16;
+        output.extend_from_slice(&(content.len() as u32).to_le_bytes());
+        output.extend_from_slice(&(header_size as u32).to_le_bytes());
+        output.extend_from_slice(&names);
+        output.extend_from_slice(&0u32.to_le_bytes());
+        output.extend_from_slice(&memory_flags.to_le_bytes());
+        output.extend_from_slice(&language.to_le_bytes());
+        output.extend_from_slice(&0u32.to_le_bytes());
+        output.extend_from_slice(&0u32.to_le_bytes());
+        output.extend_from_slice(content);
+        align_dword(&mut output);
+    }
+    output
+}
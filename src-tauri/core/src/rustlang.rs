@@ -0,0 +1,174 @@
+// Rust程序识别 从panic位置字符串中提取rustc版本、依赖crate和源码路径
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+
+use crate::capabilities::find_all;
+use crate::reader::BoundedReader;
+use crate::ExportFunction;
+
+// 标准库和运行时中常见的字符串
+const RUST_MARKERS: &[&str] = &[
+    "called `Option::unwrap()` on a `None` value",
+    "called `Result::unwrap()` on an `Err` value",
+    "RUST_BACKTRACE",
+    "/rustc/",
+    "\\rustc\\",
+    "panicked at",
+    "library/std/src/",
+    "library\\std\\src\\",
+];
+// 源码路径的数量上限
+const MAX_SOURCE_PATHS: usize = 256;
+// 单个路径的最大长度
+const MAX_PATH_LENGTH: u64 = 260;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct RustCrate {
+    pub name: String,
+    pub version: Option<String>,
+    // registry / git
+    pub source: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RustInfo {
+    pub markers: Vec<String>,
+    // 标准库路径/rustc/<commit>/中的提交哈希
+    pub rustc_commit: Option<String>,
+    pub crates: Vec<RustCrate>,
+    // 不属于registry和标准库的源码路径 通常是项目自身的代码
+    pub source_paths: Vec<String>,
+    // 使用Rust符号修饰的导出函数数量
+    pub mangled_exports: usize,
+}
+
+fn is_path_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || b"/\\._-:@+~".contains(&byte)
+}
+
+// 以.rs结尾的路径 panic位置字符串不以0结尾且紧密排列 需要从后向前找路径起点
+// 遇到前一个路径的.rs结尾时停止
+fn find_source_paths(data: &[u8]) -> BTreeSet<String> {
+    let reader = BoundedReader::new(data);
+    let mut paths: BTreeSet<String> = BTreeSet::new();
+    for offset in find_all(data, b".rs", usize::MAX) {
+        let offset = offset as u64;
+        let end = offset + 3;
+        // 排除.rsrc等 后面紧跟其他字符串的情况仍需保留
+        if reader.bytes(end, 2) == Ok(&b"rc"[..]) {
+            continue;
+        }
+        let mut start = offset;
+        while start > 0
+            && offset - start < MAX_PATH_LENGTH
+            && reader
+                .array::<1>(start - 1)
+                .is_ok_and(|[byte]| is_path_byte(byte))
+            && !(start >= 3 && reader.bytes(start - 3, 3) == Ok(&b".rs"[..]))
+        {
+            start -= 1;
+        }
+        let path = String::from_utf8_lossy(reader.bytes_truncated(start, end - start)).to_string();
+        if path.contains('/') || path.contains('\\') {
+            paths.insert(path);
+        }
+    }
+    paths
+}
+
+// "serde-1.0.188" -> ("serde", "1.0.188")
+fn split_crate_version(directory: &str) -> (String, Option<String>) {
+    let split = directory
+        .match_indices('-')
+        .map(|(i, _)| i)
+        .find(|&i| directory[i + 1..].starts_with(|c: char| c.is_ascii_digit()));
+    match split {
+        Some(i) => (
+            directory[..i].to_string(),
+            Some(directory[i + 1..].to_string()),
+        ),
+        None => (directory.to_string(), None),
+    }
+}
+
+// 路径中紧跟在marker之后的目录名
+fn segment_after<'a>(segments: &[&'a str], marker: &[&str]) -> Option<&'a str> {
+    segments
+        .windows(marker.len() + 1)
+        .find(|window| window[..marker.len()] == *marker)
+        .map(|window| window[marker.len()])
+}
+
+fn is_mangled(name: &str) -> bool {
+    // legacy: _ZN...17h<16位哈希>E  v0: _R
+    (name.starts_with("_ZN") && name.ends_with('E') && name.contains("17h"))
+        || name.starts_with("_R")
+}
+
+pub(crate) fn parse_rust_info(data: &[u8], export_table: &[ExportFunction]) -> Option<RustInfo> {
+    let markers: Vec<String> = RUST_MARKERS
+        .iter()
+        .filter(|marker| !find_all(data, marker.as_bytes(), 1).is_empty())
+        .map(|marker| marker.to_string())
+        .collect();
+    let mangled_exports = export_table.iter().filter(|f| is_mangled(&f.name)).count();
+    // 至少命中两个特征 或者存在Rust修饰的导出
+    if markers.len() < 2 && mangled_exports == 0 {
+        return None;
+    }
+
+    let mut rustc_commit: Option<String> = None;
+    let mut crates: BTreeSet<RustCrate> = BTreeSet::new();
+    let mut source_paths: Vec<String> = Vec::new();
+    for path in find_source_paths(data) {
+        let normalized = path.replace('\\', "/");
+        let segments: Vec<&str> = normalized.split('/').collect();
+
+        if let Some(commit) = segment_after(&segments, &["rustc"]) {
+            if commit.len() == 40 && commit.bytes().all(|b| b.is_ascii_hexdigit()) {
+                rustc_commit.get_or_insert_with(|| commit.to_string());
+                continue;
+            }
+        }
+        // ~/.cargo/registry/src/<index>/<crate>-<version>/
+        if let Some(index) = segment_after(&segments, &["registry", "src"]) {
+            if let Some(directory) = segment_after(&segments, &["registry", "src", index]) {
+                let (name, version) = split_crate_version(directory);
+                crates.insert(RustCrate {
+                    name,
+                    version,
+                    source: "registry".to_string(),
+                });
+                continue;
+            }
+        }
+        // ~/.cargo/git/checkouts/<repo>-<hash>/<rev>/
+        if let Some(directory) = segment_after(&segments, &["git", "checkouts"]) {
+            let name = directory
+                .rsplit_once('-')
+                .map(|(name, _)| name)
+                .unwrap_or(directory);
+            crates.insert(RustCrate {
+                name: name.to_string(),
+                version: None,
+                source: "git".to_string(),
+            });
+            continue;
+        }
+        if normalized.starts_with("library/") || normalized.contains("/library/") {
+            continue;
+        }
+        if source_paths.len() < MAX_SOURCE_PATHS {
+            source_paths.push(path);
+        }
+    }
+
+    Some(RustInfo {
+        markers,
+        rustc_commit,
+        crates: crates.into_iter().collect(),
+        source_paths,
+        mangled_exports,
+    })
+}
@@ -0,0 +1,135 @@
+// 综合可疑度评分 0-100 供非专业用户快速排序
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::Indicator;
+use crate::ep_signature::EntryPointBytes;
+use crate::heuristics::{packer_for_section_name, Severity, Warning};
+use crate::Section;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ScoreFactor {
+    pub name: String,
+    pub points: u32,
+    pub max_points: u32,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SuspicionScore {
+    pub score: u32,
+    pub factors: Vec<ScoreFactor>,
+}
+
+// 各因素的最大分值 合计超过100时截断
+const ENTROPY_MAX: u32 = 20;
+const PACKER_MAX: u32 = 20;
+const ANOMALY_MAX: u32 = 25;
+const CAPABILITY_MAX: u32 = 30;
+const UNSIGNED_MAX: u32 = 10;
+
+fn severity_points(severity: Severity, high: u32, medium: u32, low: u32) -> u32 {
+    match severity {
+        Severity::High => high,
+        Severity::Medium => medium,
+        Severity::Low => low,
+        Severity::Info => 0,
+    }
+}
+
+pub fn compute_score(
+    sections: &[Section],
+    entry_point_bytes: Option<&EntryPointBytes>,
+    warnings: &[Warning],
+    indicators: &[Indicator],
+    is_signed: bool,
+) -> SuspicionScore {
+    let mut factors: Vec<ScoreFactor> = Vec::new();
+
+    // 熵 超过6.5开始计分 7.99时满分
+    let max_entropy = sections
+        .iter()
+        .filter(|s| s.size_raw_data != 0)
+        .map(|s| s.entropy)
+        .fold(0.0, f64::max);
+    let entropy_points = (((max_entropy - 6.5) / 1.5).clamp(0.0, 1.0) * ENTROPY_MAX as f64) as u32;
+    factors.push(ScoreFactor {
+        name: "entropy".to_string(),
+        points: entropy_points,
+        max_points: ENTROPY_MAX,
+        detail: format!("节区最大熵 {:.2}", max_entropy),
+    });
+
+    // 壳特征
+    let mut packers: Vec<&str> = sections
+        .iter()
+        .filter_map(|s| packer_for_section_name(&s.name))
+        .chain(entry_point_bytes.and_then(|entry| entry.signature.as_deref()))
+        .collect();
+    packers.sort_unstable();
+    packers.dedup();
+    factors.push(ScoreFactor {
+        name: "packer".to_string(),
+        points: if packers.is_empty() { 0 } else { PACKER_MAX },
+        max_points: PACKER_MAX,
+        detail: if packers.is_empty() {
+            "未发现壳特征".to_string()
+        } else {
+            format!("壳特征: {}", packers.join(", "))
+        },
+    });
+
+    // 结构异常 注入警告由能力因素计分
+    let anomalies: Vec<&Warning> = warnings
+        .iter()
+        .filter(|w| w.kind != "process_injection")
+        .collect();
+    let anomaly_points: u32 = anomalies
+        .iter()
+        .map(|w| severity_points(w.severity, 8, 4, 1))
+        .sum();
+    factors.push(ScoreFactor {
+        name: "anomalies".to_string(),
+        points: anomaly_points.min(ANOMALY_MAX),
+        max_points: ANOMALY_MAX,
+        detail: format!("{} 条结构异常警告", anomalies.len()),
+    });
+
+    // 能力特征
+    let capability_points: u32 = indicators
+        .iter()
+        .map(|i| {
+            if i.category == "injection" {
+                25
+            } else {
+                severity_points(i.severity, 10, 5, 2)
+            }
+        })
+        .sum();
+    factors.push(ScoreFactor {
+        name: "capabilities".to_string(),
+        points: capability_points.min(CAPABILITY_MAX),
+        max_points: CAPABILITY_MAX,
+        detail: format!(
+            "{} 项能力/反分析特征",
+            indicators
+                .iter()
+                .filter(|i| i.severity != Severity::Info)
+                .count()
+        ),
+    });
+
+    // 签名
+    factors.push(ScoreFactor {
+        name: "unsigned".to_string(),
+        points: if is_signed { 0 } else { UNSIGNED_MAX },
+        max_points: UNSIGNED_MAX,
+        detail: if is_signed {
+            "存在证书目录".to_string()
+        } else {
+            "没有数字签名".to_string()
+        },
+    });
+
+    let score = factors.iter().map(|f| f.points).sum::<u32>().min(100);
+    SuspicionScore { score, factors }
+}
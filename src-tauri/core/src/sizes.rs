@@ -0,0 +1,343 @@
+// 体积构成: 文件中各部分各占多少字节 用于排查二进制膨胀
+// 函数大小来自异常目录(.pdata) 名称来自Go pclntab、COFF符号表(MinGW构建)或导出表 按Go包/Rust crate/C++命名空间汇总
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+
+use crate::demangle::demangle;
+use crate::exception::RuntimeFunction;
+use crate::golang::{go_functions, go_package};
+use crate::layout::DIRECTORY_NAMES;
+use crate::reader::BoundedReader;
+use crate::{PeInfo, Section, IMAGE_DIRECTORY_ENTRY_SECURITY};
+
+// 列表中最多显示的项数
+const MAX_RESOURCES: usize = 10;
+const MAX_FUNCTIONS: usize = 20;
+const MAX_GROUPS: usize = 30;
+const COFF_SYMBOL_SIZE: u64 = 18;
+// 符号数量上限 防止构造的文件声明超大符号表
+const MAX_COFF_SYMBOLS: u32 = 1 << 22;
+const IMAGE_SYM_DTYPE_FUNCTION: u16 = 0x20;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SizeItem {
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SymbolGroup {
+    pub name: String,
+    pub functions: usize,
+    pub size: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FunctionSizes {
+    // pdata: 异常目录中的函数范围 / symbols: 相邻符号的间距(x86等没有.pdata的文件)
+    pub source: String,
+    // 函数名来源 go / coff / exports 没有任何名称时为None
+    pub names: Option<String>,
+    pub count: usize,
+    pub total: u64,
+    pub largest: Vec<SizeItem>,
+    // 按Go包、Rust crate或C++命名空间汇总 未命名的函数单独一组
+    pub groups: Vec<SymbolGroup>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SizeReport {
+    pub file_size: u64,
+    pub headers: u64,
+    // 各节在文件中的原始数据大小
+    pub sections: Vec<SizeItem>,
+    pub overlay: u64,
+    pub certificate: u64,
+    // 各数据目录的大小 不含证书表 按大小排序
+    pub directories: Vec<SizeItem>,
+    pub debug: Vec<SizeItem>,
+    // 按资源类型汇总
+    pub resource_types: Vec<SizeItem>,
+    pub largest_resources: Vec<SizeItem>,
+    pub functions: Option<FunctionSizes>,
+}
+
+fn sorted_items(mut items: Vec<SizeItem>, limit: usize) -> Vec<SizeItem> {
+    items.retain(|item| item.size != 0);
+    items.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    items.truncate(limit);
+    items
+}
+
+// COFF符号表中的函数符号 MSVC链接的映像没有符号表 MinGW构建未strip时保留
+fn coff_functions(data: &[u8], sections: &[Section]) -> Vec<(u32, String)> {
+    let reader = BoundedReader::new(data);
+    let Ok(nt) = reader.u32(0x3C).map(u64::from) else {
+        return Vec::new();
+    };
+    let (Ok(table), Ok(count)) = (reader.u32(nt + 12), reader.u32(nt + 16)) else {
+        return Vec::new();
+    };
+    let (table, count) = (table as u64, count.min(MAX_COFF_SYMBOLS) as u64);
+    if table == 0 || count == 0 {
+        return Vec::new();
+    }
+    let strings = table + count * COFF_SYMBOL_SIZE;
+    let mut functions: Vec<(u32, String)> = Vec::new();
+    let mut index = 0;
+    while index < count {
+        let entry = table + index * COFF_SYMBOL_SIZE;
+        let Ok(symbol) = reader.bytes(entry, COFF_SYMBOL_SIZE) else {
+            break;
+        };
+        let symbol = BoundedReader::new(symbol);
+        let auxiliary = symbol.array::<1>(17).map_or(0, |[count]| count as u64);
+        index += 1 + auxiliary;
+        let section_number = symbol.i16(12).unwrap_or_default();
+        let symbol_type = symbol.u16(14).unwrap_or_default();
+        if section_number <= 0 || symbol_type & 0x30 != IMAGE_SYM_DTYPE_FUNCTION {
+            continue;
+        }
+        let Some(section) = sections.get(section_number as usize - 1) else {
+            continue;
+        };
+        // 名称不超过8字节时内联 否则前4字节为0 后4字节为字符串表偏移
+        let name = if symbol.u32(0) == Ok(0) {
+            let offset = strings + symbol.u32(4).unwrap_or_default() as u64;
+            let Ok((bytes, _)) = reader.c_string_truncated(offset, usize::MAX) else {
+                continue;
+            };
+            String::from_utf8_lossy(bytes).to_string()
+        } else {
+            let (bytes, _) = symbol.c_string_truncated(0, 8).unwrap_or_default();
+            String::from_utf8_lossy(bytes).to_string()
+        };
+        let value = symbol.u32(8).unwrap_or_default();
+        functions.push((section.rva.saturating_add(value), name));
+    }
+    functions
+}
+
+// Rust/C++按第一段路径汇总 "<alloc::vec::Vec<T> as core::ops::Drop>::drop" -> "alloc"
+fn symbol_group(name: &str, go: bool) -> String {
+    if go {
+        return go_package(name).to_string();
+    }
+    match demangle(name) {
+        Some(demangled) => {
+            let path = demangled.trim_start_matches('<');
+            match path.split_once("::") {
+                Some((first, _)) => first.to_string(),
+                None => "(全局)".to_string(),
+            }
+        }
+        None => "(全局)".to_string(),
+    }
+}
+
+fn function_sizes(
+    info: &PeInfo,
+    data: &[u8],
+    runtime_functions: &[RuntimeFunction],
+) -> Option<FunctionSizes> {
+    let go = info.go_info.is_some();
+    let (mut symbols, names): (Vec<(u32, String)>, Option<&str>) = if go {
+        let functions = go_functions(data, info.image_base);
+        (
+            functions
+                .into_iter()
+                .map(|function| (function.rva, function.name))
+                .collect(),
+            Some("go"),
+        )
+    } else {
+        (coff_functions(data, &info.sections), Some("coff"))
+    };
+    let names = if symbols.is_empty() {
+        symbols = info
+            .export_table
+            .iter()
+            .filter(|export| export.address != 0 && export.is_named)
+            .map(|export| (export.address, export.name.clone()))
+            .collect();
+        (!symbols.is_empty()).then_some("exports")
+    } else {
+        names
+    };
+    symbols.sort_by_key(|(rva, _)| *rva);
+    symbols.dedup_by_key(|(rva, _)| *rva);
+
+    // 导出只覆盖少数函数 不能把之后的代码都算到前一个导出上
+    let sparse = names == Some("exports");
+
+    // 函数范围: 优先.pdata 否则取相邻符号的间距 并限制在所在节内
+    let (source, ranges): (&str, Vec<(u32, u32)>) = if !runtime_functions.is_empty() {
+        let mut ranges: Vec<(u32, u32)> = runtime_functions
+            .iter()
+            .filter(|function| function.end > function.begin)
+            .map(|function| (function.begin, function.end))
+            .collect();
+        ranges.sort();
+        ("pdata", ranges)
+    } else if sparse {
+        return None;
+    } else {
+        let ranges = symbols
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (rva, _))| {
+                let section = info
+                    .sections
+                    .iter()
+                    .find(|section| *rva >= section.rva && *rva < section.rv_end)?;
+                let end = symbols
+                    .get(index + 1)
+                    .map_or(section.rv_end, |(next, _)| (*next).min(section.rv_end));
+                Some((*rva, end))
+            })
+            .collect();
+        ("symbols", ranges)
+    };
+    if ranges.is_empty() {
+        return None;
+    }
+
+    // 每个范围归入起始地址之前最近的符号 一个函数的多段.pdata(如funclet)合并计算
+    // 名称来自导出时只认起始地址相同的范围
+    let mut named: HashMap<usize, u64> = HashMap::new();
+    let mut unnamed: Vec<(u32, u64)> = Vec::new();
+    for &(begin, end) in &ranges {
+        let size = (end - begin) as u64;
+        match symbols.partition_point(|(rva, _)| *rva <= begin) {
+            index if index > 0 && !(sparse && symbols[index - 1].0 != begin) => {
+                *named.entry(index - 1).or_default() += size
+            }
+            _ => unnamed.push((begin, size)),
+        }
+    }
+
+    let mut largest: Vec<SizeItem> = named
+        .iter()
+        .map(|(&index, &size)| SizeItem {
+            name: symbols[index].1.clone(),
+            size,
+        })
+        .chain(unnamed.iter().map(|&(rva, size)| SizeItem {
+            name: format!("sub_{:X}", rva),
+            size,
+        }))
+        .collect();
+    largest = sorted_items(largest, MAX_FUNCTIONS);
+
+    let mut groups: HashMap<String, (usize, u64)> = HashMap::new();
+    for (&index, &size) in &named {
+        let group = groups
+            .entry(symbol_group(&symbols[index].1, go))
+            .or_default();
+        group.0 += 1;
+        group.1 += size;
+    }
+    if !unnamed.is_empty() {
+        groups.insert(
+            "(未命名)".to_string(),
+            (unnamed.len(), unnamed.iter().map(|(_, size)| size).sum()),
+        );
+    }
+    let mut groups: Vec<SymbolGroup> = groups
+        .into_iter()
+        .map(|(name, (functions, size))| SymbolGroup {
+            name,
+            functions,
+            size,
+        })
+        .collect();
+    groups.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    groups.truncate(MAX_GROUPS);
+
+    Some(FunctionSizes {
+        source: source.to_string(),
+        names: names.map(str::to_string),
+        count: ranges.len(),
+        total: ranges.iter().map(|(begin, end)| (end - begin) as u64).sum(),
+        largest,
+        groups,
+    })
+}
+
+pub fn size_report(
+    info: &PeInfo,
+    data: &[u8],
+    runtime_functions: &[RuntimeFunction],
+) -> SizeReport {
+    let sections = info
+        .sections
+        .iter()
+        .map(|section| SizeItem {
+            name: section.name.clone(),
+            size: section.size_raw_data as u64,
+        })
+        .collect();
+
+    let directories = info
+        .data_directories
+        .iter()
+        .zip(DIRECTORY_NAMES)
+        .enumerate()
+        .filter(|(index, _)| *index != IMAGE_DIRECTORY_ENTRY_SECURITY)
+        .map(|(_, (directory, name))| SizeItem {
+            name: name.to_string(),
+            size: directory.size as u64,
+        })
+        .collect();
+
+    let mut debug: HashMap<&str, u64> = HashMap::new();
+    for entry in &info.debug_entries {
+        *debug.entry(entry.type_name.as_str()).or_default() += entry.size as u64;
+    }
+    let debug = debug
+        .into_iter()
+        .map(|(name, size)| SizeItem {
+            name: name.to_string(),
+            size,
+        })
+        .collect();
+
+    let mut resource_types: HashMap<&str, u64> = HashMap::new();
+    for resource in &info.resources {
+        *resource_types
+            .entry(resource.type_name.as_str())
+            .or_default() += resource.size as u64;
+    }
+    let resource_types = resource_types
+        .into_iter()
+        .map(|(name, size)| SizeItem {
+            name: name.to_string(),
+            size,
+        })
+        .collect();
+    let largest_resources = info
+        .resources
+        .iter()
+        .map(|resource| SizeItem {
+            name: format!(
+                "{}/{}/{}",
+                resource.type_name, resource.name, resource.language
+            ),
+            size: resource.size as u64,
+        })
+        .collect();
+
+    SizeReport {
+        file_size: info.size,
+        headers: info.coverage.headers,
+        sections,
+        overlay: info.coverage.overlay,
+        certificate: info.coverage.certificate,
+        directories: sorted_items(directories, DIRECTORY_NAMES.len()),
+        debug: sorted_items(debug, usize::MAX),
+        resource_types: sorted_items(resource_types, usize::MAX),
+        largest_resources: sorted_items(largest_resources, MAX_RESOURCES),
+        functions: function_sizes(info, data, runtime_functions),
+    }
+}
@@ -0,0 +1,82 @@
+// 节区空隙 VirtualSize之后的文件对齐填充 以及节与节之间未被引用的字节
+// 正常链接器填0 非零内容可能是加壳器或植入代码藏匿的数据
+use serde::{Deserialize, Serialize};
+
+use crate::entropy::shannon_entropy;
+use crate::reader::BoundedReader;
+use crate::Section;
+
+// 可打印ASCII字符串的最小长度
+const MIN_STRING_LENGTH: usize = 5;
+// 每个空隙最多返回的字符串数量
+const MAX_STRINGS: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SlackRegion {
+    pub section: String,
+    // virtual: VirtualSize到SizeOfRawData之间 / gap: 原始数据结尾到下一节之间
+    pub kind: String,
+    pub offset: u32,
+    pub size: u32,
+    pub non_zero_bytes: u32,
+    pub entropy: f64,
+    pub strings: Vec<String>,
+}
+
+fn ascii_strings(data: &[u8]) -> Vec<String> {
+    data.split(|&b| !(0x20..0x7F).contains(&b))
+        .filter(|run| run.len() >= MIN_STRING_LENGTH)
+        .take(MAX_STRINGS)
+        .map(|run| String::from_utf8_lossy(run).to_string())
+        .collect()
+}
+
+fn region(data: &[u8], section: &Section, kind: &str, start: u32, end: u32) -> Option<SlackRegion> {
+    let length = end.checked_sub(start)?;
+    let bytes = BoundedReader::new(data).bytes_truncated(start as u64, length as u64);
+    let non_zero_bytes = bytes.iter().filter(|&&b| b != 0).count() as u32;
+    if non_zero_bytes == 0 {
+        return None;
+    }
+    Some(SlackRegion {
+        section: section.name.clone(),
+        kind: kind.to_string(),
+        offset: start,
+        size: bytes.len() as u32,
+        non_zero_bytes,
+        entropy: shannon_entropy(bytes),
+        strings: ascii_strings(bytes),
+    })
+}
+
+// 只返回含非零字节的空隙
+pub(crate) fn find_slack(data: &[u8], sections: &[Section]) -> Vec<SlackRegion> {
+    let mut regions: Vec<SlackRegion> = Vec::new();
+    let mut ordered: Vec<&Section> = sections
+        .iter()
+        .filter(|section| section.ptr_raw_data != 0 && section.size_raw_data != 0)
+        .collect();
+    ordered.sort_by_key(|section| section.ptr_raw_data);
+
+    for (i, section) in ordered.iter().enumerate() {
+        let raw_end = section.ptr_raw_data.saturating_add(section.size_raw_data);
+        // VirtualSize为0时加载器使用SizeOfRawData 没有空隙
+        let virtual_size = section.rv_end.saturating_sub(section.rva);
+        if virtual_size != 0 && virtual_size < section.size_raw_data {
+            regions.extend(region(
+                data,
+                section,
+                "virtual",
+                section.ptr_raw_data.saturating_add(virtual_size),
+                raw_end,
+            ));
+        }
+        // 最后一节之后是附加数据 不属于空隙
+        if let Some(next) = ordered.get(i + 1) {
+            if next.ptr_raw_data > raw_end {
+                regions.extend(region(data, section, "gap", raw_end, next.ptr_raw_data));
+            }
+        }
+    }
+    regions
+}
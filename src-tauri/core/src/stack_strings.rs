@@ -0,0 +1,200 @@
+// 栈字符串: 用一串 mov byte/word/dword ptr [esp/ebp/rsp/rbp+X], imm 在运行时拼出的字符串 普通的字符串提取看不到
+// 只扫描入口点前后的窗口 并限制在入口点所在的节内
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+use crate::reader::{read_i32, BoundedReader};
+use crate::{rva_to_offset, Section};
+
+const IMAGE_FILE_MACHINE_I386: u16 = 0x014C;
+const IMAGE_FILE_MACHINE_AMD64: u16 = 0x8664;
+// 入口点前后各扫描的字节数
+const WINDOW: u32 = 0x10000;
+// 同一组的两条mov之间允许夹杂的其他指令字节数
+const MAX_GAP: usize = 16;
+const MIN_STRING_LENGTH: usize = 4;
+const MAX_STACK_STRINGS: usize = 256;
+
+// 位移 -> (写入的字节, 指令位置)
+type Stores = BTreeMap<i32, (u8, usize)>;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StackString {
+    // 第一条mov指令的文件偏移和RVA
+    pub offset: u32,
+    pub rva: u32,
+    pub value: String,
+    // ascii / utf16
+    pub encoding: String,
+    pub instructions: u32,
+}
+
+// 一条写栈的mov: (基址寄存器 0=esp 1=ebp, 位移, 写入的字节, 指令长度)
+fn decode_store(code: &[u8]) -> Option<(u8, i32, Vec<u8>, usize)> {
+    // 0x66前缀: 写入2字节
+    let (prefix, code) = match code.first()? {
+        0x66 => (1, &code[1..]),
+        _ => (0, code),
+    };
+    let immediate_size = match (code.first()?, prefix) {
+        (0xC6, 0) => 1,
+        (0xC7, 1) => 2,
+        (0xC7, 0) => 4,
+        _ => return None,
+    };
+    // ModRM: [esp+disp8]=44 24 [esp+disp32]=84 24 [ebp+disp8]=45 [ebp+disp32]=85
+    let (base, displacement, operand_length) = match code.get(1..3)? {
+        [0x44, 0x24] => (0, *code.get(3)? as i8 as i32, 4),
+        [0x84, 0x24] => (0, read_i32(code, 3)?, 7),
+        [0x45, _] => (1, code[2] as i8 as i32, 3),
+        [0x85, _] => (1, read_i32(code, 2)?, 6),
+        _ => return None,
+    };
+    let bytes = code
+        .get(operand_length..operand_length + immediate_size)?
+        .to_vec();
+    Some((
+        base,
+        displacement,
+        bytes,
+        prefix + operand_length + immediate_size,
+    ))
+}
+
+// 把一组写入按位移拼成连续的片段 位移不连续处断开
+// 每个片段附带最早一条相关指令的位置和指令数
+fn assemble(stores: &Stores) -> Vec<(Vec<u8>, usize, u32)> {
+    let mut runs: Vec<(Vec<u8>, Vec<usize>)> = Vec::new();
+    let mut next: Option<i32> = None;
+    for (&displacement, &(byte, position)) in stores {
+        if next != Some(displacement) {
+            runs.push((Vec::new(), Vec::new()));
+        }
+        if let Some((bytes, positions)) = runs.last_mut() {
+            bytes.push(byte);
+            if !positions.contains(&position) {
+                positions.push(position);
+            }
+        }
+        next = Some(displacement + 1);
+    }
+    runs.into_iter()
+        .map(|(bytes, positions)| {
+            let first = positions.iter().copied().min().unwrap_or(0);
+            (bytes, first, positions.len() as u32)
+        })
+        .collect()
+}
+
+// 片段中以0结尾的字符串 第二个字节为0时按UTF-16LE解释
+fn decode_run(run: &[u8]) -> Vec<(String, &'static str)> {
+    let printable = |text: &[u8]| {
+        text.len() >= MIN_STRING_LENGTH && text.iter().all(|b| (0x20..0x7F).contains(b))
+    };
+    if run.get(1) == Some(&0) && run.first().is_some_and(|&b| b != 0) {
+        let units: Vec<u8> = run
+            .chunks(2)
+            .map(|pair| {
+                if pair.get(1).is_some_and(|&b| b != 0) {
+                    0xFF
+                } else {
+                    pair[0]
+                }
+            })
+            .collect();
+        return units
+            .split(|&b| b == 0)
+            .filter(|text| printable(text))
+            .map(|text| (String::from_utf8_lossy(text).to_string(), "utf16"))
+            .collect();
+    }
+    run.split(|&b| b == 0)
+        .filter(|text| printable(text))
+        .map(|text| (String::from_utf8_lossy(text).to_string(), "ascii"))
+        .collect()
+}
+
+pub(crate) fn find_stack_strings(
+    data: &[u8],
+    sections: &[Section],
+    entry_point: u32,
+    machine: u16,
+) -> Vec<StackString> {
+    let mut strings: Vec<StackString> = Vec::new();
+    if entry_point == 0
+        || (machine != IMAGE_FILE_MACHINE_I386 && machine != IMAGE_FILE_MACHINE_AMD64)
+    {
+        return strings;
+    }
+    let Some(section) = sections
+        .iter()
+        .find(|section| entry_point >= section.rva && entry_point < section.rv_end)
+    else {
+        return strings;
+    };
+    let start_rva = entry_point.saturating_sub(WINDOW).max(section.rva);
+    let end_rva = entry_point
+        .saturating_add(WINDOW)
+        .min(section.rv_end)
+        .min(section.rva.saturating_add(section.size_raw_data));
+    let (Some(start), Some(end)) = (
+        rva_to_offset(sections, start_rva),
+        rva_to_offset(sections, end_rva.saturating_sub(1)),
+    ) else {
+        return strings;
+    };
+    let Some(code) = (end as u64 + 1)
+        .checked_sub(start as u64)
+        .and_then(|length| BoundedReader::new(data).bytes(start as u64, length).ok())
+    else {
+        return strings;
+    };
+
+    // 当前组: 基址寄存器和其写入
+    let mut group: Option<(u8, Stores)> = None;
+    let mut last_end = 0;
+    let mut flush = |group: Option<(u8, Stores)>| {
+        let Some((_, stores)) = group else {
+            return;
+        };
+        for (run, first, instructions) in assemble(&stores) {
+            for (value, encoding) in decode_run(&run) {
+                if strings.len() >= MAX_STACK_STRINGS {
+                    return;
+                }
+                strings.push(StackString {
+                    offset: start + first as u32,
+                    rva: start_rva + first as u32,
+                    value,
+                    encoding: encoding.to_string(),
+                    instructions,
+                });
+            }
+        }
+    };
+    let mut position = 0;
+    while position < code.len() {
+        let Some((base, displacement, bytes, length)) = decode_store(&code[position..]) else {
+            if group.is_some() && position > last_end + MAX_GAP {
+                flush(group.take());
+            }
+            position += 1;
+            continue;
+        };
+        if group
+            .as_ref()
+            .is_some_and(|(group_base, _)| *group_base != base)
+        {
+            flush(group.take());
+        }
+        let (_, stores) = group.get_or_insert_with(|| (base, BTreeMap::new()));
+        for (index, byte) in bytes.into_iter().enumerate() {
+            stores.insert(displacement + index as i32, (byte, position));
+        }
+        position += length;
+        last_end = position;
+    }
+    flush(group);
+    strings
+}
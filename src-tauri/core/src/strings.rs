@@ -0,0 +1,188 @@
+// 全文件字符串提取 每个字符串按文件偏移归属到头部、节区或覆盖数据 并标记是否位于资源目录中
+use serde::{Deserialize, Serialize};
+
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, DataDirectory, Section};
+
+// 默认最小长度 与空隙字符串一致
+const DEFAULT_MIN_LENGTH: usize = 5;
+// 每次返回给前端的字符串数量 其余由前端按需分页加载
+const PAGE_SIZE: usize = 5_000;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FoundString {
+    pub offset: u64,
+    pub rva: Option<u32>,
+    // ascii / utf16
+    pub encoding: String,
+    pub value: String,
+    // 节区名 / headers / overlay / gap(节之间未映射的字节)
+    pub region: String,
+    pub in_resources: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StringList {
+    // 可用的过滤条件: 各区域名和resources
+    pub regions: Vec<String>,
+    // 过滤后的总数 每次只返回从start开始的PAGE_SIZE个
+    pub total: u32,
+    pub start: u32,
+    pub strings: Vec<FoundString>,
+}
+
+fn is_printable(byte: u8) -> bool {
+    (0x20..0x7F).contains(&byte) || byte == b'\t'
+}
+
+// (偏移, 编码, 字符数) 按偏移排序 内容在返回时才生成 大文件中数百万个字符串不必全部分配
+fn scan(data: &[u8], min_length: usize) -> Vec<(usize, &'static str, usize)> {
+    let mut found: Vec<(usize, &'static str, usize)> = Vec::new();
+    let mut start = 0;
+    for (i, &byte) in data.iter().chain(Some(&0)).enumerate() {
+        if !is_printable(byte) {
+            if i - start >= min_length {
+                found.push((start, "ascii", i - start));
+            }
+            start = i + 1;
+        }
+    }
+    // UTF-16LE: 可打印字符后跟0 两种对齐各扫描一次
+    for alignment in 0..2 {
+        let mut run: usize = 0;
+        let mut run_start = alignment;
+        let pairs = data.get(alignment..).unwrap_or(&[]).chunks(2);
+        for (index, pair) in pairs.chain(Some(&[0u8, 1][..])).enumerate() {
+            let offset = alignment + index * 2;
+            if pair.len() == 2 && is_printable(pair[0]) && pair[1] == 0 {
+                if run == 0 {
+                    run_start = offset;
+                }
+                run += 1;
+                continue;
+            }
+            if run >= min_length {
+                found.push((run_start, "utf16", run));
+            }
+            run = 0;
+        }
+    }
+    found.sort_by_key(|(offset, _, _)| *offset);
+    found
+}
+
+// 扫描时只检查了可打印字符 UTF-16取每对的低字节即可
+fn materialize(data: &[u8], offset: usize, encoding: &str, length: usize) -> String {
+    let reader = BoundedReader::new(data);
+    match encoding {
+        "utf16" => reader
+            .bytes_truncated(offset as u64, length as u64 * 2)
+            .iter()
+            .step_by(2)
+            .map(|&byte| byte as char)
+            .collect(),
+        _ => String::from_utf8_lossy(reader.bytes_truncated(offset as u64, length as u64))
+            .to_string(),
+    }
+}
+
+fn region_of(
+    sections: &[Section],
+    size_of_headers: u32,
+    overlay_offset: u64,
+    offset: u64,
+) -> (String, Option<u32>) {
+    if let Some(section) = sections.iter().find(|section| {
+        offset >= section.ptr_raw_data as u64
+            && offset < section.ptr_raw_data as u64 + section.size_raw_data as u64
+    }) {
+        let rva = section.rva as u64 + offset - section.ptr_raw_data as u64;
+        return (section.name.clone(), u32::try_from(rva).ok());
+    }
+    if offset < size_of_headers as u64 {
+        return ("headers".to_string(), Some(offset as u32));
+    }
+    if offset >= overlay_offset {
+        return ("overlay".to_string(), None);
+    }
+    // 节与节之间未被映射的字节
+    ("gap".to_string(), None)
+}
+
+// filter: None或all为全部 resources为资源目录内 其余按区域名匹配
+// start: 过滤后从第几个开始返回 用于分页
+#[allow(clippy::too_many_arguments)]
+pub fn extract_strings(
+    data: &[u8],
+    sections: &[Section],
+    resource_directory: DataDirectory,
+    size_of_headers: u32,
+    overlay_offset: u64,
+    filter: Option<&str>,
+    min_length: Option<usize>,
+    start: Option<usize>,
+) -> StringList {
+    let resources = (resource_directory.rva != 0 && resource_directory.size != 0)
+        .then(|| rva_to_offset(sections, resource_directory.rva))
+        .flatten()
+        .map(|start| start as u64..start as u64 + resource_directory.size as u64);
+
+    let mut regions: Vec<String> = vec!["headers".to_string()];
+    for section in sections {
+        if !regions.contains(&section.name) {
+            regions.push(section.name.clone());
+        }
+    }
+    if resources.is_some() {
+        regions.push("resources".to_string());
+    }
+    if (data.len() as u64) > overlay_offset {
+        regions.push("overlay".to_string());
+    }
+
+    let min_length = min_length.unwrap_or(DEFAULT_MIN_LENGTH).max(1);
+    let mut strings: Vec<FoundString> = Vec::new();
+    let start = start.unwrap_or(0);
+    let mut total: u32 = 0;
+    for (position, encoding, length) in scan(data, min_length) {
+        let offset = position as u64;
+        let (region, rva) = region_of(sections, size_of_headers, overlay_offset, offset);
+        let in_resources = resources
+            .as_ref()
+            .is_some_and(|range| range.contains(&offset));
+        let keep = match filter {
+            None | Some("all") => true,
+            Some("resources") => in_resources,
+            Some(filter) => region == filter,
+        };
+        if !keep {
+            continue;
+        }
+        total += 1;
+        if total as usize > start && strings.len() < PAGE_SIZE {
+            strings.push(FoundString {
+                offset,
+                rva,
+                encoding: encoding.to_string(),
+                value: materialize(data, position, encoding, length),
+                region,
+                in_resources,
+            });
+        }
+    }
+    StringList {
+        regions,
+        total,
+        start: start as u32,
+        strings,
+    }
+}
+
+// 不区分区域的全部字符串 (文件偏移, 内容) 按偏移排序 用于在字符串中搜索特征
+pub fn all_strings(data: &[u8], min_length: usize) -> impl Iterator<Item = (usize, String)> + '_ {
+    scan(data, min_length.max(1))
+        .into_iter()
+        .map(|(position, encoding, length)| {
+            (position, materialize(data, position, encoding, length))
+        })
+}
@@ -0,0 +1,225 @@
+// 类PE加载器存根识别: 头部有效 但没有导入 代码直接放在头部中或入口代码自行定位
+// 这类文件通常是包装成PE的shellcode 常规的导入/资源表格为空 这里说明缺少了什么以及代码如何弥补
+use serde::{Deserialize, Serialize};
+
+use crate::reader::{read_i32, BoundedReader};
+use crate::{rva_to_offset, DataDirectory, ImportTableEntry, Section};
+
+const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+const IMAGE_DIRECTORY_ENTRY_BASERELOC: usize = 5;
+const IMAGE_DIRECTORY_ENTRY_DEBUG: usize = 6;
+// 每个节的原始数据都不超过此大小时视为小节区
+const TINY_SECTION_SIZE: u32 = 0x2000;
+// 入口点之后扫描的字节数 获取自身地址和读取PEB一般在最开始
+const SCAN_SIZE: u64 = 0x200;
+// RIP相对lea向前引用的最大距离 更远的通常是普通的数据访问
+const MAX_BACKWARD_LEA: i32 = 0x1000;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CodeHint {
+    pub rva: u32,
+    pub instruction: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MissingPart {
+    pub part: String,
+    pub explanation: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StubInfo {
+    // 没有节区或入口点位于头部
+    pub headers_only: bool,
+    pub tiny_sections: bool,
+    // 各节原始数据的总大小
+    pub raw_size: u32,
+    // 获取自身地址的指令 位置无关代码的标志
+    pub self_relocating: Option<CodeHint>,
+    // 读取PEB的指令 通常用于遍历已加载模块并解析API
+    pub peb_access: Option<CodeHint>,
+    pub missing: Vec<MissingPart>,
+    pub summary: String,
+}
+
+fn has_directory(data_directories: &[DataDirectory], index: usize) -> bool {
+    data_directories
+        .get(index)
+        .is_some_and(|directory| directory.rva != 0 && directory.size != 0)
+}
+
+fn hint(entry_point: u32, position: usize, instruction: String) -> CodeHint {
+    CodeHint {
+        rva: entry_point.wrapping_add(position as u32),
+        instruction,
+    }
+}
+
+// call $+5; pop reg / fnstenv [esp-0Ch] / lea reg, [rip-X]
+fn find_self_relocation(code: &[u8], entry_point: u32, is_x64: bool) -> Option<CodeHint> {
+    for i in 0..code.len() {
+        let rest = &code[i..];
+        if rest.starts_with(&[0xE8, 0, 0, 0, 0]) {
+            // x64中pop r8-r15带REX.B前缀
+            let pop = match rest.get(5..) {
+                Some([0x58..=0x5F, ..]) => true,
+                Some([0x41, 0x58..=0x5F, ..]) => is_x64,
+                _ => false,
+            };
+            if pop {
+                return Some(hint(entry_point, i, "call $+5; pop".to_string()));
+            }
+        }
+        if !is_x64 && rest.starts_with(&[0xD9, 0x74, 0x24, 0xF4]) {
+            return Some(hint(entry_point, i, "fnstenv [esp-0Ch]".to_string()));
+        }
+        if is_x64 {
+            if let ([0x48 | 0x4C, 0x8D, modrm, ..], Some(displacement)) = (rest, read_i32(rest, 3))
+            {
+                if modrm & 0xC7 == 0x05 && (-MAX_BACKWARD_LEA..=0).contains(&displacement) {
+                    return Some(hint(
+                        entry_point,
+                        i,
+                        format!("lea reg, [rip-0x{:X}]", displacement.unsigned_abs()),
+                    ));
+                }
+            }
+        }
+    }
+    None
+}
+
+// x86: mov reg, fs:[30h]  x64: mov reg, gs:[60h]
+fn find_peb_access(code: &[u8], entry_point: u32, is_x64: bool) -> Option<CodeHint> {
+    for i in 0..code.len() {
+        let found = if is_x64 {
+            match code[i..] {
+                [0x65, 0x48 | 0x4C, 0x8B, modrm, 0x25, 0x60, 0, 0, 0, ..] => modrm & 0xC7 == 0x04,
+                _ => false,
+            }
+        } else {
+            match code[i..] {
+                [0x64, 0xA1, 0x30, 0, 0, 0, ..] => true,
+                [0x64, 0x8B, modrm, 0x30, 0, 0, 0, ..] => modrm & 0xC7 == 0x05,
+                // xor eax, eax; mov eax, fs:[eax+30h]
+                [0x64, 0x8B, modrm, 0x30, ..] => modrm & 0xC0 == 0x40 && modrm & 0x07 != 0x04,
+                _ => false,
+            }
+        };
+        if found {
+            let instruction = if is_x64 {
+                "mov reg, gs:[60h]"
+            } else {
+                "mov reg, fs:[30h]"
+            };
+            return Some(hint(entry_point, i, instruction.to_string()));
+        }
+    }
+    None
+}
+
+pub(crate) fn detect_stub(
+    data: &[u8],
+    sections: &[Section],
+    import_table: &[ImportTableEntry],
+    data_directories: &[DataDirectory],
+    entry_point: u32,
+    size_of_headers: u32,
+    is_x64: bool,
+) -> Option<StubInfo> {
+    // 没有入口点的通常是纯资源DLL
+    if !import_table.is_empty() || entry_point == 0 {
+        return None;
+    }
+    let entry_offset = match rva_to_offset(sections, entry_point) {
+        Some(offset) => Some(offset),
+        None if entry_point < size_of_headers => Some(entry_point),
+        None => None,
+    };
+    let headers_only = sections.iter().all(|section| section.size_raw_data == 0)
+        || (entry_point < size_of_headers && rva_to_offset(sections, entry_point).is_none());
+    let tiny_sections = sections
+        .iter()
+        .all(|section| section.size_raw_data <= TINY_SECTION_SIZE);
+    let raw_size = sections.iter().fold(0u32, |total, section| {
+        total.saturating_add(section.size_raw_data)
+    });
+
+    let code = entry_offset
+        .map(|offset| BoundedReader::new(data).bytes_truncated(offset as u64, SCAN_SIZE))
+        .unwrap_or_default();
+    let self_relocating = find_self_relocation(code, entry_point, is_x64);
+    let peb_access = find_peb_access(code, entry_point, is_x64);
+    // 只是很小且没有导入的程序也可能是正常的(例如不依赖CRT的小DLL) 需要代码本身的特征
+    if !headers_only && self_relocating.is_none() && peb_access.is_none() {
+        return None;
+    }
+
+    let mut missing: Vec<MissingPart> = Vec::new();
+    missing.push(MissingPart {
+        part: "导入表".to_string(),
+        explanation: if peb_access.is_some() {
+            "没有导入任何DLL 入口代码读取PEB 通常遍历其中的已加载模块链表找到kernel32/ntdll 再按名称或哈希查找导出函数".to_string()
+        } else {
+            "没有导入任何DLL 加载器只映射文件而不解析API 代码需要自行定位所需的函数".to_string()
+        },
+    });
+    if !has_directory(data_directories, IMAGE_DIRECTORY_ENTRY_BASERELOC) {
+        missing.push(MissingPart {
+            part: "重定位表".to_string(),
+            explanation: if self_relocating.is_some() {
+                "没有重定位表 代码在运行时获取自身地址 与加载基址无关 可以直接注入到任意内存中执行"
+                    .to_string()
+            } else {
+                "没有重定位表 代码不是位置无关时只能加载在首选基址".to_string()
+            },
+        });
+    }
+    if headers_only {
+        missing.push(MissingPart {
+            part: "节区".to_string(),
+            explanation:
+                "没有含数据的节区 或入口点位于PE头中 代码直接放在头部 常见于手工构造的最小PE"
+                    .to_string(),
+        });
+    }
+    if !has_directory(data_directories, IMAGE_DIRECTORY_ENTRY_RESOURCE) {
+        missing.push(MissingPart {
+            part: "资源".to_string(),
+            explanation: "没有资源 也就没有版本信息、图标和清单".to_string(),
+        });
+    }
+    if !has_directory(data_directories, IMAGE_DIRECTORY_ENTRY_DEBUG) {
+        missing.push(MissingPart {
+            part: "调试信息".to_string(),
+            explanation: "没有调试目录 不含PDB路径和编译工具信息".to_string(),
+        });
+    }
+
+    let mut traits: Vec<&str> = vec!["无导入"];
+    if headers_only {
+        traits.push("代码位于头部");
+    } else if tiny_sections {
+        traits.push("节区很小");
+    }
+    if self_relocating.is_some() {
+        traits.push("自定位代码");
+    }
+    if peb_access.is_some() {
+        traits.push("读取PEB");
+    }
+    let summary = format!(
+        "{}: 这是包装成PE的加载器存根或shellcode 常规的导入、资源等表格为空是正常现象 应重点查看入口点代码",
+        traits.join("、")
+    );
+
+    Some(StubInfo {
+        headers_only,
+        tiny_sections,
+        raw_size,
+        self_relocating,
+        peb_access,
+        missing,
+        summary,
+    })
+}
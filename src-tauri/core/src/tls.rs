@@ -0,0 +1,220 @@
+// TLS目录解析
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::find_all;
+use crate::debug::PogoInfo;
+use crate::reader::{BoundedReader, ReadError};
+use crate::{rva_to_offset, DataDirectory, ImportTableEntry, Section};
+
+// 回调数组的读取上限 防止构造的文件让回调列表无限延伸
+const MAX_TLS_CALLBACKS: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TlsInfo {
+    pub start_address_of_raw_data: u64,
+    pub end_address_of_raw_data: u64,
+    pub address_of_index: u64,
+    pub address_of_callbacks: u64,
+    // 回调函数的RVA 按数组顺序排列
+    pub callbacks: Vec<u32>,
+}
+
+// 编译器运行库自带的TLS回调 这些回调不说明程序有意在入口点之前执行代码
+#[derive(Debug, Default)]
+pub(crate) struct RuntimeCallbacks {
+    pub runtimes: Vec<&'static str>,
+    pub count: usize,
+}
+
+// MSVC CRT的__dyn_tls_init和__dyn_tls_dtor分别位于.CRT$XLC和.CRT$XLD
+const MSVC_CALLBACK_SECTIONS: &[&str] = &[".CRT$XLC", ".CRT$XLD"];
+// mingw-w64 CRT的伪重定位代码中的错误信息 它注册__dyn_tls_init和__mingw_TLScallback两个回调
+const MINGW_MARKERS: &[&str] = &["Mingw-w64 runtime failure", "Mingw runtime failure"];
+
+// 根据POGO贡献节、CRT导入和运行库特征估计运行库注册的回调数
+pub(crate) fn runtime_callbacks(
+    data: &[u8],
+    pogo: Option<&PogoInfo>,
+    import_table: &[ImportTableEntry],
+    is_rust: bool,
+    is_x64: bool,
+) -> RuntimeCallbacks {
+    let mut runtime = RuntimeCallbacks::default();
+    let pointer_size: u32 = if is_x64 { 8 } else { 4 };
+    // POGO记录了各.CRT$XL*贡献的大小 可以精确得到CRT回调数
+    let pogo_callbacks = pogo.map(|pogo| {
+        pogo.entries
+            .iter()
+            .filter(|entry| MSVC_CALLBACK_SECTIONS.contains(&entry.name.as_str()))
+            .map(|entry| (entry.size / pointer_size) as usize)
+            .sum::<usize>()
+    });
+    let msvc_crt = import_table.iter().any(|entry| {
+        let dll_name = entry.dll_name.to_ascii_lowercase();
+        dll_name.starts_with("vcruntime")
+            || (dll_name.starts_with("msvcr") && dll_name != "msvcrt.dll")
+    });
+    match pogo_callbacks {
+        Some(count) if count > 0 => {
+            runtime.runtimes.push("MSVC CRT");
+            runtime.count += count;
+        }
+        None if msvc_crt => {
+            runtime.runtimes.push("MSVC CRT");
+            runtime.count += MSVC_CALLBACK_SECTIONS.len();
+        }
+        _ => {}
+    }
+    if MINGW_MARKERS
+        .iter()
+        .any(|marker| !find_all(data, marker.as_bytes(), 1).is_empty())
+    {
+        runtime.runtimes.push("MinGW CRT");
+        runtime.count += 2;
+    }
+    // Rust标准库在.CRT$XLB中注册一个回调 用于运行线程局部变量的析构函数
+    if is_rust {
+        runtime.runtimes.push("Rust std");
+        runtime.count += 1;
+    }
+    runtime
+}
+
+pub(crate) fn parse_tls(
+    reader: BoundedReader,
+    sections: &[Section],
+    directory: DataDirectory,
+    is_x64: bool,
+    image_base: u64,
+) -> Result<Option<TlsInfo>, ReadError> {
+    if directory.rva == 0 || directory.size == 0 {
+        return Ok(None);
+    }
+    let tls_ptr = match rva_to_offset(sections, directory.rva) {
+        Some(ptr) => ptr as u64,
+        None => return Ok(None),
+    };
+
+    // 64位文件中前四个字段为8字节的VA
+    let pointer_size: u64 = if is_x64 { 8 } else { 4 };
+    let read_pointer = |offset: u64| reader.pointer(offset, is_x64);
+
+    let start_address_of_raw_data = read_pointer(tls_ptr)?;
+    let end_address_of_raw_data = read_pointer(tls_ptr + pointer_size)?;
+    let address_of_index = read_pointer(tls_ptr + pointer_size * 2)?;
+    let address_of_callbacks = read_pointer(tls_ptr + pointer_size * 3)?;
+
+    // 回调数组以0结尾 其中保存的是VA
+    let mut callbacks: Vec<u32> = Vec::new();
+    if address_of_callbacks > image_base {
+        let callbacks_rva = (address_of_callbacks - image_base) as u32;
+        if let Some(callbacks_ptr) = rva_to_offset(sections, callbacks_rva) {
+            for i in 0..MAX_TLS_CALLBACKS as u64 {
+                let callback = read_pointer(callbacks_ptr as u64 + i * pointer_size)?;
+                if callback == 0 {
+                    break;
+                }
+                callbacks.push(callback.wrapping_sub(image_base) as u32);
+            }
+        }
+    }
+
+    Ok(Some(TlsInfo {
+        start_address_of_raw_data,
+        end_address_of_raw_data,
+        address_of_index,
+        address_of_callbacks,
+        callbacks,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::debug::PogoEntry;
+    use crate::heuristics::check_tls_callbacks;
+
+    fn text_section() -> Section {
+        Section {
+            name: ".text".to_string(),
+            raw_name: None,
+            rva: 0x1000,
+            ptr_raw_data: 0x400,
+            size_raw_data: 0x1000,
+            rv_end: 0x2000,
+            characteristics: 0x6000_0020,
+            entropy: 0.0,
+        }
+    }
+
+    fn tls(callbacks: &[u32]) -> TlsInfo {
+        TlsInfo {
+            start_address_of_raw_data: 0,
+            end_address_of_raw_data: 0,
+            address_of_index: 0,
+            address_of_callbacks: 0,
+            callbacks: callbacks.to_vec(),
+        }
+    }
+
+    fn pogo(entries: &[(&str, u32)]) -> PogoInfo {
+        PogoInfo {
+            signature: "LTCG".to_string(),
+            entries: entries
+                .iter()
+                .map(|&(name, size)| PogoEntry {
+                    name: name.to_string(),
+                    rva: 0,
+                    size,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn counts_runtime_callbacks() {
+        let msvc = pogo(&[(".CRT$XLA", 8), (".CRT$XLC", 8), (".CRT$XLD", 8)]);
+        let runtime = runtime_callbacks(&[], Some(&msvc), &[], true, true);
+        assert_eq!(runtime.runtimes, ["MSVC CRT", "Rust std"]);
+        assert_eq!(runtime.count, 3);
+
+        let mingw = b"\0Mingw-w64 runtime failure:\n\0";
+        let runtime = runtime_callbacks(mingw, None, &[], false, false);
+        assert_eq!(runtime.runtimes, ["MinGW CRT"]);
+        assert_eq!(runtime.count, 2);
+
+        // msvcrt.dll是系统自带的CRT MinGW也导入它
+        let imports = [ImportTableEntry {
+            dll_name: "msvcrt.dll".to_string(),
+            raw_dll_name: None,
+            functions: Vec::new(),
+        }];
+        assert_eq!(
+            runtime_callbacks(&[], None, &imports, false, false).count,
+            0
+        );
+    }
+
+    #[test]
+    fn warns_only_beyond_runtime_callbacks() {
+        let sections = [text_section()];
+        let runtime = RuntimeCallbacks {
+            runtimes: vec!["MinGW CRT"],
+            count: 2,
+        };
+        assert!(check_tls_callbacks(&sections, &tls(&[0x1010, 0x1020]), &runtime).is_empty());
+
+        let warnings = check_tls_callbacks(&sections, &tls(&[0x1010, 0x1020, 0x1030]), &runtime);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("其中2个可能由MinGW CRT注册"));
+
+        // 不在代码节内的回调即使数量不超过运行库也要警告
+        let warnings = check_tls_callbacks(&sections, &tls(&[0x1010, 0x3000]), &runtime);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].message.contains("0x3000 不在可执行节区内"));
+
+        let warnings =
+            check_tls_callbacks(&sections, &tls(&[0x1010]), &RuntimeCallbacks::default());
+        assert_eq!(warnings.len(), 1);
+    }
+}
@@ -0,0 +1,105 @@
+// Visual Basic 5/6程序识别 解析入口点push的VB头(EXEPROJECTINFO)
+use serde::{Deserialize, Serialize};
+
+use crate::capabilities::find_all;
+use crate::reader::BoundedReader;
+use crate::{rva_to_offset, ImportTableEntry, Section};
+
+const VB_HEADER_SIGNATURE: &[u8] = b"VB5!";
+const VB_RUNTIMES: &[&str] = &["MSVBVM60.DLL", "MSVBVM50.DLL"];
+// 项目名等字符串的最大长度
+const MAX_NAME_LENGTH: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Vb6Info {
+    pub runtime: String,
+    // VB头的文件偏移
+    pub header_offset: u64,
+    pub project_name: Option<String>,
+    pub project_description: Option<String>,
+    pub exe_name: Option<String>,
+    pub help_file: Option<String>,
+    pub form_count: u16,
+    pub external_count: u16,
+    // ProjectInfo中的aNativeCode为0表示P-Code编译
+    pub native_code: Option<bool>,
+}
+
+fn read_cstring(reader: &BoundedReader, offset: u64) -> Option<String> {
+    let bytes = reader.c_string(offset, MAX_NAME_LENGTH).ok()?;
+    let text = String::from_utf8_lossy(bytes).to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+// 入口点通常是 push VBHeader; call ThunRTMain 否则退回搜索签名
+fn find_header(
+    reader: &BoundedReader,
+    data: &[u8],
+    sections: &[Section],
+    entry_point: u32,
+    image_base: u64,
+) -> Option<u64> {
+    if let Some(entry) = rva_to_offset(sections, entry_point) {
+        let entry = entry as u64;
+        // push imm32 (68 xx xx xx xx) 紧跟 call (E8)
+        if let Ok([0x68, _, _, _, _, 0xE8]) = reader.array::<6>(entry) {
+            let address = reader.u32(entry + 1).ok()? as u64;
+            if let Some(offset) = address
+                .checked_sub(image_base)
+                .and_then(|rva| rva_to_offset(sections, rva as u32))
+            {
+                let offset = offset as u64;
+                if reader.bytes(offset, 4) == Ok(VB_HEADER_SIGNATURE) {
+                    return Some(offset);
+                }
+            }
+        }
+    }
+    find_all(data, VB_HEADER_SIGNATURE, 1)
+        .first()
+        .map(|&offset| offset as u64)
+}
+
+pub(crate) fn detect_vb6(
+    data: &[u8],
+    sections: &[Section],
+    import_table: &[ImportTableEntry],
+    entry_point: u32,
+    image_base: u64,
+) -> Option<Vb6Info> {
+    let runtime = import_table.iter().find_map(|entry| {
+        VB_RUNTIMES
+            .iter()
+            .find(|runtime| entry.dll_name.eq_ignore_ascii_case(runtime))
+    })?;
+    let reader = BoundedReader::new(data);
+    let header = find_header(&reader, data, sections, entry_point, image_base)?;
+
+    // 字符串字段保存的是相对VB头的偏移
+    let string_at = |field: u64| -> Option<String> {
+        let relative = reader.u32(header + field).ok()? as u64;
+        if relative == 0 {
+            return None;
+        }
+        read_cstring(&reader, header + relative)
+    };
+    let native_code = reader
+        .u32(header + 0x30)
+        .ok()
+        .and_then(|address| (address as u64).checked_sub(image_base))
+        .and_then(|rva| rva_to_offset(sections, rva as u32))
+        .and_then(|project_info| reader.u32(project_info as u64 + 0x20).ok())
+        .map(|native| native != 0);
+
+    Some(Vb6Info {
+        runtime: runtime.to_string(),
+        header_offset: header,
+        project_name: string_at(0x64),
+        project_description: string_at(0x58),
+        exe_name: string_at(0x5C),
+        help_file: string_at(0x60),
+        form_count: reader.u16(header + 0x44).unwrap_or(0),
+        external_count: reader.u16(header + 0x46).unwrap_or(0),
+        native_code,
+    })
+}
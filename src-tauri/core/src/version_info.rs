@@ -0,0 +1,168 @@
+// 版本资源 VS_VERSIONINFO 解析固定信息和StringFileInfo中的字符串
+use serde::{Deserialize, Serialize};
+
+use crate::reader::BoundedReader;
+use crate::resources::{resource_data, ResourceEntry, RT_VERSION};
+
+const VS_FIXEDFILEINFO_SIGNATURE: u32 = 0xFEEF_04BD;
+// 嵌套深度上限
+const MAX_DEPTH: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VersionString {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct VersionInfo {
+    // VS_FIXEDFILEINFO中的版本 a.b.c.d
+    pub file_version: Option<String>,
+    pub product_version: Option<String>,
+    // StringTable的语言和代码页 如040904b0
+    pub language: Option<String>,
+    pub strings: Vec<VersionString>,
+}
+
+impl VersionInfo {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.strings
+            .iter()
+            .find(|s| s.key.eq_ignore_ascii_case(key))
+            .map(|s| s.value.as_str())
+    }
+}
+
+fn align4(value: u64) -> u64 {
+    (value + 3) & !3
+}
+
+// 以0结尾的UTF-16字符串 返回字符串和结束位置(含结尾0)
+fn read_utf16z(reader: &BoundedReader, offset: u64, end: u64) -> (String, u64) {
+    let mut units: Vec<u16> = Vec::new();
+    let mut cursor = offset;
+    while cursor + 2 <= end {
+        let unit = reader.u16(cursor).unwrap_or(0);
+        cursor += 2;
+        if unit == 0 {
+            break;
+        }
+        units.push(unit);
+    }
+    (String::from_utf16_lossy(&units), cursor)
+}
+
+// 版本结构中的通用块: wLength, wValueLength, wType, szKey, 对齐, Value, 对齐, Children
+struct Block {
+    key: String,
+    value_start: u64,
+    value_length: u64,
+    // wType为1表示文本值 wValueLength按字符计
+    is_text: bool,
+    children_start: u64,
+    end: u64,
+}
+
+fn parse_block(reader: &BoundedReader, offset: u64, limit: u64) -> Option<Block> {
+    let length = reader.u16(offset).ok()? as u64;
+    let value_length = reader.u16(offset + 2).ok()? as u64;
+    let is_text = reader.u16(offset + 4).ok()? == 1;
+    let end = (offset + length).min(limit);
+    if length < 6 || end <= offset + 6 {
+        return None;
+    }
+    let (key, key_end) = read_utf16z(reader, offset + 6, end);
+    let value_start = align4(key_end);
+    let value_bytes = if is_text {
+        value_length * 2
+    } else {
+        value_length
+    };
+    let children_start = align4(value_start + value_bytes).min(end);
+    Some(Block {
+        key,
+        value_start,
+        value_length,
+        is_text,
+        children_start,
+        end,
+    })
+}
+
+fn children(reader: &BoundedReader, block: &Block) -> Vec<Block> {
+    let mut result: Vec<Block> = Vec::new();
+    let mut cursor = block.children_start;
+    while cursor + 6 <= block.end {
+        let Some(child) = parse_block(reader, cursor, block.end) else {
+            break;
+        };
+        cursor = align4(child.end);
+        result.push(child);
+    }
+    result
+}
+
+fn fixed_version(reader: &BoundedReader, offset: u64) -> Option<String> {
+    let high = reader.u32(offset).ok()?;
+    let low = reader.u32(offset + 4).ok()?;
+    Some(format!(
+        "{}.{}.{}.{}",
+        high >> 16,
+        high & 0xFFFF,
+        low >> 16,
+        low & 0xFFFF
+    ))
+}
+
+fn collect_strings(reader: &BoundedReader, block: &Block, depth: usize, info: &mut VersionInfo) {
+    if depth > MAX_DEPTH {
+        return;
+    }
+    for child in children(reader, block) {
+        if child.key == "StringFileInfo" {
+            collect_strings(reader, &child, depth + 1, info);
+        } else if depth == 1 && child.key.len() == 8 && info.language.is_none() {
+            // StringTable 键为语言+代码页 只读取第一个
+            info.language = Some(child.key.clone());
+            for string in children(reader, &child) {
+                let value = if string.is_text && string.value_length > 0 {
+                    read_utf16z(reader, string.value_start, string.end).0
+                } else {
+                    String::new()
+                };
+                info.strings.push(VersionString {
+                    key: string.key,
+                    value,
+                });
+            }
+        }
+    }
+}
+
+pub(crate) fn parse_version_info(data: &[u8], resources: &[ResourceEntry]) -> Option<VersionInfo> {
+    let resource = resources.iter().find(|r| r.type_id == Some(RT_VERSION))?;
+    let version_data = BoundedReader::new(resource_data(data, resource));
+    let root = parse_block(&version_data, 0, version_data.len())?;
+    if root.key != "VS_VERSION_INFO" {
+        return None;
+    }
+
+    let mut info = VersionInfo::default();
+    // VS_FIXEDFILEINFO: dwSignature, dwStrucVersion, FileVersionMS/LS, ProductVersionMS/LS ...
+    if root.value_length >= 52
+        && version_data.u32(root.value_start) == Ok(VS_FIXEDFILEINFO_SIGNATURE)
+    {
+        info.file_version = fixed_version(&version_data, root.value_start + 8);
+        info.product_version = fixed_version(&version_data, root.value_start + 16);
+    }
+    collect_strings(&version_data, &root, 0, &mut info);
+    Some(info)
+}
+
+// 版本资源声称来自Microsoft
+pub fn claims_microsoft(version_info: Option<&VersionInfo>) -> bool {
+    version_info.is_some_and(|info| {
+        info.get("CompanyName")
+            .is_some_and(|company| company.to_ascii_lowercase().contains("microsoft"))
+    })
+}
@@ -0,0 +1,8 @@
+// 对任意输入运行完整分析 运行: cargo fuzz run analyze
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    pe_info_core::fuzz_analyze(data);
+});
@@ -0,0 +1,151 @@
+// 用本机的apisetschema.dll解析api-ms-win-*/ext-ms-*导入 找出在当前系统版本上没有宿主DLL的API集
+use serde::{Deserialize, Serialize};
+
+use std::sync::OnceLock;
+
+use crate::deps::{find_in_directory, is_api_set, system_directories};
+use crate::reader::{decode_utf16, BoundedReader};
+use crate::ImportTableEntry;
+
+// 只支持Windows 10起的第6版命名空间
+const API_SET_SCHEMA_VERSION: u32 = 6;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ApiSetResolution {
+    pub name: String,
+    // 默认宿主DLL
+    pub host: Option<String>,
+    // hosted / no_host(本系统有此API集但没有宿主) / missing(本系统不认识) / older_version(本系统的版本号更低 可能缺少函数)
+    pub status: String,
+    // 本系统schema中的完整名称
+    pub schema_name: Option<String>,
+}
+
+struct ApiSetEntry {
+    // 小写 不含.dll
+    name: String,
+    // 参与匹配的前缀长度(字符数) 即去掉最后一段版本号
+    hashed_length: usize,
+    host: Option<String>,
+}
+
+static SCHEMA: OnceLock<Option<Vec<ApiSetEntry>>> = OnceLock::new();
+
+// 命名空间中的字符串为UTF-16 偏移相对于命名空间起点 长度为字节数
+fn read_utf16(namespace: &BoundedReader, offset: u32, length: u32) -> Option<String> {
+    let bytes = namespace.bytes(offset as u64, length as u64).ok()?;
+    Some(decode_utf16(bytes))
+}
+
+// 在PE文件中找到.apiset节的原始数据
+fn apiset_section(data: &[u8]) -> Option<&[u8]> {
+    let reader = BoundedReader::new(data);
+    let pe = reader.u32(0x3C).ok()? as u64;
+    let count = reader.u16(pe + 6).ok()? as u64;
+    let optional_size = reader.u16(pe + 20).ok()? as u64;
+    let table = pe + 24 + optional_size;
+    (0..count).find_map(|i| {
+        let header = table + i * 40;
+        if reader.bytes(header, 8).ok()? != b".apiset\0" {
+            return None;
+        }
+        let size = reader.u32(header + 16).ok()? as u64;
+        let offset = reader.u32(header + 20).ok()? as u64;
+        reader.bytes(offset, size).ok()
+    })
+}
+
+// API_SET_NAMESPACE: Version Size Flags Count EntryOffset HashOffset HashFactor
+// 条目(24字节): Flags NameOffset NameLength HashedLength ValueOffset ValueCount
+// 值(20字节): Flags NameOffset NameLength ValueOffset ValueLength 导入方名称为空的值是默认宿主
+fn parse_schema(namespace: &[u8]) -> Option<Vec<ApiSetEntry>> {
+    let namespace = BoundedReader::new(namespace);
+    if namespace.u32(0).ok()? != API_SET_SCHEMA_VERSION {
+        return None;
+    }
+    let count = namespace.u32(12).ok()? as u64;
+    let entry_offset = namespace.u32(16).ok()? as u64;
+    let mut entries: Vec<ApiSetEntry> = Vec::new();
+    for i in 0..count {
+        let entry = entry_offset + i * 24;
+        let name = read_utf16(
+            &namespace,
+            namespace.u32(entry + 4).ok()?,
+            namespace.u32(entry + 8).ok()?,
+        )?;
+        let hashed_length = namespace.u32(entry + 12).ok()? as usize / 2;
+        let value_offset = namespace.u32(entry + 16).ok()? as u64;
+        let value_count = namespace.u32(entry + 20).ok()? as u64;
+        // 值数组越界时后面的值都读不到 不必继续
+        let host = (0..value_count)
+            .map_while(|j| {
+                let value = value_offset + j * 20;
+                Some((namespace.u32(value + 8).ok()?, value))
+            })
+            .min_by_key(|(importer_length, _)| *importer_length)
+            .and_then(|(_, value)| {
+                read_utf16(
+                    &namespace,
+                    namespace.u32(value + 12).ok()?,
+                    namespace.u32(value + 16).ok()?,
+                )
+            })
+            .filter(|host| !host.is_empty());
+        entries.push(ApiSetEntry {
+            name: name.to_ascii_lowercase(),
+            hashed_length,
+            host,
+        });
+    }
+    Some(entries)
+}
+
+fn load_schema() -> Option<Vec<ApiSetEntry>> {
+    // 64位系统上schema只在System32中
+    let path = system_directories(true)
+        .iter()
+        .find_map(|directory| find_in_directory(directory, "apisetschema.dll"))?;
+    let data = std::fs::read(path).ok()?;
+    parse_schema(apiset_section(&data)?)
+}
+
+// 最后一段版本号 如 l1-1-3 中的3
+fn minor_version(name: &str) -> Option<u32> {
+    name.rsplit('-').next()?.parse().ok()
+}
+
+fn resolve(schema: &[ApiSetEntry], dll_name: &str) -> ApiSetResolution {
+    let lower = dll_name.to_ascii_lowercase();
+    let name = lower.strip_suffix(".dll").unwrap_or(&lower);
+    // 与加载器相同 只比较去掉最后一段版本号的前缀
+    let prefix = name.rsplit_once('-').map_or(name, |(prefix, _)| prefix);
+    let entry = schema
+        .iter()
+        .find(|entry| entry.name.get(..entry.hashed_length) == Some(prefix));
+    let status = match entry {
+        None => "missing",
+        Some(entry) if entry.host.is_none() => "no_host",
+        Some(entry) if minor_version(name) > minor_version(&entry.name) => "older_version",
+        Some(_) => "hosted",
+    };
+    ApiSetResolution {
+        name: dll_name.to_string(),
+        host: entry.and_then(|entry| entry.host.clone()),
+        status: status.to_string(),
+        schema_name: entry.map(|entry| entry.name.clone()),
+    }
+}
+
+// 本机没有schema(非Windows或读取失败)时返回None
+pub(crate) fn resolve_api_set(dll_name: &str) -> Option<ApiSetResolution> {
+    let schema = SCHEMA.get_or_init(load_schema).as_ref()?;
+    Some(resolve(schema, dll_name))
+}
+
+pub(crate) fn resolve_api_sets(import_table: &[ImportTableEntry]) -> Vec<ApiSetResolution> {
+    import_table
+        .iter()
+        .filter(|import| is_api_set(&import.dll_name))
+        .filter_map(|import| resolve_api_set(&import.dll_name))
+        .collect()
+}
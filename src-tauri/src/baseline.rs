@@ -0,0 +1,358 @@
+// 基线清单 记录一组二进制的哈希/imphash/签名者/导出 之后用于校验构建输出是否被篡改
+// 也可只比对SHA-256 清单可以是基线或sha256sum格式 适合大目录的发布完整性审计
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::triage::quick_triage;
+use crate::{analyze, PeInfo};
+
+const BASELINE_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BaselineEntry {
+    // 相对基线根目录的路径 统一使用'/'分隔
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    pub imphash: String,
+    pub signer: Option<String>,
+    pub exports: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Baseline {
+    pub version: u32,
+    pub entries: Vec<BaselineEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviationKind {
+    // 基线中有 目录中没有
+    Missing,
+    // 目录中有 基线中没有
+    Unexpected,
+    // 文件存在但无法解析
+    Unreadable,
+    Size,
+    Hash,
+    Imphash,
+    Signer,
+    ExportAdded,
+    ExportRemoved,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Deviation {
+    pub path: String,
+    pub kind: DeviationKind,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DigestVerification {
+    // 清单中的文件数
+    pub expected: usize,
+    // 哈希一致的文件数
+    pub matched: usize,
+    pub deviations: Vec<Deviation>,
+}
+
+// 递归列出目录下的所有文件 按路径排序
+fn collect_files(directory: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(directory).map_err(|e| format!("无法读取目录: {}", e))?;
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn export_names(pe_info: &PeInfo) -> Vec<String> {
+    pe_info
+        .export_table
+        .iter()
+        .filter(|f| f.address != 0)
+        .map(|f| {
+            if !f.is_named {
+                format!("#{}", f.ordinal)
+            } else {
+                f.name.clone()
+            }
+        })
+        .collect()
+}
+
+fn baseline_entry(path: String, pe_info: &PeInfo) -> BaselineEntry {
+    BaselineEntry {
+        path,
+        size: pe_info.size,
+        sha256: pe_info.hashes.sha256.clone(),
+        imphash: pe_info.imphash.clone(),
+        signer: pe_info
+            .signature
+            .as_ref()
+            .and_then(|signature| signature.signer.clone()),
+        exports: export_names(pe_info),
+    }
+}
+
+// 目录中能解析为PE的文件都会记入基线
+pub(crate) fn create_baseline(directory: &str) -> Result<Baseline, String> {
+    let root = Path::new(directory);
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect_files(root, &mut files)?;
+    files.sort();
+
+    let entries = files
+        .iter()
+        .filter_map(|path| {
+            let pe_info = analyze(path.clone()).ok()?;
+            Some(baseline_entry(relative_path(root, path), &pe_info))
+        })
+        .collect();
+    Ok(Baseline {
+        version: BASELINE_VERSION,
+        entries,
+    })
+}
+
+fn deviation(
+    path: &str,
+    kind: DeviationKind,
+    expected: Option<String>,
+    actual: Option<String>,
+) -> Deviation {
+    Deviation {
+        path: path.to_string(),
+        kind,
+        expected,
+        actual,
+    }
+}
+
+fn compare_entry(
+    expected: &BaselineEntry,
+    actual: &BaselineEntry,
+    deviations: &mut Vec<Deviation>,
+) {
+    let path = expected.path.as_str();
+    if expected.size != actual.size {
+        deviations.push(deviation(
+            path,
+            DeviationKind::Size,
+            Some(expected.size.to_string()),
+            Some(actual.size.to_string()),
+        ));
+    }
+    if expected.sha256 != actual.sha256 {
+        deviations.push(deviation(
+            path,
+            DeviationKind::Hash,
+            Some(expected.sha256.clone()),
+            Some(actual.sha256.clone()),
+        ));
+    }
+    if expected.imphash != actual.imphash {
+        deviations.push(deviation(
+            path,
+            DeviationKind::Imphash,
+            Some(expected.imphash.clone()),
+            Some(actual.imphash.clone()),
+        ));
+    }
+    if expected.signer != actual.signer {
+        deviations.push(deviation(
+            path,
+            DeviationKind::Signer,
+            expected.signer.clone(),
+            actual.signer.clone(),
+        ));
+    }
+
+    let expected_exports: BTreeSet<&String> = expected.exports.iter().collect();
+    let actual_exports: BTreeSet<&String> = actual.exports.iter().collect();
+    for name in actual_exports.difference(&expected_exports) {
+        deviations.push(deviation(
+            path,
+            DeviationKind::ExportAdded,
+            None,
+            Some(name.to_string()),
+        ));
+    }
+    for name in expected_exports.difference(&actual_exports) {
+        deviations.push(deviation(
+            path,
+            DeviationKind::ExportRemoved,
+            Some(name.to_string()),
+            None,
+        ));
+    }
+}
+
+// 按相对路径逐个比对 报告所有偏差
+pub(crate) fn verify_baseline(
+    baseline: &Baseline,
+    directory: &str,
+) -> Result<Vec<Deviation>, String> {
+    let root = Path::new(directory);
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect_files(root, &mut files)?;
+    let mut files_by_path: HashMap<String, PathBuf> = files
+        .into_iter()
+        .map(|path| (relative_path(root, &path), path))
+        .collect();
+
+    let mut deviations: Vec<Deviation> = Vec::new();
+    for expected in &baseline.entries {
+        let Some(path) = files_by_path.remove(&expected.path) else {
+            deviations.push(deviation(
+                &expected.path,
+                DeviationKind::Missing,
+                Some(expected.sha256.clone()),
+                None,
+            ));
+            continue;
+        };
+        match analyze(path.clone()) {
+            Ok(pe_info) => {
+                let actual = baseline_entry(expected.path.clone(), &pe_info);
+                compare_entry(expected, &actual, &mut deviations);
+            }
+            Err(e) => deviations.push(deviation(
+                &expected.path,
+                DeviationKind::Unreadable,
+                Some(expected.sha256.clone()),
+                Some(e),
+            )),
+        }
+    }
+
+    // 基线之外新增的PE文件
+    let mut unexpected: Vec<(String, PathBuf)> = files_by_path.into_iter().collect();
+    unexpected.sort();
+    for (relative, path) in unexpected {
+        if let Ok(pe_info) = analyze(path.clone()) {
+            deviations.push(deviation(
+                &relative,
+                DeviationKind::Unexpected,
+                None,
+                Some(pe_info.hashes.sha256.clone()),
+            ));
+        }
+    }
+    Ok(deviations)
+}
+
+// 统一为基线中的相对路径形式
+fn normalize_manifest_path(path: &str) -> String {
+    let path = path.replace('\\', "/");
+    path.trim_start_matches("./").to_string()
+}
+
+// 清单为基线JSON 或每行"<SHA-256> <路径>"(sha256sum输出 二进制模式的路径带*前缀)
+fn parse_manifest(content: &str) -> Result<Vec<(String, String)>, String> {
+    if let Ok(baseline) = serde_json::from_str::<Baseline>(content) {
+        return Ok(baseline
+            .entries
+            .into_iter()
+            .map(|entry| (normalize_manifest_path(&entry.path), entry.sha256))
+            .collect());
+    }
+    let mut entries: Vec<(String, String)> = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (hash, path) = line
+            .split_once(char::is_whitespace)
+            .filter(|(hash, _)| hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| format!("清单第{}行格式错误: {}", number + 1, line))?;
+        let path = path.trim_start();
+        let path = path.strip_prefix('*').unwrap_or(path);
+        entries.push((normalize_manifest_path(path), hash.to_ascii_lowercase()));
+    }
+    if entries.is_empty() {
+        return Err("清单中没有文件".into());
+    }
+    Ok(entries)
+}
+
+// 只比对SHA-256 不做完整分析 目录中清单之外的PE文件报告为unexpected
+pub(crate) fn verify_digests(
+    manifest: &str,
+    directory: &str,
+) -> Result<DigestVerification, String> {
+    let expected = parse_manifest(manifest)?;
+    let root = Path::new(directory);
+    let mut files: Vec<PathBuf> = Vec::new();
+    collect_files(root, &mut files)?;
+    let mut files_by_path: HashMap<String, PathBuf> = files
+        .into_iter()
+        .map(|path| (relative_path(root, &path), path))
+        .collect();
+
+    let mut matched = 0;
+    let mut deviations: Vec<Deviation> = Vec::new();
+    for (relative, sha256) in &expected {
+        let Some(path) = files_by_path.remove(relative) else {
+            deviations.push(deviation(
+                relative,
+                DeviationKind::Missing,
+                Some(sha256.clone()),
+                None,
+            ));
+            continue;
+        };
+        match quick_triage(&path.to_string_lossy()) {
+            Ok(triage) if triage.sha256 == *sha256 => matched += 1,
+            Ok(triage) => deviations.push(deviation(
+                relative,
+                DeviationKind::Hash,
+                Some(sha256.clone()),
+                Some(triage.sha256),
+            )),
+            Err(e) => deviations.push(deviation(
+                relative,
+                DeviationKind::Unreadable,
+                Some(sha256.clone()),
+                Some(e),
+            )),
+        }
+    }
+
+    let mut unexpected: Vec<(String, PathBuf)> = files_by_path.into_iter().collect();
+    unexpected.sort();
+    for (relative, path) in unexpected {
+        if let Ok(triage) = quick_triage(&path.to_string_lossy()) {
+            if triage.invalid.is_none() {
+                deviations.push(deviation(
+                    &relative,
+                    DeviationKind::Unexpected,
+                    None,
+                    Some(triage.sha256),
+                ));
+            }
+        }
+    }
+    Ok(DigestVerification {
+        expected: expected.len(),
+        matched,
+        deviations,
+    })
+}
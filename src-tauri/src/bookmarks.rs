@@ -0,0 +1,125 @@
+// 地址书签和备注 按文件SHA-256保存 文件改名或移动后仍能找回 分析结果和摘要报告中附带
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Bookmark {
+    // 只给出其一时由另一个换算 不在任何节内的地址只有其中一个
+    pub rva: Option<u32>,
+    pub offset: Option<u64>,
+    pub note: String,
+    // Unix时间戳 最后修改时间
+    pub modified: u64,
+}
+
+impl Bookmark {
+    // 同一地址只保留一条书签 有RVA时按RVA比较
+    fn same_address(&self, rva: Option<u32>, offset: Option<u64>) -> bool {
+        match (self.rva, rva) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.offset.is_some() && self.offset == offset,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Store {
+    // 本地JSON文件 SHA-256 -> 书签列表
+    path: Option<PathBuf>,
+    files: HashMap<String, Vec<Bookmark>>,
+}
+
+static STORE: LazyLock<RwLock<Store>> = LazyLock::new(|| RwLock::new(Store::default()));
+
+pub(crate) fn open(path: PathBuf) -> Result<(), String> {
+    let files: HashMap<String, Vec<Bookmark>> = if path.exists() {
+        let text = fs::read_to_string(&path).map_err(|e| format!("无法读取书签: {}", e))?;
+        serde_json::from_str(&text).map_err(|e| format!("无法解析书签: {}", e))?
+    } else {
+        HashMap::new()
+    };
+    let mut store = STORE.write().map_err(|_| String::from("书签不可用"))?;
+    store.path = Some(path);
+    store.files = files;
+    Ok(())
+}
+
+fn persist(store: &Store) -> Result<(), String> {
+    if let Some(path) = &store.path {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&store.files).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("无法写入书签: {}", e))?;
+    }
+    Ok(())
+}
+
+// 按RVA排序 只有文件偏移的排在最后
+fn sorted(mut bookmarks: Vec<Bookmark>) -> Vec<Bookmark> {
+    bookmarks.sort_by_key(|bookmark| (bookmark.rva.is_none(), bookmark.rva, bookmark.offset));
+    bookmarks
+}
+
+pub(crate) fn for_file(sha256: &str) -> Vec<Bookmark> {
+    STORE
+        .read()
+        .ok()
+        .and_then(|store| store.files.get(sha256).cloned())
+        .map(sorted)
+        .unwrap_or_default()
+}
+
+// 新增或覆盖同一地址的书签 返回该文件的全部书签
+pub(crate) fn set(
+    sha256: &str,
+    rva: Option<u32>,
+    offset: Option<u64>,
+    note: &str,
+) -> Result<Vec<Bookmark>, String> {
+    if rva.is_none() && offset.is_none() {
+        return Err("书签缺少地址".into());
+    }
+    let note = note.trim();
+    if note.is_empty() {
+        return Err("书签备注为空".into());
+    }
+    let modified = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let mut store = STORE.write().map_err(|_| String::from("书签不可用"))?;
+    let bookmarks = store.files.entry(sha256.to_string()).or_default();
+    bookmarks.retain(|bookmark| !bookmark.same_address(rva, offset));
+    bookmarks.push(Bookmark {
+        rva,
+        offset,
+        note: note.to_string(),
+        modified,
+    });
+    let result = sorted(bookmarks.clone());
+    persist(&store)?;
+    Ok(result)
+}
+
+pub(crate) fn remove(
+    sha256: &str,
+    rva: Option<u32>,
+    offset: Option<u64>,
+) -> Result<Vec<Bookmark>, String> {
+    let mut store = STORE.write().map_err(|_| String::from("书签不可用"))?;
+    let Some(bookmarks) = store.files.get_mut(sha256) else {
+        return Ok(Vec::new());
+    };
+    bookmarks.retain(|bookmark| !bookmark.same_address(rva, offset));
+    let result = sorted(bookmarks.clone());
+    if result.is_empty() {
+        store.files.remove(sha256);
+    }
+    persist(&store)?;
+    Ok(result)
+}
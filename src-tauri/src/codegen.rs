@@ -0,0 +1,514 @@
+// 根据导出表生成各类脚本/代码骨架
+use crate::demangle::{demangle, undecorate_stdcall};
+use crate::heuristics::IMAGE_SCN_CNT_CODE;
+use crate::{ExportFunction, Section};
+
+// JavaScript单引号字符串转义
+fn js_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('\'');
+    for c in text.chars() {
+        match c {
+            '\'' => escaped.push_str("\\'"),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped.push('\'');
+    escaped
+}
+
+// Frida脚本: 每个导出函数一个Interceptor.attach 参数打印留作占位
+// 有名称的导出通过名称解析 仅序号导出通过模块基址加RVA定位
+pub(crate) fn frida_script(module_name: &str, exports: &[&ExportFunction]) -> String {
+    let mut script = String::new();
+    script.push_str(&format!(
+        "// Frida hook script generated by pe_info for {}\n",
+        module_name
+    ));
+    script.push_str("// usage: frida -l <this file> -n <process> (or -f <program>)\n\n");
+    script.push_str(&format!("const moduleName = {};\n", js_string(module_name)));
+    script.push_str("const module = Process.getModuleByName(moduleName);\n\n");
+    script.push_str("function hook(label, address) {\n");
+    script.push_str("  Interceptor.attach(address, {\n");
+    script.push_str("    onEnter(args) {\n");
+    script.push_str("      // TODO: adjust the argument count and types for this function\n");
+    script.push_str(
+        "      console.log(`[+] ${label}(${args[0]}, ${args[1]}, ${args[2]}, ${args[3]})`);\n",
+    );
+    script.push_str("    },\n");
+    script.push_str("    onLeave(retval) {\n");
+    script.push_str("      console.log(`[-] ${label} => ${retval}`);\n");
+    script.push_str("    },\n");
+    script.push_str("  });\n");
+    script.push_str("}\n\n");
+
+    for export in exports {
+        if !export.is_named {
+            script.push_str(&format!(
+                "hook('ordinal #{}', module.base.add(0x{:X}));\n",
+                export.ordinal, export.address
+            ));
+        } else {
+            script.push_str(&format!(
+                "hook({}, module.getExportByName({}));\n",
+                js_string(&export.name),
+                js_string(&export.name)
+            ));
+        }
+    }
+    script
+}
+
+const RUST_KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try", "gen",
+];
+
+// 转换为合法的Rust标识符
+fn rust_ident(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if RUST_KEYWORDS.contains(&ident.as_str()) {
+        if matches!(ident.as_str(), "self" | "Self" | "super" | "crate") {
+            ident.push('_');
+        } else {
+            ident.insert_str(0, "r#");
+        }
+    }
+    ident
+}
+
+fn rust_string(text: &str) -> String {
+    format!("{:?}", text)
+}
+
+// Rust FFI骨架: 有名称的导出按名称链接 仅序号导出使用raw-dylib按序号链接
+pub(crate) fn rust_bindings(
+    module_name: &str,
+    exports: &[&ExportFunction],
+    demangle_names: bool,
+) -> String {
+    let library = module_name
+        .rsplit_once('.')
+        .map(|(stem, _)| stem)
+        .unwrap_or(module_name);
+    let mut code = String::new();
+    code.push_str(&format!(
+        "// Rust FFI bindings generated by pe_info for {}\n",
+        module_name
+    ));
+    code.push_str(
+        "// Signatures are unknown: replace the placeholder parameters and return types.\n\n",
+    );
+    code.push_str("#![allow(non_snake_case, dead_code)]\n\n");
+    code.push_str(&format!(
+        "pub mod {} {{\n",
+        rust_ident(&library.to_lowercase())
+    ));
+
+    let (named, by_ordinal): (Vec<&&ExportFunction>, Vec<&&ExportFunction>) =
+        exports.iter().partition(|f| f.is_named);
+    let mut used: Vec<String> = Vec::new();
+    let mut unique_ident = |base: String| -> String {
+        let mut ident = base.clone();
+        let mut n = 1;
+        while used.contains(&ident) {
+            n += 1;
+            ident = format!("{}_{}", base, n);
+        }
+        used.push(ident.clone());
+        ident
+    };
+
+    if !named.is_empty() {
+        code.push_str(&format!("    #[link(name = {})]\n", rust_string(library)));
+        code.push_str("    extern \"system\" {\n");
+        for export in named {
+            let stdcall = undecorate_stdcall(&export.name);
+            let demangled = if demangle_names {
+                demangle(&export.name)
+            } else {
+                None
+            };
+            let base_name = match (&demangled, &stdcall) {
+                (Some(text), _) => text.split('(').next().unwrap_or(text).replace("::", "_"),
+                (None, Some((base, _))) => base.clone(),
+                (None, None) => export.name.clone(),
+            };
+            let ident = unique_ident(rust_ident(&base_name));
+            code.push_str(&format!(
+                "        /// ordinal {}, RVA 0x{:X}\n",
+                export.ordinal, export.address
+            ));
+            if let Some(text) = &demangled {
+                code.push_str(&format!("        /// demangled: {}\n", text));
+            }
+            if ident.trim_start_matches("r#") != export.name {
+                code.push_str(&format!(
+                    "        #[link_name = {}]\n",
+                    rust_string(&export.name)
+                ));
+            }
+            // stdcall修饰名中的字节数可以推算出参数个数
+            let params = match stdcall {
+                Some((_, bytes)) => (0..bytes / 4)
+                    .map(|i| format!("arg{}: usize", i))
+                    .collect::<Vec<String>>()
+                    .join(", "),
+                None => "/* TODO: parameters */".to_string(),
+            };
+            code.push_str(&format!("        pub fn {}({}) -> usize;\n", ident, params));
+        }
+        code.push_str("    }\n");
+    }
+
+    if !by_ordinal.is_empty() {
+        if code.ends_with("}\n") {
+            code.push('\n');
+        }
+        code.push_str(&format!(
+            "    #[link(name = {}, kind = \"raw-dylib\")]\n",
+            rust_string(module_name)
+        ));
+        code.push_str("    extern \"system\" {\n");
+        for export in by_ordinal {
+            code.push_str(&format!("        /// RVA 0x{:X}\n", export.address));
+            code.push_str(&format!("        #[link_ordinal({})]\n", export.ordinal));
+            code.push_str(&format!(
+                "        pub fn {}(/* TODO: parameters */) -> usize;\n",
+                unique_ident(format!("ordinal_{}", export.ordinal))
+            ));
+        }
+        code.push_str("    }\n");
+    }
+
+    code.push_str("}\n");
+    code
+}
+
+fn is_c_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// C头文件: 签名未知的函数声明为变参占位 x86 stdcall修饰名按字节数生成参数
+pub(crate) fn c_header(module_name: &str, exports: &[&ExportFunction]) -> String {
+    let guard: String = module_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let mut header = String::new();
+    header.push_str(&format!(
+        "/* C header generated by pe_info for {} */\n",
+        module_name
+    ));
+    header.push_str(
+        "/* Signatures are unknown: replace the variadic placeholders and return types. */\n\n",
+    );
+    header.push_str(&format!("#ifndef PE_INFO_{}_H\n", guard));
+    header.push_str(&format!("#define PE_INFO_{}_H\n\n", guard));
+    header.push_str("#ifdef __cplusplus\nextern \"C\" {\n#endif\n\n");
+
+    for export in exports {
+        if !export.is_named {
+            header.push_str(&format!(
+                "/* ordinal {} (no name), RVA 0x{:X}: resolve with GetProcAddress(module, MAKEINTRESOURCEA({})) */\n",
+                export.ordinal, export.address, export.ordinal
+            ));
+            continue;
+        }
+        header.push_str(&format!(
+            "/* ordinal {}, RVA 0x{:X} */\n",
+            export.ordinal, export.address
+        ));
+        if let Some((base, bytes)) = undecorate_stdcall(&export.name) {
+            let params = if bytes == 0 {
+                "void".to_string()
+            } else {
+                (0..bytes / 4)
+                    .map(|i| format!("void *arg{}", i))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            };
+            header.push_str(&format!(
+                "__declspec(dllimport) int __stdcall {}({});\n",
+                base, params
+            ));
+        } else if let (Some(bytes), true) = (export.stack_bytes, is_c_identifier(&export.name)) {
+            // 从ret imm16推测的参数字节数
+            let params = if bytes == 0 {
+                "void".to_string()
+            } else {
+                (0..bytes / 4)
+                    .map(|i| format!("void *arg{}", i))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            };
+            header.push_str(&format!(
+                "__declspec(dllimport) int __stdcall {}({}); /* inferred */\n",
+                export.name, params
+            ));
+        } else if is_c_identifier(&export.name) {
+            header.push_str(&format!(
+                "__declspec(dllimport) int {}(...);\n",
+                export.name
+            ));
+        } else {
+            // C++修饰名等无法在C中直接声明
+            match demangle(&export.name) {
+                Some(text) => header.push_str(&format!(
+                    "/* {} ({}) cannot be declared in C */\n",
+                    export.name, text
+                )),
+                None => {
+                    header.push_str(&format!("/* {} cannot be declared in C */\n", export.name))
+                }
+            }
+        }
+    }
+
+    header.push_str("\n#ifdef __cplusplus\n}\n#endif\n\n");
+    header.push_str(&format!("#endif /* PE_INFO_{}_H */\n", guard));
+    header
+}
+
+// 模块定义文件 可用lib.exe /def生成导入库
+pub(crate) fn def_file(module_name: &str, exports: &[&ExportFunction]) -> String {
+    let mut def = String::new();
+    def.push_str(&format!("LIBRARY \"{}\"\n", module_name));
+    def.push_str("EXPORTS\n");
+    for export in exports {
+        if !export.is_named {
+            def.push_str(&format!(
+                "    ordinal_{} @{} NONAME\n",
+                export.ordinal, export.ordinal
+            ));
+        } else {
+            def.push_str(&format!("    {} @{}\n", export.name, export.ordinal));
+        }
+    }
+    def
+}
+
+// 反汇编器符号: 导出函数名称以及异常目录中的函数起始地址
+pub(crate) struct Symbol {
+    pub rva: u32,
+    pub name: String,
+    pub is_function: bool,
+}
+
+pub(crate) fn collect_symbols(exports: &[&ExportFunction], function_starts: &[u32]) -> Vec<Symbol> {
+    let mut symbols: Vec<Symbol> = exports
+        .iter()
+        .map(|f| Symbol {
+            rva: f.address,
+            name: if !f.is_named {
+                format!("ordinal_{}", f.ordinal)
+            } else {
+                f.name.clone()
+            },
+            is_function: true,
+        })
+        .collect();
+    for &rva in function_starts {
+        if !symbols.iter().any(|s| s.rva == rva) {
+            symbols.push(Symbol {
+                rva,
+                name: String::new(),
+                is_function: true,
+            });
+        }
+    }
+    symbols.sort_by_key(|s| s.rva);
+    symbols
+}
+
+fn c_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// IDA IDC脚本 以映像基址为基准 兼容重定位后的数据库
+pub(crate) fn idc_script(module_name: &str, symbols: &[Symbol]) -> String {
+    let mut script = String::new();
+    script.push_str(&format!(
+        "// IDA symbol script generated by pe_info for {}\n",
+        module_name
+    ));
+    script.push_str("#include <idc.idc>\n\n");
+    script.push_str("static main()\n{\n");
+    script.push_str("    auto base = get_imagebase();\n");
+    for symbol in symbols {
+        if symbol.is_function {
+            script.push_str(&format!("    add_func(base + 0x{:X});\n", symbol.rva));
+        }
+        if !symbol.name.is_empty() {
+            script.push_str(&format!(
+                "    set_name(base + 0x{:X}, {}, SN_NOWARN | SN_NOCHECK);\n",
+                symbol.rva,
+                c_string(&symbol.name)
+            ));
+        }
+    }
+    script.push_str("}\n");
+    script
+}
+
+// Ghidra Python脚本 在Script Manager中运行
+pub(crate) fn ghidra_script(module_name: &str, symbols: &[Symbol]) -> String {
+    let mut script = String::new();
+    script.push_str(&format!(
+        "# Ghidra symbol script generated by pe_info for {}\n",
+        module_name
+    ));
+    script.push_str("# @category pe_info\n");
+    script.push_str("from ghidra.program.model.symbol import SourceType\n\n");
+    script.push_str("base = currentProgram.getImageBase()\n");
+    script.push_str("symbols = [\n");
+    for symbol in symbols {
+        script.push_str(&format!(
+            "    (0x{:X}, {}),\n",
+            symbol.rva,
+            if symbol.name.is_empty() {
+                "None".to_string()
+            } else {
+                c_string(&symbol.name)
+            }
+        ));
+    }
+    script.push_str("]\n\n");
+    script.push_str("for rva, name in symbols:\n");
+    script.push_str("    address = base.add(rva)\n");
+    script.push_str("    if getFunctionAt(address) is None:\n");
+    script.push_str("        createFunction(address, None)\n");
+    script.push_str("    if name is not None:\n");
+    script.push_str("        createLabel(address, name, True, SourceType.IMPORTED)\n");
+    script
+}
+
+// MSVC链接器风格的.map文件 IDA/x64dbg等工具均可加载
+pub(crate) fn map_file(
+    module_name: &str,
+    image_base: u64,
+    sections: &[Section],
+    symbols: &[Symbol],
+) -> String {
+    let mut map = String::new();
+    map.push_str(&format!(" {}\n\n", module_name));
+    map.push_str(&format!(
+        " Preferred load address is {:016X}\n\n",
+        image_base
+    ));
+    map.push_str(" Start         Length     Name                   Class\n");
+    for (i, section) in sections.iter().enumerate() {
+        map.push_str(&format!(
+            " {:04X}:00000000 {:08X}H {:<23} {}\n",
+            i + 1,
+            section.rv_end - section.rva,
+            section.name,
+            if section.characteristics & IMAGE_SCN_CNT_CODE != 0 {
+                "CODE"
+            } else {
+                "DATA"
+            }
+        ));
+    }
+    map.push_str("\n  Address         Publics by Value              Rva+Base\n\n");
+    for symbol in symbols {
+        let section_index = sections
+            .iter()
+            .position(|s| symbol.rva >= s.rva && symbol.rva < s.rv_end);
+        let Some(index) = section_index else {
+            continue;
+        };
+        let name = if symbol.name.is_empty() {
+            format!("sub_{:X}", image_base.wrapping_add(symbol.rva as u64))
+        } else {
+            symbol.name.clone()
+        };
+        map.push_str(&format!(
+            " {:04X}:{:08X}       {:<29} {:016X} f\n",
+            index + 1,
+            symbol.rva - sections[index].rva,
+            name,
+            image_base.wrapping_add(symbol.rva as u64)
+        ));
+    }
+    map
+}
+
+// x64dbg脚本: 为导出函数添加标签 标记入口点和TLS回调
+// 地址使用 模块名:$RVA 形式 与实际加载基址无关
+pub(crate) fn x64dbg_script(
+    module_name: &str,
+    exports: &[&ExportFunction],
+    entry_point: u32,
+    tls_callbacks: &[u32],
+) -> String {
+    let address = |rva: u32| format!("{}:$0x{:X}", module_name, rva);
+    let mut script = String::new();
+    script.push_str(&format!(
+        "// x64dbg script generated by pe_info for {}\n",
+        module_name
+    ));
+    script.push_str("// Run after the module is loaded (Script tab > Load script).\n\n");
+
+    if entry_point != 0 {
+        script.push_str(&format!("lbl {}, \"EntryPoint\"\n", address(entry_point)));
+        script.push_str(&format!(
+            "cmt {}, \"pe_info: AddressOfEntryPoint\"\n",
+            address(entry_point)
+        ));
+        script.push_str(&format!("bookmark {}\n", address(entry_point)));
+        script.push_str(&format!("// bp {}\n\n", address(entry_point)));
+    }
+
+    for (i, &callback) in tls_callbacks.iter().enumerate() {
+        script.push_str(&format!(
+            "lbl {}, \"TlsCallback_{}\"\n",
+            address(callback),
+            i
+        ));
+        script.push_str(&format!(
+            "cmt {}, \"pe_info: TLS callback, runs before the entry point\"\n",
+            address(callback)
+        ));
+        script.push_str(&format!("bookmark {}\n", address(callback)));
+        // TLS回调默认设置断点 防止在调试器到达入口点之前执行
+        script.push_str(&format!("bp {}\n\n", address(callback)));
+    }
+
+    for export in exports {
+        let name = if !export.is_named {
+            format!("ordinal_{}", export.ordinal)
+        } else {
+            export.name.replace('"', "'")
+        };
+        script.push_str(&format!("lbl {}, \"{}\"\n", address(export.address), name));
+    }
+
+    script.push_str("\nret\n");
+    script
+}
@@ -0,0 +1,81 @@
+// 常用值的规范复制文本 各视图统一调用 避免在前端重复格式化
+use crate::Analysis;
+
+const VIRUSTOTAL_URL: &str = "https://www.virustotal.com/gui/file/";
+
+fn virustotal_link(info: &Analysis) -> String {
+    format!("{}{}", VIRUSTOTAL_URL, info.hashes.sha256)
+}
+
+// 哈希为小写十六进制 证书指纹与Windows证书管理器一致使用大写
+pub(crate) fn copy_value(info: &Analysis, kind: &str) -> Result<String, String> {
+    match kind {
+        "md5" => Ok(info.hashes.md5.clone()),
+        "sha1" => Ok(info.hashes.sha1.clone()),
+        "sha256" => Ok(info.hashes.sha256.clone()),
+        "imphash" if info.imphash.is_empty() => Err("没有导入函数 无法计算imphash".to_string()),
+        "imphash" => Ok(info.imphash.clone()),
+        "authentihash" => info
+            .authentihash
+            .clone()
+            .ok_or_else(|| "无法计算Authenticode摘要".to_string()),
+        "thumbprint" => info
+            .signature
+            .as_ref()
+            .and_then(|signature| signature.signer_certificate.as_ref())
+            .map(|certificate| certificate.thumbprint.to_ascii_uppercase())
+            .ok_or_else(|| "没有签名者证书".to_string()),
+        "virustotal" => Ok(virustotal_link(info)),
+        // 报告中常用的多行摘要
+        "summary" => {
+            let mut lines = vec![
+                format!("文件: {}", info.path),
+                format!("大小: {}", info.size),
+                format!("MD5: {}", info.hashes.md5),
+                format!("SHA-1: {}", info.hashes.sha1),
+                format!("SHA-256: {}", info.hashes.sha256),
+            ];
+            if !info.imphash.is_empty() {
+                lines.push(format!("imphash: {}", info.imphash));
+            }
+            if let Some(authentihash) = &info.authentihash {
+                lines.push(format!("Authentihash: {}", authentihash));
+            }
+            if let Some(certificate) = info
+                .signature
+                .as_ref()
+                .and_then(|signature| signature.signer_certificate.as_ref())
+            {
+                lines.push(format!("签名者: {}", certificate.subject));
+                lines.push(format!(
+                    "证书指纹: {}",
+                    certificate.thumbprint.to_ascii_uppercase()
+                ));
+            }
+            if let Some(entry) = &info.entry_point_bytes {
+                // 前32字节 与常见的入口点特征长度相当
+                let bytes: Vec<&str> = (0..entry.bytes.len().min(64))
+                    .step_by(2)
+                    .map(|i| &entry.bytes[i..i + 2])
+                    .collect();
+                let mut line = format!("入口点字节: {}", bytes.join(" ").to_ascii_uppercase());
+                if let Some(packer) = &entry.signature {
+                    line.push_str(&format!(" ({})", packer));
+                }
+                lines.push(line);
+            }
+            // 用户的书签备注 使分诊结论随报告一起留存
+            for bookmark in &info.bookmarks {
+                let address = match (bookmark.rva, bookmark.offset) {
+                    (Some(rva), _) => format!("RVA 0x{:X}", rva),
+                    (None, Some(offset)) => format!("偏移 0x{:X}", offset),
+                    (None, None) => continue,
+                };
+                lines.push(format!("书签 {}: {}", address, bookmark.note));
+            }
+            lines.push(format!("VirusTotal: {}", virustotal_link(info)));
+            Ok(lines.join("\n"))
+        }
+        other => Err(format!("未知的复制项: {}", other)),
+    }
+}
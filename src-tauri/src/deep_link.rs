@@ -0,0 +1,356 @@
+// pe-info:// 协议 内部工具和Wiki可以用 pe-info://open?path=...&rva=... 链接到某个文件的某个地址
+// 协议注册在HKCU\Software\Classes\pe-info下 浏览器把完整的链接作为第一个参数启动本程序
+use serde::{Deserialize, Serialize};
+
+use crate::{analyze, handling, offset_to_rva, rva_to_offset, PeInfo};
+
+const SCHEME: &str = "pe-info";
+#[cfg(windows)]
+const PROTOCOL_KEY: &str = r"HKCU\Software\Classes\pe-info";
+
+// 启动时要打开的文件 以及链接中要定位的地址
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StartupTarget {
+    pub path: String,
+    pub rva: Option<u32>,
+    pub offset: Option<u64>,
+    // 来自协议链接 界面需要先让用户确认再打开
+    pub from_link: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProtocolStatus {
+    pub registered: bool,
+    // 注册的命令指向的程序 与当前程序不同时需要重新注册
+    pub command: Option<String>,
+}
+
+// 链接中定位的地址在文件中的各种表示 不在任何节内的地址只有其中一部分
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddressLocation {
+    pub rva: Option<u32>,
+    pub offset: Option<u64>,
+    pub va: Option<u64>,
+    pub section: Option<String>,
+}
+
+// %XX解码 加号按表单编码视为空格 解码后按UTF-8解释
+fn percent_decode(text: &str) -> Result<String, String> {
+    let bytes = text.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        match bytes[index] {
+            b'%' => {
+                let value = text
+                    .get(index + 1..index + 3)
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                    .ok_or_else(|| format!("链接中的转义序列无效: {}", &text[index..]))?;
+                decoded.push(value);
+                index += 3;
+            }
+            b'+' => {
+                decoded.push(b' ');
+                index += 1;
+            }
+            byte => {
+                decoded.push(byte);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|_| "链接中的路径不是有效的UTF-8".to_string())
+}
+
+// 地址按十六进制解释 与外部工具的占位符一致 0x前缀可省略
+fn parse_hex(name: &str, value: &str) -> Result<u64, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u64::from_str_radix(digits, 16)
+        .map_err(|_| format!("链接中的{}不是十六进制数: {}", name, value))
+}
+
+fn is_separator(byte: u8) -> bool {
+    byte == b'\\' || byte == b'/'
+}
+
+// 链接来自网页 打开UNC路径会向对方服务器发起SMB认证并泄露NTLM哈希
+// 只接受本地绝对路径: 盘符路径或单个/开头的路径 拒绝\\server、//server、\\?\和\\.\设备路径
+fn check_local_path(path: &str) -> Result<(), String> {
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && is_separator(bytes[0]) && is_separator(bytes[1]) {
+        return Err(format!("链接不能打开网络或设备路径: {}", path));
+    }
+    let drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && is_separator(bytes[2]);
+    if !drive && bytes.first() != Some(&b'/') {
+        return Err(format!("链接中的路径不是本地绝对路径: {}", path));
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_link(link: &str) -> Result<StartupTarget, String> {
+    let rest = link
+        .get(..SCHEME.len() + 1)
+        .filter(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", SCHEME)))
+        .map(|_| &link[SCHEME.len() + 1..])
+        .ok_or_else(|| format!("不是{}链接", SCHEME))?;
+    // 浏览器可能在末尾追加斜杠 例如 pe-info://open/?path=...
+    let rest = rest.trim_start_matches('/');
+    let (action, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let action = action.trim_end_matches('/');
+    if !action.eq_ignore_ascii_case("open") {
+        return Err(format!("不支持的链接操作: {}", action));
+    }
+
+    let mut path: Option<String> = None;
+    let mut rva: Option<u32> = None;
+    let mut offset: Option<u64> = None;
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let value = percent_decode(value)?;
+        match name {
+            "path" => path = Some(value),
+            "rva" => {
+                rva = Some(
+                    u32::try_from(parse_hex("rva", &value)?)
+                        .map_err(|_| format!("链接中的rva超出范围: {}", value))?,
+                )
+            }
+            "offset" => offset = Some(parse_hex("offset", &value)?),
+            // 未知参数留给以后的版本 忽略
+            _ => {}
+        }
+    }
+    let path = path
+        .filter(|path| !path.is_empty())
+        .ok_or("链接中缺少path参数")?;
+    check_local_path(&path)?;
+    Ok(StartupTarget {
+        path,
+        rva,
+        offset,
+        from_link: true,
+    })
+}
+
+// 启动参数中的协议链接或文件路径 由浏览器、右键菜单或"打开方式"传入
+pub(crate) fn startup_target() -> Result<Option<StartupTarget>, String> {
+    for arg in std::env::args_os().skip(1) {
+        let arg = arg.to_string_lossy().to_string();
+        if arg
+            .get(..SCHEME.len() + 1)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&format!("{}:", SCHEME)))
+        {
+            // 处理样本时不响应外部链接 避免网页诱导打开样本
+            handling::ensure_allowed("打开协议链接")?;
+            return parse_link(&arg).map(Some);
+        }
+        if !arg.starts_with('-') && std::path::Path::new(&arg).is_file() {
+            return Ok(Some(StartupTarget {
+                path: arg,
+                rva: None,
+                offset: None,
+                from_link: false,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+// 同时给出rva和offset时以rva为准
+pub(crate) fn locate(
+    pe_info: &PeInfo,
+    rva: Option<u32>,
+    offset: Option<u64>,
+) -> Result<AddressLocation, String> {
+    // 头部按原样映射 RVA与文件偏移相同
+    let headers = pe_info.size_of_headers;
+    let (rva, offset) = match (rva, offset) {
+        (Some(rva), _) => (
+            Some(rva),
+            rva_to_offset(&pe_info.sections, rva)
+                .or((rva < headers).then_some(rva))
+                .map(u64::from),
+        ),
+        (None, Some(offset)) => (
+            offset_to_rva(&pe_info.sections, offset)
+                .or((offset < headers as u64).then_some(offset as u32)),
+            Some(offset),
+        ),
+        (None, None) => return Err("没有要定位的地址".into()),
+    };
+    if rva.is_some_and(|rva| rva >= pe_info.size_of_image) {
+        return Err(format!(
+            "RVA超出映像范围(SizeOfImage 0x{:X})",
+            pe_info.size_of_image
+        ));
+    }
+    if offset.is_some_and(|offset| offset >= pe_info.size) {
+        return Err(format!("地址超出文件范围(0x{:X}字节)", pe_info.size));
+    }
+    let section = rva.and_then(|rva| {
+        pe_info
+            .sections
+            .iter()
+            .find(|section| rva >= section.rva && rva < section.rv_end)
+            .map(|section| section.name.clone())
+            .or((rva < headers).then(|| "头部".to_string()))
+    });
+    Ok(AddressLocation {
+        rva,
+        offset,
+        va: rva.map(|rva| pe_info.image_base + rva as u64),
+        section,
+    })
+}
+
+pub(crate) fn locate_address(
+    file_path: &str,
+    rva: Option<u32>,
+    offset: Option<u64>,
+) -> Result<AddressLocation, String> {
+    let pe_info = analyze(file_path.into())?;
+    locate(&pe_info, rva, offset)
+}
+
+#[cfg(windows)]
+pub(crate) fn register() -> Result<ProtocolStatus, String> {
+    use crate::shell_menu::{menu_command, reg};
+
+    let command = menu_command()?;
+    reg(&["add", PROTOCOL_KEY, "/ve", "/d", "URL:pe_info", "/f"])?;
+    reg(&["add", PROTOCOL_KEY, "/v", "URL Protocol", "/d", "", "/f"])?;
+    reg(&[
+        "add",
+        &format!(r"{}\shell\open\command", PROTOCOL_KEY),
+        "/ve",
+        "/d",
+        &command,
+        "/f",
+    ])?;
+    Ok(status())
+}
+
+#[cfg(windows)]
+pub(crate) fn unregister() -> Result<ProtocolStatus, String> {
+    // 未注册时删除失败 忽略
+    let _ = crate::shell_menu::reg(&["delete", PROTOCOL_KEY, "/f"]);
+    Ok(status())
+}
+
+#[cfg(windows)]
+pub(crate) fn status() -> ProtocolStatus {
+    let command = crate::shell_menu::reg(&[
+        "query",
+        &format!(r"{}\shell\open\command", PROTOCOL_KEY),
+        "/ve",
+    ])
+    .ok()
+    .and_then(|output| {
+        output
+            .lines()
+            .find_map(|line| line.split_once("REG_SZ"))
+            .map(|(_, value)| value.trim().to_string())
+    });
+    ProtocolStatus {
+        registered: command.is_some(),
+        command,
+    }
+}
+
+#[cfg(not(windows))]
+pub(crate) fn register() -> Result<ProtocolStatus, String> {
+    Err("pe-info://协议注册仅支持Windows".to_string())
+}
+
+#[cfg(not(windows))]
+pub(crate) fn unregister() -> Result<ProtocolStatus, String> {
+    Err("pe-info://协议注册仅支持Windows".to_string())
+}
+
+#[cfg(not(windows))]
+pub(crate) fn status() -> ProtocolStatus {
+    ProtocolStatus {
+        registered: false,
+        command: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_escapes_and_plus() {
+        assert_eq!(
+            percent_decode("C%3A%5Cbin%5Ca+b.exe"),
+            Ok(r"C:\bin\a b.exe".into())
+        );
+        assert_eq!(percent_decode("%E4%B8%AD"), Ok("中".into()));
+        assert!(percent_decode("a%2").is_err());
+        assert!(percent_decode("a%zz").is_err());
+        assert!(percent_decode("%").is_err());
+        // 解码后不是UTF-8
+        assert!(percent_decode("%FF%FE").is_err());
+    }
+
+    #[test]
+    fn parses_hex_with_and_without_prefix() {
+        assert_eq!(parse_hex("rva", "0x1000"), Ok(0x1000));
+        assert_eq!(parse_hex("rva", "0X1a2B"), Ok(0x1A2B));
+        assert_eq!(parse_hex("rva", "1000"), Ok(0x1000));
+        assert!(parse_hex("rva", "0x").is_err());
+        assert!(parse_hex("rva", "12g").is_err());
+    }
+
+    #[test]
+    fn parses_open_links() {
+        let target = parse_link("pe-info://open?path=C%3A%5Ca.exe&rva=0x1000").unwrap();
+        assert_eq!(target.path, r"C:\a.exe");
+        assert_eq!(target.rva, Some(0x1000));
+        assert_eq!(target.offset, None);
+        assert!(target.from_link);
+
+        let target = parse_link("PE-INFO://open/?path=C:/a.exe&rva=2000&offset=0x400").unwrap();
+        assert_eq!(target.path, "C:/a.exe");
+        assert_eq!(target.rva, Some(0x2000));
+        assert_eq!(target.offset, Some(0x400));
+
+        assert!(parse_link("pe-info://open?path=C:/a.exe&rva=0x100000000").is_err());
+        assert!(parse_link("pe-info://edit?path=C:/a.exe").is_err());
+        assert!(parse_link("http://open?path=C:/a.exe").is_err());
+    }
+
+    #[test]
+    fn rejects_missing_or_empty_path() {
+        assert!(parse_link("pe-info://open").is_err());
+        assert!(parse_link("pe-info://open?rva=0x1000").is_err());
+        assert!(parse_link("pe-info://open?path=&rva=0x1000").is_err());
+        assert!(parse_link("pe-info://open?path").is_err());
+    }
+
+    #[test]
+    fn rejects_network_and_device_paths() {
+        for path in [
+            r"\\attacker\share\x",
+            "%5C%5Cattacker%5Cshare%5Cx",
+            "//attacker/share/x",
+            r"\/attacker/share/x",
+            r"\\?\C:\a.exe",
+            r"\\?\UNC\attacker\share\x",
+            r"\\.\PhysicalDrive0",
+            "a.exe",
+            r"..\a.exe",
+            "C:a.exe",
+        ] {
+            let link = format!("pe-info://open?path={}", path);
+            assert!(parse_link(&link).is_err(), "{}", path);
+        }
+        assert!(parse_link("pe-info://open?path=/home/a.exe").is_ok());
+    }
+}
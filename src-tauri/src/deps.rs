@@ -0,0 +1,321 @@
+// 递归解析DLL依赖关系 按简化的Windows搜索顺序查找: 程序目录 -> 系统目录 -> Windows目录
+use serde::{Deserialize, Serialize};
+
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::{analyze, apiset, handling};
+
+// 递归深度上限 系统DLL之间的依赖很深 超过后不再展开
+const MAX_DEPTH: u32 = 8;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DependencyKind {
+    Root,
+    // 位于程序目录
+    Local,
+    // 位于系统目录
+    System,
+    // api-ms-win-*/ext-ms-* 由系统在加载时重定向
+    ApiSet,
+    Missing,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DependencyNode {
+    pub name: String,
+    pub path: Option<String>,
+    pub kind: DependencyKind,
+    // 距离根节点的层数 前端可按层布局
+    pub depth: u32,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DependencyEdge {
+    pub from: usize,
+    pub to: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DependencyGraph {
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<DependencyEdge>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeploymentReport {
+    // 复制到目标目录的文件名
+    pub copied: Vec<String>,
+    pub total_size: u64,
+    // 从副本重新解析后仍找不到或不再来自目标目录的DLL
+    pub missing: Vec<String>,
+}
+
+pub(crate) fn is_api_set(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    lower.starts_with("api-ms-win-") || lower.starts_with("ext-ms-")
+}
+
+// Windows目录 非Windows上为None
+pub(crate) fn windows_directory() -> Option<PathBuf> {
+    std::env::var_os("SystemRoot")
+        .or_else(|| std::env::var_os("windir"))
+        .map(PathBuf::from)
+}
+
+// 系统目录 32位程序在64位系统上使用SysWOW64
+pub(crate) fn system_directories(is_x64: bool) -> Vec<PathBuf> {
+    let mut directories: Vec<PathBuf> = Vec::new();
+    if let Some(root) = windows_directory() {
+        let wow64 = root.join("SysWOW64");
+        if !is_x64 && wow64.is_dir() {
+            directories.push(wow64);
+        } else {
+            directories.push(root.join("System32"));
+        }
+        directories.push(root);
+    }
+    directories
+}
+
+// 在目录中查找文件 Windows文件名不区分大小写
+pub(crate) fn find_in_directory(directory: &Path, name: &str) -> Option<PathBuf> {
+    let candidate = directory.join(name);
+    if candidate.is_file() {
+        return Some(candidate);
+    }
+    std::fs::read_dir(directory)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .find(|entry| {
+            entry
+                .file_name()
+                .to_string_lossy()
+                .eq_ignore_ascii_case(name)
+        })
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+}
+
+// 按搜索顺序定位DLL
+pub(crate) fn locate_dll(
+    name: &str,
+    app_directory: &Path,
+    is_x64: bool,
+) -> Option<(PathBuf, DependencyKind)> {
+    if let Some(path) = find_in_directory(app_directory, name) {
+        return Some((path, DependencyKind::Local));
+    }
+    for directory in system_directories(is_x64) {
+        if let Some(path) = find_in_directory(&directory, name) {
+            return Some((path, DependencyKind::System));
+        }
+    }
+    None
+}
+
+// 广度优先解析依赖 相同名称的DLL只展开一次
+pub(crate) fn resolve_dependencies(file_path: &str) -> Result<DependencyGraph, String> {
+    let root = analyze(file_path.into())?;
+    let app_directory = Path::new(file_path)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    let mut nodes: Vec<DependencyNode> = vec![DependencyNode {
+        name: crate::module_name(file_path),
+        path: Some(file_path.to_string()),
+        kind: DependencyKind::Root,
+        depth: 0,
+        error: None,
+    }];
+    let mut edges: Vec<DependencyEdge> = Vec::new();
+    let mut index_by_name: HashMap<String, usize> = HashMap::new();
+    index_by_name.insert(nodes[0].name.to_ascii_lowercase(), 0);
+
+    let root_imports: Vec<String> = root
+        .import_table
+        .iter()
+        .map(|e| e.dll_name.clone())
+        .collect();
+    let mut queue: VecDeque<(usize, Vec<String>)> = VecDeque::new();
+    queue.push_back((0, root_imports));
+
+    while let Some((from, imports)) = queue.pop_front() {
+        let depth = nodes[from].depth + 1;
+        for dll_name in imports {
+            let key = dll_name.to_ascii_lowercase();
+            if let Some(&to) = index_by_name.get(&key) {
+                if !edges.iter().any(|e| e.from == from && e.to == to) {
+                    edges.push(DependencyEdge { from, to });
+                }
+                continue;
+            }
+
+            let to = nodes.len();
+            index_by_name.insert(key, to);
+            edges.push(DependencyEdge { from, to });
+
+            if is_api_set(&dll_name) {
+                // 宿主DLL由本机的apisetschema决定 不再展开
+                let resolution = apiset::resolve_api_set(&dll_name);
+                let error = resolution
+                    .as_ref()
+                    .filter(|resolution| resolution.host.is_none())
+                    .map(|_| "本系统的apisetschema中没有宿主".to_string());
+                nodes.push(DependencyNode {
+                    name: dll_name,
+                    path: resolution.and_then(|resolution| resolution.host),
+                    kind: DependencyKind::ApiSet,
+                    depth,
+                    error,
+                });
+                continue;
+            }
+
+            match locate_dll(&dll_name, &app_directory, root.is_x64) {
+                Some((path, kind)) => {
+                    let path_text = path.to_string_lossy().to_string();
+                    let mut error = None;
+                    if depth < MAX_DEPTH {
+                        match analyze(path.clone()) {
+                            Ok(info) => queue.push_back((
+                                to,
+                                info.import_table
+                                    .iter()
+                                    .map(|e| e.dll_name.clone())
+                                    .collect(),
+                            )),
+                            Err(e) => error = Some(e),
+                        }
+                    }
+                    nodes.push(DependencyNode {
+                        name: dll_name,
+                        path: Some(path_text),
+                        kind,
+                        depth,
+                        error,
+                    });
+                }
+                None => nodes.push(DependencyNode {
+                    name: dll_name,
+                    path: None,
+                    kind: DependencyKind::Missing,
+                    depth,
+                    error: None,
+                }),
+            }
+        }
+    }
+
+    Ok(DependencyGraph { nodes, edges })
+}
+
+// 把程序及其非系统依赖(程序目录中的DLL)复制到目标目录 再从副本重新解析依赖确认没有缺失
+pub(crate) fn export_closure(
+    file_path: &str,
+    output_directory: &str,
+) -> Result<DeploymentReport, String> {
+    // 复制出的是可以直接运行的程序目录 样本处理模式下不允许
+    handling::ensure_allowed("导出依赖目录")?;
+    let graph = resolve_dependencies(file_path)?;
+    let output_directory = Path::new(output_directory);
+    let app_directory = Path::new(file_path).parent().unwrap_or(Path::new(""));
+    if output_directory.canonicalize().ok() == app_directory.canonicalize().ok() {
+        return Err("目标目录不能是程序所在的目录".to_string());
+    }
+    std::fs::create_dir_all(output_directory).map_err(|e| format!("无法创建目录: {}", e))?;
+
+    let mut copied: Vec<String> = Vec::new();
+    let mut total_size: u64 = 0;
+    for node in graph
+        .nodes
+        .iter()
+        .filter(|n| matches!(n.kind, DependencyKind::Root | DependencyKind::Local))
+    {
+        let Some(source) = node.path.as_deref().map(Path::new) else {
+            continue;
+        };
+        let Some(file_name) = source.file_name() else {
+            continue;
+        };
+        total_size += std::fs::copy(source, output_directory.join(file_name))
+            .map_err(|e| format!("无法复制 {}: {}", source.display(), e))?;
+        copied.push(file_name.to_string_lossy().to_string());
+    }
+
+    let root_copy = output_directory.join(&copied[0]);
+    let verified = resolve_dependencies(&root_copy.to_string_lossy())?;
+    let missing: Vec<String> = verified
+        .nodes
+        .iter()
+        .filter(|node| {
+            node.kind == DependencyKind::Missing
+                || (node.kind != DependencyKind::Local
+                    && graph.nodes.iter().any(|original| {
+                        original.kind == DependencyKind::Local
+                            && original.name.eq_ignore_ascii_case(&node.name)
+                    }))
+        })
+        .map(|node| node.name.clone())
+        .collect();
+    Ok(DeploymentReport {
+        copied,
+        total_size,
+        missing,
+    })
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Graphviz DOT 按节点类型着色 同层节点排在同一行
+pub(crate) fn to_dot(graph: &DependencyGraph) -> String {
+    let mut dot = String::new();
+    dot.push_str("digraph dependencies {\n");
+    dot.push_str("    rankdir=LR;\n");
+    dot.push_str("    node [shape=box, style=filled, fontname=\"Helvetica\"];\n\n");
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let color = match node.kind {
+            DependencyKind::Root => "#fde68a",
+            DependencyKind::Local => "#bfdbfe",
+            DependencyKind::System => "#e5e7eb",
+            DependencyKind::ApiSet => "#ddd6fe",
+            DependencyKind::Missing => "#fca5a5",
+        };
+        let mut label = dot_escape(&node.name);
+        if let Some(path) = &node.path {
+            label.push_str("\\n");
+            label.push_str(&dot_escape(&path.replace('\\', "/")));
+        }
+        dot.push_str(&format!(
+            "    n{} [label=\"{}\", fillcolor=\"{}\"];\n",
+            i, label, color
+        ));
+    }
+    dot.push('\n');
+
+    let max_depth = graph.nodes.iter().map(|n| n.depth).max().unwrap_or(0);
+    for depth in 0..=max_depth {
+        let same_rank: Vec<String> = graph
+            .nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, n)| n.depth == depth)
+            .map(|(i, _)| format!("n{}", i))
+            .collect();
+        if same_rank.len() > 1 {
+            dot.push_str(&format!("    {{ rank=same; {}; }}\n", same_rank.join("; ")));
+        }
+    }
+    dot.push('\n');
+
+    for edge in &graph.edges {
+        dot.push_str(&format!("    n{} -> n{};\n", edge.from, edge.to));
+    }
+    dot.push_str("}\n");
+    dot
+}
@@ -0,0 +1,148 @@
+// 两次分析结果的差异 用于重建后展示"自上次构建以来的变化"
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+
+use crate::PeInfo;
+
+// 熵变化超过该值视为节区内容改变
+const ENTROPY_EPSILON: f64 = 0.01;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SectionChange {
+    pub name: String,
+    // added / removed / resized / modified
+    pub change: String,
+    pub size_before: u32,
+    pub size_after: u32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BuildDiff {
+    pub path: String,
+    pub size_before: u64,
+    pub size_after: u64,
+    pub size_delta: i64,
+    // 形如 kernel32.dll!CreateFileW
+    pub added_imports: Vec<String>,
+    pub removed_imports: Vec<String>,
+    pub added_exports: Vec<String>,
+    pub removed_exports: Vec<String>,
+    pub changed_sections: Vec<SectionChange>,
+    // 摘要发生变化或新增、删除的数据目录
+    pub changed_directories: Vec<String>,
+    pub imphash_changed: bool,
+    pub sha256_changed: bool,
+}
+
+impl BuildDiff {
+    pub fn is_empty(&self) -> bool {
+        !self.sha256_changed
+    }
+}
+
+fn import_names(info: &PeInfo) -> BTreeSet<String> {
+    info.import_table
+        .iter()
+        .flat_map(|entry| {
+            entry
+                .functions
+                .iter()
+                .map(move |f| format!("{}!{}", entry.dll_name.to_ascii_lowercase(), f.name))
+        })
+        .collect()
+}
+
+// 只按序号导出的函数以#序号表示
+fn export_names(info: &PeInfo) -> BTreeSet<String> {
+    info.export_table
+        .iter()
+        .map(|f| {
+            if f.is_named {
+                f.name.clone()
+            } else {
+                format!("#{}", f.ordinal)
+            }
+        })
+        .collect()
+}
+
+fn section_changes(before: &PeInfo, after: &PeInfo) -> Vec<SectionChange> {
+    let mut changes: Vec<SectionChange> = Vec::new();
+    for new in &after.sections {
+        let old = before.sections.iter().find(|s| s.name == new.name);
+        let change = match old {
+            None => Some("added"),
+            Some(old)
+                if old.size_raw_data != new.size_raw_data
+                    || old.rv_end - old.rva != new.rv_end - new.rva =>
+            {
+                Some("resized")
+            }
+            Some(old)
+                if old.characteristics != new.characteristics
+                    || (old.entropy - new.entropy).abs() > ENTROPY_EPSILON =>
+            {
+                Some("modified")
+            }
+            Some(_) => None,
+        };
+        if let Some(change) = change {
+            changes.push(SectionChange {
+                name: new.name.clone(),
+                change: change.to_string(),
+                size_before: old.map_or(0, |s| s.size_raw_data),
+                size_after: new.size_raw_data,
+            });
+        }
+    }
+    for old in &before.sections {
+        if !after.sections.iter().any(|s| s.name == old.name) {
+            changes.push(SectionChange {
+                name: old.name.clone(),
+                change: "removed".to_string(),
+                size_before: old.size_raw_data,
+                size_after: 0,
+            });
+        }
+    }
+    changes
+}
+
+fn directory_changes(before: &PeInfo, after: &PeInfo) -> Vec<String> {
+    let digests = |info: &PeInfo| -> BTreeSet<(usize, String, String)> {
+        info.directory_digests
+            .iter()
+            .map(|d| (d.index, d.name.clone(), d.sha256.clone()))
+            .collect()
+    };
+    let digests_before = digests(before);
+    let digests_after = digests(after);
+    let changed: BTreeSet<(usize, String)> = digests_before
+        .symmetric_difference(&digests_after)
+        .map(|(index, name, _)| (*index, name.clone()))
+        .collect();
+    changed.into_iter().map(|(_, name)| name).collect()
+}
+
+pub(crate) fn diff_analysis(before: &PeInfo, after: &PeInfo) -> BuildDiff {
+    let imports_before = import_names(before);
+    let imports_after = import_names(after);
+    let exports_before = export_names(before);
+    let exports_after = export_names(after);
+
+    BuildDiff {
+        path: after.path.clone(),
+        size_before: before.size,
+        size_after: after.size,
+        size_delta: after.size as i64 - before.size as i64,
+        added_imports: imports_after.difference(&imports_before).cloned().collect(),
+        removed_imports: imports_before.difference(&imports_after).cloned().collect(),
+        added_exports: exports_after.difference(&exports_before).cloned().collect(),
+        removed_exports: exports_before.difference(&exports_after).cloned().collect(),
+        changed_sections: section_changes(before, after),
+        changed_directories: directory_changes(before, after),
+        imphash_changed: before.imphash != after.imphash,
+        sha256_changed: before.hashes.sha256 != after.hashes.sha256,
+    }
+}
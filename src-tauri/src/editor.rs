@@ -0,0 +1,475 @@
+// 头部字段编辑器: 列出每个头部字段的偏移、大小和类型 在工作副本上修改字段后重新分析 报告新出现和消失的异常
+use serde::{Deserialize, Serialize};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::diff::{diff_analysis, BuildDiff};
+use crate::heuristics::Warning;
+use crate::reader::BoundedReader;
+use crate::{analyze, file_access, handling, PeInfo};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HeaderField {
+    // 如 OptionalHeader.ImageBase / SectionHeaders[0].Name
+    pub path: String,
+    // 所属结构 前端按此分组显示为树
+    pub structure: String,
+    pub offset: u64,
+    pub size: u32,
+    // u8 / u16 / u32 / u64 / name(定长ASCII) / bytes
+    pub kind: String,
+    pub value: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EditSessionInfo {
+    pub session: String,
+    pub working_copy: String,
+    pub fields: Vec<HeaderField>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FieldEdit {
+    pub path: String,
+    pub old_value: String,
+    pub new_value: String,
+    // 修改后无法解析时的错误 此时警告和差异为空
+    pub error: Option<String>,
+    pub added_warnings: Vec<Warning>,
+    pub resolved_warnings: Vec<Warning>,
+    // 与上一次成功解析相比 导入导出、节区和数据目录的变化
+    pub changes: Option<BuildDiff>,
+    // 修改后重新计算的字段 e_lfanew等字段会改变其他结构的位置
+    pub fields: Vec<HeaderField>,
+}
+
+struct EditSession {
+    source: String,
+    working_copy: PathBuf,
+    data: Vec<u8>,
+    // 最近一次成功解析的结果 用于比较
+    previous: Option<PeInfo>,
+}
+
+#[derive(Default)]
+struct Sessions {
+    next_id: u32,
+    open: HashMap<String, EditSession>,
+}
+
+// 打开的编辑会话 关闭时删除工作副本
+#[derive(Default)]
+pub struct EditorState {
+    sessions: Mutex<Sessions>,
+}
+
+// (字段名, 相对偏移, 大小, 类型)
+type FieldLayout = (&'static str, usize, u32, &'static str);
+
+const DOS_HEADER: &[FieldLayout] = &[
+    ("e_magic", 0x00, 2, "u16"),
+    ("e_cblp", 0x02, 2, "u16"),
+    ("e_cp", 0x04, 2, "u16"),
+    ("e_crlc", 0x06, 2, "u16"),
+    ("e_cparhdr", 0x08, 2, "u16"),
+    ("e_minalloc", 0x0A, 2, "u16"),
+    ("e_maxalloc", 0x0C, 2, "u16"),
+    ("e_ss", 0x0E, 2, "u16"),
+    ("e_sp", 0x10, 2, "u16"),
+    ("e_csum", 0x12, 2, "u16"),
+    ("e_ip", 0x14, 2, "u16"),
+    ("e_cs", 0x16, 2, "u16"),
+    ("e_lfarlc", 0x18, 2, "u16"),
+    ("e_ovno", 0x1A, 2, "u16"),
+    ("e_res", 0x1C, 8, "bytes"),
+    ("e_oemid", 0x24, 2, "u16"),
+    ("e_oeminfo", 0x26, 2, "u16"),
+    ("e_res2", 0x28, 20, "bytes"),
+    ("e_lfanew", 0x3C, 4, "u32"),
+];
+
+const FILE_HEADER: &[FieldLayout] = &[
+    ("Machine", 0x00, 2, "u16"),
+    ("NumberOfSections", 0x02, 2, "u16"),
+    ("TimeDateStamp", 0x04, 4, "u32"),
+    ("PointerToSymbolTable", 0x08, 4, "u32"),
+    ("NumberOfSymbols", 0x0C, 4, "u32"),
+    ("SizeOfOptionalHeader", 0x10, 2, "u16"),
+    ("Characteristics", 0x12, 2, "u16"),
+];
+
+// 32位和64位共有的前半部分
+const OPTIONAL_HEADER_COMMON: &[FieldLayout] = &[
+    ("Magic", 0x00, 2, "u16"),
+    ("MajorLinkerVersion", 0x02, 1, "u8"),
+    ("MinorLinkerVersion", 0x03, 1, "u8"),
+    ("SizeOfCode", 0x04, 4, "u32"),
+    ("SizeOfInitializedData", 0x08, 4, "u32"),
+    ("SizeOfUninitializedData", 0x0C, 4, "u32"),
+    ("AddressOfEntryPoint", 0x10, 4, "u32"),
+    ("BaseOfCode", 0x14, 4, "u32"),
+    ("SectionAlignment", 0x20, 4, "u32"),
+    ("FileAlignment", 0x24, 4, "u32"),
+    ("MajorOperatingSystemVersion", 0x28, 2, "u16"),
+    ("MinorOperatingSystemVersion", 0x2A, 2, "u16"),
+    ("MajorImageVersion", 0x2C, 2, "u16"),
+    ("MinorImageVersion", 0x2E, 2, "u16"),
+    ("MajorSubsystemVersion", 0x30, 2, "u16"),
+    ("MinorSubsystemVersion", 0x32, 2, "u16"),
+    ("Win32VersionValue", 0x34, 4, "u32"),
+    ("SizeOfImage", 0x38, 4, "u32"),
+    ("SizeOfHeaders", 0x3C, 4, "u32"),
+    ("CheckSum", 0x40, 4, "u32"),
+    ("Subsystem", 0x44, 2, "u16"),
+    ("DllCharacteristics", 0x46, 2, "u16"),
+];
+
+const OPTIONAL_HEADER_32: &[FieldLayout] = &[
+    ("BaseOfData", 0x18, 4, "u32"),
+    ("ImageBase", 0x1C, 4, "u32"),
+    ("SizeOfStackReserve", 0x48, 4, "u32"),
+    ("SizeOfStackCommit", 0x4C, 4, "u32"),
+    ("SizeOfHeapReserve", 0x50, 4, "u32"),
+    ("SizeOfHeapCommit", 0x54, 4, "u32"),
+    ("LoaderFlags", 0x58, 4, "u32"),
+    ("NumberOfRvaAndSizes", 0x5C, 4, "u32"),
+];
+
+const OPTIONAL_HEADER_64: &[FieldLayout] = &[
+    ("ImageBase", 0x18, 8, "u64"),
+    ("SizeOfStackReserve", 0x48, 8, "u64"),
+    ("SizeOfStackCommit", 0x50, 8, "u64"),
+    ("SizeOfHeapReserve", 0x58, 8, "u64"),
+    ("SizeOfHeapCommit", 0x60, 8, "u64"),
+    ("LoaderFlags", 0x68, 4, "u32"),
+    ("NumberOfRvaAndSizes", 0x6C, 4, "u32"),
+];
+
+const SECTION_HEADER: &[FieldLayout] = &[
+    ("Name", 0x00, 8, "name"),
+    ("VirtualSize", 0x08, 4, "u32"),
+    ("VirtualAddress", 0x0C, 4, "u32"),
+    ("SizeOfRawData", 0x10, 4, "u32"),
+    ("PointerToRawData", 0x14, 4, "u32"),
+    ("PointerToRelocations", 0x18, 4, "u32"),
+    ("PointerToLinenumbers", 0x1C, 4, "u32"),
+    ("NumberOfRelocations", 0x20, 2, "u16"),
+    ("NumberOfLinenumbers", 0x22, 2, "u16"),
+    ("Characteristics", 0x24, 4, "u32"),
+];
+
+fn read_number(data: &[u8], offset: usize, size: u32) -> Option<u64> {
+    BoundedReader::new(data)
+        .uint(offset as u64, size as u64)
+        .ok()
+}
+
+fn format_value(data: &[u8], offset: usize, size: u32, kind: &str) -> Option<String> {
+    let bytes = BoundedReader::new(data)
+        .bytes(offset as u64, size as u64)
+        .ok()?;
+    Some(match kind {
+        "name" => {
+            let end = bytes.iter().position(|&b| b == 0).unwrap_or(bytes.len());
+            String::from_utf8_lossy(&bytes[..end]).to_string()
+        }
+        "bytes" => bytes
+            .iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => format!("0x{:X}", read_number(data, offset, size)?),
+    })
+}
+
+fn push_fields(
+    fields: &mut Vec<HeaderField>,
+    data: &[u8],
+    structure: &str,
+    base: usize,
+    layout: &[FieldLayout],
+) {
+    for &(name, relative, size, kind) in layout {
+        let offset = base + relative;
+        let Some(value) = format_value(data, offset, size, kind) else {
+            continue;
+        };
+        fields.push(HeaderField {
+            path: format!("{}.{}", structure, name),
+            structure: structure.to_string(),
+            offset: offset as u64,
+            size,
+            kind: kind.to_string(),
+            value,
+        });
+    }
+}
+
+// 按文件中的顺序列出全部头部字段 不校验签名 Magic无效时只列出到Magic为止
+// 编辑中的工作副本可能暂时无效 仍要能把字段改回去
+pub(crate) fn header_structure(data: &[u8]) -> Result<Vec<HeaderField>, String> {
+    if data.len() < 0x40 {
+        return Err("文件小于DOS头".into());
+    }
+    let mut fields: Vec<HeaderField> = Vec::new();
+    push_fields(&mut fields, data, "DosHeader", 0, DOS_HEADER);
+    let nt = read_number(data, 0x3C, 4).unwrap_or(0) as usize;
+    push_fields(
+        &mut fields,
+        data,
+        "NtHeaders",
+        nt,
+        &[("Signature", 0, 4, "u32")],
+    );
+    let coff = nt + 4;
+    push_fields(&mut fields, data, "FileHeader", coff, FILE_HEADER);
+
+    let optional = coff + 0x14;
+    let is_x64 = match read_number(data, optional, 2) {
+        Some(0x10B) => false,
+        Some(0x20B) => true,
+        _ => {
+            push_fields(
+                &mut fields,
+                data,
+                "OptionalHeader",
+                optional,
+                &OPTIONAL_HEADER_COMMON[..1],
+            );
+            return Ok(fields);
+        }
+    };
+    let mut optional_layout: Vec<FieldLayout> = OPTIONAL_HEADER_COMMON.to_vec();
+    optional_layout.extend_from_slice(if is_x64 {
+        OPTIONAL_HEADER_64
+    } else {
+        OPTIONAL_HEADER_32
+    });
+    optional_layout.sort_by_key(|&(_, relative, _, _)| relative);
+    push_fields(
+        &mut fields,
+        data,
+        "OptionalHeader",
+        optional,
+        &optional_layout,
+    );
+
+    let data_directory = optional + if is_x64 { 0x70 } else { 0x60 };
+    let number_of_rva_and_sizes = read_number(data, data_directory - 4, 4)
+        .unwrap_or(0)
+        .min(16);
+    for index in 0..number_of_rva_and_sizes as usize {
+        push_fields(
+            &mut fields,
+            data,
+            &format!("DataDirectory[{}]", index),
+            data_directory + index * 8,
+            &[("VirtualAddress", 0, 4, "u32"), ("Size", 4, 4, "u32")],
+        );
+    }
+
+    let optional_header_size = read_number(data, coff + 0x10, 2).unwrap_or(0) as usize;
+    let number_of_sections = read_number(data, coff + 2, 2).unwrap_or(0) as usize;
+    let section_table = optional + optional_header_size;
+    for index in 0..number_of_sections {
+        push_fields(
+            &mut fields,
+            data,
+            &format!("SectionHeaders[{}]", index),
+            section_table + index * 40,
+            SECTION_HEADER,
+        );
+    }
+    Ok(fields)
+}
+
+// 数值支持0x前缀的十六进制和十进制 name为ASCII文本 bytes为十六进制字节
+fn encode_value(field: &HeaderField, value: &str) -> Result<Vec<u8>, String> {
+    let value = value.trim();
+    match field.kind.as_str() {
+        "name" => {
+            if !value.is_ascii() || value.len() > field.size as usize {
+                return Err(format!("名称必须是不超过{}字节的ASCII文本", field.size));
+            }
+            let mut bytes = value.as_bytes().to_vec();
+            bytes.resize(field.size as usize, 0);
+            Ok(bytes)
+        }
+        "bytes" => {
+            let digits: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+            let bytes = (0..digits.len())
+                .step_by(2)
+                .map(|i| {
+                    digits
+                        .get(i..i + 2)
+                        .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                })
+                .collect::<Option<Vec<u8>>>()
+                .ok_or("无效的十六进制字节")?;
+            if bytes.len() != field.size as usize {
+                return Err(format!("需要{}个字节", field.size));
+            }
+            Ok(bytes)
+        }
+        _ => {
+            let number = match value
+                .strip_prefix("0x")
+                .or_else(|| value.strip_prefix("0X"))
+            {
+                Some(hex) => u64::from_str_radix(hex, 16),
+                None => value.parse::<u64>(),
+            }
+            .map_err(|_| format!("无效的数值: {}", value))?;
+            if field.size < 8 && number >> (field.size * 8) != 0 {
+                return Err(format!("数值超出{}字节字段的范围", field.size));
+            }
+            Ok(number.to_le_bytes()[..field.size as usize].to_vec())
+        }
+    }
+}
+
+fn working_copy_path(id: u32, source: &str) -> PathBuf {
+    let file_name = Path::new(source)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let path = std::env::temp_dir().join(format!(
+        "pe_info_edit_{}_{}_{}",
+        std::process::id(),
+        id,
+        file_name
+    ));
+    // 工作副本也是完整的可执行文件 样本处理模式下同样改为.bin
+    if handling::current().enabled {
+        handling::inert_name(&path)
+    } else {
+        path
+    }
+}
+
+pub(crate) fn open_session(
+    state: &EditorState,
+    file_path: &str,
+) -> Result<EditSessionInfo, String> {
+    let data = file_access::read_file(Path::new(file_path))?;
+    let fields = header_structure(&data)?;
+    // 编辑会话只比较文件本身的解析结果
+    let previous = analyze(file_path.into())?.pe;
+    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    sessions.next_id += 1;
+    let id = sessions.next_id;
+    let working_copy = working_copy_path(id, file_path);
+    std::fs::write(&working_copy, &data).map_err(|e| format!("无法创建工作副本: {}", e))?;
+    let session = id.to_string();
+    sessions.open.insert(
+        session.clone(),
+        EditSession {
+            source: file_path.to_string(),
+            working_copy: working_copy.clone(),
+            data,
+            previous: Some(previous),
+        },
+    );
+    Ok(EditSessionInfo {
+        session,
+        working_copy: working_copy.to_string_lossy().to_string(),
+        fields,
+    })
+}
+
+// (kind, message)相同视为同一条警告
+fn same_warning(a: &Warning, b: &Warning) -> bool {
+    a.kind == b.kind && a.message == b.message
+}
+
+pub(crate) fn write_field(
+    state: &EditorState,
+    session: &str,
+    field_path: &str,
+    value: &str,
+) -> Result<FieldEdit, String> {
+    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    let edit = sessions.open.get_mut(session).ok_or("编辑会话不存在")?;
+    let field = header_structure(&edit.data)?
+        .into_iter()
+        .find(|field| field.path == field_path)
+        .ok_or_else(|| format!("没有字段 {}", field_path))?;
+    let bytes = encode_value(&field, value)?;
+    let offset = field.offset as usize;
+    edit.data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+    std::fs::write(&edit.working_copy, &edit.data)
+        .map_err(|e| format!("无法写入工作副本: {}", e))?;
+    let new_value = format_value(&edit.data, offset, field.size, &field.kind).unwrap_or_default();
+    // 修改后可能连头部都无法定位 此时字段列表为空
+    let fields = header_structure(&edit.data).unwrap_or_default();
+
+    let mut result = FieldEdit {
+        path: field.path,
+        old_value: field.value,
+        new_value,
+        error: None,
+        added_warnings: Vec::new(),
+        resolved_warnings: Vec::new(),
+        changes: None,
+        fields,
+    };
+    let info = match analyze(edit.working_copy.clone()) {
+        Ok(info) => info.pe,
+        Err(error) => {
+            result.error = Some(error);
+            return Ok(result);
+        }
+    };
+    if let Some(previous) = edit.previous.take() {
+        result.changes = Some(diff_analysis(&previous, &info));
+        result.added_warnings = info
+            .warnings
+            .iter()
+            .filter(|warning| {
+                !previous
+                    .warnings
+                    .iter()
+                    .any(|old| same_warning(old, warning))
+            })
+            .map(|warning| Warning {
+                kind: warning.kind.clone(),
+                severity: warning.severity,
+                message: warning.message.clone(),
+                pane: warning.pane.clone(),
+            })
+            .collect();
+        result.resolved_warnings = previous
+            .warnings
+            .into_iter()
+            .filter(|warning| !info.warnings.iter().any(|new| same_warning(new, warning)))
+            .collect();
+    }
+    edit.previous = Some(info);
+    Ok(result)
+}
+
+pub(crate) fn save_session(
+    state: &EditorState,
+    session: &str,
+    output_path: &str,
+) -> Result<u64, String> {
+    let sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    let edit = sessions.open.get(session).ok_or("编辑会话不存在")?;
+    if Path::new(output_path) == Path::new(&edit.source) {
+        return Err("不能覆盖原文件 请另存为新文件".into());
+    }
+    // 样本处理模式下按解出文件处理 追加.bin后缀或打包
+    handling::write_payload(Path::new(output_path), &edit.data)?;
+    Ok(edit.data.len() as u64)
+}
+
+pub(crate) fn close_session(state: &EditorState, session: &str) -> Result<bool, String> {
+    let mut sessions = state.sessions.lock().map_err(|e| e.to_string())?;
+    let Some(edit) = sessions.open.remove(session) else {
+        return Ok(false);
+    };
+    let _ = std::fs::remove_file(&edit.working_copy);
+    Ok(true)
+}
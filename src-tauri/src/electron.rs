@@ -0,0 +1,137 @@
+// Electron/NW.js应用识别 依据导出函数、版本资源、内嵌UA字符串和程序目录中的app.asar
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use crate::capabilities::find_all;
+use crate::version_info::VersionInfo;
+use crate::ExportFunction;
+
+// Chromium/Node嵌入程序的典型导出
+const CHROMIUM_EXPORTS: &[&str] = &["GetHandleVerifier", "IsSandboxedProcess"];
+const NODE_EXPORT_PREFIXES: &[&str] = &["napi_", "node_module_register", "uv_"];
+// 版本号的最大长度
+const MAX_VERSION_LENGTH: usize = 24;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ElectronInfo {
+    // Electron / NW.js
+    pub framework: String,
+    pub electron_version: Option<String>,
+    pub chromium_version: Option<String>,
+    pub node_version: Option<String>,
+    // 程序目录中的应用包 resources/app.asar 或 package.nw
+    pub app_package: Option<String>,
+    pub evidence: Vec<String>,
+}
+
+// 前缀之后形如1.2.3的版本号
+fn version_after(data: &[u8], prefix: &str) -> Option<String> {
+    find_all(data, prefix.as_bytes(), 16)
+        .into_iter()
+        .find_map(|offset| {
+            let version: String = data[offset + prefix.len()..]
+                .iter()
+                .take(MAX_VERSION_LENGTH)
+                .take_while(|b| b.is_ascii_digit() || **b == b'.')
+                .map(|&b| b as char)
+                .collect();
+            let version = version.trim_end_matches('.');
+            (version.contains('.') && version.starts_with(|c: char| c.is_ascii_digit()))
+                .then(|| version.to_string())
+        })
+}
+
+fn find_app_package(file_path: &Path) -> Option<(String, bool)> {
+    let directory = file_path.parent()?;
+    let candidates = [
+        ("resources/app.asar", false),
+        ("resources/app", false),
+        ("package.nw", true),
+        ("nw.pak", true),
+    ];
+    candidates.iter().find_map(|(relative, is_nw)| {
+        let path = directory.join(relative);
+        path.exists()
+            .then(|| (path.to_string_lossy().to_string(), *is_nw))
+    })
+}
+
+pub(crate) fn detect_electron(
+    file_path: &Path,
+    data: &[u8],
+    export_table: &[ExportFunction],
+    version_info: Option<&VersionInfo>,
+) -> Option<ElectronInfo> {
+    let mut evidence: Vec<String> = Vec::new();
+
+    let chromium_exports: Vec<&str> = CHROMIUM_EXPORTS
+        .iter()
+        .filter(|name| export_table.iter().any(|f| f.name == **name))
+        .copied()
+        .collect();
+    if !chromium_exports.is_empty() {
+        evidence.push(format!("Chromium导出: {}", chromium_exports.join(", ")));
+    }
+    let node_exports = export_table
+        .iter()
+        .filter(|f| {
+            NODE_EXPORT_PREFIXES
+                .iter()
+                .any(|prefix| f.name.starts_with(prefix))
+        })
+        .count();
+    if node_exports > 0 {
+        evidence.push(format!("{} 个Node.js导出", node_exports));
+    }
+
+    let product_name = version_info.and_then(|info| info.get("ProductName"));
+    let is_electron_product =
+        product_name.is_some_and(|name| name.eq_ignore_ascii_case("Electron"));
+    if is_electron_product {
+        evidence.push("版本资源 ProductName=Electron".to_string());
+    }
+
+    // 未改名的electron.exe中 文件版本即Electron版本
+    let electron_version = version_after(data, "Electron/").or_else(|| {
+        is_electron_product
+            .then(|| version_info.and_then(|info| info.get("FileVersion")))
+            .flatten()
+            .map(|version| version.to_string())
+    });
+    if let Some(version) = &electron_version {
+        evidence.push(format!("UA字符串 Electron/{}", version));
+    }
+    let is_nw = !find_all(data, b"nwjs", 1).is_empty() || !find_all(data, b"NW.js", 1).is_empty();
+    if is_nw {
+        evidence.push("NW.js字符串".to_string());
+    }
+
+    let app_package = find_app_package(file_path);
+    if let Some((path, _)) = &app_package {
+        evidence.push(format!("应用包 {}", path));
+    }
+
+    // 只有Chromium导出的程序可能只是内嵌了CEF 需要至少一个Electron/NW.js相关的依据
+    let is_chromium =
+        !chromium_exports.is_empty() || electron_version.is_some() || is_electron_product;
+    let has_framework =
+        electron_version.is_some() || is_nw || node_exports > 0 || app_package.is_some();
+    if !is_chromium || !has_framework {
+        return None;
+    }
+
+    let framework = if is_nw || app_package.as_ref().is_some_and(|(_, is_nw)| *is_nw) {
+        "NW.js"
+    } else {
+        "Electron"
+    };
+    Some(ElectronInfo {
+        framework: framework.to_string(),
+        electron_version,
+        chromium_version: version_after(data, "Chrome/"),
+        node_version: version_after(data, "node/v").or_else(|| version_after(data, "Node.js v")),
+        app_package: app_package.map(|(path, _)| path),
+        evidence,
+    })
+}
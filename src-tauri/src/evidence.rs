@@ -0,0 +1,213 @@
+// 证据导出 按取证惯例把样本复制为<sha256>.bin 并在旁边保存完整的JSON分析报告和提取的IOC
+// 原文件不做改动 样本处理模式下样本按设置打包为加密zip
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{handling, strings, Analysis};
+
+// 搜索IOC的字符串最小长度 比字符串视图的默认值稍长 减少误报
+const MIN_STRING_LENGTH: usize = 6;
+// 每类IOC的数量上限
+const MAX_INDICATORS: usize = 1_000;
+const URL_SCHEMES: [&str; 3] = ["http://", "https://", "ftp://"];
+// XML命名空间等 出现在清单和元数据中 不代表网络活动
+const IGNORED_URL_HOSTS: [&str; 3] = ["schemas.microsoft.com", "www.w3.org", "ns.adobe.com"];
+// 大写比较 前一个字符不能是字母或数字
+const REGISTRY_PREFIXES: [&str; 7] = [
+    "HKEY_",
+    "HKLM\\",
+    "HKCU\\",
+    "HKCR\\",
+    "HKU\\",
+    "SOFTWARE\\",
+    "SYSTEM\\CURRENTCONTROLSET\\",
+];
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EvidenceIocs {
+    // 原始路径和导出时间 供保管链记录
+    pub source_path: String,
+    pub exported_at: u64,
+    pub size: u64,
+    pub md5: String,
+    pub sha1: String,
+    pub sha256: String,
+    pub imphash: String,
+    pub authentihash: Option<String>,
+    pub urls: Vec<String>,
+    pub ip_addresses: Vec<String>,
+    pub registry_keys: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct EvidenceExport {
+    // 样本处理模式打包时为.bin.zip
+    pub sample_path: String,
+    pub report_path: String,
+    pub iocs_path: String,
+    pub indicator_count: usize,
+}
+
+fn push_unique(list: &mut Vec<String>, value: &str) {
+    if list.len() < MAX_INDICATORS && !list.iter().any(|existing| existing == value) {
+        list.push(value.to_string());
+    }
+}
+
+// 去掉用户信息和端口 只有由字母、数字、连字符组成的多级域名或IP才算主机
+fn url_host(url: &str, scheme_length: usize) -> &str {
+    let authority = url[scheme_length..]
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default();
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    let host = host.split(':').next().unwrap_or_default();
+    let valid = host.contains('.')
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+        && host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-'));
+    if valid {
+        host
+    } else {
+        ""
+    }
+}
+
+fn find_urls(value: &str, urls: &mut Vec<String>) {
+    // 字符串只含可打印ASCII 小写后下标不变
+    let lower = value.to_ascii_lowercase();
+    for scheme in URL_SCHEMES {
+        let mut search = 0;
+        while let Some(index) = lower[search..].find(scheme) {
+            let start = search + index;
+            search = start + scheme.len();
+            let rest = &value[start..];
+            let end = rest
+                .find(|c: char| {
+                    c.is_whitespace() || matches!(c, '"' | '\'' | '<' | '>' | '`' | '{' | '}' | '|')
+                })
+                .unwrap_or(rest.len());
+            let url = rest[..end].trim_end_matches(['.', ',', ';', ')', ']']);
+            let host = url_host(url, scheme.len()).to_ascii_lowercase();
+            if !host.is_empty() && !IGNORED_URL_HOSTS.contains(&host.as_str()) {
+                push_unique(urls, url);
+            }
+        }
+    }
+}
+
+// 四段十进制 不含前导零 排除x.x.0.0形式(版本号常见)和以0或255开头的地址
+fn parse_ipv4(token: &str) -> Option<[u8; 4]> {
+    let mut octets = [0u8; 4];
+    let mut parts = token.split('.');
+    for octet in octets.iter_mut() {
+        let part = parts.next()?;
+        if part.is_empty() || part.len() > 3 || (part.len() > 1 && part.starts_with('0')) {
+            return None;
+        }
+        *octet = part.parse().ok()?;
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    let plausible = !matches!(octets[0], 0 | 255) && (octets[2], octets[3]) != (0, 0);
+    plausible.then_some(octets)
+}
+
+fn find_ip_addresses(value: &str, addresses: &mut Vec<String>) {
+    for token in value.split(|c: char| !c.is_ascii_digit() && c != '.') {
+        if let Some(octets) = parse_ipv4(token) {
+            let address = format!("{}.{}.{}.{}", octets[0], octets[1], octets[2], octets[3]);
+            push_unique(addresses, &address);
+        }
+    }
+}
+
+fn find_registry_key(value: &str, keys: &mut Vec<String>) {
+    let upper = value.to_ascii_uppercase();
+    for prefix in REGISTRY_PREFIXES {
+        let Some(index) = upper.find(prefix) else {
+            continue;
+        };
+        let bounded = index == 0 || !upper.as_bytes()[index - 1].is_ascii_alphanumeric();
+        let key = value[index..].trim_end();
+        if bounded && key.len() > prefix.len() {
+            push_unique(keys, key);
+            return;
+        }
+    }
+}
+
+fn collect_iocs(info: &Analysis, data: &[u8]) -> EvidenceIocs {
+    let mut iocs = EvidenceIocs {
+        source_path: info.path.clone(),
+        exported_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs()),
+        size: info.size,
+        md5: info.hashes.md5.clone(),
+        sha1: info.hashes.sha1.clone(),
+        sha256: info.hashes.sha256.clone(),
+        imphash: info.imphash.clone(),
+        authentihash: info.authentihash.clone(),
+        ..Default::default()
+    };
+    // 网络标记中的下载来源
+    if let Some(zone) = &info.zone_identifier {
+        for url in [&zone.host_url, &zone.referrer_url].into_iter().flatten() {
+            push_unique(&mut iocs.urls, url);
+        }
+    }
+    for (_, value) in strings::all_strings(data, MIN_STRING_LENGTH) {
+        find_urls(&value, &mut iocs.urls);
+        find_ip_addresses(&value, &mut iocs.ip_addresses);
+        find_registry_key(&value, &mut iocs.registry_keys);
+    }
+    // 版本资源中的a.b.c.d版本号与IP形式相同
+    if let Some(version) = &info.version_info {
+        let fixed = [&version.file_version, &version.product_version];
+        let versions: Vec<&str> = fixed
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .chain(version.strings.iter().map(|string| string.value.trim()))
+            .collect();
+        iocs.ip_addresses
+            .retain(|address| !versions.contains(&address.as_str()));
+    }
+    iocs
+}
+
+// 同一样本重复导出时覆盖 文件名由内容决定 不会与其他样本冲突
+pub(crate) fn export(
+    info: &Analysis,
+    data: &[u8],
+    directory: &Path,
+) -> Result<EvidenceExport, String> {
+    let sha256 = &info.hashes.sha256;
+    fs::create_dir_all(directory).map_err(|e| format!("无法创建目录: {}", e))?;
+
+    let sample_path = handling::write_payload(&directory.join(format!("{}.bin", sha256)), data)?;
+
+    let report_path = directory.join(format!("{}.json", sha256));
+    let report = serde_json::to_string_pretty(info).map_err(|e| format!("无法生成报告: {}", e))?;
+    fs::write(&report_path, report).map_err(|e| format!("无法写入文件: {}", e))?;
+
+    let iocs = collect_iocs(info, data);
+    let indicator_count = iocs.urls.len() + iocs.ip_addresses.len() + iocs.registry_keys.len();
+    let iocs_path = directory.join(format!("{}.iocs.json", sha256));
+    let json = serde_json::to_string_pretty(&iocs).map_err(|e| format!("无法生成IOC: {}", e))?;
+    fs::write(&iocs_path, json).map_err(|e| format!("无法写入文件: {}", e))?;
+
+    Ok(EvidenceExport {
+        sample_path: sample_path.to_string_lossy().to_string(),
+        report_path: report_path.to_string_lossy().to_string(),
+        iocs_path: iocs_path.to_string_lossy().to_string(),
+        indicator_count,
+    })
+}
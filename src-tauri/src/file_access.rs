@@ -0,0 +1,225 @@
+// 打开待分析的文件 处理超长路径、UNC网络共享、无响应的网络位置和被其他进程锁定的文件
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+// 网络位置在该时间内无响应视为不可访问
+const ACCESS_TIMEOUT: Duration = Duration::from_secs(10);
+// 每次读取的块大小 打开文件和读取每一块都要在超时时间内完成
+const CHUNK_SIZE: usize = 1 << 20;
+
+// Windows下转换为\\?\形式 绕过MAX_PATH限制
+// \\?\路径不会再被规范化 因此先转为绝对路径并统一分隔符
+#[cfg(windows)]
+fn extended_length_path(path: &Path) -> PathBuf {
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+    let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let wide: Vec<u16> = absolute
+        .as_os_str()
+        .encode_wide()
+        .map(|unit| {
+            if unit == u16::from(b'/') {
+                u16::from(b'\\')
+            } else {
+                unit
+            }
+        })
+        .collect();
+    let starts_with = |prefix: &str| wide.starts_with(&prefix.encode_utf16().collect::<Vec<u16>>());
+    let prefixed: Vec<u16> = if starts_with(r"\\?\") || starts_with(r"\\.\") {
+        wide
+    } else if starts_with(r"\\") {
+        // \\server\share -> \\?\UNC\server\share
+        r"\\?\UNC\"
+            .encode_utf16()
+            .chain(wide[2..].iter().copied())
+            .collect()
+    } else {
+        r"\\?\".encode_utf16().chain(wide).collect()
+    };
+    PathBuf::from(OsString::from_wide(&prefixed))
+}
+
+#[cfg(not(windows))]
+fn extended_length_path(path: &Path) -> PathBuf {
+    path.to_path_buf()
+}
+
+// 在后台线程中读取元数据 断开的网络共享可能让调用阻塞数十秒
+fn metadata_with_timeout(path: &Path) -> Result<fs::Metadata, String> {
+    let (sender, receiver) = mpsc::channel();
+    let target = path.to_path_buf();
+    thread::spawn(move || {
+        let _ = sender.send(fs::metadata(&target));
+    });
+    match receiver.recv_timeout(ACCESS_TIMEOUT) {
+        Ok(Ok(metadata)) => Ok(metadata),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => Err("文件不存在".to_string()),
+        Ok(Err(e)) => Err(format!("无法访问文件: {}", e)),
+        Err(_) => Err(timeout_error()),
+    }
+}
+
+fn timeout_error() -> String {
+    format!(
+        "文件位置在{}秒内无响应 网络共享可能无法访问",
+        ACCESS_TIMEOUT.as_secs()
+    )
+}
+
+// 检查文件可访问并返回实际用于打开的路径
+pub(crate) fn resolve(file_path: &Path) -> Result<PathBuf, String> {
+    let path = extended_length_path(file_path);
+    let metadata = metadata_with_timeout(&path)?;
+    if metadata.is_dir() {
+        return Err("路径是目录而不是文件".to_string());
+    }
+    Ok(path)
+}
+
+// 其他进程以不共享读的方式打开了文件
+#[cfg(windows)]
+fn is_sharing_violation(error: &std::io::Error) -> bool {
+    const ERROR_SHARING_VIOLATION: i32 = 32;
+    const ERROR_LOCK_VIOLATION: i32 = 33;
+    matches!(
+        error.raw_os_error(),
+        Some(ERROR_SHARING_VIOLATION | ERROR_LOCK_VIOLATION)
+    )
+}
+
+// 允许他人读写删除 并以备份语义打开 拥有SeBackupPrivilege时可绕过部分锁定
+#[cfg(windows)]
+fn open_shared(path: &Path) -> std::io::Result<File> {
+    use std::os::windows::fs::OpenOptionsExt;
+
+    const FILE_SHARE_READ: u32 = 0x1;
+    const FILE_SHARE_WRITE: u32 = 0x2;
+    const FILE_SHARE_DELETE: u32 = 0x4;
+    const FILE_FLAG_BACKUP_SEMANTICS: u32 = 0x0200_0000;
+    fs::OpenOptions::new()
+        .read(true)
+        .share_mode(FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE)
+        .custom_flags(FILE_FLAG_BACKUP_SEMANTICS)
+        .open(path)
+}
+
+// 用系统自带的esentutl通过卷影副本复制被锁定的文件 需要管理员权限
+// 副本以关闭时删除的方式打开 不在临时目录中残留
+#[cfg(windows)]
+fn open_snapshot_copy(path: &Path) -> Result<File, String> {
+    use std::os::windows::fs::OpenOptionsExt;
+    use std::process::{Command, Stdio};
+
+    const GENERIC_READ: u32 = 0x8000_0000;
+    const DELETE: u32 = 0x0001_0000;
+    const FILE_FLAG_DELETE_ON_CLOSE: u32 = 0x0400_0000;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let copy = std::env::temp_dir().join(format!("pe_info_{}_{}", std::process::id(), file_name));
+    let _ = fs::remove_file(&copy);
+    let status = Command::new("esentutl.exe")
+        .arg("/y")
+        .arg(path)
+        .arg("/vss")
+        .arg("/d")
+        .arg(&copy)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("无法启动esentutl: {}", e))?;
+    if !status.success() || !copy.exists() {
+        let _ = fs::remove_file(&copy);
+        return Err("卷影复制失败 需要以管理员身份运行".to_string());
+    }
+    fs::OpenOptions::new()
+        .access_mode(GENERIC_READ | DELETE)
+        .custom_flags(FILE_FLAG_DELETE_ON_CLOSE)
+        .open(&copy)
+        .map_err(|e| format!("无法打开卷影副本: {}", e))
+}
+
+#[cfg(windows)]
+fn open_locked(path: &Path, error: std::io::Error) -> Result<File, String> {
+    if !is_sharing_violation(&error) {
+        return Err(format!("无法打开文件: {}", error));
+    }
+    if let Ok(file) = open_shared(path) {
+        return Ok(file);
+    }
+    open_snapshot_copy(path)
+        .map_err(|snapshot_error| format!("文件被其他进程独占锁定 无法读取 ({})", snapshot_error))
+}
+
+#[cfg(not(windows))]
+fn open_locked(_path: &Path, error: std::io::Error) -> Result<File, String> {
+    Err(format!("无法打开文件: {}", error))
+}
+
+fn open_file(path: &Path) -> Result<File, String> {
+    match File::open(path) {
+        Ok(file) => Ok(file),
+        Err(error) => open_locked(path, error),
+    }
+}
+
+// 在后台线程中打开文件并分块读取最多limit字节
+// 大文件可以读很久 但打开(包括卷影复制)或任意一块在超时时间内没有完成时返回错误
+fn read_with_timeout(file_path: &Path, limit: u64) -> Result<Vec<u8>, String> {
+    let path = resolve(file_path)?;
+    let (sender, receiver) = mpsc::channel::<Result<Option<Vec<u8>>, String>>();
+    thread::spawn(move || {
+        let mut file = match open_file(&path) {
+            Ok(file) => file.take(limit),
+            Err(e) => {
+                let _ = sender.send(Err(e));
+                return;
+            }
+        };
+        loop {
+            let mut chunk = vec![0u8; CHUNK_SIZE];
+            let result = match file.read(&mut chunk) {
+                Ok(0) => Ok(None),
+                Ok(length) => {
+                    chunk.truncate(length);
+                    Ok(Some(chunk))
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => Err(format!("无法读取文件: {}", e)),
+            };
+            let finished = !matches!(result, Ok(Some(_)));
+            // 接收端超时后不再读取
+            if sender.send(result).is_err() || finished {
+                return;
+            }
+        }
+    });
+    let mut data: Vec<u8> = Vec::new();
+    loop {
+        match receiver.recv_timeout(ACCESS_TIMEOUT) {
+            Ok(Ok(Some(chunk))) => data.extend_from_slice(&chunk),
+            Ok(Ok(None)) => return Ok(data),
+            Ok(Err(e)) => return Err(e),
+            Err(mpsc::RecvTimeoutError::Timeout) => return Err(timeout_error()),
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                return Err("读取文件的线程异常退出".to_string())
+            }
+        }
+    }
+}
+
+pub(crate) fn read_file(file_path: &Path) -> Result<Vec<u8>, String> {
+    read_with_timeout(file_path, u64::MAX)
+}
+
+// 文件开头最多length字节 用于检查文件头
+pub(crate) fn read_prefix(file_path: &Path, length: usize) -> Result<Vec<u8>, String> {
+    read_with_timeout(file_path, length as u64)
+}
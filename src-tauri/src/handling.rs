@@ -0,0 +1,155 @@
+// 样本处理模式 处理活体恶意软件时的防护: 不启动任何程序、不访问网络
+// 解出的文件名追加.bin后缀 设置了密码时打包为加密zip 保存在本地配置文件中
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+use crate::zip_writer::encrypted_zip;
+
+// 多个文件打包时的归档名
+const ARCHIVE_NAME: &str = "payloads.zip";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct HandlingMode {
+    pub enabled: bool,
+    // 为空时不打包 样本交换通常使用"infected"
+    pub zip_password: String,
+}
+
+impl HandlingMode {
+    fn zips(&self) -> bool {
+        self.enabled && !self.zip_password.is_empty()
+    }
+}
+
+#[derive(Default)]
+struct HandlingConfig {
+    path: Option<PathBuf>,
+    mode: HandlingMode,
+}
+
+static CONFIG: LazyLock<RwLock<HandlingConfig>> =
+    LazyLock::new(|| RwLock::new(HandlingConfig::default()));
+
+pub(crate) fn open(path: PathBuf) -> Result<(), String> {
+    let mode: HandlingMode = if path.exists() {
+        let text = fs::read_to_string(&path).map_err(|e| format!("无法读取样本处理设置: {}", e))?;
+        serde_json::from_str(&text).map_err(|e| format!("无法解析样本处理设置: {}", e))?
+    } else {
+        HandlingMode::default()
+    };
+    let mut config = CONFIG
+        .write()
+        .map_err(|_| String::from("样本处理设置不可用"))?;
+    config.path = Some(path);
+    config.mode = mode;
+    Ok(())
+}
+
+pub(crate) fn current() -> HandlingMode {
+    CONFIG
+        .read()
+        .map_or_else(|_| HandlingMode::default(), |config| config.mode.clone())
+}
+
+pub(crate) fn save(mode: HandlingMode) -> Result<HandlingMode, String> {
+    let mut config = CONFIG
+        .write()
+        .map_err(|_| String::from("样本处理设置不可用"))?;
+    if let Some(path) = &config.path {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&mode).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("无法写入样本处理设置: {}", e))?;
+    }
+    config.mode = mode.clone();
+    Ok(mode)
+}
+
+// 启动外部程序和联网查询前检查
+pub(crate) fn ensure_allowed(action: &str) -> Result<(), String> {
+    if current().enabled {
+        return Err(format!("样本处理模式下不允许{}", action));
+    }
+    Ok(())
+}
+
+// 追加.bin后缀 双击不会执行或被关联程序打开
+pub(crate) fn inert_name(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    if path
+        .extension()
+        .is_none_or(|extension| !extension.eq_ignore_ascii_case("bin"))
+    {
+        name.push(".bin");
+    }
+    PathBuf::from(name)
+}
+
+fn archive_entry_name(path: &Path) -> String {
+    path.components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+// 写出单个解出的文件 打包时写为同名的.zip 返回实际写入的路径
+pub(crate) fn write_payload(path: &Path, data: &[u8]) -> Result<PathBuf, String> {
+    let mode = current();
+    if !mode.enabled {
+        fs::write(path, data).map_err(|e| format!("无法写入文件: {}", e))?;
+        return Ok(path.to_path_buf());
+    }
+    let inert = inert_name(path);
+    if !mode.zips() {
+        fs::write(&inert, data).map_err(|e| format!("无法写入文件: {}", e))?;
+        return Ok(inert);
+    }
+    let entry = inert
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let archive = encrypted_zip(&[(entry, data.to_vec())], &mode.zip_password)?;
+    let mut archive_path = path.as_os_str().to_os_string();
+    archive_path.push(".zip");
+    let archive_path = PathBuf::from(archive_path);
+    fs::write(&archive_path, archive).map_err(|e| format!("无法写入文件: {}", e))?;
+    Ok(archive_path)
+}
+
+// 把一组相对路径的文件写到目录中 打包时整个目录写为一个归档 返回文件数
+pub(crate) fn write_payloads(
+    directory: &Path,
+    files: Vec<(PathBuf, Vec<u8>)>,
+) -> Result<usize, String> {
+    let mode = current();
+    let count = files.len();
+    fs::create_dir_all(directory).map_err(|e| format!("无法创建目录: {}", e))?;
+    if mode.zips() {
+        let entries: Vec<(String, Vec<u8>)> = files
+            .into_iter()
+            .map(|(relative, data)| (archive_entry_name(&inert_name(&relative)), data))
+            .collect();
+        let archive = encrypted_zip(&entries, &mode.zip_password)?;
+        fs::write(directory.join(ARCHIVE_NAME), archive)
+            .map_err(|e| format!("无法写入文件: {}", e))?;
+        return Ok(count);
+    }
+    for (relative, data) in files {
+        let path = directory.join(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+        }
+        let path = if mode.enabled {
+            inert_name(&path)
+        } else {
+            path
+        };
+        fs::write(&path, data).map_err(|e| format!("无法写入文件: {}", e))?;
+    }
+    Ok(count)
+}
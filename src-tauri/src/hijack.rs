@@ -0,0 +1,152 @@
+// DLL搜索顺序劫持面: 不在KnownDLLs中、程序目录里也没有的导入DLL 加载器会先在程序目录中查找它们
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use crate::analyze;
+use crate::deps::{find_in_directory, is_api_set, system_directories};
+
+const IMAGE_FILE_DLL: u16 = 0x2000;
+
+// 读取注册表失败时使用的Windows 10默认KnownDLLs
+const DEFAULT_KNOWN_DLLS: &[&str] = &[
+    "advapi32.dll",
+    "clbcatq.dll",
+    "combase.dll",
+    "comdlg32.dll",
+    "coml2.dll",
+    "difxapi.dll",
+    "gdi32.dll",
+    "gdiplus.dll",
+    "imagehlp.dll",
+    "imm32.dll",
+    "kernel32.dll",
+    "msctf.dll",
+    "msvcrt.dll",
+    "normaliz.dll",
+    "nsi.dll",
+    "ole32.dll",
+    "oleaut32.dll",
+    "psapi.dll",
+    "rpcrt4.dll",
+    "sechost.dll",
+    "setupapi.dll",
+    "shcore.dll",
+    "shell32.dll",
+    "shlwapi.dll",
+    "user32.dll",
+    "wldap32.dll",
+    "wow64.dll",
+    "wow64cpu.dll",
+    "wow64win.dll",
+    "ws2_32.dll",
+];
+
+// 不在注册表列表中 但总是由系统预先加载的模块
+const ALWAYS_LOADED: &[&str] = &["ntdll.dll", "kernelbase.dll"];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HijackCandidate {
+    pub dll_name: String,
+    // sideload: 系统目录中存在 放到程序目录即可抢先加载
+    // phantom: 任何位置都不存在 可放入程序目录或PATH中的任一目录
+    pub exposure: String,
+    pub system_path: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HijackReport {
+    // registry / builtin
+    pub known_dlls_source: String,
+    pub known_dlls: Vec<String>,
+    pub candidates: Vec<HijackCandidate>,
+}
+
+// HKLM\SYSTEM\CurrentControlSet\Control\Session Manager\KnownDLLs 中每个值为一个DLL名
+#[cfg(windows)]
+fn registry_known_dlls() -> Option<Vec<String>> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new("reg.exe")
+        .arg("query")
+        .arg(r"HKLM\SYSTEM\CurrentControlSet\Control\Session Manager\KnownDLLs")
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // 每行形如 "    kernel32    REG_SZ    kernel32.dll" DllDirectory等值不是DLL名
+    let names: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| line.contains("REG_SZ"))
+        .filter_map(|line| line.split_whitespace().last())
+        .map(|name| name.to_ascii_lowercase())
+        .filter(|name| name.ends_with(".dll"))
+        .collect();
+    Some(names).filter(|names| !names.is_empty())
+}
+
+#[cfg(not(windows))]
+fn registry_known_dlls() -> Option<Vec<String>> {
+    None
+}
+
+pub(crate) fn search_order_exposure(file_path: &str) -> Result<HijackReport, String> {
+    let info = analyze(file_path.into())?;
+    if info.characteristics & IMAGE_FILE_DLL != 0 {
+        return Err("DLL的搜索目录取决于加载它的程序 仅支持分析EXE".to_string());
+    }
+    let system_directory = system_directories(info.is_x64)
+        .into_iter()
+        .next()
+        .ok_or("无法确定系统目录 仅支持在Windows上检查")?;
+    let app_directory = Path::new(file_path)
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+
+    let (known_dlls_source, mut known_dlls) = match registry_known_dlls() {
+        Some(names) => ("registry", names),
+        None => (
+            "builtin",
+            DEFAULT_KNOWN_DLLS
+                .iter()
+                .map(|name| name.to_string())
+                .collect(),
+        ),
+    };
+    known_dlls.sort();
+    known_dlls.dedup();
+
+    let mut candidates: Vec<HijackCandidate> = Vec::new();
+    for import in &info.import_table {
+        let lower = import.dll_name.to_ascii_lowercase();
+        if is_api_set(&lower)
+            || known_dlls.contains(&lower)
+            || ALWAYS_LOADED.contains(&lower.as_str())
+            || candidates
+                .iter()
+                .any(|candidate| candidate.dll_name.eq_ignore_ascii_case(&lower))
+            || find_in_directory(&app_directory, &import.dll_name).is_some()
+        {
+            continue;
+        }
+        let system_path = find_in_directory(&system_directory, &import.dll_name);
+        candidates.push(HijackCandidate {
+            dll_name: import.dll_name.clone(),
+            exposure: if system_path.is_some() {
+                "sideload"
+            } else {
+                "phantom"
+            }
+            .to_string(),
+            system_path: system_path.map(|path| path.to_string_lossy().to_string()),
+        });
+    }
+    Ok(HijackReport {
+        known_dlls_source: known_dlls_source.to_string(),
+        known_dlls,
+        candidates,
+    })
+}
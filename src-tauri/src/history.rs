@@ -0,0 +1,514 @@
+// 本地分析历史 每次在界面中分析文件时记录摘要到SQLite 可按时间、导入、标签等条件检索
+// 标签按SHA-256保存 同一文件的所有分析记录共享
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+
+use std::path::PathBuf;
+use std::sync::{LazyLock, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::workspace::summarize;
+use crate::PeInfo;
+
+// 未指定数量时最多返回的记录数
+const DEFAULT_LIMIT: u32 = 500;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS analyses (
+    id INTEGER PRIMARY KEY,
+    analyzed_at INTEGER NOT NULL,
+    path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    md5 TEXT NOT NULL,
+    sha1 TEXT NOT NULL,
+    sha256 TEXT NOT NULL,
+    imphash TEXT NOT NULL,
+    architecture TEXT NOT NULL,
+    subsystem TEXT NOT NULL,
+    is_signed INTEGER NOT NULL,
+    signer TEXT,
+    known_good INTEGER NOT NULL,
+    packer TEXT,
+    suspicion INTEGER NOT NULL,
+    warnings INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS analyses_sha256 ON analyses(sha256);
+CREATE INDEX IF NOT EXISTS analyses_time ON analyses(analyzed_at);
+CREATE TABLE IF NOT EXISTS imports (
+    analysis_id INTEGER NOT NULL REFERENCES analyses(id) ON DELETE CASCADE,
+    dll TEXT NOT NULL,
+    function TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS imports_analysis ON imports(analysis_id);
+CREATE TABLE IF NOT EXISTS tags (
+    sha256 TEXT NOT NULL,
+    tag TEXT NOT NULL,
+    PRIMARY KEY (sha256, tag)
+);
+";
+
+// 版本2: 跨文件检索所需的导出名、节区名和导入指纹 旧记录中这些字段为空
+const MIGRATION_2: &str = "
+ALTER TABLE analyses ADD COLUMN import_order_hash TEXT NOT NULL DEFAULT '';
+ALTER TABLE analyses ADD COLUMN dll_sequence_hash TEXT NOT NULL DEFAULT '';
+CREATE TABLE IF NOT EXISTS exports (
+    analysis_id INTEGER NOT NULL REFERENCES analyses(id) ON DELETE CASCADE,
+    name TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS exports_analysis ON exports(analysis_id);
+CREATE INDEX IF NOT EXISTS exports_name ON exports(name COLLATE NOCASE);
+CREATE INDEX IF NOT EXISTS imports_function ON imports(function COLLATE NOCASE);
+CREATE TABLE IF NOT EXISTS sections (
+    analysis_id INTEGER NOT NULL REFERENCES analyses(id) ON DELETE CASCADE,
+    name TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS sections_analysis ON sections(analysis_id);
+CREATE INDEX IF NOT EXISTS analyses_imphash ON analyses(imphash);
+";
+
+// 跨文件检索的字段 值按整体匹配 名称不区分大小写
+const PIVOTS: &[(&str, &str)] = &[
+    (
+        "export",
+        "EXISTS (SELECT 1 FROM exports WHERE analysis_id = analyses.id AND name = ? COLLATE NOCASE)",
+    ),
+    (
+        "import",
+        "EXISTS (SELECT 1 FROM imports WHERE analysis_id = analyses.id AND function = ? COLLATE NOCASE)",
+    ),
+    (
+        "dll",
+        "EXISTS (SELECT 1 FROM imports WHERE analysis_id = analyses.id AND dll = ? COLLATE NOCASE)",
+    ),
+    (
+        "section",
+        "EXISTS (SELECT 1 FROM sections WHERE analysis_id = analyses.id AND name = ?)",
+    ),
+    ("imphash", "imphash = lower(?)"),
+    ("import_order_hash", "import_order_hash = lower(?)"),
+    ("dll_sequence_hash", "dll_sequence_hash = lower(?)"),
+    ("sha256", "sha256 = lower(?)"),
+    ("signer", "signer = ?"),
+    ("packer", "packer = ?"),
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HistoryEntry {
+    pub id: i64,
+    // Unix时间戳
+    pub analyzed_at: u64,
+    pub path: String,
+    pub size: u64,
+    pub md5: String,
+    pub sha256: String,
+    pub imphash: String,
+    pub architecture: String,
+    pub is_signed: bool,
+    pub signer: Option<String>,
+    pub known_good: bool,
+    pub packer: Option<String>,
+    pub suspicion: u32,
+    pub warnings: u32,
+    pub tags: Vec<String>,
+}
+
+// 检索条件 均为可选 同时给出时取交集
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct HistoryQuery {
+    // 路径或任一哈希中包含的文本
+    pub text: Option<String>,
+    // 导入的DLL名或函数名中包含的文本 如 ws2_32
+    pub import: Option<String>,
+    pub tag: Option<String>,
+    // 分析时间范围 Unix时间戳
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+    pub min_suspicion: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+static DATABASE: LazyLock<Mutex<Option<Connection>>> = LazyLock::new(|| Mutex::new(None));
+
+fn with_database<T>(
+    action: impl FnOnce(&mut Connection) -> rusqlite::Result<T>,
+) -> Result<T, String> {
+    let mut database = DATABASE
+        .lock()
+        .map_err(|_| String::from("历史数据库不可用"))?;
+    let connection = database.as_mut().ok_or("历史数据库未打开")?;
+    action(connection).map_err(|e| format!("历史数据库错误: {}", e))
+}
+
+// 按user_version逐级升级 版本1的数据库由最初的SCHEMA创建
+fn migrate(connection: &Connection) -> rusqlite::Result<()> {
+    let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if version < 1 {
+        connection.execute_batch(SCHEMA)?;
+        connection.execute_batch("PRAGMA user_version = 1;")?;
+    }
+    if version < 2 {
+        connection.execute_batch(MIGRATION_2)?;
+        connection.execute_batch("PRAGMA user_version = 2;")?;
+    }
+    Ok(())
+}
+
+pub(crate) fn open(path: PathBuf) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+    }
+    let connection = Connection::open(&path)
+        .and_then(|connection| {
+            connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+            migrate(&connection)?;
+            Ok(connection)
+        })
+        .map_err(|e| format!("无法打开历史数据库: {}", e))?;
+    *DATABASE
+        .lock()
+        .map_err(|_| String::from("历史数据库不可用"))? = Some(connection);
+    Ok(())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+pub(crate) fn record(info: &PeInfo) -> Result<i64, String> {
+    let row = summarize(info);
+    with_database(|connection| {
+        let transaction = connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO analyses (analyzed_at, path, size, md5, sha1, sha256, imphash,
+                import_order_hash, dll_sequence_hash, architecture, subsystem, is_signed, signer,
+                known_good, packer, suspicion, warnings)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)",
+            params![
+                now() as i64,
+                row.path,
+                row.size as i64,
+                row.md5,
+                info.hashes.sha1,
+                row.sha256,
+                row.imphash,
+                row.import_order_hash,
+                row.dll_sequence_hash,
+                row.architecture,
+                row.subsystem,
+                row.is_signed,
+                row.signer,
+                row.known_good,
+                row.packer,
+                row.suspicion,
+                info.warnings.len() as u32,
+            ],
+        )?;
+        let id = transaction.last_insert_rowid();
+        {
+            let mut insert = transaction
+                .prepare("INSERT INTO imports (analysis_id, dll, function) VALUES (?1, ?2, ?3)")?;
+            for entry in &info.import_table {
+                // 没有按名称导入的函数时也记录DLL 便于按DLL检索
+                if entry.functions.is_empty() {
+                    insert.execute(params![id, entry.dll_name, ""])?;
+                }
+                for function in &entry.functions {
+                    insert.execute(params![id, entry.dll_name, function.name])?;
+                }
+            }
+        }
+        {
+            let mut insert =
+                transaction.prepare("INSERT INTO exports (analysis_id, name) VALUES (?1, ?2)")?;
+            for export in info.export_table.iter().filter(|export| export.is_named) {
+                insert.execute(params![id, export.name])?;
+            }
+            let mut insert =
+                transaction.prepare("INSERT INTO sections (analysis_id, name) VALUES (?1, ?2)")?;
+            for section in &info.sections {
+                insert.execute(params![id, section.name])?;
+            }
+        }
+        transaction.commit()?;
+        Ok(id)
+    })
+}
+
+// LIKE中的通配符按字面匹配
+fn like_pattern(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+fn tags_of(connection: &Connection, sha256: &str) -> rusqlite::Result<Vec<String>> {
+    let mut statement =
+        connection.prepare("SELECT tag FROM tags WHERE sha256 = ?1 ORDER BY tag")?;
+    let tags = statement
+        .query_map([sha256], |row| row.get(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    Ok(tags)
+}
+
+pub(crate) fn query(query: &HistoryQuery) -> Result<Vec<HistoryEntry>, String> {
+    let mut conditions: Vec<&str> = Vec::new();
+    let mut values: Vec<rusqlite::types::Value> = Vec::new();
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    };
+    if let Some(text) = non_empty(&query.text) {
+        conditions.push(
+            "(path LIKE ? ESCAPE '\\' OR md5 LIKE ? ESCAPE '\\' OR sha1 LIKE ? ESCAPE '\\'
+              OR sha256 LIKE ? ESCAPE '\\' OR imphash LIKE ? ESCAPE '\\')",
+        );
+        for _ in 0..5 {
+            values.push(like_pattern(&text).into());
+        }
+    }
+    if let Some(import) = non_empty(&query.import) {
+        conditions.push(
+            "EXISTS (SELECT 1 FROM imports WHERE analysis_id = analyses.id
+              AND (dll LIKE ? ESCAPE '\\' OR function LIKE ? ESCAPE '\\'))",
+        );
+        values.push(like_pattern(&import).into());
+        values.push(like_pattern(&import).into());
+    }
+    if let Some(tag) = non_empty(&query.tag) {
+        conditions
+            .push("EXISTS (SELECT 1 FROM tags WHERE tags.sha256 = analyses.sha256 AND tag = ?)");
+        values.push(tag.into());
+    }
+    if let Some(since) = query.since {
+        conditions.push("analyzed_at >= ?");
+        values.push((since as i64).into());
+    }
+    if let Some(until) = query.until {
+        conditions.push("analyzed_at < ?");
+        values.push((until as i64).into());
+    }
+    if let Some(min_suspicion) = query.min_suspicion {
+        conditions.push("suspicion >= ?");
+        values.push((min_suspicion as i64).into());
+    }
+    fetch(&conditions, values, query.limit)
+}
+
+// 按分析时间倒序返回满足全部条件的记录
+fn fetch(
+    conditions: &[&str],
+    mut values: Vec<rusqlite::types::Value>,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let mut sql = String::from(
+        "SELECT id, analyzed_at, path, size, md5, sha256, imphash, architecture, is_signed,
+            signer, known_good, packer, suspicion, warnings FROM analyses",
+    );
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(" ORDER BY analyzed_at DESC, id DESC LIMIT ?");
+    values.push((limit.unwrap_or(DEFAULT_LIMIT) as i64).into());
+
+    with_database(|connection| {
+        let mut statement = connection.prepare(&sql)?;
+        let mut entries = statement
+            .query_map(params_from_iter(values), |row| {
+                Ok(HistoryEntry {
+                    id: row.get(0)?,
+                    analyzed_at: row.get::<_, i64>(1)? as u64,
+                    path: row.get(2)?,
+                    size: row.get::<_, i64>(3)? as u64,
+                    md5: row.get(4)?,
+                    sha256: row.get(5)?,
+                    imphash: row.get(6)?,
+                    architecture: row.get(7)?,
+                    is_signed: row.get(8)?,
+                    signer: row.get(9)?,
+                    known_good: row.get(10)?,
+                    packer: row.get(11)?,
+                    suspicion: row.get(12)?,
+                    warnings: row.get(13)?,
+                    tags: Vec::new(),
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<HistoryEntry>>>()?;
+        for entry in &mut entries {
+            entry.tags = tags_of(connection, &entry.sha256)?;
+        }
+        Ok(entries)
+    })
+}
+
+// 跨文件检索 如导出CPlApplet的所有文件、imphash相同的所有文件
+// 同一文件分析过多次时只返回最近一次
+pub(crate) fn pivot(
+    field: &str,
+    value: &str,
+    limit: Option<u32>,
+) -> Result<Vec<HistoryEntry>, String> {
+    let (_, condition) = PIVOTS
+        .iter()
+        .find(|(name, _)| *name == field)
+        .ok_or_else(|| format!("不支持的检索字段: {}", field))?;
+    let value = value.trim();
+    if value.is_empty() {
+        return Err("检索值为空".into());
+    }
+    fetch(
+        &[
+            condition,
+            "id IN (SELECT MAX(id) FROM analyses GROUP BY sha256)",
+        ],
+        vec![value.to_string().into()],
+        limit,
+    )
+}
+
+// 数据库未打开时返回空列表 不影响分析
+pub(crate) fn tags_for(sha256: &str) -> Vec<String> {
+    with_database(|connection| tags_of(connection, sha256)).unwrap_or_default()
+}
+
+// 标签去除首尾空白 返回该文件的全部标签
+pub(crate) fn add_tag(sha256: &str, tag: &str) -> Result<Vec<String>, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("标签为空".into());
+    }
+    with_database(|connection| {
+        connection.execute(
+            "INSERT OR IGNORE INTO tags (sha256, tag) VALUES (?1, ?2)",
+            params![sha256, tag],
+        )?;
+        tags_of(connection, sha256)
+    })
+}
+
+pub(crate) fn remove_tag(sha256: &str, tag: &str) -> Result<Vec<String>, String> {
+    with_database(|connection| {
+        connection.execute(
+            "DELETE FROM tags WHERE sha256 = ?1 AND tag = ?2",
+            params![sha256, tag],
+        )?;
+        tags_of(connection, sha256)
+    })
+}
+
+// 所有用过的标签 供输入时选择
+pub(crate) fn all_tags() -> Result<Vec<String>, String> {
+    with_database(|connection| {
+        let mut statement = connection.prepare("SELECT DISTINCT tag FROM tags ORDER BY tag")?;
+        let tags = statement
+            .query_map([], |row| row.get(0))?
+            .collect::<rusqlite::Result<Vec<String>>>()?;
+        Ok(tags)
+    })
+}
+
+// 删除一条分析记录 标签属于文件 不随之删除
+pub(crate) fn delete_entry(id: i64) -> Result<(), String> {
+    with_database(|connection| {
+        connection.execute("DELETE FROM analyses WHERE id = ?1", [id])?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 版本1的数据库 只有最初的表
+    fn insert_v1(connection: &Connection, id: i64, path: &str, sha256: &str, dll: &str) {
+        connection
+            .execute(
+                "INSERT INTO analyses (id, analyzed_at, path, size, md5, sha1, sha256, imphash,
+                    architecture, subsystem, is_signed, known_good, suspicion, warnings)
+                 VALUES (?1, ?1, ?2, 0, '', '', ?3, '', 'x64', 'GUI', 0, 0, 0, 0)",
+                params![id, path, sha256],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO imports (analysis_id, dll, function) VALUES (?1, ?2, 'connect')",
+                params![id, dll],
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn migrates_and_queries() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection.execute_batch(SCHEMA).unwrap();
+        connection
+            .execute_batch("PRAGMA user_version = 1;")
+            .unwrap();
+        insert_v1(&connection, 1, r"C:\a.exe", "aa", "WS2_32.dll");
+        insert_v1(&connection, 2, r"C:\b.exe", "bb", "ws2x32.dll");
+        insert_v1(&connection, 3, r"C:\100%.exe", "cc", "kernel32.dll");
+        connection
+            .execute("INSERT INTO tags (sha256, tag) VALUES ('aa', 'apt')", [])
+            .unwrap();
+
+        migrate(&connection).unwrap();
+        let version: i64 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, 2);
+        // 旧记录的新字段为空
+        let hash: String = connection
+            .query_row(
+                "SELECT import_order_hash FROM analyses WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(hash, "");
+        // 已是最新版本时不再执行迁移
+        migrate(&connection).unwrap();
+        connection
+            .execute(
+                "INSERT INTO exports (analysis_id, name) VALUES (2, 'CPlApplet')",
+                [],
+            )
+            .unwrap();
+        *DATABASE.lock().unwrap() = Some(connection);
+
+        let ids = |entries: Vec<HistoryEntry>| entries.iter().map(|e| e.id).collect::<Vec<_>>();
+        // _按字面匹配 不匹配ws2x32
+        let import = HistoryQuery {
+            import: Some("ws2_32".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(query(&import).unwrap()), [1]);
+        let tag = HistoryQuery {
+            tag: Some("apt".into()),
+            import: Some("CONNECT".into()),
+            ..Default::default()
+        };
+        let entries = query(&tag).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, 1);
+        assert_eq!(entries[0].tags, ["apt"]);
+        // %按字面匹配
+        let text = HistoryQuery {
+            text: Some("100%".into()),
+            ..Default::default()
+        };
+        assert_eq!(ids(query(&text).unwrap()), [3]);
+
+        assert_eq!(ids(pivot("dll", "ws2_32.DLL", None).unwrap()), [1]);
+        assert_eq!(ids(pivot("import", "connect", None).unwrap()), [3, 2, 1]);
+        assert_eq!(ids(pivot("export", "cplapplet", None).unwrap()), [2]);
+        assert!(pivot("path", "a", None).is_err());
+        assert!(pivot("export", " ", None).is_err());
+    }
+}
@@ -0,0 +1,150 @@
+// 依赖本机环境的检查: API集合、WinSxS、系统目录中的同名文件、备用数据流和快捷方式
+use crate::apiset::ApiSetResolution;
+use crate::heuristics::{Severity, Warning};
+use crate::shortcut::ShortcutInfo;
+use crate::streams::{AlternateStream, ZoneIdentifier};
+use crate::sxs::SxsDependency;
+
+// 本系统无法满足的API集导入 程序在更旧的Windows版本上通常无法启动
+pub(crate) fn check_api_sets(api_sets: &[ApiSetResolution]) -> Vec<Warning> {
+    let unresolved: Vec<&str> = api_sets
+        .iter()
+        .filter(|api_set| api_set.status == "missing" || api_set.status == "no_host")
+        .map(|api_set| api_set.name.as_str())
+        .collect();
+    if unresolved.is_empty() {
+        return Vec::new();
+    }
+    vec![Warning::new(
+        "api_set_unresolved",
+        Severity::Info,
+        format!(
+            "以下API集在本机的apisetschema中没有宿主DLL 在当前系统版本上无法加载(ext-ms-*常为可选组件 可能是延迟加载): {}",
+            unresolved.join(", ")
+        ),
+    )
+    .with_pane("imports")]
+}
+
+// 清单要求的SxS程序集在本机和程序目录中都找不到 创建激活上下文会失败(并行配置不正确)
+pub(crate) fn check_sxs_dependencies(dependencies: &[SxsDependency]) -> Vec<Warning> {
+    dependencies
+        .iter()
+        .filter(|dependency| dependency.status == "missing")
+        .map(|dependency| {
+            Warning::new(
+                "sxs_missing",
+                Severity::Info,
+                format!(
+                    "清单依赖的程序集 {} {} ({}) 未安装 启动时会因并行配置不正确而失败",
+                    dependency.name, dependency.version, dependency.architecture
+                ),
+            )
+            .with_pane("manifest")
+        })
+        .collect()
+}
+
+// IMAGE_FILE_HEADER.Characteristics
+const IMAGE_FILE_DLL: u16 = 0x2000;
+
+// 与系统DLL同名的非系统文件 可能被放在应用目录中用于侧加载
+pub(crate) fn check_system_dll_name(system_copy: &str, characteristics: u16) -> Vec<Warning> {
+    if characteristics & IMAGE_FILE_DLL == 0 {
+        return Vec::new();
+    }
+    vec![Warning::new(
+        "system_dll_name",
+        Severity::Low,
+        format!("与系统DLL同名: {} 可能用于DLL侧加载", system_copy),
+    )]
+}
+
+// Internet及受限制区域
+const INTERNET_ZONE: u32 = 3;
+
+// 备用数据流中藏有PE 以及带有网络标记的文件
+pub(crate) fn check_alternate_streams(
+    streams: &[AlternateStream],
+    zone: Option<&ZoneIdentifier>,
+) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = streams
+        .iter()
+        .filter(|stream| stream.is_pe)
+        .map(|stream| {
+            Warning::new(
+                "pe_in_alternate_stream",
+                Severity::High,
+                format!("备用数据流 {} 中包含PE文件", stream.name),
+            )
+            .with_pane("streams")
+        })
+        .collect();
+    if let Some(zone) = zone.filter(|zone| zone.zone_id.is_some_and(|id| id >= INTERNET_ZONE)) {
+        warnings.push(
+            Warning::new(
+                "mark_of_the_web",
+                Severity::Info,
+                format!(
+                    "文件来自{}区域: {}",
+                    zone.zone_name,
+                    zone.host_url.as_deref().unwrap_or("-")
+                ),
+            )
+            .with_pane("streams"),
+        );
+    }
+    warnings
+}
+
+// 常被快捷方式用来执行脚本或载荷的系统程序
+const SCRIPT_HOSTS: &[&str] = &[
+    "cmd.exe",
+    "powershell.exe",
+    "pwsh.exe",
+    "mshta.exe",
+    "wscript.exe",
+    "cscript.exe",
+    "rundll32.exe",
+    "regsvr32.exe",
+    "msiexec.exe",
+    "conhost.exe",
+    "forfiles.exe",
+    "certutil.exe",
+    "bitsadmin.exe",
+];
+// 参数前的空白超过此长度时属性对话框中看不到真正的参数
+const HIDDEN_ARGUMENT_PADDING: usize = 64;
+const SW_SHOWMINNOACTIVE: u32 = 7;
+
+// 快捷方式投递链: 用脚本宿主执行参数 或把参数藏在大量空白之后
+pub(crate) fn check_shortcut(shortcut: &ShortcutInfo) -> Vec<Warning> {
+    let mut warnings: Vec<Warning> = Vec::new();
+    let arguments = shortcut.arguments.as_deref().unwrap_or("");
+    let target_name = shortcut
+        .target_path
+        .as_deref()
+        .and_then(|target| target.rsplit(['\\', '/']).next())
+        .unwrap_or("")
+        .to_ascii_lowercase();
+    if !arguments.trim().is_empty() && SCRIPT_HOSTS.contains(&target_name.as_str()) {
+        warnings.push(Warning::new(
+            "shortcut_script_host",
+            if shortcut.show_command == SW_SHOWMINNOACTIVE {
+                Severity::High
+            } else {
+                Severity::Medium
+            },
+            format!("快捷方式通过 {} 执行: {}", target_name, arguments.trim()),
+        ));
+    }
+    let padding = arguments.len() - arguments.trim_start().len();
+    if padding >= HIDDEN_ARGUMENT_PADDING {
+        warnings.push(Warning::new(
+            "shortcut_hidden_arguments",
+            Severity::High,
+            format!("快捷方式参数前有{}个空白字符 用于隐藏真正的参数", padding),
+        ));
+    }
+    warnings
+}
@@ -0,0 +1,108 @@
+// 已知良性文件的哈希集合 可导入NSRL(NSRLFile.txt)或自定义白名单 分析时自动标记
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, RwLock};
+
+#[derive(Default)]
+struct Database {
+    // 本地数据库文件 每行一个小写十六进制哈希
+    path: Option<PathBuf>,
+    hashes: HashSet<String>,
+}
+
+static DATABASE: LazyLock<RwLock<Database>> = LazyLock::new(|| RwLock::new(Database::default()));
+
+// MD5/SHA-1/SHA-256的十六进制长度
+fn is_hash(token: &str) -> bool {
+    matches!(token.len(), 32 | 40 | 64) && token.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// 每行取所有形如哈希的字段 兼容NSRL的带引号CSV和每行一个哈希的列表
+fn parse_line(line: &str) -> impl Iterator<Item = String> + '_ {
+    line.split([',', '\t', ' '])
+        .map(|token| token.trim().trim_matches('"'))
+        .filter(|token| is_hash(token))
+        .map(|token| token.to_ascii_lowercase())
+}
+
+// 打开本地数据库 文件不存在时为空集合
+pub(crate) fn open(path: PathBuf) -> Result<(), String> {
+    let mut hashes: HashSet<String> = HashSet::new();
+    if path.exists() {
+        let file = File::open(&path).map_err(|e| format!("无法打开哈希数据库: {}", e))?;
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("无法读取哈希数据库: {}", e))?;
+            hashes.extend(parse_line(&line));
+        }
+    }
+    let mut database = DATABASE
+        .write()
+        .map_err(|_| String::from("哈希数据库不可用"))?;
+    database.path = Some(path);
+    database.hashes = hashes;
+    Ok(())
+}
+
+// 导入哈希集合 返回新增的哈希数量
+pub(crate) fn import(source: &Path) -> Result<usize, String> {
+    let file = File::open(source).map_err(|e| format!("无法打开哈希集合: {}", e))?;
+    let mut database = DATABASE
+        .write()
+        .map_err(|_| String::from("哈希数据库不可用"))?;
+    let mut added: Vec<String> = Vec::new();
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|e| format!("无法读取哈希集合: {}", e))?;
+        for hash in parse_line(&line) {
+            if database.hashes.insert(hash.clone()) {
+                added.push(hash);
+            }
+        }
+    }
+
+    // 追加写入本地数据库
+    if let Some(path) = &database.path {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("无法写入哈希数据库: {}", e))?;
+        let mut writer = BufWriter::new(file);
+        for hash in &added {
+            writeln!(writer, "{}", hash).map_err(|e| format!("无法写入哈希数据库: {}", e))?;
+        }
+        writer
+            .flush()
+            .map_err(|e| format!("无法写入哈希数据库: {}", e))?;
+    }
+    Ok(added.len())
+}
+
+pub(crate) fn clear() -> Result<(), String> {
+    let mut database = DATABASE
+        .write()
+        .map_err(|_| String::from("哈希数据库不可用"))?;
+    database.hashes.clear();
+    if let Some(path) = &database.path {
+        if path.exists() {
+            fs::remove_file(path).map_err(|e| format!("无法删除哈希数据库: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn count() -> usize {
+    DATABASE.read().map_or(0, |database| database.hashes.len())
+}
+
+// 在读锁下使用已知良性集合 供解析时判断 数据库不可用时视为空集合
+pub(crate) fn with_known_good<T>(f: impl FnOnce(&HashSet<String>) -> T) -> T {
+    match DATABASE.read() {
+        Ok(database) => f(&database.hashes),
+        Err(_) => f(&HashSet::new()),
+    }
+}
@@ -1,593 +1,1004 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 use serde::{Deserialize, Serialize};
+use tauri::Manager;
+
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+
+mod apiset;
+mod baseline;
+mod bookmarks;
+mod codegen;
+mod copy_values;
+mod deep_link;
+mod deps;
+mod diff;
+mod editor;
+mod electron;
+mod evidence;
+mod file_access;
+mod handling;
+mod hijack;
+mod history;
+mod host_checks;
+mod known_hashes;
+mod limits;
+mod microsoft;
+mod payload;
+mod queue;
+mod session;
+mod shell_menu;
+mod shortcut;
+mod streams;
+mod sxs;
+mod system_copy;
+mod teaching;
+mod tools;
+mod triage;
+mod watcher;
+mod workspace;
+mod xor_scan;
+mod zip_writer;
+
+// 解析核心中的模块和类型 各模块仍按crate::路径引用
+use pe_info_core::{
+    authenticode, capabilities, demangle, dynamic_imports, entropy, ep_signature, exception,
+    hashes, headers, heuristics, layout, manifest, python, reader, resources, score, sizes, slack,
+    strings, version_info,
+};
+use pe_info_core::{
+    offset_to_rva, overlay_offset, record_phase, rva_to_offset, ExportFunction, ImportTableEntry,
+    PeInfo, PhaseClock, Section, IMAGE_DIRECTORY_ENTRY_EXPORT, IMAGE_DIRECTORY_ENTRY_RESOURCE,
+    IMAGE_DIRECTORY_ENTRY_SECURITY,
+};
+pub use pe_info_core::{PeError, PeFile};
+
+use apiset::ApiSetResolution;
+use bookmarks::Bookmark;
+use electron::ElectronInfo;
+use shortcut::ShortcutInfo;
+use streams::{AlternateStream, ZoneIdentifier};
+use sxs::SxsDependency;
+
+// 一次分析的结果: 解析核心得到的文件信息加上依赖本机环境的信息
+// 序列化时展开到同一层 前端看到的字段不变
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Analysis {
+    #[serde(flatten)]
+    pe: PeInfo,
+    // api-ms-win-*/ext-ms-*导入在本机apisetschema中的宿主 非Windows上为空
+    api_sets: Vec<ApiSetResolution>,
+    // 嵌入清单中的SxS程序集依赖及其在本机的安装情况
+    sxs_dependencies: Vec<SxsDependency>,
+    electron: Option<ElectronInfo>,
+    // 用户在各地址上的书签和备注
+    bookmarks: Vec<Bookmark>,
+    // 用户给文件加的标签 如 clean/suspicious 保存在分析历史数据库中
+    tags: Vec<String>,
+    // System32/SysWOW64中的同名文件
+    system_copy: Option<String>,
+    // NTFS备用数据流 非Windows平台为空
+    alternate_streams: Vec<AlternateStream>,
+    zone_identifier: Option<ZoneIdentifier>,
+    // 通过.lnk打开时的快捷方式信息 path为其目标
+    shortcut: Option<ShortcutInfo>,
+}
 
-use std::fs::File;
-use std::io::{self, Read, Seek};
-use std::path::Path;
+impl Deref for Analysis {
+    type Target = PeInfo;
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Section {
-    name: String,
-    rva: u32,
-    ptr_raw_data: u32,
-    rv_end: u32,
+    fn deref(&self) -> &PeInfo {
+        &self.pe
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ImportFunction {
-    name: String,
-    is_ordinal: bool,
-    ordinal: u16,
-    hint: u16,
+impl DerefMut for Analysis {
+    fn deref_mut(&mut self) -> &mut PeInfo {
+        &mut self.pe
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ExportFunction {
-    name: String,
-    ordinal: u32,
-    address: u32,
+// 路径以PathBuf传入 不是有效UTF-8的Windows路径也能打开
+#[tauri::command]
+fn analyze(file_path: PathBuf) -> Result<Analysis, String> {
+    analyze_with_data(&file_path).map(|(pe_info, _)| pe_info)
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct ImportTableEntry {
-    dll_name: String,
-    functions: Vec<ImportFunction>,
+// 同时返回读入的文件内容 需要原始字节的命令不必再读一次文件
+// 快捷方式返回目标文件的内容
+fn analyze_with_data(file_path: &Path) -> Result<(Analysis, Vec<u8>), String> {
+    // 快捷方式 分析其目标
+    if let Some(shortcut) = shortcut::read_shortcut(file_path)? {
+        return analyze_shortcut_target(shortcut);
+    }
+
+    // 读入整个文件 超长路径、UNC路径在Windows下转换为\\?\形式
+    let analysis_start = PhaseClock::start();
+    let file_data = file_access::read_file(file_path)?;
+    let pe_info = known_hashes::with_known_good(|known_good| {
+        PeFile::parse_bytes(
+            &file_path.to_string_lossy(),
+            &file_data,
+            &limits::current(),
+            known_good,
+        )
+    })?;
+    let mut phase_start = PhaseClock::start();
+    let mut analysis = attach_host_context(pe_info, &file_data, file_path);
+    record_phase(&mut analysis.timings, "host", &mut phase_start);
+    analysis.total_duration_us = analysis_start.elapsed_us();
+    Ok((analysis, file_data))
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-struct PeInfo {
-    path: String,
-    size: u64,
-    is_x64: bool,
-    sections: Vec<Section>,
-    export_table: Vec<ExportFunction>,
-    import_table: Vec<ImportTableEntry>,
+// 依赖本机环境的信息: 系统中的API集合与WinSxS、同目录下的应用包、
+// 系统目录中的同名文件、备用数据流以及本地保存的书签和标签
+fn attach_host_context(mut pe_info: PeInfo, file_data: &[u8], file_path: &Path) -> Analysis {
+    let api_sets = apiset::resolve_api_sets(&pe_info.import_table);
+    pe_info
+        .warnings
+        .extend(host_checks::check_api_sets(&api_sets));
+    let sxs_dependencies =
+        sxs::check_sxs_dependencies(file_data, &pe_info.resources, pe_info.machine, file_path);
+    pe_info
+        .warnings
+        .extend(host_checks::check_sxs_dependencies(&sxs_dependencies));
+    // Electron/NW.js应用
+    let electron = electron::detect_electron(
+        file_path,
+        file_data,
+        &pe_info.export_table,
+        pe_info.version_info.as_ref(),
+    );
+    // 与系统目录中的文件同名的DLL可能用于侧加载
+    let system_copy = system_copy::find_system_copy(file_path, pe_info.is_x64)
+        .map(|path| path.to_string_lossy().to_string());
+    if let Some(path) = &system_copy {
+        pe_info.warnings.extend(host_checks::check_system_dll_name(
+            path,
+            pe_info.characteristics,
+        ));
+    }
+    let alternate_streams = streams::list_streams(file_path);
+    let zone_identifier = streams::read_zone_identifier(file_path, &alternate_streams);
+    pe_info
+        .warnings
+        .extend(host_checks::check_alternate_streams(
+            &alternate_streams,
+            zone_identifier.as_ref(),
+        ));
+    let bookmarks = bookmarks::for_file(&pe_info.hashes.sha256);
+    let tags = history::tags_for(&pe_info.hashes.sha256);
+    pe_info.suspicion = score::compute_score(
+        &pe_info.sections,
+        pe_info.entry_point_bytes.as_ref(),
+        &pe_info.warnings,
+        &pe_info.indicators,
+        pe_info.is_signed,
+    );
+    Analysis {
+        pe: pe_info,
+        api_sets,
+        sxs_dependencies,
+        electron,
+        bookmarks,
+        tags,
+        system_copy,
+        alternate_streams,
+        zone_identifier,
+        shortcut: None,
+    }
+}
+
+fn analyze_shortcut_target(shortcut: ShortcutInfo) -> Result<(Analysis, Vec<u8>), String> {
+    let target = shortcut
+        .target_path
+        .clone()
+        .ok_or_else(|| "无法确定快捷方式的目标".to_string())?;
+    // 不跟随指向快捷方式的快捷方式 避免循环
+    if shortcut::is_shortcut_file(Path::new(&target)) {
+        return Err(format!("快捷方式指向另一个快捷方式: {}", target));
+    }
+    let (mut pe_info, data) = analyze_with_data(Path::new(&target)).map_err(|e| {
+        format!(
+            "快捷方式目标 {} {}: {}",
+            target,
+            shortcut.arguments.as_deref().unwrap_or(""),
+            e
+        )
+    })?;
+    pe_info
+        .warnings
+        .extend(host_checks::check_shortcut(&shortcut));
+    pe_info.suspicion = score::compute_score(
+        &pe_info.sections,
+        pe_info.entry_point_bytes.as_ref(),
+        &pe_info.warnings,
+        &pe_info.indicators,
+        pe_info.is_signed,
+    );
+    pe_info.shortcut = Some(shortcut);
+    Ok((pe_info, data))
+}
+
+// 文件名 用作生成脚本中的模块名
+fn module_name(file_path: &str) -> String {
+    Path::new(file_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| file_path.to_string())
+}
+
+// 按序号筛选导出函数 未指定时返回全部
+fn select_exports<'a>(
+    export_table: &'a [ExportFunction],
+    ordinals: &Option<Vec<u32>>,
+) -> Vec<&'a ExportFunction> {
+    export_table
+        .iter()
+        .filter(|f| f.address != 0)
+        .filter(|f| match ordinals {
+            Some(ordinals) => ordinals.contains(&f.ordinal),
+            None => true,
+        })
+        .collect()
 }
 
 #[tauri::command]
-fn analyze(file_path: &str) -> Result<PeInfo, String> {
-    // 检查文件是否存在
-    if !Path::new(file_path).exists() {
-        return Err("文件不存在".into());
+fn generate_frida_script(file_path: &str, ordinals: Option<Vec<u32>>) -> Result<String, String> {
+    let pe_info = analyze(file_path.into())?;
+    let exports = select_exports(&pe_info.export_table, &ordinals);
+    if exports.is_empty() {
+        return Err("没有可用的导出函数".into());
     }
+    Ok(codegen::frida_script(&module_name(file_path), &exports))
+}
 
-    // 打开文件
-    let mut file = File::open(file_path).map_err(|e| format!("无法打开文件: {}", e))?;
-
-    // 获取文件字节长度
-    let size = file
-        .metadata()
-        .map_err(|e| format!("无法获取文件元数据: {}", e))?
-        .len();
-    // println!("文件大小: 0x{:X} 字节", size);
-
-    let mut temp_byte_buffer = [0; 1];
-    let mut temp_word_buffer = [0; 2];
-    let mut temp_dword_buffer = [0; 4];
-    let mut temp_qword_buffer = [0; 8];
-
-    // 判断是否是PE文件
-    // PE文件的前两个字节是"MZ"
-    file.read_exact(&mut temp_word_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    if temp_word_buffer != [0x4D, 0x5A] {
-        // eprintln!("不是有效的PE文件");
-        // std::process::exit(1);
-        return Err("不是有效的PE文件".into());
+#[tauri::command]
+fn generate_rust_bindings(
+    file_path: &str,
+    ordinals: Option<Vec<u32>>,
+    demangle: bool,
+) -> Result<String, String> {
+    let pe_info = analyze(file_path.into())?;
+    let exports = select_exports(&pe_info.export_table, &ordinals);
+    if exports.is_empty() {
+        return Err("没有可用的导出函数".into());
     }
+    Ok(codegen::rust_bindings(
+        &module_name(file_path),
+        &exports,
+        demangle,
+    ))
+}
 
-    // 0x3C-0x3F是coff头的偏移位置
-    file.seek(io::SeekFrom::Start(0x3C))
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    file.read_exact(&mut temp_dword_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let coff_header_ptr = u32::from_le_bytes(temp_dword_buffer);
-    // println!("COFF头偏移位置: 0x{:X}", coff_header_ptr);
-
-    // 跳转到PE头位置
-    file.seek(io::SeekFrom::Start(coff_header_ptr as u64))
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    file.read_exact(&mut temp_dword_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    if temp_dword_buffer != [0x50, 0x45, 0x00, 0x00] {
-        // eprintln!("不是有效的PE文件");
-        // std::process::exit(1);
-        return Err("不是有效的PE文件".into());
+#[tauri::command]
+fn generate_c_header(file_path: &str, ordinals: Option<Vec<u32>>) -> Result<String, String> {
+    let pe_info = analyze(file_path.into())?;
+    let exports = select_exports(&pe_info.export_table, &ordinals);
+    if exports.is_empty() {
+        return Err("没有可用的导出函数".into());
     }
+    Ok(codegen::c_header(&module_name(file_path), &exports))
+}
 
-    // 读可选头的magic 判断是否为64为文件
-    let magic_ptr = coff_header_ptr + 0x18;
-    // println!("magic_ptr: 0x{:X}", magic_ptr);
-    file.seek(io::SeekFrom::Start(magic_ptr as u64))
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    file.read_exact(&mut temp_word_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let is_x64 = match u16::from_le_bytes(temp_word_buffer) {
-        0x10B => false,
-        0x20B => true,
-        _ => {
-            // eprintln!("未知的PE文件格式");
-            // std::process::exit(1);
-            return Err("未知的PE文件格式".into());
-        }
-    };
-    // println!("架构: {}", if is_x64 { "x64" } else { "x86" });
-
-    // 读取sizeof_optional_header
-    let optional_header_size_ptr = coff_header_ptr + 0x14;
-    file.seek(io::SeekFrom::Start(optional_header_size_ptr as u64))
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    file.read_exact(&mut temp_word_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let optional_header_size = u16::from_le_bytes(temp_word_buffer);
-    // println!("可选头大小: 0x{:X}", optional_header_size);
-    let optional_header_ptr = coff_header_ptr + 0x18;
-    // println!("可选头偏移位置: 0x{:X}", optional_header_ptr);
-
-    // 读number_of_sections
-    let number_of_sections_ptr = coff_header_ptr + 0x06;
-    file.seek(io::SeekFrom::Start(number_of_sections_ptr as u64))
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    file.read_exact(&mut temp_word_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let number_of_sections = u16::from_le_bytes(temp_word_buffer);
-    // println!("节区数量: {}", number_of_sections);
-
-    // 遍历节表信息
-    let mut sections: Vec<Section> = Vec::with_capacity(number_of_sections as usize);
-    // 节表偏移位置
-    let section_table_ptr = optional_header_ptr + optional_header_size as u32;
-
-    for i in 0..number_of_sections {
-        let item_ptr = section_table_ptr + (i * 40) as u32;
-        file.seek(io::SeekFrom::Start(item_ptr as u64))
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        file.read_exact(&mut temp_qword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let section_name = String::from_utf8_lossy(&temp_qword_buffer)
-            .trim_end_matches('\0')
-            .to_string();
-
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let virtual_size = u32::from_le_bytes(temp_dword_buffer);
-
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let rva = u32::from_le_bytes(temp_dword_buffer);
-
-        let rv_end = rva + virtual_size;
-
-        file.seek(io::SeekFrom::Start(item_ptr as u64 + 0x14))
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let ptr_raw_data = u32::from_le_bytes(temp_dword_buffer);
-
-        sections.push(Section {
-            name: section_name,
-            rva,
-            ptr_raw_data,
-            rv_end,
-        });
+#[tauri::command]
+fn generate_def_file(file_path: &str, ordinals: Option<Vec<u32>>) -> Result<String, String> {
+    let pe_info = analyze(file_path.into())?;
+    let exports = select_exports(&pe_info.export_table, &ordinals);
+    if exports.is_empty() {
+        return Err("没有可用的导出函数".into());
+    }
+    Ok(codegen::def_file(&module_name(file_path), &exports))
+}
+
+// 导出反汇编器可导入的符号 format: map / idc / ghidra
+#[tauri::command]
+fn generate_symbol_map(file_path: &str, format: &str) -> Result<String, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    let exports = select_exports(&pe_info.export_table, &None);
+
+    // 异常目录中的函数起始地址
+    let function_starts: Vec<u32> = pe_info
+        .runtime_functions(&data)?
+        .iter()
+        .map(|f| f.begin)
+        .collect();
+
+    let symbols = codegen::collect_symbols(&exports, &function_starts);
+    let module_name = module_name(file_path);
+    match format {
+        "map" => Ok(codegen::map_file(
+            &module_name,
+            pe_info.image_base,
+            &pe_info.sections,
+            &symbols,
+        )),
+        "idc" => Ok(codegen::idc_script(&module_name, &symbols)),
+        "ghidra" => Ok(codegen::ghidra_script(&module_name, &symbols)),
+        _ => Err(format!("未知的格式: {}", format)),
     }
+}
 
-    // println!("节表信息:");
-    // println!(
-    //     "{:<10} {:<12} {:<12} {:<12}",
-    //     "名称", "原始地址", "RVA", "RV结束"
-    // );
-    // for section in &sections {
-    //     println!(
-    //         "{:<10}   0x{:08X}   0x{:08X}   0x{:08X}",
-    //         section.name, section.ptr_raw_data, section.rva, section.rv_end
-    //     );
-    // }
-
-    // 实现函数rva -> raw_ptr转换
-    let relative_virtual_difference = |rva: u32| -> Option<u32> {
-        for section in &sections {
-            if rva >= section.rva && rva < section.rv_end {
-                return Some(section.ptr_raw_data + (rva - section.rva));
-            }
-        }
-        None
-    };
+// 体积构成 节区、资源、数据目录、调试数据和函数大小
+#[tauri::command]
+fn size_report(file_path: &str) -> Result<sizes::SizeReport, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    let runtime_functions = pe_info.runtime_functions(&data)?;
+    Ok(sizes::size_report(&pe_info, &data, &runtime_functions))
+}
 
-    // 测试rva -> raw_ptr转换
-    // let test_rva = 0x003BA1A4;
-    // let test_raw_ptr = relative_virtual_difference(test_rva);
-    // match test_raw_ptr {
-    //     Some(ptr) => println!("RVA 0x{:08X} 对应的原始地址: 0x{:08X}", test_rva, ptr),
-    //     None => println!("RVA 0x{:08X} 不在任何节区内", test_rva),
-    // }
-
-    // 获取导出表和导入表信息
-    // 导出表在可选头的数据目录中第1个位置
-    // 导入表在可选头的数据目录中第2个位置
-    let data_directory_ptr = if is_x64 {
-        optional_header_ptr + 0x70
-    } else {
-        optional_header_ptr + 0x60
-    };
-    // println!("数据目录偏移位置: 0x{:X}", data_directory_ptr);
-
-    file.seek(io::SeekFrom::Start(data_directory_ptr as u64))
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    // 导出表rva
-    file.read_exact(&mut temp_dword_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let export_table_rva = u32::from_le_bytes(temp_dword_buffer);
-    // 导出表size
-    file.read_exact(&mut temp_dword_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let export_table_size = u32::from_le_bytes(temp_dword_buffer);
-    // 导入表rva
-    file.read_exact(&mut temp_dword_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let import_table_rva = u32::from_le_bytes(temp_dword_buffer);
-    // 导入表size
-    file.read_exact(&mut temp_dword_buffer)
-        .map_err(|e| format!("无法读取文件: {}", e))?;
-    let import_table_size = u32::from_le_bytes(temp_dword_buffer);
-
-    // println!(
-    //     "导出表 RVA: 0x{:08X}, 大小: 0x{:X}",
-    //     export_table_rva, export_table_size
-    // );
-
-    let mut export_table: Vec<ExportFunction> = Vec::new();
-
-    if export_table_size != 0 {
-        // 导出表rva -> raw_ptr
-        let export_table_ptr = match relative_virtual_difference(export_table_rva) {
-            Some(ptr) => ptr,
-            None => {
-                // eprintln!("导出表RVA转换失败");
-                // std::process::exit(1);
-                return Err("导出表RVA转换失败".into());
-            }
-        };
-        // println!("导出表偏移位置: 0x{:X}", export_table_ptr);
-        // 读导出表的条目总数 和 以函数名导出的数量
-        file.seek(io::SeekFrom::Start((export_table_ptr + 0x10) as u64))
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let ordinal_base = u32::from_le_bytes(temp_dword_buffer);
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let addresses_amount = u32::from_le_bytes(temp_dword_buffer);
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let name_pointers_amount = u32::from_le_bytes(temp_dword_buffer);
-
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let address_table_rva = u32::from_le_bytes(temp_dword_buffer);
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let name_pointer_table_rva = u32::from_le_bytes(temp_dword_buffer);
-        file.read_exact(&mut temp_dword_buffer)
-            .map_err(|e| format!("无法读取文件: {}", e))?;
-        let ordinal_table_rva = u32::from_le_bytes(temp_dword_buffer);
-
-        // rva全部转换成raw_ptr
-        let address_table_ptr = match relative_virtual_difference(address_table_rva) {
-            Some(ptr) => ptr,
-            None => {
-                // eprintln!("导出地址表RVA转换失败");
-                // std::process::exit(1);
-                return Err("导出地址表RVA转换失败".into());
-            }
-        };
-        let name_pointer_table_ptr = match relative_virtual_difference(name_pointer_table_rva) {
-            Some(ptr) => ptr,
-            None => {
-                // eprintln!("导出符号名表RVA转换失败");
-                // std::process::exit(1);
-                return Err("导出符号名表RVA转换失败".into());
-            }
-        };
-        let ordinal_table_ptr = match relative_virtual_difference(ordinal_table_rva) {
-            Some(ptr) => ptr,
-            None => {
-                // eprintln!("导出序号表RVA转换失败");
-                // std::process::exit(1);
-                return Err("导出序号表RVA转换失败".into());
-            }
-        };
-
-        // 先把所有地址都push进去
-        for i in 0..addresses_amount {
-            file.seek(io::SeekFrom::Start((address_table_ptr + i * 4) as u64))
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            file.read_exact(&mut temp_dword_buffer)
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            let func_rva = u32::from_le_bytes(temp_dword_buffer);
-            export_table.push(ExportFunction {
-                name: String::new(),
-                ordinal: 0,
-                address: func_rva,
-            });
-        }
-
-        // 读出所有名称
-        let mut name_list: Vec<String> = Vec::with_capacity(name_pointers_amount as usize);
-        for i in 0..name_pointers_amount {
-            file.seek(io::SeekFrom::Start((name_pointer_table_ptr + i * 4) as u64))
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            file.read_exact(&mut temp_dword_buffer)
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            let name_rva = u32::from_le_bytes(temp_dword_buffer);
-            let name_ptr = match relative_virtual_difference(name_rva) {
-                Some(ptr) => ptr,
-                None => {
-                    name_list.push(String::new());
-                    continue;
-                }
-            };
-            // 读名称
-            let mut func_name_bytes: Vec<u8> = Vec::new();
-            file.seek(io::SeekFrom::Start(name_ptr as u64))
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            loop {
-                file.read_exact(&mut temp_byte_buffer)
-                    .map_err(|e| format!("无法读取文件: {}", e))?;
-                if temp_byte_buffer[0] == 0 {
-                    break;
-                }
-                func_name_bytes.push(temp_byte_buffer[0]);
-            }
-            let func_name = String::from_utf8_lossy(&func_name_bytes).to_string();
-            name_list.push(func_name);
-        }
-
-        // 读出所有序号
-        let mut ordinal_list: Vec<u16> = Vec::with_capacity(name_pointers_amount as usize);
-        for i in 0..name_pointers_amount {
-            file.seek(io::SeekFrom::Start((ordinal_table_ptr + i * 2) as u64))
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            file.read_exact(&mut temp_word_buffer)
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            let ordinal = u16::from_le_bytes(temp_word_buffer);
-            ordinal_list.push(ordinal);
-        }
-
-        // 遍历ordinal_list
-        for (i, &ordinal) in ordinal_list.iter().enumerate() {
-            let name = name_list.get(i).cloned().unwrap_or_default();
-            if let Some(func) = export_table.get_mut(i) {
-                func.name = name;
-                func.ordinal = ordinal as u32 + ordinal_base;
-            }
-        }
+// 按异常目录估算的最大函数 用于排查膨胀和伪装成代码的数据块
+#[tauri::command]
+fn largest_functions(
+    file_path: &str,
+    limit: Option<usize>,
+) -> Result<Vec<exception::FunctionSize>, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    let runtime_functions = pe_info.runtime_functions(&data)?;
+    if runtime_functions.is_empty() {
+        return Err("文件没有异常目录(仅x64和ARM64文件包含函数范围)".into());
     }
+    Ok(exception::largest_functions(
+        &data,
+        &pe_info.sections,
+        &pe_info.export_table,
+        &runtime_functions,
+        limit.unwrap_or(exception::DEFAULT_FUNCTION_LIMIT),
+    ))
+}
 
-    // 先通过序号排序
-    export_table.sort_by_key(|f| f.ordinal);
-    // println!("导出的函数:");
-    // println!("{:<8} {:<10} 名称", "序号", "地址");
-
-    // for func in &export_table {
-    //     println!("{:<8} 0x{:08X} {}", func.ordinal, func.address, func.name);
-    // }
-
-    // println!(
-    //     "导入表 RVA: 0x{:08X}, 大小: 0x{:X}",
-    //     import_table_rva, import_table_size
-    // );
-
-    let mut import_table: Vec<ImportTableEntry> = Vec::new();
-
-    if import_table_size != 0 {
-        // 导入表rva -> raw_ptr
-        let import_table_ptr = match relative_virtual_difference(import_table_rva) {
-            Some(ptr) => ptr,
-            None => {
-                // eprintln!("导入表RVA转换失败");
-                // std::process::exit(1);
-                return Err("导入表RVA转换失败".into());
-            }
-        };
-        // println!("导入表偏移位置: 0x{:X}", import_table_ptr);
-        // 一个导入表项的大小是20字节
-        let import_table_item_count = import_table_size / 20;
-        // 遍历
-        for i in 0..import_table_item_count {
-            let import_table_item_ptr = import_table_ptr + (i * 20);
-            // 读第一个字段 OriginalFirstThunk
-            file.seek(io::SeekFrom::Start(import_table_item_ptr as u64))
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            file.read_exact(&mut temp_dword_buffer)
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            let lookup_table_rva = u32::from_le_bytes(temp_dword_buffer);
-            let lookup_table_ptr = match relative_virtual_difference(lookup_table_rva) {
-                Some(ptr) => ptr,
-                None => {
-                    continue;
-                }
-            };
-
-            // 读第四个字段 Name
-            file.seek(io::SeekFrom::Start(import_table_item_ptr as u64 + 12))
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            file.read_exact(&mut temp_dword_buffer)
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            let name_rva = u32::from_le_bytes(temp_dword_buffer);
-            let name_ptr = match relative_virtual_difference(name_rva) {
-                Some(ptr) => ptr,
-                None => {
-                    continue;
-                }
-            };
-
-            // 读DLL名称
-            let mut dll_name_bytes: Vec<u8> = Vec::new();
-            file.seek(io::SeekFrom::Start(name_ptr as u64))
-                .map_err(|e| format!("无法读取文件: {}", e))?;
-            loop {
-                file.read_exact(&mut temp_byte_buffer)
-                    .map_err(|e| format!("无法读取文件: {}", e))?;
-                if temp_byte_buffer[0] == 0 {
-                    break;
-                }
-                dll_name_bytes.push(temp_byte_buffer[0]);
-            }
-            let dll_name = String::from_utf8_lossy(&dll_name_bytes).to_string();
-
-            // println!("DLL名称: {}", dll_name);
-
-            // 逐个读取函数名称和序号
-            let mut functions: Vec<ImportFunction> = Vec::new();
-            let mut lookup_item_ptr = lookup_table_ptr;
-            let lookup_item_size = if is_x64 { 8 } else { 4 };
-
-            loop {
-                file.seek(io::SeekFrom::Start(lookup_item_ptr as u64))
-                    .map_err(|e| format!("无法读取文件: {}", e))?;
-                if is_x64 {
-                    file.read_exact(&mut temp_qword_buffer)
-                        .map_err(|e| format!("无法读取文件: {}", e))?;
-                    let entry = u64::from_le_bytes(temp_qword_buffer);
-                    if entry == 0 {
-                        break;
-                    }
-                    let is_ordinal = (entry & 0x8000000000000000) != 0;
-                    if is_ordinal {
-                        let ordinal = (entry & 0xFFFF) as u16;
-                        functions.push(ImportFunction {
-                            name: String::new(),
-                            is_ordinal: true,
-                            ordinal,
-                            hint: 0,
-                        });
-                    } else {
-                        let hint_name_rva = (entry & 0x7FFFFFFFFFFFFFFF) as u32;
-                        let hint_name_ptr = match relative_virtual_difference(hint_name_rva) {
-                            Some(ptr) => ptr,
-                            None => {
-                                lookup_item_ptr += lookup_item_size;
-                                continue;
-                            }
-                        };
-                        // 读hint和name
-                        file.seek(io::SeekFrom::Start(hint_name_ptr as u64))
-                            .map_err(|e| format!("无法读取文件: {}", e))?;
-                        file.read_exact(&mut temp_word_buffer)
-                            .map_err(|e| format!("无法读取文件: {}", e))?;
-                        let hint = u16::from_le_bytes(temp_word_buffer);
-                        // 读名称
-                        let mut func_name_bytes: Vec<u8> = Vec::new();
-                        loop {
-                            file.read_exact(&mut temp_byte_buffer)
-                                .map_err(|e| format!("无法读取文件: {}", e))?;
-                            if temp_byte_buffer[0] == 0 {
-                                break;
-                            }
-                            func_name_bytes.push(temp_byte_buffer[0]);
-                        }
-                        let func_name = String::from_utf8_lossy(&func_name_bytes).to_string();
-                        functions.push(ImportFunction {
-                            name: func_name,
-                            is_ordinal: false,
-                            ordinal: 0,
-                            hint,
-                        });
-                    }
-                } else {
-                    file.read_exact(&mut temp_dword_buffer)
-                        .map_err(|e| format!("无法读取文件: {}", e))?;
-                    let entry = u32::from_le_bytes(temp_dword_buffer);
-                    if entry == 0 {
-                        break;
-                    }
-                    let is_ordinal = (entry & 0x80000000) != 0;
-                    if is_ordinal {
-                        let ordinal = (entry & 0xFFFF) as u16;
-                        functions.push(ImportFunction {
-                            name: String::new(),
-                            is_ordinal: true,
-                            ordinal,
-                            hint: 0,
-                        });
-                    } else {
-                        let hint_name_rva = entry & 0x7FFFFFFF;
-                        let hint_name_ptr = match relative_virtual_difference(hint_name_rva) {
-                            Some(ptr) => ptr,
-                            None => {
-                                lookup_item_ptr += lookup_item_size;
-                                continue;
-                            }
-                        };
-                        // 读hint和name
-                        file.seek(io::SeekFrom::Start(hint_name_ptr as u64))
-                            .map_err(|e| format!("无法读取文件: {}", e))?;
-                        file.read_exact(&mut temp_word_buffer)
-                            .map_err(|e| format!("无法读取文件: {}", e))?;
-                        let hint = u16::from_le_bytes(temp_word_buffer);
-                        // 读名称
-                        let mut func_name_bytes: Vec<u8> = Vec::new();
-                        loop {
-                            file.read_exact(&mut temp_byte_buffer)
-                                .map_err(|e| format!("无法读取文件: {}", e))?;
-                            if temp_byte_buffer[0] == 0 {
-                                break;
-                            }
-                            func_name_bytes.push(temp_byte_buffer[0]);
-                        }
-                        let func_name = String::from_utf8_lossy(&func_name_bytes).to_string();
-                        functions.push(ImportFunction {
-                            name: func_name,
-                            is_ordinal: false,
-                            ordinal: 0,
-                            hint,
-                        });
-                    }
-                }
-                lookup_item_ptr += lookup_item_size;
-            }
+#[tauri::command]
+fn generate_x64dbg_script(file_path: &str) -> Result<String, String> {
+    let pe_info = analyze(file_path.into())?;
+    let exports = select_exports(&pe_info.export_table, &None);
+    let tls_callbacks = pe_info
+        .tls
+        .as_ref()
+        .map(|tls| tls.callbacks.clone())
+        .unwrap_or_default();
+    Ok(codegen::x64dbg_script(
+        &module_name(file_path),
+        &exports,
+        pe_info.entry_point,
+        &tls_callbacks,
+    ))
+}
 
-            // 通过hint排序
-            functions.sort_by_key(|f| f.hint);
-
-            // println!("导入的函数:");
-            // println!("{:<8} 名称", "序号");
-            // for func in &functions {
-            //     if func.ordinal != 0 {
-            //         println!("{:<8} {}", func.ordinal, func.name);
-            //     } else {
-            //         println!("         {}", func.name);
-            //     }
-            // }
-
-            import_table.push(ImportTableEntry {
-                dll_name,
-                functions,
-            });
-        }
+#[tauri::command]
+fn resolve_dependencies(file_path: &str) -> Result<deps::DependencyGraph, String> {
+    deps::resolve_dependencies(file_path)
+}
+
+#[tauri::command]
+fn generate_dependency_dot(file_path: &str) -> Result<String, String> {
+    let graph = deps::resolve_dependencies(file_path)?;
+    Ok(deps::to_dot(&graph))
+}
+
+// 复制程序及其非系统依赖到目标目录
+#[tauri::command]
+fn export_dependency_closure(
+    file_path: &str,
+    output_directory: &str,
+) -> Result<deps::DeploymentReport, String> {
+    deps::export_closure(file_path, output_directory)
+}
+
+// 加壳程序运行时解析的API: 字符串中的API名和ror13哈希常量
+#[tauri::command]
+fn probable_dynamic_imports(
+    file_path: &str,
+) -> Result<dynamic_imports::DynamicImportReport, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    Ok(dynamic_imports::find_dynamic_imports(
+        &data,
+        &pe_info.sections,
+        &pe_info.import_table,
+    ))
+}
+
+// 可被程序目录或PATH中同名DLL劫持的导入
+#[tauri::command]
+fn search_order_exposure(file_path: &str) -> Result<hijack::HijackReport, String> {
+    hijack::search_order_exposure(file_path)
+}
+
+// 生成目录的基线清单(JSON)
+#[tauri::command]
+fn generate_baseline(directory: &str) -> Result<String, String> {
+    let baseline = baseline::create_baseline(directory)?;
+    serde_json::to_string_pretty(&baseline).map_err(|e| format!("无法生成基线: {}", e))
+}
+
+// 用基线清单校验目录 返回全部偏差
+#[tauri::command]
+fn verify_baseline(
+    baseline_path: &str,
+    directory: &str,
+) -> Result<Vec<baseline::Deviation>, String> {
+    let content =
+        std::fs::read_to_string(baseline_path).map_err(|e| format!("无法读取文件: {}", e))?;
+    let baseline: baseline::Baseline =
+        serde_json::from_str(&content).map_err(|e| format!("基线格式错误: {}", e))?;
+    baseline::verify_baseline(&baseline, directory)
+}
+
+// 只按SHA-256清单校验目录 清单可为基线或sha256sum格式
+#[tauri::command]
+fn verify_digests(
+    manifest_path: &str,
+    directory: &str,
+) -> Result<baseline::DigestVerification, String> {
+    let content =
+        std::fs::read_to_string(manifest_path).map_err(|e| format!("无法读取文件: {}", e))?;
+    baseline::verify_digests(&content, directory)
+}
+
+// 解出PyInstaller/py2exe归档 返回写出的文件数 未指定目录时写入会话临时目录
+#[tauri::command]
+fn extract_python_archive(
+    file_path: &str,
+    output_directory: Option<&str>,
+) -> Result<usize, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    let package = pe_info
+        .python_package
+        .as_ref()
+        .ok_or_else(|| String::from("未发现Python打包数据"))?;
+    let output_directory = session::resolve_output(
+        output_directory,
+        &format!("{}.extracted", module_name(file_path)),
+    )?;
+    let files = python::extract_entries(&data, package, &limits::current())?;
+    handling::write_payloads(&output_directory, files)
+}
+
+// 导出AutoIt脚本数据 返回写出的字节数
+#[tauri::command]
+fn extract_autoit_script(file_path: &str, output_path: Option<&str>) -> Result<u64, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    let script = pe_info
+        .autoit
+        .as_ref()
+        .ok_or_else(|| String::from("未发现AutoIt脚本"))?;
+    let script = reader::BoundedReader::new(&data).bytes_truncated(script.offset, script.size);
+    let output_path =
+        session::resolve_output(output_path, &format!("{}.a3x", module_name(file_path)))?;
+    handling::write_payload(&output_path, script)?;
+    Ok(script.len() as u64)
+}
+
+// 把前SizeOfHeaders字节写入文件 供外部比较或其他工具使用
+#[tauri::command]
+fn dump_headers(file_path: &str, output_path: Option<&str>) -> Result<u64, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    let end = (pe_info.size_of_headers as usize).min(data.len());
+    let output_path = session::resolve_output(
+        output_path,
+        &format!("{}.headers.bin", module_name(file_path)),
+    )?;
+    handling::write_payload(&output_path, &data[..end])?;
+    Ok(end as u64)
+}
+
+// 从资源目录重建RC兼容的.res文件
+#[tauri::command]
+fn export_res_file(file_path: &str, output_path: Option<&str>) -> Result<u64, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    if pe_info.resources.is_empty() {
+        return Err("没有资源".into());
     }
+    let res = resources::build_res_file(&data, &pe_info.resources);
+    let output_path =
+        session::resolve_output(output_path, &format!("{}.res", module_name(file_path)))?;
+    handling::write_payload(&output_path, &res)?;
+    Ok(res.len() as u64)
+}
 
-    let pe_info = PeInfo {
-        path: String::from(file_path),
-        size,
-        is_x64,
-        sections,
-        export_table,
-        import_table,
-    };
+// 按取证惯例导出证据: <sha256>.bin样本副本、JSON报告和IOC 原文件不变
+#[tauri::command]
+fn export_evidence(file_path: &str, directory: &str) -> Result<evidence::EvidenceExport, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    evidence::export(&pe_info, &data, Path::new(directory))
+}
+
+// 会话临时目录中的文件
+#[tauri::command]
+fn session_files() -> Result<Vec<session::SessionFile>, String> {
+    session::list_files()
+}
+
+// 删除会话临时目录 返回删除的文件数
+#[tauri::command]
+fn cleanup_session() -> Result<usize, String> {
+    session::cleanup()
+}
+
+// 全文件字符串 按区域过滤: all / resources / overlay / headers / 节区名
+// 结果可能很大 以打包的二进制返回 见payload.rs
+#[tauri::command]
+fn extract_strings(
+    file_path: &str,
+    filter: Option<&str>,
+    min_length: Option<usize>,
+    start: Option<usize>,
+) -> Result<tauri::ipc::Response, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    let strings = strings::extract_strings(
+        &data,
+        &pe_info.sections,
+        pe_info
+            .data_directories
+            .get(IMAGE_DIRECTORY_ENTRY_RESOURCE)
+            .copied()
+            .unwrap_or_default(),
+        pe_info.size_of_headers,
+        overlay_offset(&pe_info.sections),
+        filter,
+        min_length,
+        start,
+    );
+    Ok(tauri::ipc::Response::new(payload::pack(&strings)?))
+}
+
+// 按需对资源、覆盖数据和空隙中的数据块爆破XOR密钥
+#[tauri::command]
+fn xor_scan(file_path: &str) -> Result<Vec<xor_scan::XorHit>, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    Ok(xor_scan::brute_force_xor(
+        &data,
+        &pe_info.resources,
+        &pe_info.slack,
+        overlay_offset(&pe_info.sections),
+    ))
+}
+
+// 教学模式 复述头部解析过程中读取的偏移和结构
+#[tauri::command]
+fn explain_parse(file_path: &str) -> Result<Vec<teaching::ParseStep>, String> {
+    let (pe_info, data) = analyze_with_data(Path::new(file_path))?;
+    teaching::explain_parse(&data, &pe_info.sections)
+}
+
+// 全部头部字段的偏移、大小和类型 供字段编辑器显示
+#[tauri::command]
+fn header_structure(file_path: &str) -> Result<Vec<editor::HeaderField>, String> {
+    let (_, data) = analyze_with_data(Path::new(file_path))?;
+    editor::header_structure(&data)
+}
+
+// 为字段编辑创建工作副本 原文件不会被修改
+#[tauri::command]
+fn open_edit_session(
+    state: tauri::State<'_, editor::EditorState>,
+    file_path: &str,
+) -> Result<editor::EditSessionInfo, String> {
+    editor::open_session(&state, file_path)
+}
+
+// 修改工作副本中的一个字段并重新分析
+#[tauri::command]
+fn write_field(
+    state: tauri::State<'_, editor::EditorState>,
+    session: &str,
+    field_path: &str,
+    value: &str,
+) -> Result<editor::FieldEdit, String> {
+    editor::write_field(&state, session, field_path, value)
+}
+
+#[tauri::command]
+fn save_edit_session(
+    state: tauri::State<'_, editor::EditorState>,
+    session: &str,
+    output_path: &str,
+) -> Result<u64, String> {
+    editor::save_session(&state, session, output_path)
+}
+
+#[tauri::command]
+fn close_edit_session(
+    state: tauri::State<'_, editor::EditorState>,
+    session: &str,
+) -> Result<bool, String> {
+    editor::close_session(&state, session)
+}
+
+// 虚拟内存布局 供前端绘制映像分布图
+#[tauri::command]
+fn memory_layout(file_path: &str) -> Result<layout::MemoryLayout, String> {
+    let pe_info = analyze(file_path.into())?;
+    Ok(layout::build_layout(
+        &pe_info.sections,
+        &pe_info.data_directories,
+        pe_info.size_of_image,
+        pe_info.size_of_headers,
+        pe_info.section_alignment,
+        pe_info
+            .pogo
+            .as_ref()
+            .map_or(&[][..], |pogo| pogo.entries.as_slice()),
+    ))
+}
+
+// 按文件偏移降采样的熵序列 分辨率为块数
+#[tauri::command]
+fn entropy_strip(
+    file_path: &str,
+    resolution: Option<usize>,
+) -> Result<entropy::EntropyStrip, String> {
+    let data = file_access::read_file(Path::new(file_path))?;
+    Ok(entropy::entropy_strip(
+        &data,
+        resolution.unwrap_or(entropy::DEFAULT_STRIP_RESOLUTION),
+    ))
+}
+
+// 监视文件重建 变化通过build-changed事件发送
+#[tauri::command]
+fn watch_file(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, watcher::WatchState>,
+    file_path: &str,
+) -> Result<(), String> {
+    watcher::watch_file(app, &state, file_path)
+}
+
+#[tauri::command]
+fn unwatch_file(
+    state: tauri::State<'_, watcher::WatchState>,
+    file_path: &str,
+) -> Result<bool, String> {
+    watcher::unwatch_file(&state, file_path)
+}
+
+// 一次拖入多个文件时加入后台队列 结果通过analysis-completed事件逐个发送
+#[tauri::command]
+fn enqueue_analysis(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, queue::AnalysisQueue>,
+    file_paths: Vec<String>,
+) -> Result<usize, String> {
+    queue::enqueue(app, &state, file_paths)
+}
+
+// 只解析头部的快速分诊 用于批量扫描的第一轮
+#[tauri::command]
+fn quick_triage(file_path: &str) -> Result<triage::QuickTriage, String> {
+    triage::quick_triage(file_path)
+}
+
+// 资源管理器右键菜单的注册状态
+#[tauri::command]
+fn context_menu_status() -> shell_menu::ContextMenuStatus {
+    shell_menu::status()
+}
+
+#[tauri::command]
+fn register_context_menu() -> Result<shell_menu::ContextMenuStatus, String> {
+    shell_menu::register()
+}
+
+#[tauri::command]
+fn unregister_context_menu() -> Result<shell_menu::ContextMenuStatus, String> {
+    shell_menu::unregister()
+}
+
+// pe-info://协议的注册状态
+#[tauri::command]
+fn protocol_status() -> deep_link::ProtocolStatus {
+    deep_link::status()
+}
+
+#[tauri::command]
+fn register_protocol() -> Result<deep_link::ProtocolStatus, String> {
+    deep_link::register()
+}
+
+#[tauri::command]
+fn unregister_protocol() -> Result<deep_link::ProtocolStatus, String> {
+    deep_link::unregister()
+}
+
+// 通过协议链接或右键菜单启动时要分析的文件和定位的地址
+#[tauri::command]
+fn startup_target() -> Result<Option<deep_link::StartupTarget>, String> {
+    deep_link::startup_target()
+}
+
+// 链接中的地址换算为RVA/文件偏移/VA
+#[tauri::command]
+fn locate_address(
+    file_path: &str,
+    rva: Option<u32>,
+    offset: Option<u64>,
+) -> Result<deep_link::AddressLocation, String> {
+    deep_link::locate_address(file_path, rva, offset)
+}
+
+// 新增或修改地址书签 返回该文件的全部书签
+#[tauri::command]
+fn set_bookmark(
+    file_path: &str,
+    rva: Option<u32>,
+    offset: Option<u64>,
+    note: &str,
+) -> Result<Vec<Bookmark>, String> {
+    let pe_info = analyze(file_path.into())?;
+    let location = deep_link::locate(&pe_info, rva, offset)?;
+    bookmarks::set(&pe_info.hashes.sha256, location.rva, location.offset, note)
+}
+
+#[tauri::command]
+fn remove_bookmark(
+    file_path: &str,
+    rva: Option<u32>,
+    offset: Option<u64>,
+) -> Result<Vec<Bookmark>, String> {
+    let data = file_access::read_file(Path::new(file_path))?;
+    bookmarks::remove(&hashes::compute_hashes(&data).sha256, rva, offset)
+}
 
-    // let pe_info_json = serde_json::to_string(&pe_info).unwrap();
-    // println!("PE信息(JSON):\n{}", pe_info_json);
+// 界面中打开文件时的分析 同时记录到分析历史
+#[tauri::command]
+fn analyze_and_record(file_path: &str) -> Result<Analysis, String> {
+    let pe_info = analyze(file_path.into())?;
+    // 记录失败不影响分析结果
+    let _ = history::record(&pe_info);
     Ok(pe_info)
 }
 
+#[tauri::command]
+fn query_history(query: history::HistoryQuery) -> Result<Vec<history::HistoryEntry>, String> {
+    history::query(&query)
+}
+
+// 在分析历史中按导出名、imphash等整体匹配 跨文件定位同类样本
+#[tauri::command]
+fn pivot_history(
+    field: &str,
+    value: &str,
+    limit: Option<u32>,
+) -> Result<Vec<history::HistoryEntry>, String> {
+    history::pivot(field, value, limit)
+}
+
+#[tauri::command]
+fn delete_history_entry(id: i64) -> Result<(), String> {
+    history::delete_entry(id)
+}
+
+// 文件标签 按SHA-256保存
+#[tauri::command]
+fn add_file_tag(sha256: &str, tag: &str) -> Result<Vec<String>, String> {
+    history::add_tag(sha256, tag)
+}
+
+#[tauri::command]
+fn remove_file_tag(sha256: &str, tag: &str) -> Result<Vec<String>, String> {
+    history::remove_tag(sha256, tag)
+}
+
+#[tauri::command]
+fn list_tags() -> Result<Vec<String>, String> {
+    history::all_tags()
+}
+
+// 工作区中每个文件的摘要 供对比表格使用
+#[tauri::command]
+fn workspace_summary(file_paths: Vec<String>) -> Vec<workspace::WorkspaceRow> {
+    workspace::workspace_summary(&file_paths)
+}
+
+// 导入NSRL或自定义白名单 返回新增的哈希数量
+#[tauri::command]
+fn import_hash_set(path: &str) -> Result<usize, String> {
+    known_hashes::import(Path::new(path))
+}
+
+#[tauri::command]
+fn clear_hash_set() -> Result<(), String> {
+    known_hashes::clear()
+}
+
+#[tauri::command]
+fn hash_set_size() -> usize {
+    known_hashes::count()
+}
+
+// 判断是否为真正的Microsoft文件 online为true时查询Winbindex
+#[tauri::command]
+fn check_microsoft_origin(
+    file_path: &str,
+    online: bool,
+) -> Result<microsoft::MicrosoftOrigin, String> {
+    let pe_info = analyze(file_path.into())?;
+    Ok(microsoft::assess(&pe_info, online))
+}
+
+// 与系统目录中的同名文件对比
+#[tauri::command]
+fn compare_system_copy(file_path: &str) -> Result<system_copy::SystemComparison, String> {
+    let local = analyze(file_path.into())?;
+    let system_path = local
+        .system_copy
+        .clone()
+        .ok_or_else(|| String::from("系统目录中没有同名文件"))?;
+    let system = analyze(system_path.into())?;
+    Ok(system_copy::compare(&local, &system))
+}
+
+// 按资源逐项比较两个文件 证明换皮样本复用了其他产品的图标、版本信息或清单
+#[tauri::command]
+fn compare_resources(
+    file_path: &str,
+    other_path: &str,
+) -> Result<resources::ResourceComparison, String> {
+    let first = analyze(file_path.into())?;
+    let second = analyze(other_path.into())?;
+    Ok(resources::compare_resources(
+        &first.resources,
+        &second.resources,
+    ))
+}
+
+// 复制用的规范文本 kind: md5/sha1/sha256/imphash/authentihash/thumbprint/virustotal/summary
+#[tauri::command]
+fn copy_value(file_path: &str, kind: &str) -> Result<String, String> {
+    let pe_info = analyze(file_path.into())?;
+    copy_values::copy_value(&pe_info, kind)
+}
+
+#[tauri::command]
+fn list_external_tools() -> Vec<tools::ExternalTool> {
+    tools::list()
+}
+
+#[tauri::command]
+fn save_external_tools(tools: Vec<tools::ExternalTool>) -> Result<(), String> {
+    tools::save(tools)
+}
+
+#[tauri::command]
+fn get_limits() -> limits::Limits {
+    limits::current()
+}
+
+#[tauri::command]
+fn save_limits(limits: limits::Limits) -> Result<limits::Limits, String> {
+    limits::save(limits)
+}
+
+// 样本处理模式
+#[tauri::command]
+fn get_handling_mode() -> handling::HandlingMode {
+    handling::current()
+}
+
+#[tauri::command]
+fn save_handling_mode(mode: handling::HandlingMode) -> Result<handling::HandlingMode, String> {
+    handling::save(mode)
+}
+
+// 用外部工具打开文件 rva和offset都可省略
+#[tauri::command]
+fn launch_external_tool(
+    name: &str,
+    file_path: &str,
+    rva: Option<u32>,
+    offset: Option<u32>,
+) -> Result<(), String> {
+    let pe_info = analyze(file_path.into())?;
+    let target = tools::ToolTarget {
+        rva: rva.or_else(|| offset.and_then(|o| offset_to_rva(&pe_info.sections, o as u64))),
+        offset: offset.or_else(|| rva.and_then(|r| rva_to_offset(&pe_info.sections, r))),
+    };
+    tools::launch(name, file_path, target, pe_info.image_base)
+}
+
+// 保存生成的文本文件
+#[tauri::command]
+fn write_text_file(path: &str, content: &str) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|e| format!("无法写入文件: {}", e))
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![analyze])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .manage(watcher::WatchState::default())
+        .manage(queue::AnalysisQueue::default())
+        .manage(editor::EditorState::default())
+        .setup(|app| {
+            let directory = app.path().app_data_dir()?;
+            known_hashes::open(directory.join("known_hashes.txt"))?;
+            tools::open(directory.join("external_tools.json"))?;
+            limits::open(directory.join("limits.json"))?;
+            handling::open(directory.join("handling.json"))?;
+            bookmarks::open(directory.join("bookmarks.json"))?;
+            history::open(directory.join("history.sqlite"))?;
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![
+            analyze,
+            generate_frida_script,
+            generate_rust_bindings,
+            generate_c_header,
+            generate_def_file,
+            generate_symbol_map,
+            size_report,
+            largest_functions,
+            generate_x64dbg_script,
+            resolve_dependencies,
+            generate_dependency_dot,
+            export_dependency_closure,
+            search_order_exposure,
+            probable_dynamic_imports,
+            generate_baseline,
+            verify_baseline,
+            verify_digests,
+            extract_python_archive,
+            extract_autoit_script,
+            dump_headers,
+            export_res_file,
+            export_evidence,
+            session_files,
+            cleanup_session,
+            extract_strings,
+            xor_scan,
+            explain_parse,
+            header_structure,
+            open_edit_session,
+            write_field,
+            save_edit_session,
+            close_edit_session,
+            memory_layout,
+            entropy_strip,
+            watch_file,
+            unwatch_file,
+            workspace_summary,
+            quick_triage,
+            enqueue_analysis,
+            context_menu_status,
+            register_context_menu,
+            unregister_context_menu,
+            protocol_status,
+            register_protocol,
+            unregister_protocol,
+            startup_target,
+            locate_address,
+            set_bookmark,
+            remove_bookmark,
+            analyze_and_record,
+            query_history,
+            pivot_history,
+            delete_history_entry,
+            add_file_tag,
+            remove_file_tag,
+            list_tags,
+            import_hash_set,
+            clear_hash_set,
+            hash_set_size,
+            check_microsoft_origin,
+            compare_system_copy,
+            compare_resources,
+            copy_value,
+            list_external_tools,
+            save_external_tools,
+            get_limits,
+            save_limits,
+            get_handling_mode,
+            save_handling_mode,
+            launch_external_tool,
+            write_text_file
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_app, event| {
+            // 退出时删除会话临时目录 不残留解出的载荷
+            if let tauri::RunEvent::Exit = event {
+                let _ = session::cleanup();
+            }
+        });
 }
@@ -0,0 +1,52 @@
+// 解析上限的本地配置 分析时取当前值传给解析器
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{LazyLock, RwLock};
+
+pub(crate) use pe_info_core::limits::Limits;
+
+#[derive(Default)]
+struct LimitConfig {
+    path: Option<PathBuf>,
+    limits: Limits,
+}
+
+static CONFIG: LazyLock<RwLock<LimitConfig>> =
+    LazyLock::new(|| RwLock::new(LimitConfig::default()));
+
+pub(crate) fn open(path: PathBuf) -> Result<(), String> {
+    let limits: Limits = if path.exists() {
+        let text = fs::read_to_string(&path).map_err(|e| format!("无法读取解析上限: {}", e))?;
+        serde_json::from_str(&text).map_err(|e| format!("无法解析解析上限配置: {}", e))?
+    } else {
+        Limits::default()
+    };
+    let mut config = CONFIG
+        .write()
+        .map_err(|_| String::from("解析上限配置不可用"))?;
+    config.path = Some(path);
+    config.limits = limits;
+    Ok(())
+}
+
+pub(crate) fn current() -> Limits {
+    CONFIG
+        .read()
+        .map_or_else(|_| Limits::default(), |config| config.limits)
+}
+
+pub(crate) fn save(limits: Limits) -> Result<Limits, String> {
+    limits.validate()?;
+    let mut config = CONFIG
+        .write()
+        .map_err(|_| String::from("解析上限配置不可用"))?;
+    if let Some(path) = &config.path {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&limits).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("无法写入解析上限: {}", e))?;
+    }
+    config.limits = limits;
+    Ok(limits)
+}
@@ -0,0 +1,156 @@
+// 是否为真正的Microsoft文件 综合签名链、映像摘要、版本资源和可选的Winbindex在线查询
+use flate2::read::GzDecoder;
+use serde::{Deserialize, Serialize};
+
+use std::io::Read;
+use std::path::Path;
+
+use crate::authenticode::{is_microsoft_signature, subject_common_name};
+use crate::handling;
+use crate::version_info::claims_microsoft;
+use crate::PeInfo;
+
+// Winbindex按文件名索引的各版本哈希 键为SHA-256
+const WINBINDEX_URL: &str = "https://winbindex.m417z.com/data/by_filename_compressed";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct OriginCheck {
+    pub name: String,
+    // 未检查或无法判断时为None
+    pub passed: Option<bool>,
+    pub detail: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MicrosoftOrigin {
+    // genuine / masquerade / unverified / not_microsoft
+    pub verdict: String,
+    pub checks: Vec<OriginCheck>,
+}
+
+fn check(name: &str, passed: Option<bool>, detail: String) -> OriginCheck {
+    OriginCheck {
+        name: name.to_string(),
+        passed,
+        detail,
+    }
+}
+
+// 文件名在Winbindex中不存在时返回Ok(false)
+fn winbindex_lookup(file_name: &str, sha256: &str) -> Result<bool, String> {
+    handling::ensure_allowed("联网查询")?;
+    let url = format!(
+        "{}/{}.json.gz",
+        WINBINDEX_URL,
+        file_name.to_ascii_lowercase()
+    );
+    let response = match ureq::get(&url).call() {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(false),
+        Err(e) => return Err(format!("无法访问Winbindex: {}", e)),
+    };
+    let mut compressed: Vec<u8> = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut compressed)
+        .map_err(|e| format!("无法读取Winbindex数据: {}", e))?;
+    let mut json = String::new();
+    GzDecoder::new(compressed.as_slice())
+        .read_to_string(&mut json)
+        .map_err(|e| format!("无法解压Winbindex数据: {}", e))?;
+    let index: serde_json::Value =
+        serde_json::from_str(&json).map_err(|e| format!("无法解析Winbindex数据: {}", e))?;
+    Ok(index.get(sha256).is_some())
+}
+
+pub(crate) fn assess(info: &PeInfo, online: bool) -> MicrosoftOrigin {
+    let mut checks: Vec<OriginCheck> = Vec::new();
+
+    let claims = claims_microsoft(info.version_info.as_ref());
+    let company = info
+        .version_info
+        .as_ref()
+        .and_then(|v| v.get("CompanyName"))
+        .unwrap_or("-");
+    checks.push(check(
+        "版本资源",
+        Some(claims),
+        format!("CompanyName: {}", company),
+    ));
+
+    let signed_by_microsoft = info.signature.as_ref().map(is_microsoft_signature);
+    checks.push(match &info.signature {
+        Some(signature) => check(
+            "签名链",
+            signed_by_microsoft,
+            signature
+                .chain
+                .iter()
+                .map(|c| {
+                    subject_common_name(&c.subject)
+                        .unwrap_or(&c.subject)
+                        .to_string()
+                })
+                .collect::<Vec<String>>()
+                .join(" <- "),
+        ),
+        // 系统目录中的大部分文件使用编录签名 签名不在文件内
+        None => check(
+            "签名链",
+            None,
+            "无内嵌签名 系统文件通常使用编录(.cat)签名 需在Windows上验证".to_string(),
+        ),
+    });
+
+    let digest_valid = info.signature.as_ref().and_then(|s| s.digest_valid);
+    if info.signature.is_some() {
+        checks.push(check(
+            "映像摘要",
+            digest_valid,
+            match digest_valid {
+                Some(true) => "与签名中的摘要一致".to_string(),
+                Some(false) => "与签名中的摘要不符 文件在签名后被修改".to_string(),
+                None => "无法计算摘要".to_string(),
+            },
+        ));
+    }
+
+    let mut winbindex: Option<bool> = None;
+    if online {
+        let file_name = Path::new(&info.path)
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        checks.push(match winbindex_lookup(&file_name, &info.hashes.sha256) {
+            Ok(found) => {
+                winbindex = Some(found);
+                check(
+                    "Winbindex",
+                    Some(found),
+                    if found {
+                        format!("{} 的此版本已被Winbindex收录", file_name)
+                    } else {
+                        format!("Winbindex中没有该哈希的 {}", file_name)
+                    },
+                )
+            }
+            Err(error) => check("Winbindex", None, error),
+        });
+    }
+
+    let verdict = if digest_valid == Some(false) {
+        "masquerade"
+    } else if signed_by_microsoft == Some(true) || winbindex == Some(true) {
+        "genuine"
+    } else if claims && (signed_by_microsoft == Some(false) || winbindex == Some(false)) {
+        "masquerade"
+    } else if claims {
+        "unverified"
+    } else {
+        "not_microsoft"
+    };
+    MicrosoftOrigin {
+        verdict: verdict.to_string(),
+        checks,
+    }
+}
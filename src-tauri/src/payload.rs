@@ -0,0 +1,44 @@
+// 大结果的传输打包: 以二进制返回给前端 避免JSON再作为字符串在IPC中转义一次
+// 首字节为格式标记 其后为JSON原文或gzip压缩的JSON 前端按标记解压后解析
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+
+use std::io::Write;
+
+use crate::entropy::shannon_entropy;
+
+const FORMAT_JSON: u8 = 0;
+const FORMAT_GZIP: u8 = 1;
+// 小结果压缩的开销比传输更大
+const MIN_COMPRESS_SIZE: usize = 64 * 1024;
+// 采样熵高于此值时压缩率很低(例如大量十六进制或Base64内容) 直接返回原文
+const MAX_COMPRESS_ENTROPY: f64 = 6.5;
+// 从开头、中间和结尾各取一段采样
+const SAMPLE_SIZE: usize = 16 * 1024;
+
+fn sample_entropy(json: &[u8]) -> f64 {
+    let middle = json.len() / 2 - SAMPLE_SIZE / 2;
+    let sample: Vec<u8> = [0, middle, json.len() - SAMPLE_SIZE]
+        .iter()
+        .flat_map(|&start| &json[start..start + SAMPLE_SIZE])
+        .copied()
+        .collect();
+    shannon_entropy(&sample)
+}
+
+pub(crate) fn pack<T: Serialize>(value: &T) -> Result<Vec<u8>, String> {
+    let json = serde_json::to_vec(value).map_err(|e| format!("无法序列化结果: {}", e))?;
+    if json.len() < MIN_COMPRESS_SIZE || sample_entropy(&json) > MAX_COMPRESS_ENTROPY {
+        let mut packed = Vec::with_capacity(json.len() + 1);
+        packed.push(FORMAT_JSON);
+        packed.extend_from_slice(&json);
+        return Ok(packed);
+    }
+    // 压缩级别取速度优先 大结果的瓶颈在WebView解析而不是压缩率
+    let mut encoder = GzEncoder::new(vec![FORMAT_GZIP], Compression::fast());
+    encoder
+        .write_all(&json)
+        .and_then(|_| encoder.finish())
+        .map_err(|e| format!("无法压缩结果: {}", e))
+}
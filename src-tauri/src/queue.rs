@@ -0,0 +1,115 @@
+// 批量分析队列 一次拖入多个文件时在后台并发分析 每完成一个文件发送一次事件
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::workspace::{summarize, WorkspaceRow};
+
+// 发送给前端的事件名
+pub(crate) const ANALYSIS_COMPLETED_EVENT: &str = "analysis-completed";
+// 同时分析的文件数上限 每次分析内部还会使用多个线程
+const MAX_WORKERS: usize = 4;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct QueueCompletion {
+    pub row: WorkspaceRow,
+    // 尚未完成的文件数 包括正在分析的
+    pub remaining: usize,
+}
+
+#[derive(Default)]
+struct QueueState {
+    pending: VecDeque<String>,
+    // 正在分析的文件
+    active: Vec<String>,
+    workers: usize,
+}
+
+impl QueueState {
+    fn remaining(&self) -> usize {
+        self.pending.len() + self.active.len()
+    }
+}
+
+#[derive(Default)]
+pub struct AnalysisQueue {
+    state: Arc<Mutex<QueueState>>,
+}
+
+fn worker_limit() -> usize {
+    thread::available_parallelism()
+        .map(|count| count.get())
+        .unwrap_or(1)
+        .clamp(1, MAX_WORKERS)
+}
+
+fn run_worker(app: AppHandle, state: Arc<Mutex<QueueState>>) {
+    loop {
+        let path = {
+            let Ok(mut queue) = state.lock() else {
+                return;
+            };
+            match queue.pending.pop_front() {
+                Some(path) => {
+                    queue.active.push(path.clone());
+                    path
+                }
+                None => {
+                    queue.workers -= 1;
+                    return;
+                }
+            }
+        };
+        let row = match crate::analyze(PathBuf::from(&path)) {
+            Ok(info) => {
+                // 记录失败不影响分析结果
+                let _ = crate::history::record(&info);
+                summarize(&info)
+            }
+            Err(error) => WorkspaceRow {
+                path: path.clone(),
+                error: Some(error),
+                ..Default::default()
+            },
+        };
+        let remaining = {
+            let Ok(mut queue) = state.lock() else {
+                return;
+            };
+            if let Some(index) = queue.active.iter().position(|active| *active == path) {
+                queue.active.remove(index);
+            }
+            queue.remaining()
+        };
+        let _ = app.emit(ANALYSIS_COMPLETED_EVENT, QueueCompletion { row, remaining });
+    }
+}
+
+// 加入队列并按需启动工作线程 已在队列中的文件不重复加入 返回尚未完成的文件数
+pub(crate) fn enqueue(
+    app: AppHandle,
+    queue: &AnalysisQueue,
+    file_paths: Vec<String>,
+) -> Result<usize, String> {
+    let mut state = queue
+        .state
+        .lock()
+        .map_err(|_| String::from("分析队列不可用"))?;
+    for path in file_paths {
+        if !state.pending.contains(&path) && !state.active.contains(&path) {
+            state.pending.push_back(path);
+        }
+    }
+    let wanted = worker_limit().min(state.pending.len() + state.workers);
+    while state.workers < wanted {
+        state.workers += 1;
+        let app = app.clone();
+        let shared = Arc::clone(&queue.state);
+        thread::spawn(move || run_worker(app, shared));
+    }
+    Ok(state.remaining())
+}
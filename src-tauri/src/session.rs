@@ -0,0 +1,109 @@
+// 会话临时目录 解出的载荷、导出的头部和资源等未指定路径时写到这里 手动清理或退出程序时删除
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SessionFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Default)]
+struct Session {
+    directory: Option<PathBuf>,
+    // 输出文件名的序号前缀 同名输出不会互相覆盖
+    next_id: u32,
+}
+
+static SESSION: LazyLock<Mutex<Session>> = LazyLock::new(|| Mutex::new(Session::default()));
+
+fn lock() -> Result<std::sync::MutexGuard<'static, Session>, String> {
+    SESSION.lock().map_err(|_| String::from("会话目录不可用"))
+}
+
+// 每个进程一个目录 多开时互不影响
+fn ensure_directory(session: &mut Session) -> Result<PathBuf, String> {
+    let directory = session.directory.get_or_insert_with(|| {
+        std::env::temp_dir().join(format!("pe_info_session_{}", std::process::id()))
+    });
+    fs::create_dir_all(&*directory).map_err(|e| format!("无法创建会话目录: {}", e))?;
+    Ok(directory.clone())
+}
+
+// 文件名中只保留安全的字符
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// 在会话目录中分配输出路径 只分配不创建
+pub(crate) fn output_path(name: &str) -> Result<PathBuf, String> {
+    let mut session = lock()?;
+    let directory = ensure_directory(&mut session)?;
+    session.next_id += 1;
+    Ok(directory.join(format!("{:03}_{}", session.next_id, sanitize(name))))
+}
+
+// 指定了路径时原样使用 否则写入会话目录
+pub(crate) fn resolve_output(output_path: Option<&str>, name: &str) -> Result<PathBuf, String> {
+    match output_path {
+        Some(path) => Ok(PathBuf::from(path)),
+        None => self::output_path(name),
+    }
+}
+
+fn collect_files(directory: &Path, files: &mut Vec<SessionFile>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+    for entry in entries.filter_map(|entry| entry.ok()) {
+        let path = entry.path();
+        match entry.metadata() {
+            Ok(metadata) if metadata.is_dir() => collect_files(&path, files),
+            Ok(metadata) => files.push(SessionFile {
+                path: path.to_string_lossy().to_string(),
+                size: metadata.len(),
+            }),
+            Err(_) => {}
+        }
+    }
+}
+
+pub(crate) fn list_files() -> Result<Vec<SessionFile>, String> {
+    let session = lock()?;
+    let mut files: Vec<SessionFile> = Vec::new();
+    if let Some(directory) = &session.directory {
+        collect_files(directory, &mut files);
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+// 删除整个会话目录 返回删除的文件数 之后的输出会重新创建目录
+pub(crate) fn cleanup() -> Result<usize, String> {
+    let mut session = lock()?;
+    let Some(directory) = session.directory.take() else {
+        return Ok(0);
+    };
+    let mut files: Vec<SessionFile> = Vec::new();
+    collect_files(&directory, &mut files);
+    match fs::remove_dir_all(&directory) {
+        Ok(()) => Ok(files.len()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(0),
+        Err(e) => {
+            // 删除失败(例如文件被其他程序打开)时保留目录 以便再次清理
+            session.directory = Some(directory);
+            Err(format!("无法删除会话目录: {}", e))
+        }
+    }
+}
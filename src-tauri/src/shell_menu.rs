@@ -0,0 +1,123 @@
+// 资源管理器右键菜单 注册在HKCU\Software\Classes\SystemFileAssociations下 不需要管理员权限
+// 菜单命令以文件路径作为第一个参数启动本程序 前端启动时通过startup_target取得并直接分析
+use serde::{Deserialize, Serialize};
+
+// 显示菜单的扩展名
+#[cfg(windows)]
+const EXTENSIONS: &[&str] = &[".exe", ".dll", ".sys", ".ocx", ".cpl", ".scr", ".efi"];
+#[cfg(windows)]
+const MENU_KEY: &str = "pe_info";
+#[cfg(windows)]
+const MENU_LABEL: &str = "用 pe_info 分析";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ContextMenuStatus {
+    pub registered: bool,
+    // 已注册的扩展名
+    pub extensions: Vec<String>,
+    // 注册的命令指向的程序 与当前程序不同时需要重新注册
+    pub command: Option<String>,
+}
+
+#[cfg(windows)]
+fn menu_key(extension: &str) -> String {
+    format!(
+        r"HKCU\Software\Classes\SystemFileAssociations\{}\shell\{}",
+        extension, MENU_KEY
+    )
+}
+
+#[cfg(windows)]
+pub(crate) fn reg(args: &[&str]) -> Result<String, String> {
+    use std::process::{Command, Stdio};
+
+    let output = Command::new("reg.exe")
+        .args(args)
+        .stderr(Stdio::null())
+        .output()
+        .map_err(|e| format!("无法启动reg.exe: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("reg.exe {} 失败", args.first().unwrap_or(&"")));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(windows)]
+pub(crate) fn menu_command() -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("无法确定程序路径: {}", e))?;
+    Ok(format!("\"{}\" \"%1\"", exe.to_string_lossy()))
+}
+
+#[cfg(windows)]
+pub(crate) fn register() -> Result<ContextMenuStatus, String> {
+    let command = menu_command()?;
+    let exe = std::env::current_exe().map_err(|e| format!("无法确定程序路径: {}", e))?;
+    let icon = format!("{},0", exe.to_string_lossy());
+    for extension in EXTENSIONS {
+        let key = menu_key(extension);
+        reg(&["add", &key, "/ve", "/d", MENU_LABEL, "/f"])?;
+        reg(&["add", &key, "/v", "Icon", "/d", &icon, "/f"])?;
+        reg(&[
+            "add",
+            &format!(r"{}\command", key),
+            "/ve",
+            "/d",
+            &command,
+            "/f",
+        ])?;
+    }
+    Ok(status())
+}
+
+#[cfg(windows)]
+pub(crate) fn unregister() -> Result<ContextMenuStatus, String> {
+    for extension in EXTENSIONS {
+        // 未注册的扩展名删除失败 忽略
+        let _ = reg(&["delete", &menu_key(extension), "/f"]);
+    }
+    Ok(status())
+}
+
+#[cfg(windows)]
+pub(crate) fn status() -> ContextMenuStatus {
+    let mut extensions: Vec<String> = Vec::new();
+    let mut command: Option<String> = None;
+    for extension in EXTENSIONS {
+        // 输出形如 "    (默认)    REG_SZ    "C:\...\pe_info.exe" "%1""
+        let Ok(output) = reg(&["query", &format!(r"{}\command", menu_key(extension)), "/ve"])
+        else {
+            continue;
+        };
+        extensions.push(extension.to_string());
+        if command.is_none() {
+            command = output
+                .lines()
+                .find_map(|line| line.split_once("REG_SZ"))
+                .map(|(_, value)| value.trim().to_string());
+        }
+    }
+    ContextMenuStatus {
+        registered: !extensions.is_empty(),
+        extensions,
+        command,
+    }
+}
+
+#[cfg(not(windows))]
+pub(crate) fn register() -> Result<ContextMenuStatus, String> {
+    Err("右键菜单仅支持Windows资源管理器".to_string())
+}
+
+#[cfg(not(windows))]
+pub(crate) fn unregister() -> Result<ContextMenuStatus, String> {
+    Err("右键菜单仅支持Windows资源管理器".to_string())
+}
+
+#[cfg(not(windows))]
+pub(crate) fn status() -> ContextMenuStatus {
+    ContextMenuStatus {
+        registered: false,
+        extensions: Vec::new(),
+        command: None,
+    }
+}
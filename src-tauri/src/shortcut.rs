@@ -0,0 +1,237 @@
+// Windows快捷方式(.lnk) Shell Link二进制格式 [MS-SHLLINK]
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use crate::file_access;
+use crate::reader::{decode_utf16, utf16_units, BoundedReader};
+
+const HEADER_SIZE: u64 = 0x4C;
+// {00021401-0000-0000-C000-000000000046}
+const LINK_CLSID: [u8; 16] = [
+    0x01, 0x14, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0xC0, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x46,
+];
+// .lnk文件上限 正常快捷方式只有几KB
+const MAX_SHORTCUT_SIZE: usize = 1024 * 1024;
+
+const HAS_LINK_TARGET_ID_LIST: u32 = 0x01;
+const HAS_LINK_INFO: u32 = 0x02;
+const HAS_NAME: u32 = 0x04;
+const HAS_RELATIVE_PATH: u32 = 0x08;
+const HAS_WORKING_DIR: u32 = 0x10;
+const HAS_ARGUMENTS: u32 = 0x20;
+const HAS_ICON_LOCATION: u32 = 0x40;
+const IS_UNICODE: u32 = 0x80;
+
+const VOLUME_ID_AND_LOCAL_BASE_PATH: u32 = 0x01;
+const COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX: u32 = 0x02;
+
+// EnvironmentVariableDataBlock 目标路径含环境变量时存在
+const ENVIRONMENT_VARIABLE_SIGNATURE: u32 = 0xA000_0001;
+const ENVIRONMENT_TARGET_OFFSET: u64 = 8;
+const ENVIRONMENT_TARGET_UNICODE_OFFSET: u64 = 268;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ShortcutInfo {
+    pub link_path: String,
+    // 解析出的目标路径 环境变量已展开
+    pub target_path: Option<String>,
+    pub description: Option<String>,
+    pub relative_path: Option<String>,
+    pub working_directory: Option<String>,
+    pub arguments: Option<String>,
+    pub icon_location: Option<String>,
+    pub icon_index: i32,
+    // 1正常 3最大化 7最小化
+    pub show_command: u32,
+    // 创建快捷方式时目标文件的大小
+    pub target_size: u32,
+}
+
+// 以0结尾的ANSI字符串 代码页未知 按UTF-8宽松解码
+// offset之后的全部字节 offset等于长度时为空
+fn rest<'a>(reader: &BoundedReader<'a>, offset: u64) -> Option<&'a [u8]> {
+    reader.bytes(offset, reader.len().checked_sub(offset)?).ok()
+}
+
+fn ansi_string(reader: &BoundedReader, offset: u64) -> Option<String> {
+    let rest = rest(reader, offset)?;
+    let length = rest.iter().position(|&b| b == 0).unwrap_or(rest.len());
+    Some(String::from_utf8_lossy(&rest[..length]).to_string())
+}
+
+fn unicode_string(reader: &BoundedReader, offset: u64) -> Option<String> {
+    let rest = rest(reader, offset)?;
+    let units: Vec<u16> = utf16_units(rest).take_while(|&unit| unit != 0).collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+// 文件以Shell Link头开头
+pub(crate) fn is_shortcut(data: &[u8]) -> bool {
+    let reader = BoundedReader::new(data);
+    reader.u32(0) == Ok(HEADER_SIZE as u32) && reader.array(4) == Ok(LINK_CLSID)
+}
+
+pub(crate) fn is_shortcut_file(file_path: &Path) -> bool {
+    file_access::read_prefix(file_path, 20).is_ok_and(|header| is_shortcut(&header))
+}
+
+// LinkInfo中的本地路径或网络路径
+fn link_info_path(link_info: &[u8]) -> Option<String> {
+    let link_info = BoundedReader::new(link_info);
+    let header_size = link_info.u32(4).ok()?;
+    let flags = link_info.u32(8).ok()?;
+    // 头部不小于0x24时含Unicode版本的偏移
+    let unicode = header_size >= 0x24;
+    let suffix = if unicode {
+        unicode_string(&link_info, link_info.u32(0x20).ok()? as u64)
+    } else {
+        ansi_string(&link_info, link_info.u32(0x18).ok()? as u64)
+    }
+    .unwrap_or_default();
+
+    if flags & VOLUME_ID_AND_LOCAL_BASE_PATH != 0 {
+        let base = if unicode {
+            unicode_string(&link_info, link_info.u32(0x1C).ok()? as u64)?
+        } else {
+            ansi_string(&link_info, link_info.u32(0x10).ok()? as u64)?
+        };
+        return Some(format!("{}{}", base, suffix));
+    }
+    if flags & COMMON_NETWORK_RELATIVE_LINK_AND_PATH_SUFFIX != 0 {
+        let network = BoundedReader::new(rest(&link_info, link_info.u32(0x14).ok()? as u64)?);
+        let share = ansi_string(&network, network.u32(8).ok()? as u64)?;
+        return Some(if suffix.is_empty() {
+            share
+        } else {
+            format!("{}\\{}", share, suffix)
+        });
+    }
+    None
+}
+
+// 按%NAME%展开环境变量 未定义的保持原样
+fn expand_environment(path: &str) -> String {
+    let mut expanded = String::new();
+    let mut rest = path;
+    while let Some(start) = rest.find('%') {
+        let Some(length) = rest[start + 1..].find('%') else {
+            break;
+        };
+        let name = &rest[start + 1..start + 1 + length];
+        expanded.push_str(&rest[..start]);
+        match std::env::var(name) {
+            Ok(value) if !name.is_empty() => expanded.push_str(&value),
+            _ => expanded.push_str(&rest[start..start + length + 2]),
+        }
+        rest = &rest[start + length + 2..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+// ExtraData中EnvironmentVariableDataBlock的目标
+fn environment_target(reader: &BoundedReader, mut offset: u64) -> Option<String> {
+    loop {
+        let size = reader.u32(offset).ok()? as u64;
+        // 结束块的大小小于4
+        if size < 4 {
+            return None;
+        }
+        if reader.u32(offset + 4).ok()? == ENVIRONMENT_VARIABLE_SIGNATURE {
+            let block = BoundedReader::new(reader.bytes(offset, size).ok()?);
+            let target = unicode_string(&block, ENVIRONMENT_TARGET_UNICODE_OFFSET)
+                .filter(|target| !target.is_empty())
+                .or_else(|| ansi_string(&block, ENVIRONMENT_TARGET_OFFSET))?;
+            return Some(expand_environment(&target));
+        }
+        offset += size;
+    }
+}
+
+pub(crate) fn parse_shortcut(link_path: &str, data: &[u8]) -> Option<ShortcutInfo> {
+    if !is_shortcut(data) {
+        return None;
+    }
+    let reader = BoundedReader::new(data);
+    let flags = reader.u32(0x14).ok()?;
+    let target_size = reader.u32(0x34).ok()?;
+    let icon_index = reader.i32(0x38).ok()?;
+    let show_command = reader.u32(0x3C).ok()?;
+
+    let mut offset = HEADER_SIZE;
+    if flags & HAS_LINK_TARGET_ID_LIST != 0 {
+        offset += 2 + reader.u16(offset).ok()? as u64;
+    }
+    let mut link_info_target: Option<String> = None;
+    if flags & HAS_LINK_INFO != 0 {
+        let size = reader.u32(offset).ok()? as u64;
+        link_info_target = reader
+            .bytes(offset, size)
+            .ok()
+            .and_then(link_info_path)
+            .filter(|path| !path.is_empty());
+        offset += size;
+    }
+
+    // StringData 每项为字符数 + 字符 无结尾0
+    let mut read_string = |present: bool| -> Option<Option<String>> {
+        if !present {
+            return Some(None);
+        }
+        let count = reader.u16(offset).ok()? as u64;
+        let start = offset + 2;
+        let value = if flags & IS_UNICODE != 0 {
+            let bytes = reader.bytes(start, count * 2).ok()?;
+            offset = start + count * 2;
+            decode_utf16(bytes)
+        } else {
+            let bytes = reader.bytes(start, count).ok()?;
+            offset = start + count;
+            String::from_utf8_lossy(bytes).to_string()
+        };
+        Some(Some(value))
+    };
+    let description = read_string(flags & HAS_NAME != 0)?;
+    let relative_path = read_string(flags & HAS_RELATIVE_PATH != 0)?;
+    let working_directory = read_string(flags & HAS_WORKING_DIR != 0)?;
+    let arguments = read_string(flags & HAS_ARGUMENTS != 0)?;
+    let icon_location = read_string(flags & HAS_ICON_LOCATION != 0)?;
+
+    // 依次尝试LinkInfo、环境变量块和相对于.lnk所在目录的相对路径
+    let target_path = link_info_target
+        .or_else(|| environment_target(&reader, offset))
+        .or_else(|| {
+            let relative = relative_path.as_ref()?;
+            let directory = Path::new(link_path).parent()?;
+            Some(directory.join(relative).to_string_lossy().to_string())
+        });
+
+    Some(ShortcutInfo {
+        link_path: link_path.to_string(),
+        target_path,
+        description,
+        relative_path,
+        working_directory,
+        arguments,
+        icon_location,
+        icon_index,
+        show_command,
+        target_size,
+    })
+}
+
+// 文件不是快捷方式时返回Ok(None)
+pub(crate) fn read_shortcut(link_path: &Path) -> Result<Option<ShortcutInfo>, String> {
+    if !is_shortcut_file(link_path) {
+        return Ok(None);
+    }
+    // 多读一个字节以判断是否超过上限
+    let data = file_access::read_prefix(link_path, MAX_SHORTCUT_SIZE + 1)?;
+    if data.len() > MAX_SHORTCUT_SIZE {
+        return Err("快捷方式文件过大".to_string());
+    }
+    parse_shortcut(&link_path.to_string_lossy(), &data)
+        .map(Some)
+        .ok_or_else(|| "无法解析快捷方式".to_string())
+}
@@ -0,0 +1,172 @@
+// NTFS备用数据流 包括记录下载来源的Zone.Identifier(网络标记)
+use serde::{Deserialize, Serialize};
+
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use crate::file_access;
+
+const ZONE_IDENTIFIER_STREAM: &str = "Zone.Identifier";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AlternateStream {
+    // 不含前导冒号和:$DATA后缀 以"文件路径:名称"打开
+    pub name: String,
+    pub size: u64,
+    // 数据以MZ开头 可作为PE单独分析
+    pub is_pe: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct ZoneIdentifier {
+    pub zone_id: Option<u32>,
+    pub zone_name: String,
+    pub referrer_url: Option<String>,
+    pub host_url: Option<String>,
+}
+
+// FindFirstStreamW/FindNextStreamW枚举$DATA流 跳过无名的主数据流
+#[cfg(windows)]
+fn enumerate(file_path: &Path) -> Vec<(String, u64)> {
+    use std::ffi::c_void;
+    use std::os::windows::ffi::OsStrExt;
+
+    // MAX_PATH + 36
+    #[repr(C)]
+    struct Win32FindStreamData {
+        stream_size: i64,
+        stream_name: [u16; 296],
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn FindFirstStreamW(
+            file_name: *const u16,
+            info_level: i32,
+            find_stream_data: *mut c_void,
+            flags: u32,
+        ) -> isize;
+        fn FindNextStreamW(find_stream: isize, find_stream_data: *mut c_void) -> i32;
+        fn FindClose(find_file: isize) -> i32;
+    }
+    const FIND_STREAM_INFO_STANDARD: i32 = 0;
+    const INVALID_HANDLE_VALUE: isize = -1;
+    // 数据流数量上限
+    const MAX_STREAMS: usize = 256;
+
+    let mut streams: Vec<(String, u64)> = Vec::new();
+    let Ok(path) = file_access::resolve(file_path) else {
+        return streams;
+    };
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut data = Win32FindStreamData {
+        stream_size: 0,
+        stream_name: [0; 296],
+    };
+    // SAFETY: wide以0结尾 data的布局与WIN32_FIND_STREAM_DATA一致 句柄在返回前关闭
+    unsafe {
+        let handle = FindFirstStreamW(
+            wide.as_ptr(),
+            FIND_STREAM_INFO_STANDARD,
+            &mut data as *mut Win32FindStreamData as *mut c_void,
+            0,
+        );
+        if handle == INVALID_HANDLE_VALUE {
+            return streams;
+        }
+        loop {
+            let length = data
+                .stream_name
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(data.stream_name.len());
+            let name = String::from_utf16_lossy(&data.stream_name[..length]);
+            // 名称形如":Zone.Identifier:$DATA" 主数据流为"::$DATA"
+            let name = name
+                .strip_prefix(':')
+                .and_then(|name| name.strip_suffix(":$DATA"))
+                .unwrap_or(&name)
+                .to_string();
+            if !name.is_empty() {
+                streams.push((name, data.stream_size.max(0) as u64));
+            }
+            if streams.len() >= MAX_STREAMS
+                || FindNextStreamW(handle, &mut data as *mut Win32FindStreamData as *mut c_void)
+                    == 0
+            {
+                break;
+            }
+        }
+        FindClose(handle);
+    }
+    streams
+}
+
+// 备用数据流只存在于NTFS
+#[cfg(not(windows))]
+fn enumerate(_file_path: &Path) -> Vec<(String, u64)> {
+    Vec::new()
+}
+
+pub(crate) fn stream_path(file_path: &Path, name: &str) -> PathBuf {
+    let mut path = OsString::from(file_path);
+    path.push(":");
+    path.push(name);
+    PathBuf::from(path)
+}
+
+fn starts_with_mz(file_path: &Path, name: &str) -> bool {
+    file_access::read_prefix(&stream_path(file_path, name), 2).is_ok_and(|magic| magic == b"MZ")
+}
+
+pub(crate) fn list_streams(file_path: &Path) -> Vec<AlternateStream> {
+    enumerate(file_path)
+        .into_iter()
+        .map(|(name, size)| AlternateStream {
+            is_pe: starts_with_mz(file_path, &name),
+            name,
+            size,
+        })
+        .collect()
+}
+
+fn zone_name(zone_id: u32) -> String {
+    match zone_id {
+        0 => "本地计算机".to_string(),
+        1 => "本地Intranet".to_string(),
+        2 => "受信任的站点".to_string(),
+        3 => "Internet".to_string(),
+        4 => "受限制的站点".to_string(),
+        other => format!("未知区域({})", other),
+    }
+}
+
+// [ZoneTransfer]节中的ZoneId/ReferrerUrl/HostUrl
+pub(crate) fn parse_zone_identifier(text: &str) -> ZoneIdentifier {
+    let mut zone = ZoneIdentifier::default();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "ZoneId" => zone.zone_id = value.parse().ok(),
+            "ReferrerUrl" => zone.referrer_url = Some(value.to_string()),
+            "HostUrl" => zone.host_url = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    zone.zone_name = zone.zone_id.map_or_else(|| "-".to_string(), zone_name);
+    zone
+}
+
+pub(crate) fn read_zone_identifier(
+    file_path: &Path,
+    streams: &[AlternateStream],
+) -> Option<ZoneIdentifier> {
+    streams
+        .iter()
+        .find(|stream| stream.name.eq_ignore_ascii_case(ZONE_IDENTIFIER_STREAM))?;
+    let data = file_access::read_file(&stream_path(file_path, ZONE_IDENTIFIER_STREAM)).ok()?;
+    Some(parse_zone_identifier(&String::from_utf8_lossy(&data)))
+}
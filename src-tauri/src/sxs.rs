@@ -0,0 +1,171 @@
+// 嵌入清单(RT_MANIFEST)中的并行程序集(SxS)依赖 检查本机WinSxS或程序目录中是否存在 缺失时激活上下文创建失败 程序无法启动
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::deps::windows_directory;
+use crate::manifest::{attribute, decode_manifest, dependent_identities};
+use crate::resources::{resource_data, ResourceEntry, RT_MANIFEST};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SxsDependency {
+    pub name: String,
+    pub version: String,
+    pub architecture: String,
+    pub public_key_token: Option<String>,
+    // installed(WinSxS中有相同版本) / policy(有更高的修订版本 通常由发布者策略重定向)
+    // private(程序目录中的私有程序集) / missing / unknown(本机没有WinSxS)
+    pub status: String,
+    // 满足依赖的WinSxS目录名或私有程序集路径
+    pub resolved: Option<String>,
+}
+
+static WINSXS: OnceLock<Option<Vec<String>>> = OnceLock::new();
+
+// WinSxS目录下的程序集目录名 全部小写
+fn load_winsxs() -> Option<Vec<String>> {
+    let directory = windows_directory()?.join("WinSxS");
+    let entries = std::fs::read_dir(directory).ok()?;
+    Some(
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_ascii_lowercase())
+            .collect(),
+    )
+}
+
+fn version_parts(version: &str) -> Vec<u32> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+// WinSxS目录名: 架构_名称_公钥_版本_语言_哈希 过长的名称中间以..省略
+fn winsxs_version<'a>(
+    entry: &'a str,
+    architecture: &str,
+    name: &str,
+    token: &str,
+) -> Option<&'a str> {
+    let (entry_architecture, rest) = entry.split_once('_')?;
+    let mut parts = rest.rsplitn(5, '_');
+    let (_hash, _culture, version, entry_token, entry_name) = (
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+    );
+    let name_matches = match entry_name.split_once("..") {
+        Some((head, tail)) => name.starts_with(head) && name.ends_with(tail),
+        None => entry_name == name,
+    };
+    (name_matches && entry_architecture == architecture && entry_token == token).then_some(version)
+}
+
+// 私有程序集: 程序目录中的 名称.manifest / 名称.dll 或 名称\名称.manifest / 名称\名称.dll
+fn private_assembly(app_directory: &Path, name: &str) -> Option<String> {
+    [
+        app_directory.join(format!("{}.manifest", name)),
+        app_directory.join(format!("{}.dll", name)),
+        app_directory.join(name).join(format!("{}.manifest", name)),
+        app_directory.join(name).join(format!("{}.dll", name)),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+    .map(|path| path.to_string_lossy().to_string())
+}
+
+fn image_architecture(machine: u16) -> &'static str {
+    match machine {
+        0x014C => "x86",
+        0x8664 => "amd64",
+        0xAA64 => "arm64",
+        0x01C4 => "arm",
+        _ => "x86",
+    }
+}
+
+fn resolve(
+    identity: &str,
+    machine: u16,
+    app_directory: &Path,
+    winsxs: Option<&[String]>,
+) -> Option<SxsDependency> {
+    let name = attribute(identity, "name")?;
+    let version = attribute(identity, "version").unwrap_or_default();
+    let architecture = attribute(identity, "processorArchitecture")
+        .filter(|architecture| architecture != "*")
+        .unwrap_or_else(|| image_architecture(machine).to_string())
+        .to_ascii_lowercase();
+    let public_key_token = attribute(identity, "publicKeyToken");
+
+    let lower_name = name.to_ascii_lowercase();
+    let token = public_key_token
+        .as_deref()
+        .unwrap_or("none")
+        .to_ascii_lowercase();
+    let required = version_parts(&version);
+    let installed = winsxs.map(|entries| {
+        entries
+            .iter()
+            .filter_map(|entry| {
+                winsxs_version(entry, &architecture, &lower_name, &token)
+                    .map(|found| (entry, version_parts(found)))
+            })
+            .collect::<Vec<_>>()
+    });
+
+    let (status, resolved) = if let Some(path) = private_assembly(app_directory, &name) {
+        ("private", Some(path))
+    } else if let Some(installed) = installed {
+        if let Some((entry, _)) = installed.iter().find(|(_, found)| *found == required) {
+            ("installed", Some(entry.to_string()))
+        } else if let Some((entry, _)) = installed
+            .iter()
+            .filter(|(_, found)| found.get(..2) == required.get(..2) && *found > required)
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+        {
+            ("policy", Some(entry.to_string()))
+        } else {
+            ("missing", None)
+        }
+    } else {
+        ("unknown", None)
+    };
+    Some(SxsDependency {
+        name,
+        version,
+        architecture,
+        public_key_token,
+        status: status.to_string(),
+        resolved,
+    })
+}
+
+pub(crate) fn check_sxs_dependencies(
+    data: &[u8],
+    resources: &[ResourceEntry],
+    machine: u16,
+    file_path: &Path,
+) -> Vec<SxsDependency> {
+    let mut dependencies: Vec<SxsDependency> = Vec::new();
+    let manifests: Vec<String> = resources
+        .iter()
+        .filter(|resource| resource.type_id == Some(RT_MANIFEST))
+        .map(|resource| decode_manifest(resource_data(data, resource)))
+        .collect();
+    if manifests.is_empty() {
+        return dependencies;
+    }
+    let app_directory = file_path.parent().unwrap_or(Path::new(""));
+    let winsxs = WINSXS.get_or_init(load_winsxs).as_deref();
+    for identity in manifests.iter().flat_map(|xml| dependent_identities(xml)) {
+        if let Some(dependency) = resolve(&identity, machine, app_directory, winsxs) {
+            dependencies.push(dependency);
+        }
+    }
+    dependencies
+}
@@ -0,0 +1,148 @@
+// 与System32/SysWOW64中同名文件对比 用于发现DLL劫持(侧加载)载荷
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+use crate::deps::{find_in_directory, system_directories};
+use crate::{PeInfo, IMAGE_DIRECTORY_ENTRY_EXPORT};
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ComparedField {
+    pub name: String,
+    pub local: String,
+    pub system: String,
+    pub matches: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SystemComparison {
+    pub system_path: String,
+    // 两个文件的SHA-256相同
+    pub identical: bool,
+    pub fields: Vec<ComparedField>,
+    // 只存在于本地文件的导出 通常是劫持DLL自己的入口
+    pub extra_exports: Vec<String>,
+    // 系统文件有但本地文件缺少的导出 未转发时调用方会加载失败
+    pub missing_exports: Vec<String>,
+    // 本地导出中转发到其他模块的数量 劫持DLL常把导出转发回系统副本
+    pub forwarded_exports: usize,
+}
+
+// 系统目录中的同名文件 文件本身就位于系统目录时为None
+pub(crate) fn find_system_copy(path: &Path, is_x64: bool) -> Option<PathBuf> {
+    let name = path.file_name()?.to_string_lossy().to_string();
+    let directory = system_directories(is_x64).into_iter().next()?;
+    let candidate = find_in_directory(&directory, &name)?;
+    let same_file = match (path.canonicalize(), candidate.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    };
+    (!same_file).then_some(candidate)
+}
+
+fn field(name: &str, local: String, system: String) -> ComparedField {
+    ComparedField {
+        name: name.to_string(),
+        matches: local == system,
+        local,
+        system,
+    }
+}
+
+fn version_string(info: &PeInfo, key: &str) -> String {
+    info.version_info
+        .as_ref()
+        .and_then(|v| v.get(key))
+        .unwrap_or("-")
+        .to_string()
+}
+
+fn signer(info: &PeInfo) -> String {
+    match &info.signature {
+        Some(signature) => signature
+            .signer
+            .clone()
+            .unwrap_or_else(|| "未知".to_string()),
+        None if info.is_signed => "存在证书目录".to_string(),
+        None => "未签名".to_string(),
+    }
+}
+
+// 导出地址落在导出目录内的是转发导出 地址指向"模块.函数"字符串
+fn forwarded_exports(info: &PeInfo) -> usize {
+    let Some(directory) = info.data_directories.get(IMAGE_DIRECTORY_ENTRY_EXPORT) else {
+        return 0;
+    };
+    let end = directory.rva.saturating_add(directory.size);
+    info.export_table
+        .iter()
+        .filter(|f| f.address >= directory.rva && f.address < end)
+        .count()
+}
+
+// 只按序号导出的函数以#序号表示
+fn export_names(info: &PeInfo) -> BTreeSet<String> {
+    info.export_table
+        .iter()
+        .map(|f| {
+            if f.is_named {
+                f.name.clone()
+            } else {
+                format!("#{}", f.ordinal)
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn compare(local: &PeInfo, system: &PeInfo) -> SystemComparison {
+    let fields = vec![
+        field(
+            "SHA-256",
+            local.hashes.sha256.clone(),
+            system.hashes.sha256.clone(),
+        ),
+        field("文件大小", local.size.to_string(), system.size.to_string()),
+        field(
+            "文件版本",
+            local
+                .version_info
+                .as_ref()
+                .and_then(|v| v.file_version.clone())
+                .unwrap_or_else(|| "-".to_string()),
+            system
+                .version_info
+                .as_ref()
+                .and_then(|v| v.file_version.clone())
+                .unwrap_or_else(|| "-".to_string()),
+        ),
+        field(
+            "CompanyName",
+            version_string(local, "CompanyName"),
+            version_string(system, "CompanyName"),
+        ),
+        field(
+            "OriginalFilename",
+            version_string(local, "OriginalFilename"),
+            version_string(system, "OriginalFilename"),
+        ),
+        field("签名者", signer(local), signer(system)),
+        field("imphash", local.imphash.clone(), system.imphash.clone()),
+        field(
+            "导出数量",
+            local.export_table.len().to_string(),
+            system.export_table.len().to_string(),
+        ),
+    ];
+
+    let local_exports = export_names(local);
+    let system_exports = export_names(system);
+    SystemComparison {
+        system_path: system.path.clone(),
+        identical: local.hashes.sha256 == system.hashes.sha256,
+        fields,
+        extra_exports: local_exports.difference(&system_exports).cloned().collect(),
+        missing_exports: system_exports.difference(&local_exports).cloned().collect(),
+        forwarded_exports: forwarded_exports(local),
+    }
+}
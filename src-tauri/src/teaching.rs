@@ -0,0 +1,498 @@
+// 教学模式: 按解析器的实际顺序复述读取了哪些偏移、访问了哪些结构 便于对照学习PE格式
+use serde::{Deserialize, Serialize};
+
+use crate::headers::{machine_name, subsystem_name};
+use crate::layout::DIRECTORY_NAMES;
+use crate::reader::{read_u16, read_u32, read_u64, BoundedReader};
+use crate::{rva_to_offset, Section, IMAGE_DIRECTORY_ENTRY_SECURITY};
+
+// 导入描述符最多讲解的数量 其余只给出总数
+const MAX_IMPORT_DESCRIPTORS: usize = 8;
+const MAX_NAME_LENGTH: usize = 256;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ParseStep {
+    // 读取位置的文件偏移和字节数 纯推导步骤的size为0
+    pub offset: u64,
+    pub size: u32,
+    // 所属结构 如 IMAGE_DOS_HEADER
+    pub structure: String,
+    pub field: String,
+    pub value: String,
+    pub explanation: String,
+}
+
+fn read_name(data: &[u8], offset: usize) -> String {
+    BoundedReader::new(data)
+        .c_string_truncated(offset as u64, MAX_NAME_LENGTH)
+        .map(|(bytes, _)| String::from_utf8_lossy(bytes).to_string())
+        .unwrap_or_default()
+}
+
+struct Narrator {
+    steps: Vec<ParseStep>,
+}
+
+impl Narrator {
+    fn step(
+        &mut self,
+        offset: usize,
+        size: u32,
+        structure: &str,
+        field: &str,
+        value: String,
+        explanation: String,
+    ) {
+        self.steps.push(ParseStep {
+            offset: offset as u64,
+            size,
+            structure: structure.to_string(),
+            field: field.to_string(),
+            value,
+            explanation,
+        });
+    }
+
+    // RVA到文件偏移的换算过程
+    fn translate(&mut self, sections: &[Section], what: &str, rva: u32) -> Option<usize> {
+        let Some(section) = sections
+            .iter()
+            .find(|section| rva >= section.rva && rva < section.rv_end)
+        else {
+            self.step(
+                0,
+                0,
+                "RVA换算",
+                what,
+                format!("0x{:X}", rva),
+                "不在任何节的虚拟地址范围内 无法换算为文件偏移".to_string(),
+            );
+            return None;
+        };
+        let offset = rva_to_offset(sections, rva);
+        self.step(
+            offset.unwrap_or(0) as usize,
+            0,
+            "RVA换算",
+            what,
+            format!("0x{:X}", rva),
+            match offset {
+                Some(offset) => format!(
+                    "位于节{} (VirtualAddress 0x{:X}) 文件偏移 = 0x{:X} - 0x{:X} + PointerToRawData 0x{:X} = 0x{:X}",
+                    section.name, section.rva, rva, section.rva, section.ptr_raw_data, offset
+                ),
+                None => format!(
+                    "位于节{} 但超出其原始数据 加载时由0填充 文件中没有对应字节",
+                    section.name
+                ),
+            },
+        );
+        offset.map(|offset| offset as usize)
+    }
+}
+
+fn walk_imports(narrator: &mut Narrator, data: &[u8], sections: &[Section], start: usize) {
+    let mut index = 0;
+    loop {
+        let descriptor = start + index * 20;
+        let (Some(lookup), Some(name_rva), Some(first_thunk)) = (
+            read_u32(data, descriptor),
+            read_u32(data, descriptor + 12),
+            read_u32(data, descriptor + 16),
+        ) else {
+            break;
+        };
+        if lookup == 0 && name_rva == 0 && first_thunk == 0 {
+            narrator.step(
+                descriptor,
+                20,
+                "IMAGE_IMPORT_DESCRIPTOR",
+                &format!("[{}]", index),
+                "全0".to_string(),
+                format!("全0的描述符标志导入表结束 共{}个DLL", index),
+            );
+            break;
+        }
+        if index < MAX_IMPORT_DESCRIPTORS {
+            let dll_name = rva_to_offset(sections, name_rva)
+                .map(|offset| read_name(data, offset as usize))
+                .unwrap_or_default();
+            narrator.step(
+                descriptor + 12,
+                4,
+                "IMAGE_IMPORT_DESCRIPTOR",
+                &format!("[{}].Name", index),
+                format!("0x{:X} → {}", name_rva, dll_name),
+                "指向以0结尾的DLL名称".to_string(),
+            );
+            narrator.step(
+                descriptor,
+                4,
+                "IMAGE_IMPORT_DESCRIPTOR",
+                &format!("[{}].OriginalFirstThunk", index),
+                format!("0x{:X}", lookup),
+                "导入名称表(INT) 每项是序号或指向IMAGE_IMPORT_BY_NAME(Hint+名称)的RVA".to_string(),
+            );
+            narrator.step(
+                descriptor + 16,
+                4,
+                "IMAGE_IMPORT_DESCRIPTOR",
+                &format!("[{}].FirstThunk", index),
+                format!("0x{:X}", first_thunk),
+                "导入地址表(IAT) 磁盘上通常与INT相同 加载器把解析出的函数地址写入这里".to_string(),
+            );
+        }
+        index += 1;
+    }
+    if index > MAX_IMPORT_DESCRIPTORS {
+        narrator.step(
+            start,
+            0,
+            "IMAGE_IMPORT_DESCRIPTOR",
+            "...",
+            format!("{}", index),
+            format!("只讲解了前{}个描述符 其余结构相同", MAX_IMPORT_DESCRIPTORS),
+        );
+    }
+}
+
+fn walk_exports(narrator: &mut Narrator, data: &[u8], sections: &[Section], start: usize) {
+    const STRUCTURE: &str = "IMAGE_EXPORT_DIRECTORY";
+    if let Some(name_rva) = read_u32(data, start + 0x0C) {
+        let name = rva_to_offset(sections, name_rva)
+            .map(|offset| read_name(data, offset as usize))
+            .unwrap_or_default();
+        narrator.step(
+            start + 0x0C,
+            4,
+            STRUCTURE,
+            "Name",
+            format!("0x{:X} → {}", name_rva, name),
+            "链接时的模块名 可能与文件名不同".to_string(),
+        );
+    }
+    let fields: [(usize, &str, &str); 6] = [
+        (0x10, "Base", "序号基数 导出序号 = Base + 地址表下标"),
+        (0x14, "NumberOfFunctions", "导出地址表(EAT)的项数"),
+        (
+            0x18,
+            "NumberOfNames",
+            "按名称导出的数量 名称表和序号表的项数",
+        ),
+        (
+            0x1C,
+            "AddressOfFunctions",
+            "导出地址表 每项为函数RVA 落在导出目录内时为转发字符串",
+        ),
+        (
+            0x20,
+            "AddressOfNames",
+            "按字母排序的名称RVA数组 加载器用二分查找",
+        ),
+        (
+            0x24,
+            "AddressOfNameOrdinals",
+            "与名称表一一对应的WORD数组 值为地址表下标",
+        ),
+    ];
+    for (field_offset, field, explanation) in fields {
+        if let Some(value) = read_u32(data, start + field_offset) {
+            narrator.step(
+                start + field_offset,
+                4,
+                STRUCTURE,
+                field,
+                if field_offset < 0x1C {
+                    value.to_string()
+                } else {
+                    format!("0x{:X}", value)
+                },
+                explanation.to_string(),
+            );
+        }
+    }
+}
+
+pub(crate) fn explain_parse(data: &[u8], sections: &[Section]) -> Result<Vec<ParseStep>, String> {
+    let mut narrator = Narrator { steps: Vec::new() };
+
+    // DOS头
+    if !data.starts_with(b"MZ") {
+        return Err("不是有效的PE文件".into());
+    }
+    narrator.step(
+        0,
+        2,
+        "IMAGE_DOS_HEADER",
+        "e_magic",
+        "\"MZ\"".to_string(),
+        "所有PE文件都以DOS头开始 开头两个字节必须是MZ".to_string(),
+    );
+    let nt = read_u32(data, 0x3C).ok_or("不是有效的PE文件")? as usize;
+    narrator.step(
+        0x3C,
+        4,
+        "IMAGE_DOS_HEADER",
+        "e_lfanew",
+        format!("0x{:X}", nt),
+        format!(
+            "NT头的文件偏移 0x40到0x{:X}之间通常是DOS存根程序和Rich头",
+            nt
+        ),
+    );
+    if data.get(nt..nt.saturating_add(4)) != Some(b"PE\0\0".as_slice()) {
+        return Err("不是有效的PE文件".into());
+    }
+    narrator.step(
+        nt,
+        4,
+        "IMAGE_NT_HEADERS",
+        "Signature",
+        "\"PE\\0\\0\"".to_string(),
+        "NT头签名 紧随其后是20字节的COFF文件头".to_string(),
+    );
+
+    // COFF文件头
+    let coff = nt + 4;
+    let machine = read_u16(data, coff).unwrap_or(0);
+    narrator.step(
+        coff,
+        2,
+        "IMAGE_FILE_HEADER",
+        "Machine",
+        format!("0x{:04X}", machine),
+        format!("目标架构 {}", machine_name(machine)),
+    );
+    let number_of_sections = read_u16(data, coff + 2).unwrap_or(0);
+    narrator.step(
+        coff + 2,
+        2,
+        "IMAGE_FILE_HEADER",
+        "NumberOfSections",
+        number_of_sections.to_string(),
+        "节表中的项数".to_string(),
+    );
+    let time_date_stamp = read_u32(data, coff + 4).unwrap_or(0);
+    narrator.step(
+        coff + 4,
+        4,
+        "IMAGE_FILE_HEADER",
+        "TimeDateStamp",
+        format!("0x{:08X}", time_date_stamp),
+        "链接时间(Unix时间戳) 可重现构建中是内容哈希".to_string(),
+    );
+    let optional_header_size = read_u16(data, coff + 0x10).unwrap_or(0);
+    narrator.step(
+        coff + 0x10,
+        2,
+        "IMAGE_FILE_HEADER",
+        "SizeOfOptionalHeader",
+        format!("0x{:X}", optional_header_size),
+        "可选头的大小 节表紧跟在可选头之后 必须用这个值定位 而不是假设固定大小".to_string(),
+    );
+    let characteristics = read_u16(data, coff + 0x12).unwrap_or(0);
+    narrator.step(
+        coff + 0x12,
+        2,
+        "IMAGE_FILE_HEADER",
+        "Characteristics",
+        format!("0x{:04X}", characteristics),
+        if characteristics & 0x2000 != 0 {
+            "文件属性标志 含IMAGE_FILE_DLL(0x2000) 这是一个DLL".to_string()
+        } else {
+            "文件属性标志 不含IMAGE_FILE_DLL(0x2000) 这是一个可执行程序".to_string()
+        },
+    );
+
+    // 可选头
+    let optional = coff + 0x14;
+    let magic = read_u16(data, optional).unwrap_or(0);
+    let is_x64 = match magic {
+        0x10B => false,
+        0x20B => true,
+        _ => return Err("未知的PE文件格式".into()),
+    };
+    narrator.step(
+        optional,
+        2,
+        "IMAGE_OPTIONAL_HEADER",
+        "Magic",
+        format!("0x{:X}", magic),
+        if is_x64 {
+            "0x20B表示PE32+(64位) ImageBase为8字节 之后的字段整体后移".to_string()
+        } else {
+            "0x10B表示PE32(32位) 多一个BaseOfData字段 ImageBase为4字节".to_string()
+        },
+    );
+    let entry_point = read_u32(data, optional + 0x10).unwrap_or(0);
+    narrator.step(
+        optional + 0x10,
+        4,
+        "IMAGE_OPTIONAL_HEADER",
+        "AddressOfEntryPoint",
+        format!("0x{:X}", entry_point),
+        "入口点RVA 相对于映像基址 不是文件偏移".to_string(),
+    );
+    let image_base = if is_x64 {
+        read_u64(data, optional + 0x18).unwrap_or(0)
+    } else {
+        read_u32(data, optional + 0x1C).unwrap_or(0) as u64
+    };
+    narrator.step(
+        optional + if is_x64 { 0x18 } else { 0x1C },
+        if is_x64 { 8 } else { 4 },
+        "IMAGE_OPTIONAL_HEADER",
+        "ImageBase",
+        format!("0x{:X}", image_base),
+        "首选加载地址 启用ASLR时加载器会重定位到别处".to_string(),
+    );
+    let section_alignment = read_u32(data, optional + 0x20).unwrap_or(0);
+    let file_alignment = read_u32(data, optional + 0x24).unwrap_or(0);
+    narrator.step(
+        optional + 0x20,
+        8,
+        "IMAGE_OPTIONAL_HEADER",
+        "SectionAlignment / FileAlignment",
+        format!("0x{:X} / 0x{:X}", section_alignment, file_alignment),
+        "节在内存中和文件中的对齐粒度 两者不同是RVA与文件偏移需要换算的原因".to_string(),
+    );
+    let size_of_image = read_u32(data, optional + 0x38).unwrap_or(0);
+    narrator.step(
+        optional + 0x38,
+        4,
+        "IMAGE_OPTIONAL_HEADER",
+        "SizeOfImage",
+        format!("0x{:X}", size_of_image),
+        "映像加载到内存后的总大小 按SectionAlignment对齐".to_string(),
+    );
+    let size_of_headers = read_u32(data, optional + 0x3C).unwrap_or(0);
+    narrator.step(
+        optional + 0x3C,
+        4,
+        "IMAGE_OPTIONAL_HEADER",
+        "SizeOfHeaders",
+        format!("0x{:X}", size_of_headers),
+        "DOS头、NT头和节表合计的大小 按FileAlignment对齐 第一个节的数据从这里之后开始".to_string(),
+    );
+    let subsystem = read_u16(data, optional + 0x44).unwrap_or(0);
+    narrator.step(
+        optional + 0x44,
+        2,
+        "IMAGE_OPTIONAL_HEADER",
+        "Subsystem",
+        subsystem.to_string(),
+        format!("子系统 {}", subsystem_name(subsystem)),
+    );
+    let dll_characteristics = read_u16(data, optional + 0x46).unwrap_or(0);
+    narrator.step(
+        optional + 0x46,
+        2,
+        "IMAGE_OPTIONAL_HEADER",
+        "DllCharacteristics",
+        format!("0x{:04X}", dll_characteristics),
+        "安全相关标志 如ASLR(0x40)、DEP(0x100)、CFG(0x4000)".to_string(),
+    );
+
+    // 数据目录
+    let data_directory = optional + if is_x64 { 0x70 } else { 0x60 };
+    let number_of_rva_and_sizes = read_u32(data, data_directory - 4).unwrap_or(0);
+    narrator.step(
+        data_directory - 4,
+        4,
+        "IMAGE_OPTIONAL_HEADER",
+        "NumberOfRvaAndSizes",
+        number_of_rva_and_sizes.to_string(),
+        "数据目录的项数 通常为16 超过16的部分被忽略".to_string(),
+    );
+    let mut directories: Vec<(usize, u32, u32)> = Vec::new();
+    for (index, directory_name) in DIRECTORY_NAMES
+        .iter()
+        .enumerate()
+        .take(number_of_rva_and_sizes as usize)
+    {
+        let entry = data_directory + index * 8;
+        let (Some(rva), Some(size)) = (read_u32(data, entry), read_u32(data, entry + 4)) else {
+            break;
+        };
+        if rva == 0 && size == 0 {
+            continue;
+        }
+        narrator.step(
+            entry,
+            8,
+            "IMAGE_DATA_DIRECTORY",
+            &format!("[{}] {}", index, directory_name),
+            format!("RVA 0x{:X} 大小 0x{:X}", rva, size),
+            if index == IMAGE_DIRECTORY_ENTRY_SECURITY {
+                "证书表例外: 这里的地址是文件偏移而不是RVA 它不会被映射到内存".to_string()
+            } else {
+                "数据目录项 指向映像中的一个表".to_string()
+            },
+        );
+        directories.push((index, rva, size));
+    }
+
+    // 节表
+    let section_table = optional + optional_header_size as usize;
+    narrator.step(
+        section_table,
+        number_of_sections as u32 * 40,
+        "IMAGE_SECTION_HEADER[]",
+        "节表",
+        format!("0x{:X} + 0x{:X}", optional, optional_header_size),
+        format!("节表位于可选头之后 共{}项 每项40字节", number_of_sections),
+    );
+    for index in 0..number_of_sections as usize {
+        let entry = section_table + index * 40;
+        let Some(name_bytes) = data.get(entry..entry + 8) else {
+            break;
+        };
+        let name = String::from_utf8_lossy(name_bytes)
+            .trim_end_matches('\0')
+            .to_string();
+        let virtual_size = read_u32(data, entry + 8).unwrap_or(0);
+        let virtual_address = read_u32(data, entry + 12).unwrap_or(0);
+        let size_raw_data = read_u32(data, entry + 16).unwrap_or(0);
+        let ptr_raw_data = read_u32(data, entry + 20).unwrap_or(0);
+        let section_characteristics = read_u32(data, entry + 36).unwrap_or(0);
+        narrator.step(
+            entry,
+            40,
+            "IMAGE_SECTION_HEADER",
+            &name,
+            format!(
+                "VA 0x{:X} VSize 0x{:X} / 文件 0x{:X} 大小 0x{:X}",
+                virtual_address, virtual_size, ptr_raw_data, size_raw_data
+            ),
+            format!(
+                "文件中0x{:X}起的0x{:X}字节被映射到RVA 0x{:X} 属性0x{:08X}{}",
+                ptr_raw_data,
+                size_raw_data,
+                virtual_address,
+                section_characteristics,
+                if virtual_size > size_raw_data {
+                    " VirtualSize大于原始数据 多出的部分加载时以0填充"
+                } else {
+                    ""
+                }
+            ),
+        );
+    }
+
+    // 入口点和数据目录都要换算到文件偏移才能读取
+    narrator.translate(sections, "AddressOfEntryPoint", entry_point);
+    for (index, rva, _) in directories {
+        if index == IMAGE_DIRECTORY_ENTRY_SECURITY {
+            continue;
+        }
+        let Some(offset) = narrator.translate(sections, DIRECTORY_NAMES[index], rva) else {
+            continue;
+        };
+        match index {
+            0 => walk_exports(&mut narrator, data, sections, offset),
+            1 => walk_imports(&mut narrator, data, sections, offset),
+            _ => {}
+        }
+    }
+    Ok(narrator.steps)
+}
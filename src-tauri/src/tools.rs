@@ -0,0 +1,144 @@
+// 外部工具 命令模板中的{path}/{rva}/{offset}/{va}在启动时替换
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::{LazyLock, RwLock};
+use std::thread;
+
+use crate::handling;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExternalTool {
+    pub name: String,
+    // 如 "C:\Tools\x64dbg\x64dbg.exe" "{path}"
+    pub command: String,
+}
+
+// 启动位置 rva和offset只给出其一时由另一个换算
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct ToolTarget {
+    pub rva: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Default)]
+struct ToolConfig {
+    // 本地配置文件 JSON数组
+    path: Option<PathBuf>,
+    tools: Vec<ExternalTool>,
+}
+
+static CONFIG: LazyLock<RwLock<ToolConfig>> = LazyLock::new(|| RwLock::new(ToolConfig::default()));
+
+pub(crate) fn open(path: PathBuf) -> Result<(), String> {
+    let tools: Vec<ExternalTool> = if path.exists() {
+        let text = fs::read_to_string(&path).map_err(|e| format!("无法读取工具配置: {}", e))?;
+        serde_json::from_str(&text).map_err(|e| format!("无法解析工具配置: {}", e))?
+    } else {
+        Vec::new()
+    };
+    let mut config = CONFIG.write().map_err(|_| String::from("工具配置不可用"))?;
+    config.path = Some(path);
+    config.tools = tools;
+    Ok(())
+}
+
+pub(crate) fn list() -> Vec<ExternalTool> {
+    CONFIG
+        .read()
+        .map_or_else(|_| Vec::new(), |config| config.tools.clone())
+}
+
+pub(crate) fn save(tools: Vec<ExternalTool>) -> Result<(), String> {
+    if let Some(tool) = tools
+        .iter()
+        .find(|tool| split_command(&tool.command).is_empty())
+    {
+        return Err(format!("工具 {} 的命令为空", tool.name));
+    }
+    let mut config = CONFIG.write().map_err(|_| String::from("工具配置不可用"))?;
+    if let Some(path) = &config.path {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("无法创建目录: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(&tools).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| format!("无法写入工具配置: {}", e))?;
+    }
+    config.tools = tools;
+    Ok(())
+}
+
+// 按空白分割参数 双引号内的空白保留 用于带空格的程序路径
+fn split_command(command: &str) -> Vec<String> {
+    let mut arguments: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut has_argument = false;
+    for c in command.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                has_argument = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if has_argument {
+                    arguments.push(std::mem::take(&mut current));
+                    has_argument = false;
+                }
+            }
+            c => {
+                current.push(c);
+                has_argument = true;
+            }
+        }
+    }
+    if has_argument {
+        arguments.push(current);
+    }
+    arguments
+}
+
+// 数值以不带前缀的十六进制替换 未知的位置替换为空
+fn expand(argument: &str, file_path: &str, target: ToolTarget, image_base: u64) -> String {
+    let hex = |value: Option<u64>| value.map(|v| format!("{:X}", v)).unwrap_or_default();
+    argument
+        .replace("{path}", file_path)
+        .replace("{rva}", &hex(target.rva.map(u64::from)))
+        .replace("{offset}", &hex(target.offset.map(u64::from)))
+        .replace(
+            "{va}",
+            &hex(target.rva.map(|rva| image_base.wrapping_add(rva as u64))),
+        )
+}
+
+pub(crate) fn launch(
+    name: &str,
+    file_path: &str,
+    target: ToolTarget,
+    image_base: u64,
+) -> Result<(), String> {
+    // 调试器等工具可能运行样本
+    handling::ensure_allowed("启动外部工具")?;
+    let tool = list()
+        .into_iter()
+        .find(|tool| tool.name == name)
+        .ok_or_else(|| format!("未配置外部工具: {}", name))?;
+    let arguments: Vec<String> = split_command(&tool.command)
+        .iter()
+        .map(|argument| expand(argument, file_path, target, image_base))
+        .collect();
+    let (program, arguments) = arguments
+        .split_first()
+        .ok_or_else(|| format!("工具 {} 的命令为空", name))?;
+    let mut child = Command::new(program)
+        .args(arguments)
+        .spawn()
+        .map_err(|e| format!("无法启动 {}: {}", name, e))?;
+    // 回收子进程 不阻塞前端
+    thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
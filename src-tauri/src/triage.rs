@@ -0,0 +1,178 @@
+// 快速分诊: 只解析头部、节表和入口点 不做完整分析 用于批量扫描目录时的第一轮筛选
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use std::path::Path;
+use std::time::Instant;
+
+use crate::entropy::{shannon_entropy, PACKED_ENTROPY};
+use crate::ep_signature::read_entry_point;
+use crate::file_access;
+use crate::hashes::to_hex;
+use crate::headers::read_headers;
+use crate::heuristics::{is_executable, is_writable, packer_for_section_name};
+use crate::reader::BoundedReader;
+use crate::Section;
+
+const IMAGE_FILE_DLL: u16 = 0x2000;
+// 导入目录小于两个描述符(含结尾的全0项) 即最多只导入一个DLL
+const TINY_IMPORT_DIRECTORY: u32 = 40;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct QuickTriage {
+    pub path: String,
+    // 不是PE文件时的原因 其余字段只有size和sha256有效
+    pub invalid: Option<String>,
+    pub size: u64,
+    pub architecture: String,
+    pub is_x64: bool,
+    pub is_dll: bool,
+    pub subsystem: String,
+    // 只看证书目录是否存在 不验证签名
+    pub has_certificate: bool,
+    // low / medium / high
+    pub packed: String,
+    pub packed_reasons: Vec<String>,
+    pub sha256: String,
+    pub duration_us: u64,
+}
+
+// 解析头部并填入triage 不是PE文件时返回原因
+// 头部字段与完整分析一样由headers::read_headers读取
+fn parse_headers(data: &[u8], triage: &mut QuickTriage) -> Result<(), String> {
+    let reader = BoundedReader::new(data);
+    if !data.starts_with(b"MZ") {
+        return Err("缺少MZ签名".into());
+    }
+    let nt = reader.u32(0x3C).map_err(|_| "DOS头不完整")? as u64;
+    if reader.bytes(nt, 4).ok() != Some(b"PE\0\0".as_slice()) {
+        return Err(format!("e_lfanew(0x{:X})处没有PE签名", nt));
+    }
+    let optional = nt + 0x18;
+    triage.is_x64 = match reader.u16(optional) {
+        Ok(0x10B) => false,
+        Ok(0x20B) => true,
+        _ => return Err("未知的可选头Magic".into()),
+    };
+    let (_, coff_header, optional_header) =
+        read_headers(&reader, data, nt, triage.is_x64).map_err(|e| format!("头部不完整: {}", e))?;
+    triage.architecture = coff_header.machine_name;
+    triage.is_dll = coff_header.characteristics & IMAGE_FILE_DLL != 0;
+    triage.subsystem = optional_header.subsystem_name;
+    let entry_point = optional_header.address_of_entry_point;
+
+    let data_directory = optional + if triage.is_x64 { 0x70 } else { 0x60 };
+    let directory_size = |index: u32| {
+        if index < optional_header.number_of_rva_and_sizes {
+            reader
+                .u32(data_directory + index as u64 * 8 + 4)
+                .unwrap_or(0)
+        } else {
+            0
+        }
+    };
+    triage.has_certificate = directory_size(4) != 0;
+
+    let section_table = optional + coff_header.size_of_optional_header as u64;
+    let mut sections: Vec<Section> = Vec::new();
+    for index in 0..coff_header.number_of_sections as u64 {
+        let entry = section_table + index * 40;
+        let Ok(name_bytes) = reader.bytes(entry, 8) else {
+            break;
+        };
+        let rva = reader.u32(entry + 12).unwrap_or(0);
+        let ptr_raw_data = reader.u32(entry + 20).unwrap_or(0);
+        let size_raw_data = reader.u32(entry + 16).unwrap_or(0);
+        let raw_end = (ptr_raw_data as usize)
+            .saturating_add(size_raw_data as usize)
+            .min(data.len());
+        sections.push(Section {
+            name: String::from_utf8_lossy(name_bytes)
+                .trim_end_matches('\0')
+                .to_string(),
+            raw_name: None,
+            rva,
+            ptr_raw_data,
+            size_raw_data,
+            rv_end: rva.saturating_add(reader.u32(entry + 8).unwrap_or(0)),
+            characteristics: reader.u32(entry + 36).unwrap_or(0),
+            entropy: data
+                .get(ptr_raw_data as usize..raw_end)
+                .map_or(0.0, shannon_entropy),
+        });
+    }
+
+    // 加壳迹象: 已知壳的节区名或入口点特征、可写的入口节、高熵代码节、几乎没有导入
+    let mut reasons: Vec<String> = Vec::new();
+    let mut strong = false;
+    for section in &sections {
+        if let Some(packer) = packer_for_section_name(&section.name) {
+            let reason = format!("节区名{}属于{}", section.name, packer);
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+            strong = true;
+        }
+        let executable = is_executable(section);
+        if executable
+            && is_writable(section)
+            && entry_point >= section.rva
+            && entry_point < section.rv_end
+        {
+            reasons.push(format!("入口点所在的节{}可写可执行", section.name));
+        }
+        if executable && section.entropy > PACKED_ENTROPY {
+            reasons.push(format!(
+                "代码节{}的熵为{:.2}",
+                section.name, section.entropy
+            ));
+        }
+    }
+    if let Some(packer) =
+        read_entry_point(data, &sections, entry_point).and_then(|entry| entry.signature)
+    {
+        reasons.push(format!("入口点代码与{}的解压存根一致", packer));
+        strong = true;
+    }
+    // 纯资源DLL本来就没有导入 只对可执行程序计入
+    let import_size = directory_size(1);
+    if !triage.is_dll && import_size < TINY_IMPORT_DIRECTORY {
+        reasons.push(if import_size == 0 {
+            "没有导入表".to_string()
+        } else {
+            "只导入了一个DLL".to_string()
+        });
+    }
+    triage.packed = if strong || reasons.len() >= 2 {
+        "high"
+    } else if reasons.is_empty() {
+        "low"
+    } else {
+        "medium"
+    }
+    .to_string();
+    triage.packed_reasons = reasons;
+    Ok(())
+}
+
+pub(crate) fn quick_triage(file_path: &str) -> Result<QuickTriage, String> {
+    let start = Instant::now();
+    let data = file_access::read_file(Path::new(file_path))?;
+    let mut triage = QuickTriage {
+        path: file_path.to_string(),
+        size: data.len() as u64,
+        sha256: to_hex(&Sha256::digest(&data)),
+        ..Default::default()
+    };
+    if let Err(reason) = parse_headers(&data, &mut triage) {
+        triage = QuickTriage {
+            invalid: Some(reason),
+            path: triage.path,
+            size: triage.size,
+            sha256: triage.sha256,
+            ..Default::default()
+        };
+    }
+    triage.duration_us = start.elapsed().as_micros() as u64;
+    Ok(triage)
+}
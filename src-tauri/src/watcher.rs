@@ -0,0 +1,99 @@
+// 监视文件重建 每次写入稳定后重新分析 并把与上次结果的差异发送给前端
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use tauri::{AppHandle, Emitter};
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use crate::diff::diff_analysis;
+
+// 发送给前端的事件名
+pub(crate) const BUILD_CHANGED_EVENT: &str = "build-changed";
+// 链接器会多次写入同一文件 等待事件静默后再分析
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+
+// 正在监视的文件 丢弃watcher即停止监视
+#[derive(Default)]
+pub struct WatchState {
+    watchers: Mutex<HashMap<String, RecommendedWatcher>>,
+}
+
+pub(crate) fn watch_file(
+    app: AppHandle,
+    state: &WatchState,
+    file_path: &str,
+) -> Result<(), String> {
+    let mut previous = crate::analyze(file_path.into())?;
+    // 记录失败不影响监视
+    let _ = crate::history::record(&previous);
+    let target = PathBuf::from(file_path);
+    // 监视所在目录 链接器常先删除再重新创建文件
+    let directory = target
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+
+    let (sender, receiver) = mpsc::channel::<()>();
+    let file_name = target.file_name().map(|name| name.to_os_string());
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if event.kind.is_access() {
+            return;
+        }
+        if event
+            .paths
+            .iter()
+            .any(|path| path.file_name().map(|name| name.to_os_string()) == file_name)
+        {
+            let _ = sender.send(());
+        }
+    })
+    .map_err(|e| format!("无法创建文件监视: {}", e))?;
+    watcher
+        .watch(&directory, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("无法监视目录: {}", e))?;
+
+    let path = file_path.to_string();
+    thread::spawn(move || {
+        // watcher被丢弃后发送端关闭 线程随之退出
+        while receiver.recv().is_ok() {
+            while receiver.recv_timeout(SETTLE_DELAY).is_ok() {}
+            // 构建过程中文件可能暂时不完整 分析失败时保留上次结果
+            let Ok(current) = crate::analyze(PathBuf::from(&path)) else {
+                continue;
+            };
+            // 内容不变的重新生成不重复记录
+            if current.hashes.sha256 != previous.hashes.sha256 {
+                let _ = crate::history::record(&current);
+            }
+            let diff = diff_analysis(&previous, &current);
+            if !diff.is_empty() {
+                let _ = app.emit(BUILD_CHANGED_EVENT, diff);
+            }
+            previous = current;
+        }
+    });
+
+    state
+        .watchers
+        .lock()
+        .map_err(|_| String::from("监视状态不可用"))?
+        .insert(file_path.to_string(), watcher);
+    Ok(())
+}
+
+pub(crate) fn unwatch_file(state: &WatchState, file_path: &str) -> Result<bool, String> {
+    Ok(state
+        .watchers
+        .lock()
+        .map_err(|_| String::from("监视状态不可用"))?
+        .remove(file_path)
+        .is_some())
+}
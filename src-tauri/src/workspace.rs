@@ -0,0 +1,93 @@
+// 工作区 多个文件的可比较摘要 每个文件一行
+use serde::{Deserialize, Serialize};
+
+use crate::entropy::PACKED_ENTROPY;
+use crate::headers::{machine_name, subsystem_name};
+use crate::heuristics::{packer_for_section_name, IMAGE_SCN_MEM_EXECUTE};
+use crate::PeInfo;
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+pub struct WorkspaceRow {
+    pub path: String,
+    // 分析失败时的错误 其余字段为默认值
+    pub error: Option<String>,
+    pub size: u64,
+    pub architecture: String,
+    pub subsystem: String,
+    pub is_signed: bool,
+    pub signer: Option<String>,
+    pub known_good: bool,
+    pub imphash: String,
+    pub import_order_hash: String,
+    pub dll_sequence_hash: String,
+    pub packer: Option<String>,
+    pub suspicion: u32,
+    pub md5: String,
+    pub sha256: String,
+}
+
+// 壳判断: 已知壳的节区名或入口点特征 其次是打包器 最后是可执行节区的高熵
+fn packer_verdict(info: &PeInfo) -> Option<String> {
+    if let Some(packer) = info
+        .sections
+        .iter()
+        .find_map(|s| packer_for_section_name(&s.name))
+    {
+        return Some(packer.to_string());
+    }
+    if let Some(packer) = info
+        .entry_point_bytes
+        .as_ref()
+        .and_then(|entry| entry.signature.clone())
+    {
+        return Some(packer);
+    }
+    if let Some(package) = &info.python_package {
+        return Some(package.packer.clone());
+    }
+    if info.autoit.is_some() {
+        return Some("AutoIt".to_string());
+    }
+    info.sections
+        .iter()
+        .any(|s| s.characteristics & IMAGE_SCN_MEM_EXECUTE != 0 && s.entropy > PACKED_ENTROPY)
+        .then(|| "未知(代码节高熵)".to_string())
+}
+
+pub(crate) fn summarize(info: &PeInfo) -> WorkspaceRow {
+    WorkspaceRow {
+        path: info.path.clone(),
+        error: None,
+        size: info.size,
+        architecture: machine_name(info.machine),
+        subsystem: subsystem_name(info.subsystem),
+        is_signed: info.is_signed,
+        signer: info.signature.as_ref().and_then(|s| s.signer.clone()),
+        known_good: info.known_good,
+        imphash: info.imphash.clone(),
+        import_order_hash: info.import_order_hash.clone(),
+        dll_sequence_hash: info.dll_sequence_hash.clone(),
+        packer: packer_verdict(info),
+        suspicion: info.suspicion.score,
+        md5: info.hashes.md5.clone(),
+        sha256: info.hashes.sha256.clone(),
+    }
+}
+
+pub(crate) fn workspace_summary(file_paths: &[String]) -> Vec<WorkspaceRow> {
+    file_paths
+        .iter()
+        .map(|path| match crate::analyze(path.into()) {
+            Ok(info) => {
+                // 记录失败不影响分析结果
+                let _ = crate::history::record(&info);
+                summarize(&info)
+            }
+            Err(error) => WorkspaceRow {
+                path: path.clone(),
+                error: Some(error),
+                ..Default::default()
+            },
+        })
+        .collect()
+}
@@ -0,0 +1,198 @@
+// XOR密钥爆破(按需执行): 对资源、覆盖数据和节区空隙中熵较高的数据块尝试单字节和常见4字节密钥
+// 解码后出现完整PE头或大段可打印文本(配置)时报告 常见于商品化加载器
+use serde::{Deserialize, Serialize};
+
+use crate::entropy::shannon_entropy;
+use crate::reader::read_u32;
+use crate::resources::{resource_data, ResourceEntry};
+use crate::slack::SlackRegion;
+
+// 熵低于此值的数据块不尝试 单字节密钥异或不改变熵 PE和文本配置通常在4以上
+const MIN_ENTROPY: f64 = 4.0;
+const MIN_BLOB_SIZE: usize = 64;
+// 每个数据块只扫描开头部分
+const MAX_SCAN_SIZE: usize = 0x10000;
+// 判定为文本配置: 开头这么多字节中可打印字符的比例
+const TEXT_SAMPLE: usize = 256;
+const TEXT_RATIO: f64 = 0.95;
+// 英文配置文本的熵一般在4左右
+const MIN_TEXT_ENTROPY: f64 = 3.0;
+const PREVIEW_LENGTH: usize = 64;
+const MAX_HITS: usize = 64;
+
+const COMMON_KEYS: &[u32] = &[
+    0xDEAD_BEEF,
+    0x1234_5678,
+    0xCAFE_BABE,
+    0xAABB_CCDD,
+    0x7856_3412,
+];
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct XorHit {
+    // resource / overlay / slack
+    pub source: String,
+    // 资源的类型/名称/语言 或空隙所在的节
+    pub location: String,
+    // 解码内容在文件中的偏移
+    pub offset: u64,
+    pub key: String,
+    // pe / text
+    pub kind: String,
+    pub preview: String,
+}
+
+fn xor_with(data: &[u8], key: &[u8]) -> Vec<u8> {
+    data.iter()
+        .enumerate()
+        .map(|(i, byte)| byte ^ key[i % key.len()])
+        .collect()
+}
+
+fn is_text(byte: u8) -> bool {
+    (0x20..0x7F).contains(&byte) || matches!(byte, b'\r' | b'\n' | b'\t')
+}
+
+fn printable_ratio(data: &[u8]) -> f64 {
+    let sample = &data[..data.len().min(TEXT_SAMPLE)];
+    if sample.is_empty() {
+        return 0.0;
+    }
+    sample.iter().filter(|&&b| is_text(b)).count() as f64 / sample.len() as f64
+}
+
+fn preview(data: &[u8]) -> String {
+    data.iter()
+        .take(PREVIEW_LENGTH)
+        .map(|&b| {
+            if is_text(b) && b >= 0x20 {
+                b as char
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+// 解码后在position处是否为MZ头 且e_lfanew指向PE签名
+fn has_pe_header(decoded: &[u8], position: usize) -> bool {
+    let Some(header) = decoded.get(position..) else {
+        return false;
+    };
+    if !header.starts_with(b"MZ") {
+        return false;
+    }
+    let Some(e_lfanew) = read_u32(header, 0x3C).map(|value| value as usize) else {
+        return false;
+    };
+    e_lfanew >= 0x40 && header.get(e_lfanew..e_lfanew + 4) == Some(b"PE\0\0".as_slice())
+}
+
+// 候选密钥: 1..=255的单字节 常见4字节密钥 以及假设开头为"MZ\x90\0"时推出的4字节密钥
+fn candidate_keys(blob: &[u8]) -> Vec<Vec<u8>> {
+    let mut keys: Vec<Vec<u8>> = (1..=255u8).map(|key| vec![key]).collect();
+    keys.extend(COMMON_KEYS.iter().map(|key| key.to_le_bytes().to_vec()));
+    if let Some(head) = blob.get(..4) {
+        let derived: Vec<u8> = head.iter().zip(b"MZ\x90\0").map(|(a, b)| a ^ b).collect();
+        if derived.iter().any(|&b| b != 0) && !keys.contains(&derived) {
+            keys.push(derived);
+        }
+    }
+    keys
+}
+
+// 解码后的开头是否像文本配置 要求可打印且字符有一定变化 避免大片0字节异或出单一字符
+fn looks_like_text(decoded: &[u8]) -> bool {
+    let sample = &decoded[..decoded.len().min(TEXT_SAMPLE)];
+    printable_ratio(sample) >= TEXT_RATIO && shannon_entropy(sample) >= MIN_TEXT_ENTROPY
+}
+
+// 多个密钥都能解出可打印文本时 按常见字母和配置分隔符的数量挑出最可能的一个
+fn text_score(decoded: &[u8]) -> usize {
+    decoded
+        .iter()
+        .take(TEXT_SAMPLE)
+        .filter(|b| b"etaoinsrhldcu0123456789 =:;/.,".contains(&b.to_ascii_lowercase()))
+        .count()
+}
+
+// 每个数据块最多报告一次 优先报告PE头 否则报告得分最高的文本
+fn scan_blob(blob: &[u8], source: &str, location: &str, base: u64, hits: &mut Vec<XorHit>) {
+    let blob = &blob[..blob.len().min(MAX_SCAN_SIZE)];
+    if hits.len() >= MAX_HITS || blob.len() < MIN_BLOB_SIZE || shannon_entropy(blob) < MIN_ENTROPY {
+        return;
+    }
+    // 本身就是文本的数据块 用0x20等密钥异或后仍可打印 不做文本判定
+    let plain_text = printable_ratio(blob) >= TEXT_RATIO;
+    let mut found: Option<(Vec<u8>, Vec<u8>, usize, &str)> = None;
+    let mut best_score = 0;
+    for key in candidate_keys(blob) {
+        let decoded = xor_with(blob, &key);
+        let pe_at = decoded
+            .windows(2)
+            .enumerate()
+            // 多字节密钥只认与密钥对齐的位置
+            .filter(|(position, _)| position % key.len() == 0)
+            .find(|(position, pair)| *pair == b"MZ" && has_pe_header(&decoded, *position))
+            .map(|(position, _)| position);
+        if let Some(position) = pe_at {
+            found = Some((key, decoded, position, "pe"));
+            break;
+        }
+        if !plain_text && looks_like_text(&decoded) && text_score(&decoded) > best_score {
+            best_score = text_score(&decoded);
+            found = Some((key, decoded, 0, "text"));
+        }
+    }
+    if let Some((key, decoded, position, kind)) = found {
+        hits.push(XorHit {
+            source: source.to_string(),
+            location: location.to_string(),
+            offset: base + position as u64,
+            key: key.iter().map(|b| format!("{:02X}", b)).collect(),
+            kind: kind.to_string(),
+            preview: preview(&decoded[position..]),
+        });
+    }
+}
+
+pub(crate) fn brute_force_xor(
+    data: &[u8],
+    resources: &[ResourceEntry],
+    slack: &[SlackRegion],
+    overlay_offset: u64,
+) -> Vec<XorHit> {
+    let mut hits: Vec<XorHit> = Vec::new();
+    for resource in resources {
+        let Some(offset) = resource.offset else {
+            continue;
+        };
+        let location = format!(
+            "{}/{}/{}",
+            resource.type_name, resource.name, resource.language
+        );
+        scan_blob(
+            resource_data(data, resource),
+            "resource",
+            &location,
+            offset,
+            &mut hits,
+        );
+    }
+    if let Some(overlay) = data.get(overlay_offset as usize..) {
+        scan_blob(overlay, "overlay", "", overlay_offset, &mut hits);
+    }
+    for region in slack {
+        let start = region.offset as usize;
+        if let Some(bytes) = data.get(start..start.saturating_add(region.size as usize)) {
+            scan_blob(
+                bytes,
+                "slack",
+                &region.section,
+                region.offset as u64,
+                &mut hits,
+            );
+        }
+    }
+    hits
+}
@@ -0,0 +1,249 @@
+// 传统PKWARE加密(ZipCrypto)的zip归档 样本交换的惯例格式 压缩软件都能用密码直接解开
+// 加密强度很弱 只用于防止杀毒软件误删和误双击 不用于保密
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use sha2::{Digest, Sha256};
+
+use std::io::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const LOCAL_HEADER_SIGNATURE: u32 = 0x0403_4B50;
+const CENTRAL_HEADER_SIGNATURE: u32 = 0x0201_4B50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4B50;
+// 通用标志: 位0为加密 位11为UTF-8文件名
+const FLAG_ENCRYPTED: u16 = 0x0001;
+const FLAG_UTF8: u16 = 0x0800;
+const METHOD_DEFLATE: u16 = 8;
+const VERSION_NEEDED: u16 = 20;
+// 1980-01-01 00:00 DOS日期时间的最小值
+const DOS_TIME: u16 = 0;
+const DOS_DATE: u16 = 0x0021;
+
+static NONCE: AtomicU64 = AtomicU64::new(0);
+
+fn crc32_byte(crc: u32, byte: u8) -> u32 {
+    let mut value = (crc ^ byte as u32) & 0xFF;
+    for _ in 0..8 {
+        value = if value & 1 != 0 {
+            (value >> 1) ^ 0xEDB8_8320
+        } else {
+            value >> 1
+        };
+    }
+    value ^ (crc >> 8)
+}
+
+struct Keys([u32; 3]);
+
+impl Keys {
+    fn new(password: &[u8]) -> Self {
+        let mut keys = Keys([0x1234_5678, 0x2345_6789, 0x3456_7890]);
+        for &byte in password {
+            keys.update(byte);
+        }
+        keys
+    }
+
+    fn update(&mut self, byte: u8) {
+        self.0[0] = crc32_byte(self.0[0], byte);
+        self.0[1] = self.0[1]
+            .wrapping_add(self.0[0] & 0xFF)
+            .wrapping_mul(134_775_813)
+            .wrapping_add(1);
+        self.0[2] = crc32_byte(self.0[2], (self.0[1] >> 24) as u8);
+    }
+
+    fn encrypt(&mut self, byte: u8) -> u8 {
+        let temp = (self.0[2] | 2) & 0xFFFF;
+        let mask = (temp.wrapping_mul(temp ^ 1) >> 8) as u8;
+        self.update(byte);
+        byte ^ mask
+    }
+}
+
+// 加密头的前11字节随机 不需要密码学强度 只要每个条目不同
+fn random_header() -> [u8; 11] {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_nanos());
+    let mut hasher = Sha256::new();
+    hasher.update(nanos.to_le_bytes());
+    hasher.update(std::process::id().to_le_bytes());
+    hasher.update(NONCE.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    let digest = hasher.finalize();
+    let mut header = [0u8; 11];
+    header.copy_from_slice(&digest[..11]);
+    header
+}
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+// 条目名称为归档内的相对路径 使用/分隔 不支持zip64 单个条目和整个归档都不能超过4GB
+pub(crate) fn encrypted_zip(
+    files: &[(String, Vec<u8>)],
+    password: &str,
+) -> Result<Vec<u8>, String> {
+    let too_large = || String::from("文件过大 无法写入zip归档");
+    let mut archive: Vec<u8> = Vec::new();
+    let mut central: Vec<u8> = Vec::new();
+    for (name, data) in files {
+        let mut crc = Crc::new();
+        crc.update(data);
+        let crc = crc.sum();
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(data)
+            .map_err(|e| format!("压缩失败 {}: {}", name, e))?;
+        let compressed = encoder
+            .finish()
+            .map_err(|e| format!("压缩失败 {}: {}", name, e))?;
+
+        // 加密头最后一字节为CRC的最高字节 解压软件用它校验密码
+        let mut keys = Keys::new(password.as_bytes());
+        let mut encrypted: Vec<u8> = Vec::with_capacity(12 + compressed.len());
+        let header = random_header();
+        for byte in header.iter().chain(std::iter::once(&((crc >> 24) as u8))) {
+            encrypted.push(keys.encrypt(*byte));
+        }
+        encrypted.extend(compressed.iter().map(|byte| keys.encrypt(*byte)));
+
+        let offset = u32::try_from(archive.len()).map_err(|_| too_large())?;
+        let compressed_size = u32::try_from(encrypted.len()).map_err(|_| too_large())?;
+        let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+        let name_length = u16::try_from(name.len()).map_err(|_| format!("文件名过长: {}", name))?;
+        let flags = FLAG_ENCRYPTED | FLAG_UTF8;
+
+        put_u32(&mut archive, LOCAL_HEADER_SIGNATURE);
+        put_u16(&mut archive, VERSION_NEEDED);
+        put_u16(&mut archive, flags);
+        put_u16(&mut archive, METHOD_DEFLATE);
+        put_u16(&mut archive, DOS_TIME);
+        put_u16(&mut archive, DOS_DATE);
+        put_u32(&mut archive, crc);
+        put_u32(&mut archive, compressed_size);
+        put_u32(&mut archive, size);
+        put_u16(&mut archive, name_length);
+        put_u16(&mut archive, 0);
+        archive.extend_from_slice(name.as_bytes());
+        archive.extend_from_slice(&encrypted);
+
+        put_u32(&mut central, CENTRAL_HEADER_SIGNATURE);
+        put_u16(&mut central, VERSION_NEEDED);
+        put_u16(&mut central, VERSION_NEEDED);
+        put_u16(&mut central, flags);
+        put_u16(&mut central, METHOD_DEFLATE);
+        put_u16(&mut central, DOS_TIME);
+        put_u16(&mut central, DOS_DATE);
+        put_u32(&mut central, crc);
+        put_u32(&mut central, compressed_size);
+        put_u32(&mut central, size);
+        put_u16(&mut central, name_length);
+        // 扩展字段、注释长度、起始磁盘号、内部属性
+        put_u16(&mut central, 0);
+        put_u16(&mut central, 0);
+        put_u16(&mut central, 0);
+        put_u16(&mut central, 0);
+        // 外部属性
+        put_u32(&mut central, 0);
+        put_u32(&mut central, offset);
+        central.extend_from_slice(name.as_bytes());
+    }
+
+    let entries =
+        u16::try_from(files.len()).map_err(|_| String::from("文件过多 无法写入zip归档"))?;
+    let central_offset = u32::try_from(archive.len()).map_err(|_| too_large())?;
+    let central_size = u32::try_from(central.len()).map_err(|_| too_large())?;
+    archive.extend_from_slice(&central);
+    put_u32(&mut archive, END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+    put_u16(&mut archive, 0);
+    put_u16(&mut archive, 0);
+    put_u16(&mut archive, entries);
+    put_u16(&mut archive, entries);
+    put_u32(&mut archive, central_size);
+    put_u32(&mut archive, central_offset);
+    put_u16(&mut archive, 0);
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::DeflateDecoder;
+    use std::io::Read;
+
+    fn decrypt(password: &str, encrypted: &[u8]) -> Vec<u8> {
+        let mut keys = Keys::new(password.as_bytes());
+        encrypted
+            .iter()
+            .map(|&byte| {
+                let temp = (keys.0[2] | 2) & 0xFFFF;
+                let plain = byte ^ (temp.wrapping_mul(temp ^ 1) >> 8) as u8;
+                keys.update(plain);
+                plain
+            })
+            .collect()
+    }
+
+    fn u16_at(data: &[u8], offset: usize) -> u16 {
+        u16::from_le_bytes([data[offset], data[offset + 1]])
+    }
+
+    fn u32_at(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn decrypts_info_zip_vector() {
+        // zip -0 -e -P infected 生成的条目数据 带数据描述符 校验字节为修改时间的高字节0x0A
+        let encrypted = [
+            0xF8, 0x13, 0xB6, 0x32, 0xDD, 0x91, 0x46, 0x35, 0x71, 0x5D, 0x96, 0x40, 0x1F, 0x52,
+            0x43, 0xB6, 0xEA, 0x40, 0xBE, 0x8C, 0xFD, 0xA4, 0xC0, 0xE4, 0x10, 0x62, 0x55, 0x3F,
+            0x8D, 0x06,
+        ];
+        let plain = decrypt("infected", &encrypted);
+        assert_eq!(plain[11], 0x0A);
+        assert_eq!(&plain[12..], b"MZ sample payload\n");
+    }
+
+    #[test]
+    fn round_trips_encrypted_entries() {
+        let data = b"MZ\x90\x00 sample payload ".repeat(64);
+        let archive =
+            encrypted_zip(&[("a.exe.bin".to_string(), data.clone())], "infected").unwrap();
+
+        assert_eq!(u32_at(&archive, 0), LOCAL_HEADER_SIGNATURE);
+        assert_eq!(u16_at(&archive, 6), FLAG_ENCRYPTED | FLAG_UTF8);
+        assert_eq!(u16_at(&archive, 8), METHOD_DEFLATE);
+        let crc = u32_at(&archive, 14);
+        let compressed_size = u32_at(&archive, 18) as usize;
+        assert_eq!(u32_at(&archive, 22) as usize, data.len());
+        let name_length = u16_at(&archive, 26) as usize;
+        assert_eq!(&archive[30..30 + name_length], b"a.exe.bin");
+
+        let start = 30 + name_length;
+        let plain = decrypt("infected", &archive[start..start + compressed_size]);
+        // 加密头最后一字节为CRC最高字节 错误的密码通常在这里就被发现
+        assert_eq!(plain[11], (crc >> 24) as u8);
+
+        let mut inflated = Vec::new();
+        DeflateDecoder::new(&plain[12..])
+            .read_to_end(&mut inflated)
+            .unwrap();
+        assert_eq!(inflated, data);
+        let mut check = Crc::new();
+        check.update(&inflated);
+        assert_eq!(check.sum(), crc);
+
+        // 中央目录结尾记录一个条目
+        let end = archive.len() - 22;
+        assert_eq!(u32_at(&archive, end), END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        assert_eq!(u16_at(&archive, end + 10), 1);
+    }
+}